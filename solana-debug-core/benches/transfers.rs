@@ -0,0 +1,43 @@
+use bokken_core::{
+	BokkenLedger,
+	BokkenLedgerInitConfig,
+	ProgramCaller,
+	bench::bench_transfer,
+	debug_ledger::DEFAULT_INITIAL_MINT_LAMPORTS,
+	program_caller::{ProgramCallerConfig, bind_ipc_socket, remove_ipc_socket}
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+use solana_sdk::{signature::Keypair, signer::Signer};
+use tokio::runtime::Runtime;
+
+/// Time from signing a system transfer to it being committed and found by its signature
+fn transfer(c: &mut Criterion) {
+	let rt = Runtime::new().unwrap();
+	let ledger_path = std::env::temp_dir().join(format!("bokken-criterion-{}", std::process::id()));
+	let socket_path = std::env::temp_dir().join(format!("bokken-criterion-{}.sock", std::process::id()));
+	let payer = Keypair::new();
+	let ledger = rt.block_on(async {
+		BokkenLedger::new(
+			ledger_path.clone(),
+			ProgramCaller::new(bind_ipc_socket(&socket_path).unwrap(), ProgramCallerConfig::default()),
+			Some(
+				BokkenLedgerInitConfig {
+					initial_mint: payer.pubkey(),
+					initial_mint_lamports: DEFAULT_INITIAL_MINT_LAMPORTS,
+					identity: None
+				}
+			)
+		).await.unwrap()
+	});
+	c.bench_function("transfer", |b| {
+		b.iter(|| rt.block_on(bench_transfer(&ledger, &payer)).unwrap());
+	});
+	ledger.stop();
+	rt.block_on(async {
+		remove_ipc_socket(&socket_path).await.unwrap();
+		tokio::fs::remove_dir_all(&ledger_path).await.unwrap();
+	});
+}
+
+criterion_group!(benches, transfer);
+criterion_main!(benches);
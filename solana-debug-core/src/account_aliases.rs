@@ -0,0 +1,115 @@
+//! Human-readable names for accounts, so tests don't have to be read with a table of base58 strings at hand.
+//!
+//! Aliases are shared by every ledger in the process. Once an account has one, Bokken's output and error messages show
+//! it as `alice (A1b2...)`, and admin RPC methods accept the alias wherever they expect a pubkey.
+
+use std::{collections::{BTreeMap, HashMap}, str::FromStr, sync::RwLock};
+
+use lazy_static::lazy_static;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::BokkenError;
+
+/// How many characters of an aliased pubkey are shown next to its alias
+const ABBREVIATED_PUBKEY_LEN: usize = 4;
+
+#[derive(Debug, Default)]
+struct BokkenAccountAliases {
+	pubkeys: BTreeMap<String, Pubkey>,
+	/// Pubkey -> the alias it was most recently given
+	names: HashMap<Pubkey, String>
+}
+
+lazy_static! {
+	static ref ACCOUNT_ALIASES: RwLock<BokkenAccountAliases> = RwLock::new(BokkenAccountAliases::default());
+}
+
+/// Gives `pubkey` the alias `name`, replacing whatever the alias referred to before. Returns the pubkey it replaced.
+///
+/// Aliases may only contain letters, numbers, dashes, and underscores, and can't be valid pubkeys themselves.
+pub fn set_account_alias(name: String, pubkey: Pubkey) -> Result<Option<Pubkey>, BokkenError> {
+	if
+		name.is_empty() ||
+		!name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') ||
+		Pubkey::from_str(&name).is_ok()
+	{
+		return Err(BokkenError::InvalidAccountAlias(name));
+	}
+	let mut aliases = ACCOUNT_ALIASES.write().unwrap();
+	let old_pubkey = aliases.pubkeys.insert(name.clone(), pubkey);
+	if let Some(old_pubkey) = old_pubkey {
+		forget_name(&mut aliases, &old_pubkey, &name);
+	}
+	aliases.names.insert(pubkey, name);
+	Ok(old_pubkey)
+}
+
+/// Removes the alias, returning the pubkey it referred to
+pub fn remove_account_alias(name: &str) -> Option<Pubkey> {
+	let mut aliases = ACCOUNT_ALIASES.write().unwrap();
+	let pubkey = aliases.pubkeys.remove(name)?;
+	forget_name(&mut aliases, &pubkey, name);
+	Some(pubkey)
+}
+
+/// If `name` is what `pubkey` is displayed as, display it with one of its other aliases instead, if it has any
+fn forget_name(aliases: &mut BokkenAccountAliases, pubkey: &Pubkey, name: &str) {
+	if aliases.names.get(pubkey).map_or(false, |current_name| current_name == name) {
+		let other_name = aliases.pubkeys.iter()
+			.find(|(_, other_pubkey)| *other_pubkey == pubkey)
+			.map(|(other_name, _)| other_name.clone());
+		match other_name {
+			Some(other_name) => aliases.names.insert(*pubkey, other_name),
+			None => aliases.names.remove(pubkey)
+		};
+	}
+}
+
+/// Every alias and the pubkey it refers to, sorted by alias
+pub fn account_aliases() -> BTreeMap<String, Pubkey> {
+	ACCOUNT_ALIASES.read().unwrap().pubkeys.clone()
+}
+
+/// The pubkey `pubkey_or_alias` is the base58 encoding or alias of
+pub fn resolve_pubkey(pubkey_or_alias: &str) -> Result<Pubkey, BokkenError> {
+	if let Some(pubkey) = ACCOUNT_ALIASES.read().unwrap().pubkeys.get(pubkey_or_alias) {
+		return Ok(*pubkey);
+	}
+	Pubkey::from_str(pubkey_or_alias).map_err(|_| BokkenError::UnknownPubkeyOrAlias(pubkey_or_alias.to_string()))
+}
+
+fn aliased_display(name: &str, pubkey: &str) -> String {
+	format!("{} ({}...)", name, &pubkey[..ABBREVIATED_PUBKEY_LEN.min(pubkey.len())])
+}
+
+/// `alias (A1b2...)` if the pubkey has an alias, the pubkey itself otherwise
+pub fn display_pubkey(pubkey: &Pubkey) -> String {
+	let pubkey_str = pubkey.to_string();
+	match ACCOUNT_ALIASES.read().unwrap().names.get(pubkey) {
+		Some(name) => aliased_display(name, &pubkey_str),
+		None => pubkey_str
+	}
+}
+
+/// Replaces every aliased pubkey in `text` with how `display_pubkey` shows it
+pub fn annotate_pubkeys(text: &str) -> String {
+	let aliases = ACCOUNT_ALIASES.read().unwrap();
+	if aliases.names.is_empty() {
+		return text.to_string();
+	}
+	let mut result = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find(|c: char| c.is_ascii_alphanumeric()) {
+		result.push_str(&rest[..start]);
+		rest = &rest[start..];
+		let end = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+		let word = &rest[..end];
+		match Pubkey::from_str(word).ok().and_then(|pubkey| aliases.names.get(&pubkey)) {
+			Some(name) => result.push_str(&aliased_display(name, word)),
+			None => result.push_str(word)
+		}
+		rest = &rest[end..];
+	}
+	result.push_str(rest);
+	result
+}
@@ -0,0 +1,70 @@
+use std::ops::Range;
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{debug_ledger::BokkenLedger, error::BokkenDetailedError};
+
+/// The state of an account before and after a transaction
+#[derive(Debug)]
+pub struct BokkenAccountDiff {
+	pub pubkey: Pubkey,
+	pub pre: BokkenAccountData,
+	pub post: BokkenAccountData,
+	/// Byte ranges of the account data which differ. Ranges past the end of the shorter data are included.
+	pub changed_ranges: Vec<Range<usize>>
+}
+
+/// Returns the contiguous byte ranges which differ between `pre` and `post`
+pub fn diff_byte_ranges(pre: &[u8], post: &[u8]) -> Vec<Range<usize>> {
+	let mut ranges = Vec::new();
+	let mut range_start = None;
+	for i in 0..pre.len().max(post.len()) {
+		if pre.get(i) != post.get(i) {
+			if range_start.is_none() {
+				range_start = Some(i);
+			}
+		}else if let Some(start) = range_start.take() {
+			ranges.push(start..i);
+		}
+	}
+	if let Some(start) = range_start {
+		ranges.push(start..pre.len().max(post.len()));
+	}
+	ranges
+}
+
+/// Compares every writable account of the transaction as it was before and after it was committed. Returns the
+/// transaction's slot along with the diffs, or `None` if the transaction wasn't found.
+/// 
+/// The states are read from the ledger at the slot before the transaction's and at the transaction's own slot, as
+/// that's where it saved its changes. Account edits through admin methods like `bokken_setAccountOwner` are saved as
+/// part of the current slot though, so any made after the transaction, and before the next one, show up in its post
+/// state.
+pub async fn get_transaction_account_diff(
+	ledger: &BokkenLedger,
+	signature: [u8; 64]
+) -> Result<Option<(u64, Vec<BokkenAccountDiff>)>, BokkenDetailedError> {
+	let Some(entry) = ledger.get_bokken_entry_by_tx(signature).await? else {
+		return Ok(None);
+	};
+	let message = &entry.tx_data.message;
+	let mut diffs = Vec::new();
+	for (i, pubkey) in message.account_keys.iter().enumerate() {
+		if !message.is_writable(i) {
+			continue;
+		}
+		// Transactions save their changes as part of the slot they were committed in
+		let pre = ledger.read_account_at_slot(pubkey, entry.slot.saturating_sub(1), None).await?;
+		let post = ledger.read_account_at_slot(pubkey, entry.slot, None).await?;
+		diffs.push(
+			BokkenAccountDiff {
+				pubkey: *pubkey,
+				changed_ranges: diff_byte_ranges(&pre.data, &post.data),
+				pre,
+				post
+			}
+		);
+	}
+	Ok(Some((entry.slot, diffs)))
+}
@@ -0,0 +1,515 @@
+//! Decoding program accounts into readable fields, using schemas registered per program.
+//!
+//! Schemas are written like (legacy or 0.30) Anchor IDLs, only `name`, `accounts`, and `types` are used:
+//!
+//! ```json
+//! {
+//! 	"name": "my_program",
+//! 	"accounts": [
+//! 		{"name": "Counter", "type": {"kind": "struct", "fields": [
+//! 			{"name": "authority", "type": "publicKey"},
+//! 			{"name": "count", "type": "u64"},
+//! 			{"name": "history", "type": {"vec": {"defined": "Entry"}}}
+//! 		]}}
+//! 	],
+//! 	"types": [
+//! 		{"name": "Entry", "type": {"kind": "struct", "fields": [{"name": "slot", "type": "u64"}]}}
+//! 	]
+//! }
+//! ```
+//!
+//! Anchor accounts are told apart by their 8-byte discriminator. Plain Borsh accounts don't have one, so each account
+//! type is tried in order until one of them fits.
+//!
+//! Programs with an Anchor schema also get an IDL account at the address `anchor idl init` would create it at, so
+//! `Program.fetchIdl` works without uploading the IDL on-chain first.
+
+use std::{collections::HashMap, io::{self, Write}, path::PathBuf, sync::{Arc, RwLock}};
+
+use borsh::BorshSerialize;
+use flate2::{write::ZlibEncoder, Compression};
+use solana_sdk::{hash::hashv, pubkey::Pubkey};
+use tokio::fs;
+
+use crate::error::{BokkenError, BokkenDetailedError};
+
+/// Length of Anchor's account discriminators
+const ANCHOR_DISCRIMINATOR_LEN: usize = 8;
+/// Seed Anchor derives the IDL account's address with
+const ANCHOR_IDL_SEED: &str = "anchor:idl";
+/// Deeper than any sane account layout, but shallow enough that self-referencing types can't overflow the stack
+const MAX_DECODE_DEPTH: usize = 64;
+
+/// How accounts described by a schema are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BokkenAccountSchemaFormat {
+	/// Borsh-encoded and prefixed with `sha256("account:<name>")[..8]`, or the account's `discriminator` if specified
+	Anchor,
+	/// Borsh-encoded without any prefix
+	Borsh
+}
+impl Default for BokkenAccountSchemaFormat {
+	fn default() -> Self {
+		Self::Anchor
+	}
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+enum IdlDefined {
+	Name(String),
+	Generic {
+		name: String
+	}
+}
+impl IdlDefined {
+	fn name(&self) -> &str {
+		match self {
+			Self::Name(name) => name,
+			Self::Generic { name } => name
+		}
+	}
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+enum IdlType {
+	Primitive(String),
+	Vec {
+		vec: Box<IdlType>
+	},
+	Option {
+		option: Box<IdlType>
+	},
+	COption {
+		coption: Box<IdlType>
+	},
+	Array {
+		array: (Box<IdlType>, usize)
+	},
+	Defined {
+		defined: IdlDefined
+	}
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct IdlField {
+	name: String,
+	#[serde(rename = "type")]
+	ty: IdlType
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+enum IdlFields {
+	Named(Vec<IdlField>),
+	Tuple(Vec<IdlType>)
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct IdlEnumVariant {
+	name: String,
+	#[serde(default)]
+	fields: Option<IdlFields>
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum IdlTypeDefTy {
+	Struct {
+		#[serde(default)]
+		fields: Option<IdlFields>
+	},
+	Enum {
+		variants: Vec<IdlEnumVariant>
+	}
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct IdlTypeDef {
+	name: String,
+	#[serde(rename = "type")]
+	ty: IdlTypeDefTy
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct IdlAccountDef {
+	name: String,
+	/// Anchor 0.30 IDLs specify the type separately in `types`
+	#[serde(rename = "type", default)]
+	ty: Option<IdlTypeDefTy>,
+	#[serde(default)]
+	discriminator: Option<Vec<u8>>
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct IdlMetadata {
+	#[serde(default)]
+	name: Option<String>
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct Idl {
+	#[serde(default)]
+	name: Option<String>,
+	#[serde(default)]
+	metadata: Option<IdlMetadata>,
+	#[serde(default)]
+	accounts: Vec<IdlAccountDef>,
+	#[serde(default)]
+	types: Vec<IdlTypeDef>
+}
+
+/// Reads Borsh-encoded values described by IDL types
+struct BorshDecoder<'a> {
+	data: &'a [u8],
+	pos: usize,
+	types: &'a HashMap<String, IdlTypeDefTy>
+}
+impl<'a> BorshDecoder<'a> {
+	fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+		let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len())
+			.ok_or_else(|| format!("Unexpected end of data at offset {}", self.pos))?;
+		let bytes = &self.data[self.pos..end];
+		self.pos = end;
+		Ok(bytes)
+	}
+	fn take_array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+		Ok(self.take(N)?.try_into().expect("slice to be N bytes"))
+	}
+	fn take_len(&mut self) -> Result<usize, String> {
+		let len = u32::from_le_bytes(self.take_array()?) as usize;
+		// Every element takes at least a byte in practice, so this stops garbage lengths from allocating huge vecs
+		if len > self.data.len() - self.pos {
+			return Err(format!("Length {} at offset {} is longer than the remaining data", len, self.pos - 4));
+		}
+		Ok(len)
+	}
+	fn decode_primitive(&mut self, name: &str) -> Result<serde_json::Value, String> {
+		Ok(
+			match name {
+				"bool" => serde_json::Value::from(self.take(1)?[0] != 0),
+				"u8" => serde_json::Value::from(self.take(1)?[0]),
+				"i8" => serde_json::Value::from(self.take(1)?[0] as i8),
+				"u16" => serde_json::Value::from(u16::from_le_bytes(self.take_array()?)),
+				"i16" => serde_json::Value::from(i16::from_le_bytes(self.take_array()?)),
+				"u32" => serde_json::Value::from(u32::from_le_bytes(self.take_array()?)),
+				"i32" => serde_json::Value::from(i32::from_le_bytes(self.take_array()?)),
+				"u64" => serde_json::Value::from(u64::from_le_bytes(self.take_array()?)),
+				"i64" => serde_json::Value::from(i64::from_le_bytes(self.take_array()?)),
+				"f32" => serde_json::Value::from(f32::from_le_bytes(self.take_array()?)),
+				"f64" => serde_json::Value::from(f64::from_le_bytes(self.take_array()?)),
+				// JSON numbers can't hold these without losing precision
+				"u128" => serde_json::Value::from(u128::from_le_bytes(self.take_array()?).to_string()),
+				"i128" => serde_json::Value::from(i128::from_le_bytes(self.take_array()?).to_string()),
+				"publicKey" | "pubkey" => serde_json::Value::from(Pubkey::new_from_array(self.take_array()?).to_string()),
+				"string" => {
+					let len = self.take_len()?;
+					serde_json::Value::from(
+						String::from_utf8(self.take(len)?.to_vec()).map_err(|e| e.to_string())?
+					)
+				},
+				"bytes" => {
+					let len = self.take_len()?;
+					serde_json::Value::from(base64::encode(self.take(len)?))
+				},
+				_ => return Err(format!("Unknown type {:?}", name))
+			}
+		)
+	}
+	fn decode_type(&mut self, ty: &IdlType, depth: usize) -> Result<serde_json::Value, String> {
+		if depth > MAX_DECODE_DEPTH {
+			return Err("Types are nested too deeply".to_string());
+		}
+		match ty {
+			IdlType::Primitive(name) => self.decode_primitive(name),
+			IdlType::Vec { vec } => {
+				let len = self.take_len()?;
+				let mut values = Vec::with_capacity(len);
+				for _ in 0..len {
+					values.push(self.decode_type(vec, depth + 1)?);
+				}
+				Ok(serde_json::Value::Array(values))
+			},
+			IdlType::Option { option } => {
+				match self.take(1)?[0] {
+					0 => Ok(serde_json::Value::Null),
+					_ => self.decode_type(option, depth + 1)
+				}
+			},
+			IdlType::COption { coption } => {
+				match u32::from_le_bytes(self.take_array()?) {
+					0 => {
+						// Unlike Borsh options, the value is always there even if it's None
+						self.decode_type(coption, depth + 1)?;
+						Ok(serde_json::Value::Null)
+					},
+					_ => self.decode_type(coption, depth + 1)
+				}
+			},
+			IdlType::Array { array: (ty, len) } => {
+				let mut values = Vec::with_capacity((*len).min(self.data.len()));
+				for _ in 0..*len {
+					values.push(self.decode_type(ty, depth + 1)?);
+				}
+				Ok(serde_json::Value::Array(values))
+			},
+			IdlType::Defined { defined } => {
+				let types = self.types;
+				let def = types.get(defined.name()).ok_or_else(|| format!("Unknown type {:?}", defined.name()))?;
+				self.decode_type_def(def, depth + 1)
+			}
+		}
+	}
+	fn decode_fields(&mut self, fields: &Option<IdlFields>, depth: usize) -> Result<serde_json::Value, String> {
+		match fields {
+			None => Ok(serde_json::Value::Object(serde_json::Map::new())),
+			Some(IdlFields::Named(fields)) => {
+				let mut values = serde_json::Map::new();
+				for field in fields.iter() {
+					values.insert(field.name.clone(), self.decode_type(&field.ty, depth)?);
+				}
+				Ok(serde_json::Value::Object(values))
+			},
+			Some(IdlFields::Tuple(types)) => {
+				let mut values = Vec::with_capacity(types.len());
+				for ty in types.iter() {
+					values.push(self.decode_type(ty, depth)?);
+				}
+				Ok(serde_json::Value::Array(values))
+			}
+		}
+	}
+	fn decode_type_def(&mut self, def: &IdlTypeDefTy, depth: usize) -> Result<serde_json::Value, String> {
+		match def {
+			IdlTypeDefTy::Struct { fields } => self.decode_fields(fields, depth),
+			IdlTypeDefTy::Enum { variants } => {
+				let index = self.take(1)?[0];
+				let variant = variants.get(index as usize)
+					.ok_or_else(|| format!("Enum variant {} at offset {} doesn't exist", index, self.pos - 1))?;
+				match &variant.fields {
+					// Unit variants are just their name, like serde does it
+					None => Ok(serde_json::Value::from(variant.name.clone())),
+					fields => {
+						let mut value = serde_json::Map::new();
+						value.insert(variant.name.clone(), self.decode_fields(fields, depth)?);
+						Ok(serde_json::Value::Object(value))
+					}
+				}
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+struct BokkenAccountType {
+	name: String,
+	discriminator: Option<Vec<u8>>,
+	ty: IdlTypeDefTy
+}
+
+/// Describes the accounts of a single program
+#[derive(Debug, Clone)]
+pub struct BokkenAccountSchema {
+	format: BokkenAccountSchemaFormat,
+	/// The schema as it was registered
+	document: serde_json::Value,
+	program_name: Option<String>,
+	account_types: Vec<BokkenAccountType>,
+	types: HashMap<String, IdlTypeDefTy>
+}
+impl BokkenAccountSchema {
+	pub fn new(format: BokkenAccountSchemaFormat, document: serde_json::Value) -> Result<Self, BokkenError> {
+		let idl: Idl = serde_json::from_value(document.clone())
+			.map_err(|e| BokkenError::InvalidAccountSchema(e.to_string()))?;
+		let types: HashMap<String, IdlTypeDefTy> = idl.types.into_iter().map(|def| (def.name, def.ty)).collect();
+		let mut account_types = Vec::with_capacity(idl.accounts.len());
+		for account in idl.accounts.into_iter() {
+			let ty = match account.ty {
+				Some(ty) => ty,
+				None => types.get(&account.name).cloned().ok_or_else(|| {
+					BokkenError::InvalidAccountSchema(format!("Account {} has no type", account.name))
+				})?
+			};
+			let discriminator = match format {
+				BokkenAccountSchemaFormat::Anchor => Some(
+					account.discriminator.unwrap_or_else(|| {
+						hashv(&[b"account:", account.name.as_bytes()]).to_bytes()[..ANCHOR_DISCRIMINATOR_LEN].to_vec()
+					})
+				),
+				BokkenAccountSchemaFormat::Borsh => account.discriminator
+			};
+			account_types.push(
+				BokkenAccountType {
+					name: account.name,
+					discriminator,
+					ty
+				}
+			);
+		}
+		Ok(
+			Self {
+				format,
+				document,
+				program_name: idl.name.or(idl.metadata.and_then(|metadata| metadata.name)),
+				account_types,
+				types
+			}
+		)
+	}
+	pub fn from_json(format: BokkenAccountSchemaFormat, json: &str) -> Result<Self, BokkenError> {
+		Self::new(format, serde_json::from_str(json).map_err(|e| BokkenError::InvalidAccountSchema(e.to_string()))?)
+	}
+	pub fn format(&self) -> BokkenAccountSchemaFormat {
+		self.format
+	}
+	pub fn document(&self) -> &serde_json::Value {
+		&self.document
+	}
+	pub fn program_name(&self) -> Option<&str> {
+		self.program_name.as_deref()
+	}
+	pub fn account_type_names(&self) -> Vec<String> {
+		self.account_types.iter().map(|account_type| account_type.name.clone()).collect()
+	}
+	/// Returns the name of the account's type and its decoded fields, or None if none of the account types fit
+	pub fn decode(&self, data: &[u8]) -> Option<(String, serde_json::Value)> {
+		self.account_types.iter().find_map(|account_type| {
+			let offset = match &account_type.discriminator {
+				Some(discriminator) if data.starts_with(discriminator) => discriminator.len(),
+				Some(_) => return None,
+				None => 0
+			};
+			let mut decoder = BorshDecoder {
+				data,
+				pos: offset,
+				types: &self.types
+			};
+			// Trailing bytes are fine, accounts are often allocated larger than their contents
+			let value = decoder.decode_type_def(&account_type.ty, 0).ok()?;
+			Some((account_type.name.clone(), value))
+		})
+	}
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BokkenAccountSchemaFile {
+	format: BokkenAccountSchemaFormat,
+	schema: serde_json::Value
+}
+
+/// The address Anchor keeps the program's IDL at
+pub fn anchor_idl_address(program_id: &Pubkey) -> Pubkey {
+	let (base, _) = Pubkey::find_program_address(&[], program_id);
+	Pubkey::create_with_seed(&base, ANCHOR_IDL_SEED, program_id).expect("IDL seed should be valid")
+}
+
+/// Anchor's on-chain `IdlAccount`
+#[derive(BorshSerialize)]
+struct AnchorIdlAccount {
+	authority: Pubkey,
+	/// zlib-compressed IDL JSON
+	data: Vec<u8>
+}
+
+/// The account schemas registered for each program, saved in the ledger
+#[derive(Debug)]
+pub struct BokkenAccountSchemaRegistry {
+	path: PathBuf,
+	schemas: RwLock<HashMap<Pubkey, Arc<BokkenAccountSchema>>>,
+	/// IDL account address -> program ID, for programs with an Anchor schema
+	idl_addresses: RwLock<HashMap<Pubkey, Pubkey>>
+}
+impl BokkenAccountSchemaRegistry {
+	/// Loads every schema saved in `path`
+	pub async fn load(path: PathBuf) -> Result<Self, BokkenDetailedError> {
+		let mut schemas = HashMap::new();
+		match fs::read_dir(&path).await {
+			Ok(mut files) => {
+				while let Some(file) = files.next_entry().await? {
+					let file_name = file.file_name();
+					let Some(program_id) = file_name.to_str()
+						.and_then(|name| name.strip_suffix(".json"))
+						.and_then(|name| name.parse::<Pubkey>().ok())
+					else {
+						continue;
+					};
+					let schema_file: BokkenAccountSchemaFile = serde_json::from_slice(&fs::read(file.path()).await?)
+						.map_err(io::Error::from)?;
+					schemas.insert(program_id, Arc::new(BokkenAccountSchema::new(schema_file.format, schema_file.schema)?));
+				}
+			},
+			Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+			Err(e) => return Err(e.into())
+		}
+		let idl_addresses = schemas.iter()
+			.filter(|(_, schema)| schema.format == BokkenAccountSchemaFormat::Anchor)
+			.map(|(program_id, _)| (anchor_idl_address(program_id), *program_id))
+			.collect();
+		Ok(
+			Self {
+				path,
+				schemas: RwLock::new(schemas),
+				idl_addresses: RwLock::new(idl_addresses)
+			}
+		)
+	}
+	pub fn get(&self, program_id: &Pubkey) -> Option<Arc<BokkenAccountSchema>> {
+		self.schemas.read().unwrap().get(program_id).cloned()
+	}
+	/// Every program with a schema, sorted by program ID
+	pub fn program_ids(&self) -> Vec<Pubkey> {
+		let mut program_ids: Vec<Pubkey> = self.schemas.read().unwrap().keys().copied().collect();
+		program_ids.sort();
+		program_ids
+	}
+	/// Saves the schema, replacing any schema the program already had
+	pub async fn register(&self, program_id: Pubkey, schema: BokkenAccountSchema) -> Result<(), BokkenDetailedError> {
+		fs::create_dir_all(&self.path).await?;
+		fs::write(
+			self.path.join(format!("{}.json", program_id)),
+			serde_json::to_vec_pretty(
+				&BokkenAccountSchemaFile {
+					format: schema.format,
+					schema: schema.document.clone()
+				}
+			).map_err(io::Error::from)?
+		).await?;
+		let idl_address = anchor_idl_address(&program_id);
+		if schema.format == BokkenAccountSchemaFormat::Anchor {
+			self.idl_addresses.write().unwrap().insert(idl_address, program_id);
+		}else{
+			self.idl_addresses.write().unwrap().remove(&idl_address);
+		}
+		self.schemas.write().unwrap().insert(program_id, Arc::new(schema));
+		Ok(())
+	}
+	/// Returns whether the program had a schema
+	pub async fn unregister(&self, program_id: &Pubkey) -> Result<bool, BokkenDetailedError> {
+		if self.schemas.write().unwrap().remove(program_id).is_none() {
+			return Ok(false);
+		}
+		self.idl_addresses.write().unwrap().remove(&anchor_idl_address(program_id));
+		match fs::remove_file(self.path.join(format!("{}.json", program_id))).await {
+			Ok(_) => Ok(true),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(true),
+			Err(e) => Err(e.into())
+		}
+	}
+	/// If `pubkey` is the IDL account of a program with an Anchor schema, the program ID and the IDL account's data as
+	/// `anchor idl init` would've written it
+	pub fn anchor_idl_account_data(&self, pubkey: &Pubkey, authority: &Pubkey) -> Option<(Pubkey, Vec<u8>)> {
+		let program_id = *self.idl_addresses.read().unwrap().get(pubkey)?;
+		let schema = self.get(&program_id)?;
+		let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+		encoder.write_all(&serde_json::to_vec(&schema.document).ok()?).ok()?;
+		let idl_account = AnchorIdlAccount {
+			authority: *authority,
+			data: encoder.finish().ok()?
+		};
+		let mut data = hashv(&[b"account:IdlAccount"]).to_bytes()[..ANCHOR_DISCRIMINATOR_LEN].to_vec();
+		idl_account.serialize(&mut data).ok()?;
+		Some((program_id, data))
+	}
+}
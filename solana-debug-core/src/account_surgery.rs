@@ -0,0 +1,235 @@
+//! Targeted edits of account state, for forging ledger state without having to hand-craft entire accounts.
+//!
+//! Every edit is written to the ledger's audit log, so that state which didn't come from a transaction can be told
+//! apart later.
+
+use std::{path::PathBuf, time::{SystemTime, UNIX_EPOCH}};
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::{pubkey, pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction::MAX_PERMITTED_DATA_LENGTH};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+use crate::{debug_ledger::BokkenLedger, error::{BokkenError, BokkenDetailedError}};
+
+pub const PUBKEY_TOKEN_PROGRAM: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCYEgxmCC4crt3fwxLeTn");
+pub const PUBKEY_TOKEN_2022_PROGRAM: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PnBZEbRthaQaDx3K");
+
+/// Size of an SPL token account without any token-2022 extensions
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const TOKEN_ACCOUNT_MINT_OFFSET: usize = 0;
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+const TOKEN_ACCOUNT_STATE_OFFSET: usize = 108;
+/// `COption<u64>` holding the rent-exempt reserve of wrapped SOL accounts
+const TOKEN_ACCOUNT_IS_NATIVE_OFFSET: usize = 109;
+/// Size of an SPL mint without any token-2022 extensions
+const MINT_LEN: usize = 82;
+const MINT_SUPPLY_OFFSET: usize = 36;
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+	u64::from_le_bytes(data[offset..offset + 8].try_into().expect("slice to be 8 bytes"))
+}
+fn write_u64(data: &mut [u8], offset: usize, value: u64) {
+	data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+fn is_token_program(program_id: &Pubkey) -> bool {
+	*program_id == PUBKEY_TOKEN_PROGRAM || *program_id == PUBKEY_TOKEN_2022_PROGRAM
+}
+
+/// Append-only record of every account edit which didn't come from a transaction
+#[derive(Debug)]
+pub struct BokkenAuditLog {
+	path: PathBuf
+}
+impl BokkenAuditLog {
+	pub fn new(path: PathBuf) -> Self {
+		Self {
+			path
+		}
+	}
+	/// The audit log saved alongside the ledger's state
+	pub fn for_ledger(ledger: &BokkenLedger) -> Self {
+		Self::new(ledger.base_path().join("audit.log"))
+	}
+	/// Appends a line with the current time and slot to the log. It's only written to the file, where it doesn't get
+	/// lost among RPC logs.
+	pub async fn record(&self, slot: u64, action: &str, details: &str) -> Result<(), BokkenDetailedError> {
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).expect("We're in 1970").as_secs();
+		let line = format!("{} slot={} {} {}\n", timestamp, slot, action, details);
+		let mut file = OpenOptions::new().create(true).append(true).open(&self.path).await?;
+		file.write_all(line.as_bytes()).await?;
+		Ok(())
+	}
+}
+
+async fn read_existing_account(ledger: &BokkenLedger, pubkey: &Pubkey) -> Result<BokkenAccountData, BokkenDetailedError> {
+	let account = ledger.read_account(pubkey, None).await?;
+	if account.lamports == 0 {
+		return Err(BokkenError::AccountNotFound(*pubkey).into());
+	}
+	Ok(account)
+}
+
+/// Changes the owner of an existing account, returning its new state
+pub async fn set_account_owner(
+	ledger: &BokkenLedger,
+	pubkey: &Pubkey,
+	owner: &Pubkey
+) -> Result<BokkenAccountData, BokkenDetailedError> {
+	let _commit_guard = ledger.lock_commits().await;
+	let mut account = read_existing_account(ledger, pubkey).await?;
+	let old_owner = account.owner;
+	account.owner = *owner;
+	ledger.save_account(pubkey, &account).await?;
+	BokkenAuditLog::for_ledger(ledger).record(
+		ledger.slot().await,
+		"setAccountOwner",
+		&format!("account={} old_owner={} new_owner={}", pubkey, old_owner, owner)
+	).await?;
+	Ok(account)
+}
+
+/// Overwrites `bytes.len()` bytes of an existing account's data starting at `offset`, returning its new state. The
+/// data is never resized, so the patch must fit within it.
+pub async fn patch_account_data(
+	ledger: &BokkenLedger,
+	pubkey: &Pubkey,
+	offset: usize,
+	bytes: &[u8]
+) -> Result<BokkenAccountData, BokkenDetailedError> {
+	let _commit_guard = ledger.lock_commits().await;
+	let mut account = read_existing_account(ledger, pubkey).await?;
+	let end = offset.checked_add(bytes.len()).filter(|end| *end <= account.data.len()).ok_or(
+		BokkenError::AccountDataOutOfBounds(offset, bytes.len(), account.data.len())
+	)?;
+	let old_bytes = account.data[offset..end].to_vec();
+	account.data[offset..end].copy_from_slice(bytes);
+	ledger.save_account(pubkey, &account).await?;
+	BokkenAuditLog::for_ledger(ledger).record(
+		ledger.slot().await,
+		"patchAccountData",
+		&format!(
+			"account={} offset={} old={} new={}",
+			pubkey,
+			offset,
+			bs58::encode(&old_bytes).into_string(),
+			bs58::encode(bytes).into_string()
+		)
+	).await?;
+	Ok(account)
+}
+
+/// Sets the balance of an SPL token (or token-2022) account, returning its new state.
+///
+/// The supply of the token's mint is adjusted by the same amount so that the two stay consistent, and wrapped SOL
+/// accounts get their lamports adjusted to match the new balance, like `SyncNative` would.
+pub async fn set_token_balance(
+	ledger: &BokkenLedger,
+	token_account: &Pubkey,
+	amount: u64
+) -> Result<BokkenAccountData, BokkenDetailedError> {
+	let _commit_guard = ledger.lock_commits().await;
+	let mut account = read_existing_account(ledger, token_account).await?;
+	if
+		!is_token_program(&account.owner) ||
+		account.data.len() < TOKEN_ACCOUNT_LEN ||
+		// Uninitialized
+		account.data[TOKEN_ACCOUNT_STATE_OFFSET] == 0
+	{
+		return Err(BokkenError::NotATokenAccount(*token_account).into());
+	}
+	let old_amount = read_u64(&account.data, TOKEN_ACCOUNT_AMOUNT_OFFSET);
+	write_u64(&mut account.data, TOKEN_ACCOUNT_AMOUNT_OFFSET, amount);
+	let is_native = account.data[TOKEN_ACCOUNT_IS_NATIVE_OFFSET] != 0;
+	if is_native {
+		let rent_exempt_reserve = read_u64(&account.data, TOKEN_ACCOUNT_IS_NATIVE_OFFSET + 4);
+		account.lamports = rent_exempt_reserve.saturating_add(amount);
+	}
+
+	let mint_pubkey = Pubkey::new_from_array(
+		account.data[TOKEN_ACCOUNT_MINT_OFFSET..TOKEN_ACCOUNT_MINT_OFFSET + 32].try_into().expect("slice to be 32 bytes")
+	);
+	let mut mint = ledger.read_account(&mint_pubkey, None).await?;
+	let mint_supply_details = if
+		// Wrapped SOL mints don't keep track of their supply
+		!is_native &&
+		mint.lamports > 0 &&
+		mint.owner == account.owner &&
+		mint.data.len() >= MINT_LEN
+	{
+		let old_supply = read_u64(&mint.data, MINT_SUPPLY_OFFSET);
+		let new_supply = if amount >= old_amount {
+			old_supply.saturating_add(amount - old_amount)
+		}else{
+			old_supply.saturating_sub(old_amount - amount)
+		};
+		write_u64(&mut mint.data, MINT_SUPPLY_OFFSET, new_supply);
+		ledger.save_account(&mint_pubkey, &mint).await?;
+		format!(" old_supply={} new_supply={}", old_supply, new_supply)
+	}else{
+		String::new()
+	};
+	ledger.save_account(token_account, &account).await?;
+	BokkenAuditLog::for_ledger(ledger).record(
+		ledger.slot().await,
+		"setTokenBalance",
+		&format!(
+			"account={} mint={} old_amount={} new_amount={}{}",
+			token_account,
+			mint_pubkey,
+			old_amount,
+			amount,
+			mint_supply_details
+		)
+	).await?;
+	Ok(account)
+}
+
+/// Creates a zeroed account of `space` bytes owned by `program_id`, as if it had been allocated through the system
+/// program. Returns the new account's address, the PDA bump if `seeds` were specified, and its state.
+///
+/// The account is created at the program address derived from `seeds` if specified, otherwise at a random address.
+/// It holds the larger of `lamports` and the rent-exempt minimum.
+pub async fn create_program_owned_account(
+	ledger: &BokkenLedger,
+	program_id: &Pubkey,
+	space: usize,
+	lamports: Option<u64>,
+	seeds: Option<&[Vec<u8>]>
+) -> Result<(Pubkey, Option<u8>, BokkenAccountData), BokkenDetailedError> {
+	if space as u64 > MAX_PERMITTED_DATA_LENGTH {
+		return Err(BokkenError::AccountTooLarge(space, MAX_PERMITTED_DATA_LENGTH).into());
+	}
+	let (pubkey, bump) = match seeds {
+		Some(seeds) => {
+			let seeds: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+			let (pubkey, bump) = Pubkey::try_find_program_address(&seeds, program_id).ok_or(BokkenError::InvalidSeeds)?;
+			(pubkey, Some(bump))
+		},
+		None => (Keypair::new().pubkey(), None)
+	};
+	let _commit_guard = ledger.lock_commits().await;
+	if ledger.read_account(&pubkey, None).await?.lamports > 0 {
+		return Err(BokkenError::AccountAlreadyExists(pubkey).into());
+	}
+	let account = BokkenAccountData {
+		lamports: lamports.unwrap_or_default().max(ledger.calc_min_balance_for_rent_exemption(space as u64)),
+		data: vec![0; space],
+		owner: *program_id,
+		executable: false,
+		rent_epoch: 0
+	};
+	ledger.save_account(&pubkey, &account).await?;
+	BokkenAuditLog::for_ledger(ledger).record(
+		ledger.slot().await,
+		"createProgramOwnedAccount",
+		&format!(
+			"account={} owner={} space={} lamports={}{}",
+			pubkey,
+			program_id,
+			space,
+			account.lamports,
+			bump.map(|bump| format!(" bump={}", bump)).unwrap_or_default()
+		)
+	).await?;
+	Ok((pubkey, bump, account))
+}
@@ -0,0 +1,188 @@
+use std::{io, ops::Range, path::{Path, PathBuf}, time::Duration};
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::pubkey::Pubkey;
+use tokio::fs;
+
+use crate::{
+	account_diff::diff_byte_ranges,
+	account_schema::BokkenAccountSchemaRegistry,
+	debug_ledger::{parse_account_file, read_account_dir, read_ledger_block_count},
+	error::BokkenDetailedError
+};
+
+/// A decoded field whose value changed, or which only exists before or after the change
+#[derive(Debug, Clone)]
+pub struct BokkenFieldChange {
+	/// Dot-separated path to the field, e.g. `config.authority`
+	pub path: String,
+	pub pre: Option<serde_json::Value>,
+	pub post: Option<serde_json::Value>
+}
+
+/// A change to a watched account
+#[derive(Debug, Clone)]
+pub struct BokkenAccountWatchChange {
+	/// The slot the account changed at
+	pub slot: u64,
+	pub pre: BokkenAccountData,
+	pub post: BokkenAccountData,
+	/// Byte ranges of the account data which differ
+	pub changed_ranges: Vec<Range<usize>>,
+	/// The account type and the fields which changed, if the owner has a schema registered which the new state could
+	/// be decoded with
+	pub decoded: Option<(String, Vec<BokkenFieldChange>)>
+}
+impl BokkenAccountWatchChange {
+	pub fn lamports_delta(&self) -> i128 {
+		self.post.lamports as i128 - self.pre.lamports as i128
+	}
+}
+
+/// Follows a single account of a saved ledger, straight from the files on disk, so a ledger which is in use by a
+/// running Bokken instance can be watched from another process.
+///
+/// Account states are only picked up once the block they were saved for has been committed, so a transaction which is
+/// halfway through being committed, or which fails to be, is never reported.
+#[derive(Debug)]
+pub struct BokkenAccountWatcher {
+	ledger_path: PathBuf,
+	pubkey: Pubkey,
+	/// Loaded once, schemas registered after the watcher was created aren't used
+	schemas: BokkenAccountSchemaRegistry,
+	slot: u64,
+	account: BokkenAccountData
+}
+impl BokkenAccountWatcher {
+	pub async fn new(ledger_path: PathBuf, pubkey: Pubkey) -> Result<Self, BokkenDetailedError> {
+		let schemas = BokkenAccountSchemaRegistry::load(ledger_path.join("account_schemas")).await?;
+		let slot = read_ledger_block_count(&ledger_path).await?;
+		let account = read_account_as_of(&ledger_path, &pubkey, slot).await?;
+		Ok(
+			Self {
+				ledger_path,
+				pubkey,
+				schemas,
+				slot,
+				account
+			}
+		)
+	}
+	pub fn pubkey(&self) -> &Pubkey {
+		&self.pubkey
+	}
+	/// The latest slot which has been looked at
+	pub fn slot(&self) -> u64 {
+		self.slot
+	}
+	/// The state of the account as of `slot()`
+	pub fn account(&self) -> &BokkenAccountData {
+		&self.account
+	}
+	/// Waits until the account changes, checking for new blocks every `poll_interval`
+	pub async fn next_change(&mut self, poll_interval: Duration) -> Result<BokkenAccountWatchChange, BokkenDetailedError> {
+		loop {
+			let slot = read_ledger_block_count(&self.ledger_path).await?;
+			if slot != self.slot {
+				let account = read_account_as_of(&self.ledger_path, &self.pubkey, slot).await?;
+				self.slot = slot;
+				if account != self.account {
+					let pre = std::mem::replace(&mut self.account, account.clone());
+					return Ok(self.describe_change(slot, pre, account));
+				}
+			}
+			tokio::time::sleep(poll_interval).await;
+		}
+	}
+	fn describe_change(&self, slot: u64, pre: BokkenAccountData, post: BokkenAccountData) -> BokkenAccountWatchChange {
+		let decoded = self.schemas.get(&post.owner).and_then(|schema| {
+			let (account_type, post_fields) = schema.decode(&post.data)?;
+			// A different account type before means the account was just initialized, so every field is new
+			let pre_fields = schema.decode(&pre.data)
+				.filter(|(pre_type, _)| *pre_type == account_type)
+				.map(|(_, pre_fields)| pre_fields);
+			let mut changed_fields = Vec::new();
+			diff_fields(String::new(), pre_fields.as_ref(), Some(&post_fields), &mut changed_fields);
+			Some((account_type, changed_fields))
+		});
+		BokkenAccountWatchChange {
+			slot,
+			changed_ranges: diff_byte_ranges(&pre.data, &post.data),
+			pre,
+			post,
+			decoded
+		}
+	}
+}
+
+/// Reads the latest state of the account saved at or before `max_slot`, which is a defaulted account if it doesn't
+/// exist
+pub(crate) async fn read_account_as_of(
+	ledger_path: &Path,
+	pubkey: &Pubkey,
+	max_slot: u64
+) -> Result<BokkenAccountData, BokkenDetailedError> {
+	let Some((account_dir, mut files)) = read_account_dir(ledger_path, pubkey).await? else {
+		return Ok(BokkenAccountData::default());
+	};
+	let mut latest_slot = None;
+	while let Some(file) = files.next_entry().await? {
+		let Ok(file_slot) = file.file_name().to_str().unwrap_or_default().parse::<u64>() else {
+			continue;
+		};
+		if file_slot <= max_slot && latest_slot.map_or(true, |latest_slot| file_slot > latest_slot) {
+			latest_slot = Some(file_slot);
+		}
+	}
+	let Some(latest_slot) = latest_slot else {
+		return Ok(BokkenAccountData::default());
+	};
+	match fs::read(account_dir.join(latest_slot.to_string())).await {
+		Ok(file_data) => Ok(parse_account_file(&file_data)?),
+		// Compacted away since we listed the directory
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BokkenAccountData::default()),
+		Err(e) => Err(e.into())
+	}
+}
+
+/// Treats a missing value as an empty object, so the fields of an object which was added or removed are listed one by one
+fn as_object<'a>(
+	value: Option<&'a serde_json::Value>,
+	empty: &'a serde_json::Map<String, serde_json::Value>
+) -> Option<&'a serde_json::Map<String, serde_json::Value>> {
+	match value {
+		Some(serde_json::Value::Object(object)) => Some(object),
+		Some(_) => None,
+		None => Some(empty)
+	}
+}
+
+/// Collects the leaf fields which differ between `pre` and `post`. Objects are compared field by field, anything else
+/// as a whole.
+fn diff_fields(
+	path: String,
+	pre: Option<&serde_json::Value>,
+	post: Option<&serde_json::Value>,
+	changes: &mut Vec<BokkenFieldChange>
+) {
+	let empty = serde_json::Map::new();
+	if let (Some(pre), Some(post)) = (as_object(pre, &empty), as_object(post, &empty)) {
+		let mut keys: Vec<&String> = pre.keys().chain(post.keys()).collect();
+		keys.sort();
+		keys.dedup();
+		for key in keys {
+			let field_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+			diff_fields(field_path, pre.get(key), post.get(key), changes);
+		}
+		return;
+	}
+	if pre != post {
+		changes.push(
+			BokkenFieldChange {
+				path,
+				pre: pre.cloned(),
+				post: post.cloned()
+			}
+		);
+	}
+}
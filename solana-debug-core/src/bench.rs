@@ -0,0 +1,115 @@
+//! Measures how fast a ledger processes transactions, used by `bokken bench` and the criterion benchmarks.
+
+use std::time::{Duration, Instant};
+
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction, transaction::Transaction};
+
+use crate::{debug_ledger::BokkenLedger, error::BokkenDetailedError};
+
+/// Lamports sent by each benchmark transfer, enough for the recipient to be rent-exempt
+pub const BENCH_TRANSFER_LAMPORTS: u64 = 1_000_000;
+
+/// Timings of a single benchmark transaction
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BokkenBenchTimings {
+	/// Building and signing the transaction
+	pub sign: Duration,
+	/// Executing the transaction and committing its block
+	pub execute: Duration,
+	/// Looking up the committed transaction by its signature
+	pub confirm: Duration
+}
+impl BokkenBenchTimings {
+	pub fn total(&self) -> Duration {
+		self.sign + self.execute + self.confirm
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct BokkenBenchReport {
+	pub transactions: usize,
+	pub failed: usize,
+	pub elapsed: Duration,
+	/// Timings of every transaction, sorted by total latency
+	pub timings: Vec<BokkenBenchTimings>
+}
+impl BokkenBenchReport {
+	pub fn transactions_per_second(&self) -> f64 {
+		self.transactions as f64 / self.elapsed.as_secs_f64()
+	}
+	/// Total latency at the specified percentile, between 0 and 100
+	pub fn latency_percentile(&self, percentile: f64) -> Duration {
+		if self.timings.is_empty() {
+			return Duration::ZERO;
+		}
+		let index = ((self.timings.len() - 1) as f64 * percentile / 100.0).round() as usize;
+		self.timings[index].total()
+	}
+	/// Average time spent in each stage
+	pub fn average_timings(&self) -> BokkenBenchTimings {
+		let count = self.timings.len().max(1) as u32;
+		let mut sum = BokkenBenchTimings::default();
+		for timings in self.timings.iter() {
+			sum.sign += timings.sign;
+			sum.execute += timings.execute;
+			sum.confirm += timings.confirm;
+		}
+		BokkenBenchTimings {
+			sign: sum.sign / count,
+			execute: sum.execute / count,
+			confirm: sum.confirm / count
+		}
+	}
+}
+
+/// Sends `BENCH_TRANSFER_LAMPORTS` from `payer` to a new account, returning how long each stage took
+pub async fn bench_transfer(ledger: &BokkenLedger, payer: &Keypair) -> Result<BokkenBenchTimings, BokkenDetailedError> {
+	let sign_start = Instant::now();
+	let tx = Transaction::new_signed_with_payer(
+		&[system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), BENCH_TRANSFER_LAMPORTS)],
+		Some(&payer.pubkey()),
+		&[payer],
+		Hash::new_from_array(ledger.blockhash().await)
+	);
+	let signature: [u8; 64] = tx.signatures[0].as_ref().try_into().expect("signatures to be 64 bytes");
+	let sign = sign_start.elapsed();
+
+	let execute_start = Instant::now();
+	ledger.execute_transaction(tx, true).await?;
+	let execute = execute_start.elapsed();
+
+	let confirm_start = Instant::now();
+	ledger.get_bokken_entry_by_tx(signature).await?;
+	let confirm = confirm_start.elapsed();
+
+	Ok(BokkenBenchTimings { sign, execute, confirm })
+}
+
+/// Sends `count` transfers from `payer` one after another. `payer` must hold at least `count` times
+/// `BENCH_TRANSFER_LAMPORTS` plus fees.
+pub async fn run_transfer_bench(
+	ledger: &BokkenLedger,
+	payer: &Keypair,
+	count: usize
+) -> BokkenBenchReport {
+	let mut timings = Vec::with_capacity(count);
+	let mut failed = 0;
+	let start = Instant::now();
+	for _ in 0..count {
+		match bench_transfer(ledger, payer).await {
+			Ok(tx_timings) => timings.push(tx_timings),
+			Err(e) => {
+				eprintln!("Benchmark transaction failed: {}", e);
+				failed += 1;
+			}
+		}
+	}
+	let elapsed = start.elapsed();
+	timings.sort_by_key(|timings| timings.total());
+	BokkenBenchReport {
+		transactions: count,
+		failed,
+		elapsed,
+		timings
+	}
+}
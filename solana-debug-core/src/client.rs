@@ -0,0 +1,67 @@
+//! Typed client for a running Bokken, for Rust test code which would otherwise hand-roll JSON-RPC calls.
+//!
+//! The client is generated from the same traits the server implements, with the same request and response structs
+//! from `rpc_endpoint_structs`, so the two can't drift apart. Import the traits to get their methods:
+//!
+//! ```ignore
+//! use bokken_core::client::{BokkenClient, SolanaDebuggerRpcClient, BokkenAdminRpcClient};
+//!
+//! let client = BokkenClient::new("http://127.0.0.1:8899")?;
+//! let fork_id = client.create_fork().await?;
+//! let balance = client.get_balance(pubkey.to_string(), None).await?;
+//! ```
+//!
+//! Subscriptions aren't covered, as they need a websocket connection.
+
+use std::ops::Deref;
+
+use jsonrpsee::{core::Error, http_client::{HttpClient, HttpClientBuilder}};
+use solana_sdk::transaction::Transaction;
+
+use crate::rpc_endpoint::DEFAULT_MAX_RESPONSE_SIZE;
+use crate::rpc_endpoint_structs::{RpcBinaryEncoding, RpcSendTransactionRequest};
+
+pub use crate::rpc_endpoint::SolanaDebuggerRpcClient;
+pub use crate::rpc_admin_endpoint::BokkenAdminRpcClient;
+pub use crate::rpc_instance_endpoint::BokkenInstanceRpcClient;
+
+/// An HTTP connection to Bokken's JSON-RPC endpoint, which every client trait is implemented for
+#[derive(Debug, Clone)]
+pub struct BokkenClient {
+	inner: HttpClient
+}
+impl BokkenClient {
+	/// Responses can be as large as the server allows them to be by default
+	pub fn new(url: &str) -> Result<Self, Error> {
+		Ok(
+			Self {
+				inner: HttpClientBuilder::default()
+					.max_request_body_size(DEFAULT_MAX_RESPONSE_SIZE)
+					.build(url)?
+			}
+		)
+	}
+	/// Talks to an instance created with `bokken_createInstance` instead of the default ledger
+	pub fn for_instance(url: &str, instance_name: &str) -> Result<Self, Error> {
+		Self::new(&format!("{}/instances/{}", url.trim_end_matches('/'), instance_name))
+	}
+}
+impl Deref for BokkenClient {
+	type Target = HttpClient;
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
+/// Encodes the transaction as `sendTransaction`, `simulateTransaction`, and the like expect it, along with a
+/// `sendTransaction` config saying how it's encoded
+pub fn encode_transaction(tx: &Transaction) -> Result<(String, RpcSendTransactionRequest), Error> {
+	let tx_data = bincode::serialize(tx).map_err(|e| Error::Custom(e.to_string()))?;
+	Ok((
+		base64::encode(tx_data),
+		RpcSendTransactionRequest {
+			encoding: Some(RpcBinaryEncoding::Base64),
+			..Default::default()
+		}
+	))
+}
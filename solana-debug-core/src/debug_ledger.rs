@@ -0,0 +1,2082 @@
+use std::{path::{Path, PathBuf}, collections::{HashMap, HashSet}, io, time::{SystemTime, UNIX_EPOCH}, sync::{Arc, atomic::{AtomicBool, AtomicU64, Ordering}}};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+use color_eyre::eyre;
+use bokken_runtime::debug_env::{BokkenAccountData, BorshAccountMeta};
+use solana_sdk::{pubkey, pubkey::Pubkey, system_program, transaction::{TransactionError, Transaction}, signature::{Keypair, Signature}, signer::Signer, hash::Hash, rent::Rent, fee_calculator::DEFAULT_BURN_PERCENT, clock::{MAX_PROCESSING_AGE, MAX_RECENT_BLOCKHASHES}, epoch_schedule::EpochSchedule, stake_history::{StakeHistory, StakeHistoryEntry}, vote::state::VoteState};
+use tokio::{fs, io::AsyncWriteExt, sync::{broadcast, Mutex, RwLock}};
+use lazy_static::lazy_static;
+
+mod ledger_file;
+mod accounts_overlay;
+mod log_index;
+mod snapshot;
+mod account_locks;
+mod ledger_info;
+mod failure_artifacts;
+mod genesis;
+mod crash_report;
+mod compute_budget;
+mod account_close;
+mod feature_set;
+mod time_model;
+mod compaction;
+mod vote_account;
+mod program_accounts;
+mod log_archive;
+mod account_shards;
+mod durability;
+mod preflight_pre_state;
+mod pending_transactions;
+pub use accounts_overlay::AccountsOverlay;
+pub use snapshot::BokkenLedgerSnapshot;
+pub use account_locks::{BokkenAccountLocks, BokkenAccountLockGuard, writable_accounts};
+pub use pending_transactions::{BokkenPendingTransactions, BokkenPendingTransactionGuard};
+pub use ledger_info::{BokkenLedgerInfo, read_ledger_info, read_ledger_block_count};
+pub use crash_report::BokkenCrashReport;
+pub use compute_budget::MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES;
+pub use account_close::{is_closed_account, normalize_closed_account};
+pub use feature_set::BokkenFeatureSet;
+pub use time_model::BokkenTimeModel;
+pub use compaction::BokkenCompactionProgress;
+pub use vote_account::identity_vote_address;
+pub use program_accounts::{BokkenProgramAccountLayout, PUBKEY_LOADER_V4, programdata_address};
+pub use durability::BokkenDurability;
+use durability::sync_dir;
+pub use preflight_pre_state::BokkenPreflightPreState;
+
+use crate::{error::{BokkenError, BokkenDetailedError}, program_launcher::{BokkenProgramSupervisor, BokkenProgramProcessStatus}, account_schema::BokkenAccountSchemaRegistry, program_caller::{ProgramCaller, BokkenDebuggableProgramStatus, BokkenProgramLog}, program_stats::{BokkenProgramStats, read_program_stats_file, write_program_stats_file}, debug_ledger::ledger_file::BokkenLedgerFile, utils::indexable_file::IndexableFile};
+
+pub use self::ledger_file::{BokkenLedgerFileSlotEntry, BokkenBlockReward, BokkenRewardType, read_blocks_from_file};
+pub use self::log_index::program_ids_from_logs;
+use self::log_index::BokkenLogIndex;
+use self::failure_artifacts::{BokkenFailedExecution, write_failure_artifacts};
+use self::genesis::load_or_create_genesis;
+use self::crash_report::{write_crash_report, read_crash_report};
+use self::compute_budget::{is_compute_budget_instruction, loaded_accounts_data_size_limit, prioritization_fee};
+use self::account_close::check_lamports_balanced;
+use self::feature_set::{read_feature_set, write_feature_set};
+use self::time_model::{read_time_model, write_time_model};
+use self::compaction::{COMPACTION_CHUNK_SIZE, compact_account_dir};
+use self::vote_account::identity_vote_account;
+use self::program_accounts::{BokkenProgramAccountLayouts, program_account, programdata_account};
+use self::log_archive::{write_log_archive, read_log_archive, prune_log_archive};
+use self::ledger_file::logs_fit_in_block;
+use self::preflight_pre_state::capture_pre_state;
+use self::account_shards::{account_dir, accounts_dir, list_account_dirs, shard_unsharded_accounts};
+pub(crate) use self::account_shards::{BokkenStoredAccount, read_account_dir, scan_stored_accounts};
+
+const RENT_BASE_SIZE: u64 = 128;
+/// How many changes can be queued up for each change bus subscriber before they start lagging behind
+const CHANGE_BUS_CAPACITY: usize = 1024;
+/// How many slots are looked up in the log index at a time while searching logs
+const LOG_SEARCH_BATCH_SIZE: usize = 256;
+pub const DEFAULT_INITIAL_MINT_LAMPORTS: u64 = 500000000000000000;
+/// Fee charged for every unique signer of a transaction
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+/// How much of each fee is burned, the rest goes to the ledger's identity as the block's leader
+pub const FEE_BURN_PERCENT: u64 = DEFAULT_BURN_PERCENT as u64;
+/// How many slots old a transaction's blockhash can be by default, same as a real validator
+pub const DEFAULT_MAX_TRANSACTION_AGE: u64 = MAX_PROCESSING_AGE as u64;
+pub const PUBKEY_NULL: Pubkey = pubkey!("nu11111111111111111111111111111111111111111");
+pub const PUBKEY_DEBUG_PROGRAM_LOADER: Pubkey = pubkey!("Debugab1eProgramLoader111111111111111111111");
+lazy_static! {
+    static ref GHOST_DATA: Vec<u8> = vec![0xf0, 0x9f, 0x91, 0xbb, 0xf0, 0x9f, 0x90, 0x9b, 0xf0, 0x9f, 0xa7, 0x91, 0xe2, 0x80, 0x8d, 0xf0, 0x9f, 0x92, 0xbb];
+}
+
+fn unix_timestamp_now() -> i64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).expect("We're in 1970").as_secs() as i64
+}
+
+#[derive(Debug, Clone)]
+pub struct BokkenLedgerInitConfig {
+	pub initial_mint: Pubkey,
+	pub initial_mint_lamports: u64,
+	/// Identity reported by getIdentity, a new one is generated if this isn't specified
+	pub identity: Option<Arc<Keypair>>
+}
+
+/// How much rent accounts need to hold to be rent-exempt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BokkenRentConfig {
+	pub lamports_per_byte_year: u64,
+	/// How many years of rent an account needs to hold
+	pub exemption_threshold: f64
+}
+impl BokkenRentConfig {
+	/// Every account is rent-exempt, regardless of its balance
+	pub fn rent_free() -> Self {
+		Self {
+			lamports_per_byte_year: 0,
+			exemption_threshold: 2.0
+		}
+	}
+	/// As seen through the rent sysvar
+	pub fn to_rent(&self) -> Rent {
+		Rent {
+			lamports_per_byte_year: self.lamports_per_byte_year,
+			exemption_threshold: self.exemption_threshold,
+			burn_percent: 100 // we don't have no "validators" here
+		}
+	}
+}
+impl Default for BokkenRentConfig {
+	/// Same as mainnet
+	fn default() -> Self {
+		Self {
+			lamports_per_byte_year: 3480,
+			exemption_threshold: 2.0
+		}
+	}
+}
+
+/// Sent over the ledger's change bus whenever committed state changes, or a transaction has been dropped
+#[derive(Debug, Clone)]
+pub enum BokkenLedgerChange {
+	Account {
+		pubkey: Pubkey,
+		slot: u64,
+		data: BokkenAccountData
+	},
+	Transaction {
+		slot: u64,
+		signature: Signature,
+		transaction: Transaction,
+		tx_error: Option<TransactionError>,
+		logs: Vec<String>
+	},
+	/// The transaction was dropped without being processed, e.g. as its blockhash expired while it was waiting for
+	/// its turn
+	TransactionDropped {
+		signature: Signature,
+		tx_error: TransactionError
+	}
+}
+
+/// Parses a saved account state. Deleted accounts are saved as an empty file (or a defaulted account by older
+/// versions), both of which are read as an account with no lamports.
+pub(crate) fn parse_account_file(file_data: &[u8]) -> io::Result<BokkenAccountData> {
+	if file_data.is_empty() {
+		return Ok(BokkenAccountData::default());
+	}
+	BokkenAccountData::try_from_slice(file_data)
+}
+
+/// Abstraction around Bokken's save directory
+#[derive(Debug)]
+pub struct BokkenLedger {
+	base_path: PathBuf,
+	program_stats_path: PathBuf,
+	program_caller: ProgramCaller,
+	transaction_index: Mutex<IndexableFile<0, 64, [u8; 64], u64>>,
+	log_index: Mutex<BokkenLogIndex>,
+	state: RwLock<BokkenLedgerFile>,
+	/// The validator's identity, saved when the ledger is created
+	identity: Keypair,
+	/// Unique to this ledger, saved when the ledger is created
+	genesis_hash: [u8; 32],
+	rent: std::sync::RwLock<BokkenRentConfig>,
+	/// How slots are divided into epochs, as reported by the clock and epoch schedule sysvars
+	epoch_schedule: std::sync::RwLock<EpochSchedule>,
+	/// Which features are reported as active, saved in the ledger whenever it changes
+	feature_set: std::sync::RwLock<BokkenFeatureSet>,
+	/// Where the clock sysvar's and blocks' timestamps come from, saved in the ledger whenever it changes
+	time_model: std::sync::RwLock<BokkenTimeModel>,
+	/// Used to decode the accounts of programs which have a schema registered
+	account_schemas: BokkenAccountSchemaRegistry,
+	change_sender: broadcast::Sender<BokkenLedgerChange>,
+	/// Incremented every time any committed state changes
+	state_version: AtomicU64,
+	/// Debuggable programs can only handle one invocation at a time, so instruction execution is done one at a time.
+	execution_lock: Mutex<()>,
+	/// Makes sure that only one transaction is being committed at a time
+	commit_lock: Mutex<()>,
+	/// Accounts used by transactions which are waiting for `commit_lock` or are being executed
+	account_locks: BokkenAccountLocks,
+	/// Transactions waiting for `commit_lock`, which are dropped as soon as their blockhash expires
+	pending_transactions: BokkenPendingTransactions,
+	/// Whether transactions fail with `AccountInUse` instead of waiting for conflicting transactions to finish
+	account_in_use_errors: AtomicBool,
+	/// Whether transactions fail when they read an account which was never created, instead of getting an empty one
+	strict_accounts: AtomicBool,
+	/// The slot of each recent blockhash
+	recent_blockhashes: std::sync::Mutex<HashMap<[u8; 32], u64>>,
+	/// Transactions with a blockhash older than this many slots are dropped, 0 if they never expire
+	max_transaction_age: AtomicU64,
+	/// Archived logs of blocks more than this many slots old are removed, 0 if they're kept forever
+	log_archive_retention: AtomicU64,
+	/// When written account states, blocks, and index entries are synced to disk
+	durability: std::sync::RwLock<BokkenDurability>,
+	/// Account states written since they were last synced, if they're synced once per slot
+	unsynced_account_files: std::sync::Mutex<Vec<PathBuf>>,
+	/// If not 0, a transaction which fails while being executed has the accounts it loaded attached to its error, up to
+	/// this many bytes of account data
+	preflight_pre_state_limit: AtomicU64,
+	/// If set, everything needed to investigate a failed transaction is saved in here
+	failure_artifacts_path: std::sync::RwLock<Option<PathBuf>>,
+	/// Uncommitted account states which transactions can be executed against, e.g. to isolate tests from each other
+	forks: Mutex<HashMap<u64, AccountsOverlay>>,
+	next_fork_id: AtomicU64,
+	/// Progress of the running or latest account compaction
+	compaction: std::sync::Mutex<BokkenCompactionProgress>,
+	program_account_layouts: std::sync::RwLock<BokkenProgramAccountLayouts>,
+	/// The processes of debuggable programs launched along with this ledger, if any
+	program_supervisor: std::sync::RwLock<Option<Arc<BokkenProgramSupervisor>>>
+}
+/// A committed transaction whose logs matched a search
+#[derive(Debug)]
+pub struct BokkenLogSearchResult {
+	pub slot: u64,
+	pub signature: Signature,
+	pub tx_error: Option<TransactionError>,
+	pub logs: Vec<String>
+}
+#[derive(Debug)]
+pub struct BokkenLedgerInstruction {
+	pub program_id: Pubkey,
+	pub account_metas: Vec<BorshAccountMeta>,
+	pub data: Vec<u8>
+}
+impl BokkenLedgerInstruction {
+	/// The fee for executing the instructions, which is charged for the fee payer and every other unique signer
+	pub fn signature_fee(fee_payer: &Pubkey, instructions: &[Self]) -> u64 {
+		let mut unique_sigs = HashSet::new();
+		unique_sigs.insert(fee_payer);
+		for meta in instructions.iter().flat_map(|ix| ix.account_metas.iter()).filter(|meta| meta.is_signer) {
+			unique_sigs.insert(&meta.pubkey);
+		}
+		LAMPORTS_PER_SIGNATURE * unique_sigs.len() as u64
+	}
+	/// The fee actually charged for executing the instructions, which is the signature fee plus the prioritization fee
+	/// if a compute unit price is set
+	pub fn transaction_fee(fee_payer: &Pubkey, instructions: &[Self]) -> Result<u64, TransactionError> {
+		Ok(Self::signature_fee(fee_payer, instructions).saturating_add(prioritization_fee(instructions)?))
+	}
+	/// Assumes the transaction's message has already been sanitized
+	pub fn from_transaction(tx: &Transaction) -> Vec<Self> {
+		let account_pubkeys = &tx.message.account_keys;
+		tx.message.instructions.iter().map(|ix| {
+			// Alright to directly index these since the message was sanitized earlier
+			let program_id = account_pubkeys[ix.program_id_index as usize];
+			// ChatGPT Assistant told me to do it this way
+			let account_metas = ix.accounts.iter().map(|account_index|{
+				BorshAccountMeta {
+					pubkey: account_pubkeys[*account_index as usize],
+					is_signer: tx.message.is_signer(*account_index as usize),
+					is_writable: tx.message.is_writable(*account_index as usize)
+				}
+			}).collect::<Vec<BorshAccountMeta>>();
+			Self {
+				program_id,
+				account_metas,
+				data: ix.data.clone()
+			}
+		}).collect()
+	}
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BokkenLedgerAccountReturnChoice {
+	None,
+	All,
+	Edited,
+	Only(Vec<Pubkey>)
+}
+impl BokkenLedger {
+	/// Manages Bokken's state at the specified path
+	/// 
+	pub async fn new(
+		base_path: PathBuf,
+		program_caller: ProgramCaller,
+		init_mint_config: Option<BokkenLedgerInitConfig>
+	) -> eyre::Result<Self> {
+		let state_path = {
+			let mut p = base_path.clone();
+			p.push("state.blob");
+			p
+		};
+		let tx_index_path = {
+			let mut p = base_path.clone();
+			p.push("state_tx_index.blob");
+			p
+		};
+		let log_index_path = {
+			let mut p = base_path.clone();
+			p.push("state_log_index.blob");
+			p
+		};
+		let program_stats_path = {
+			let mut p = base_path.clone();
+			p.push("state_program_stats.blob");
+			p
+		};
+		let create_initial_mint = match fs::create_dir(&base_path).await {
+			Ok(_) => {
+				fs::create_dir(accounts_dir(&base_path)).await?;
+				true
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+				// TODO: Verify integrity?
+				let sharded_accounts = shard_unsharded_accounts(&base_path).await?;
+				if sharded_accounts > 0 {
+					println!("Moved {} accounts into their shards", sharded_accounts);
+				}
+				false
+			},
+			Err(e) => {
+				return Err(e.into())
+			}
+		};
+		let state = BokkenLedgerFile::new(state_path).await?;
+		let genesis = load_or_create_genesis(
+			&base_path,
+			init_mint_config.as_ref().and_then(|config| config.identity.as_deref())
+		).await?;
+		program_caller.set_program_stats(read_program_stats_file(&program_stats_path).await?).await;
+		let feature_set = read_feature_set(&base_path).await?;
+		let time_model = read_time_model(&base_path).await?;
+		let account_schemas = BokkenAccountSchemaRegistry::load(base_path.join("account_schemas")).await?;
+		let new_self = Self {
+			base_path,
+			program_stats_path,
+			program_caller,
+			rent: std::sync::RwLock::new(
+				BokkenRentConfig {
+					lamports_per_byte_year: state.rent_per_byte_year(),
+					exemption_threshold: state.exemption_threshold()
+				}
+			),
+			epoch_schedule: std::sync::RwLock::new(EpochSchedule::without_warmup()),
+			feature_set: std::sync::RwLock::new(feature_set),
+			time_model: std::sync::RwLock::new(time_model),
+			account_schemas,
+			state: RwLock::new(state),
+			identity: genesis.identity,
+			genesis_hash: genesis.genesis_hash,
+			transaction_index: Mutex::new(IndexableFile::new(
+				tx_index_path,
+				8,
+				true
+			).await?),
+			log_index: Mutex::new(BokkenLogIndex::new(log_index_path).await?),
+			change_sender: broadcast::channel(CHANGE_BUS_CAPACITY).0,
+			state_version: AtomicU64::new(0),
+			execution_lock: Mutex::new(()),
+			commit_lock: Mutex::new(()),
+			account_locks: BokkenAccountLocks::new(),
+			pending_transactions: BokkenPendingTransactions::new(),
+			account_in_use_errors: AtomicBool::new(false),
+			strict_accounts: AtomicBool::new(false),
+			recent_blockhashes: std::sync::Mutex::new(HashMap::new()),
+			max_transaction_age: AtomicU64::new(DEFAULT_MAX_TRANSACTION_AGE),
+			log_archive_retention: AtomicU64::new(0),
+			durability: std::sync::RwLock::new(BokkenDurability::default()),
+			unsynced_account_files: std::sync::Mutex::new(Vec::new()),
+			preflight_pre_state_limit: AtomicU64::new(0),
+			failure_artifacts_path: std::sync::RwLock::new(None),
+			forks: Mutex::new(HashMap::new()),
+			next_fork_id: AtomicU64::new(0),
+			compaction: std::sync::Mutex::new(BokkenCompactionProgress::default()),
+			program_account_layouts: std::sync::RwLock::new(BokkenProgramAccountLayouts::default()),
+			program_supervisor: std::sync::RwLock::new(None)
+		};
+		if create_initial_mint {
+			let init_mint_config = init_mint_config.ok_or(BokkenError::InitConfigIsNone)?;
+			let init_mint_account = BokkenAccountData {
+				lamports: init_mint_config.initial_mint_lamports,
+				data: Vec::new(),
+				owner: system_program::id(),
+				executable: false,
+				rent_epoch: 0
+			};
+			new_self.save_account(&init_mint_config.initial_mint, &init_mint_account).await?;
+			println!("Created initial mint @ {}", init_mint_config.initial_mint);
+		}
+		new_self.build_log_index_if_missing().await?;
+		new_self.load_recent_blockhashes().await?;
+		let slot = new_self.slot().await;
+		new_self.program_caller.notify_new_slot(slot, new_self.epoch_schedule().get_epoch(slot));
+		new_self.program_caller.notify_rent_change(&new_self.rent().to_rent());
+		Ok(new_self)
+	}
+	/// Remembers the blockhashes of the blocks which transactions may still refer to
+	async fn load_recent_blockhashes(&self) -> Result<(), BokkenDetailedError> {
+		let state = self.state.read().await;
+		let mut recent_blockhashes = HashMap::new();
+		let first_slot = state.slot().saturating_sub(MAX_RECENT_BLOCKHASHES as u64);
+		if first_slot == 0 {
+			// Transactions sent before the first block can only use the genesis blockhash
+			recent_blockhashes.insert(<[u8; 32]>::default(), 0);
+		}
+		for slot in first_slot.max(1)..=state.slot() {
+			if let Some(entry) = state.read_block_at_slot(slot).await? {
+				recent_blockhashes.insert(entry.block_hash, slot);
+			}
+		}
+		*self.recent_blockhashes.lock().unwrap() = recent_blockhashes;
+		Ok(())
+	}
+	/// Sets how many slots old a transaction's blockhash can be before the transaction is dropped with
+	/// `BlockhashNotFound`, 0 lets transactions use any blockhash, even ones which never existed.
+	pub fn set_max_transaction_age(&self, max_age: u64) {
+		self.max_transaction_age.store(max_age, Ordering::Relaxed);
+	}
+	/// The last block height a transaction using the blockhash of `blockhash_slot` can be sent at. Every slot has a
+	/// block, so the block height is the same as the slot. When transaction ages aren't enforced, this is the height a
+	/// real validator would give up at, as clients treat it as a number they can do arithmetic on, and `u64::MAX`
+	/// doesn't survive being a JS number.
+	pub fn last_valid_block_height(&self, blockhash_slot: u64) -> u64 {
+		match self.max_transaction_age.load(Ordering::Relaxed) {
+			0 => blockhash_slot.saturating_add(DEFAULT_MAX_TRANSACTION_AGE),
+			max_age => blockhash_slot.saturating_add(max_age)
+		}
+	}
+	/// Whether a transaction using the blockhash would still be processed at `cur_slot`
+	pub fn is_blockhash_valid(&self, blockhash: &[u8; 32], cur_slot: u64) -> bool {
+		let max_age = self.max_transaction_age.load(Ordering::Relaxed);
+		if max_age == 0 {
+			return true;
+		}
+		let blockhash_slot = self.recent_blockhashes.lock().unwrap().get(blockhash).copied();
+		blockhash_slot.map_or(false, |blockhash_slot| cur_slot.saturating_sub(blockhash_slot) <= max_age)
+	}
+	/// Whenever `execute_transaction` fails, a copy of the ledger along with the transaction, its logs, and how it
+	/// changed accounts is saved into a new timestamped directory in `path`. See `write_failure_artifacts` for what's
+	/// in there. `None` turns this off.
+	pub fn set_failure_artifacts_path(&self, path: Option<PathBuf>) {
+		*self.failure_artifacts_path.write().unwrap() = path;
+	}
+	/// Must be called while holding the commit lock, errors are only logged as the transaction's error is what matters
+	async fn save_failure_artifacts(
+		&self,
+		slot: u64,
+		tx: &Transaction,
+		error: &BokkenDetailedError,
+		failed_execution: Option<&BokkenFailedExecution>
+	) {
+		let Some(artifacts_path) = self.failure_artifacts_path.read().unwrap().clone() else {
+			return;
+		};
+		match write_failure_artifacts(&artifacts_path, &self.base_path, slot, tx, error, failed_execution).await {
+			Ok(artifact_path) => {
+				println!("Saved failure artifacts of transaction {} to {:?}", tx.signatures[0], artifact_path);
+			},
+			Err(e) => {
+				eprintln!("Warning: Couldn't save failure artifacts of transaction {}: {}", tx.signatures[0], e);
+			}
+		}
+	}
+	/// Errors are only logged, like with `save_failure_artifacts`
+	async fn save_crash_report(
+		&self,
+		slot: u64,
+		tx: &Transaction,
+		error: &BokkenDetailedError,
+		failed_execution: &BokkenFailedExecution
+	) {
+		let crash_report = BokkenCrashReport {
+			slot,
+			error: error.to_string(),
+			panics: failed_execution.panics.clone(),
+			logs: failed_execution.logs.clone()
+		};
+		if let Err(e) = write_crash_report(&self.base_path, &tx.signatures[0], &crash_report).await {
+			eprintln!("Warning: Couldn't save crash report of transaction {}: {}", tx.signatures[0], e);
+		}
+	}
+	/// Returns the crash report of a transaction which failed because a debuggable program panicked, `None` if there
+	/// isn't one
+	pub async fn crash_report(&self, signature: &Signature) -> Result<Option<BokkenCrashReport>, BokkenDetailedError> {
+		read_crash_report(&self.base_path, signature).await
+	}
+	fn remember_blockhash(&self, blockhash: [u8; 32], slot: u64) {
+		let kept_slots = self.max_transaction_age.load(Ordering::Relaxed).max(MAX_RECENT_BLOCKHASHES as u64);
+		let mut recent_blockhashes = self.recent_blockhashes.lock().unwrap();
+		recent_blockhashes.insert(blockhash, slot);
+		recent_blockhashes.retain(|_, blockhash_slot| slot.saturating_sub(*blockhash_slot) <= kept_slots);
+	}
+	/// Drops every transaction waiting for its turn whose blockhash is too old to be processed at `new_slot`, and
+	/// tells signature subscribers about it. Must be called once `new_slot` has been committed.
+	fn drop_expired_transactions(&self, new_slot: u64) {
+		let expired = self.pending_transactions.remove_expired(|blockhash| !self.is_blockhash_valid(blockhash, new_slot));
+		for signature in expired {
+			let _ = self.change_sender.send(BokkenLedgerChange::TransactionDropped {
+				signature,
+				tx_error: TransactionError::BlockhashNotFound
+			});
+		}
+	}
+	/// Fails if the transaction's blockhash is unknown or too old to be processed at `cur_slot`
+	fn check_transaction_age(&self, tx: &Transaction, cur_slot: u64) -> Result<(), TransactionError> {
+		if !self.is_blockhash_valid(&tx.message.recent_blockhash.to_bytes(), cur_slot) {
+			return Err(TransactionError::BlockhashNotFound);
+		}
+		Ok(())
+	}
+	/// Ledgers created before the log index existed need it built from the saved blocks
+	async fn build_log_index_if_missing(&self) -> Result<(), BokkenDetailedError> {
+		let mut log_index = self.log_index.lock().await;
+		let state = self.state.read().await;
+		if !log_index.is_empty() || state.slot() == 0 {
+			return Ok(());
+		}
+		println!("Building log index for {} slots...", state.slot());
+		for slot in 1..=state.slot() {
+			if let Some(entry) = state.read_block_at_slot(slot).await? {
+				log_index.insert(slot, &program_ids_from_logs(&entry.tx_logs)).await?;
+			}
+		}
+		Ok(())
+	}
+	/// Searches the logs of committed transactions between `min_slot` and `max_slot` (inclusive), in slot order.
+	/// 
+	/// Only transactions which invoked `program_id` are searched, and only log lines containing `contains` are
+	/// returned. Returns up to `limit` results, along with the slot to continue searching from if there may be more.
+	pub async fn search_logs(
+		&self,
+		program_id: Option<&Pubkey>,
+		contains: Option<&str>,
+		min_slot: u64,
+		max_slot: Option<u64>,
+		limit: usize
+	) -> Result<(Vec<BokkenLogSearchResult>, Option<u64>), BokkenDetailedError> {
+		let max_slot = max_slot.unwrap_or(u64::MAX).min(self.slot().await);
+		// There's no block at slot 0
+		let mut next_slot = min_slot.max(1);
+		let mut results = Vec::new();
+		while next_slot <= max_slot && results.len() < limit {
+			let candidate_slots: Vec<u64> = match program_id {
+				Some(program_id) => {
+					self.log_index.lock().await.slots_for_program(
+						program_id,
+						next_slot,
+						max_slot,
+						LOG_SEARCH_BATCH_SIZE
+					).await?
+				},
+				None => (next_slot..=max_slot).take(LOG_SEARCH_BATCH_SIZE).collect()
+			};
+			if candidate_slots.len() < LOG_SEARCH_BATCH_SIZE {
+				// The index has no more slots for this program within the range
+				next_slot = max_slot + 1;
+			}
+			for slot in candidate_slots {
+				if results.len() >= limit {
+					next_slot = slot;
+					break;
+				}
+				next_slot = next_slot.max(slot + 1);
+				let Some(entry) = self.state.read().await.read_block_at_slot(slot).await? else {
+					continue;
+				};
+				let logs: Vec<String> = match contains {
+					Some(contains) => entry.tx_logs.into_iter().filter(|log| log.contains(contains)).collect(),
+					None => entry.tx_logs
+				};
+				if contains.is_some() && logs.is_empty() {
+					continue;
+				}
+				results.push(
+					BokkenLogSearchResult {
+						slot,
+						signature: entry.tx_data.signatures[0],
+						tx_error: entry.tx_error,
+						logs
+					}
+				);
+			}
+		}
+		Ok((results, if next_slot <= max_slot { Some(next_slot) } else { None }))
+	}
+	/// Usage counters of every program which has been invoked on this ledger, including by simulated transactions
+	pub async fn program_stats(&self) -> HashMap<Pubkey, BokkenProgramStats> {
+		self.program_caller.program_stats().await
+	}
+	/// Connection status of every debuggable program attached to this ledger
+	pub async fn debuggable_programs(&self) -> Vec<BokkenDebuggableProgramStatus> {
+		self.program_caller.debuggable_programs().await
+	}
+	/// Keeps track of the debuggable programs which were launched along with this ledger, see `program_processes`
+	pub fn set_program_supervisor(&self, supervisor: Arc<BokkenProgramSupervisor>) {
+		*self.program_supervisor.write().unwrap() = Some(supervisor);
+	}
+	/// Process state of every debuggable program launched along with this ledger
+	pub fn program_processes(&self) -> Vec<BokkenProgramProcessStatus> {
+		match self.program_supervisor.read().unwrap().as_ref() {
+			Some(supervisor) => supervisor.statuses(|program_id| self.program_caller.is_connected(program_id)),
+			None => Vec::new()
+		}
+	}
+	/// Receives every message logged by the debuggable programs attached to this ledger as soon as it arrives
+	pub fn subscribe_program_logs(&self) -> broadcast::Receiver<BokkenProgramLog> {
+		self.program_caller.subscribe_logs()
+	}
+	/// Waits until at least `min_count` debuggable programs are attached, including every one in `program_ids`
+	pub async fn wait_for_debuggable_programs(&self, min_count: usize, program_ids: &[Pubkey]) -> Result<(), BokkenError> {
+		self.program_caller.wait_for_debuggable_programs(min_count, program_ids).await
+	}
+	/// The directory all of the ledger's state is saved in
+	pub fn base_path(&self) -> &PathBuf {
+		&self.base_path
+	}
+	pub fn identity(&self) -> &Keypair {
+		&self.identity
+	}
+	pub fn program_account_layout(&self, program_id: &Pubkey) -> BokkenProgramAccountLayout {
+		self.program_account_layouts.read().unwrap().get(program_id)
+	}
+	/// Changes how the debuggable program's accounts look to clients, until Bokken is restarted
+	pub fn set_program_account_layout(&self, program_id: Pubkey, layout: BokkenProgramAccountLayout) {
+		self.program_account_layouts.write().unwrap().set(program_id, layout);
+		self.state_version.fetch_add(1, Ordering::Relaxed);
+	}
+	/// The vote account synthesized for the identity, see `identity_vote_address`
+	pub fn identity_vote_address(&self) -> Pubkey {
+		identity_vote_address(&self.identity.pubkey())
+	}
+	pub fn genesis_hash(&self) -> [u8; 32] {
+		self.genesis_hash
+	}
+	/// Account, block, and disk usage statistics of this ledger
+	pub async fn ledger_info(&self) -> Result<BokkenLedgerInfo, BokkenDetailedError> {
+		read_ledger_info(&self.base_path).await
+	}
+	/// Stops accepting new connections from debuggable programs
+	pub fn stop(&self) {
+		self.program_caller.stop();
+	}
+	pub async fn slot(&self) -> u64 {
+		self.state.read().await.slot()
+	}
+	pub async fn blockhash(&self) -> [u8; 32] {
+		self.state.read().await.blockhash()
+	}
+	/// Takes a consistent view of the ledger as of the current slot. RPC handlers should read through this rather than
+	/// calling `slot()` and `read_account()` separately, as a transaction may be committed in between.
+	pub async fn snapshot(&self) -> BokkenLedgerSnapshot<'_> {
+		let state = self.state.read().await;
+		BokkenLedgerSnapshot::new(self, state.slot(), state.blockhash())
+	}
+	/// Changes whenever an account is saved, a new block is created, or a debuggable program connects or disconnects.
+	/// If this value is the same between two calls, then executing the same transaction will result in the same
+	/// outcome.
+	pub fn state_version(&self) -> u64 {
+		// Both only ever go up, so their sum changes whenever either of them does
+		self.state_version.load(Ordering::Relaxed).wrapping_add(self.program_caller.program_generation())
+	}
+	/// Receive all account and transaction changes committed to the ledger from this point onwards
+	pub fn subscribe_changes(&self) -> broadcast::Receiver<BokkenLedgerChange> {
+		self.change_sender.subscribe()
+	}
+	pub fn calc_min_balance_for_rent_exemption(&self, data_len: u64) -> u64 {
+		let rent = self.rent();
+		(((RENT_BASE_SIZE + data_len) * rent.lamports_per_byte_year) as f64 * rent.exemption_threshold) as u64
+	}
+	pub fn rent(&self) -> BokkenRentConfig {
+		*self.rent.read().unwrap()
+	}
+	/// Saves the new rent parameters to the ledger. Existing accounts are left as they are, even if they're no longer
+	/// rent-exempt.
+	pub async fn set_rent(&self, rent: BokkenRentConfig) -> Result<(), BokkenDetailedError> {
+		let mut state = self.state.write().await;
+		state.set_rent(rent.lamports_per_byte_year, rent.exemption_threshold).await?;
+		*self.rent.write().unwrap() = rent;
+		self.program_caller.notify_rent_change(&rent.to_rent());
+		// Anything reading the rent sysvar may now behave differently
+		self.state_version.fetch_add(1, Ordering::Relaxed);
+		Ok(())
+	}
+	pub fn epoch_schedule(&self) -> EpochSchedule {
+		*self.epoch_schedule.read().unwrap()
+	}
+	/// Changes how slots are divided into epochs. This applies to every slot, including the ones already committed.
+	pub fn set_epoch_schedule(&self, epoch_schedule: EpochSchedule) {
+		*self.epoch_schedule.write().unwrap() = epoch_schedule;
+		// Anything reading the clock or epoch schedule sysvars may now behave differently
+		self.state_version.fetch_add(1, Ordering::Relaxed);
+	}
+	pub fn feature_set(&self) -> BokkenFeatureSet {
+		self.feature_set.read().unwrap().clone()
+	}
+	/// Applies `change` to the feature set and saves the result to the ledger
+	async fn update_feature_set(
+		&self,
+		change: impl FnOnce(&mut BokkenFeatureSet)
+	) -> Result<BokkenFeatureSet, BokkenDetailedError> {
+		// Held so that concurrent changes are saved in the same order they're applied
+		let _state = self.state.write().await;
+		let feature_set = {
+			let mut feature_set = self.feature_set.write().unwrap();
+			change(&mut feature_set);
+			feature_set.clone()
+		};
+		write_feature_set(&self.base_path, &feature_set).await?;
+		// Feature accounts may now read differently
+		self.state_version.fetch_add(1, Ordering::Relaxed);
+		Ok(feature_set)
+	}
+	/// Changes the `feature-set` reported by getVersion, None reverts to the default
+	pub async fn set_feature_set_id(&self, feature_set_id: Option<u32>) -> Result<BokkenFeatureSet, BokkenDetailedError> {
+		self.update_feature_set(|feature_set| feature_set.feature_set_id = feature_set_id).await
+	}
+	/// Reports the feature as active from the specified slot onwards, which is the current one if not specified
+	pub async fn activate_feature(
+		&self,
+		feature_id: Pubkey,
+		slot: Option<u64>
+	) -> Result<BokkenFeatureSet, BokkenDetailedError> {
+		let slot = match slot {
+			Some(slot) => slot,
+			None => self.slot().await
+		};
+		self.update_feature_set(|feature_set| feature_set.activate(feature_id, slot)).await
+	}
+	pub async fn deactivate_feature(&self, feature_id: Pubkey) -> Result<BokkenFeatureSet, BokkenDetailedError> {
+		self.update_feature_set(|feature_set| feature_set.deactivate(feature_id)).await
+	}
+	pub fn account_schemas(&self) -> &BokkenAccountSchemaRegistry {
+		&self.account_schemas
+	}
+	/// The IDL account Anchor clients fetch, if `pubkey` is the IDL address of a program with an Anchor IDL registered
+	/// as its account schema
+	fn anchor_idl_account(&self, pubkey: &Pubkey) -> Option<BokkenAccountData> {
+		let (program_id, idl_data) = self.account_schemas.anchor_idl_account_data(pubkey, &self.identity.pubkey())?;
+		Some(
+			BokkenAccountData {
+				lamports: self.calc_min_balance_for_rent_exemption(idl_data.len() as u64),
+				data: idl_data,
+				owner: program_id,
+				executable: false,
+				rent_epoch: 0
+			}
+		)
+	}
+	pub fn time_model(&self) -> BokkenTimeModel {
+		*self.time_model.read().unwrap()
+	}
+	/// Changes where timestamps come from and saves it to the ledger. Blocks which were already committed keep their
+	/// timestamps.
+	pub async fn set_time_model(&self, time_model: BokkenTimeModel) -> Result<(), BokkenDetailedError> {
+		// Held so that no block is committed with the old time model after this returns
+		let _state = self.state.write().await;
+		write_time_model(&self.base_path, &time_model).await?;
+		*self.time_model.write().unwrap() = time_model;
+		// Anything reading the clock sysvar may now behave differently
+		self.state_version.fetch_add(1, Ordering::Relaxed);
+		Ok(())
+	}
+	/// The unix timestamp the clock sysvar reports at the specified slot, according to the time model
+	pub fn unix_timestamp_at_slot(&self, slot: u64) -> i64 {
+		self.time_model().unix_timestamp(slot)
+	}
+	/// Timestamp of the first block of the epoch which the slot is in, or `fallback` if that block hasn't been
+	/// committed yet
+	async fn epoch_start_timestamp(&self, slot: u64, fallback: i64) -> Result<i64, BokkenDetailedError> {
+		let epoch_schedule = self.epoch_schedule();
+		let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch_schedule.get_epoch(slot));
+		// There's no block at slot 0, so the ledger's first block starts the first epoch
+		Ok(
+			self.state.read().await.read_block_at_slot(first_slot.max(1)).await?
+				.filter(|entry| entry.slot <= slot)
+				.map_or(fallback, |entry| entry.timestamp)
+		)
+	}
+	/// If enabled, transactions using an account which another transaction is writing to (or writing to an account
+	/// which another transaction is using) fail with `AccountInUse`, like they would if they were racing each other
+	/// for the same slot on a real validator. Otherwise they wait for their turn.
+	pub fn set_account_in_use_errors(&self, enabled: bool) {
+		self.account_in_use_errors.store(enabled, Ordering::Relaxed);
+	}
+	/// If enabled, transactions which use a read-only account that has never been created fail with
+	/// `StrictAccountNotFound`, rather than the account being treated as an empty one owned by the system program.
+	/// Writable accounts aren't checked, as the transaction may be what creates them. Sysvars and programs are never
+	/// missing.
+	pub fn set_strict_accounts(&self, enabled: bool) {
+		self.strict_accounts.store(enabled, Ordering::Relaxed);
+	}
+	/// A committed transaction's logs are truncated in its block like on a real validator, in which case the full logs
+	/// are archived next to the ledger. Archives of blocks more than `slots` old are removed as new ones are made, 0
+	/// keeps them forever.
+	pub fn set_log_archive_retention(&self, slots: u64) {
+		self.log_archive_retention.store(slots, Ordering::Relaxed);
+	}
+	/// Attaches the accounts a transaction loaded, as they were before it ran, to its error if it fails while being
+	/// executed, so the failure can be reproduced elsewhere. Accounts are left out once their data adds up to more than
+	/// `max_data_bytes`, 0 attaches nothing.
+	pub fn set_preflight_pre_state_limit(&self, max_data_bytes: u64) {
+		self.preflight_pre_state_limit.store(max_data_bytes, Ordering::Relaxed);
+	}
+	pub fn durability(&self) -> BokkenDurability {
+		*self.durability.read().unwrap()
+	}
+	/// Trades how much survives the machine crashing for how fast transactions are committed. Syncing once per slot by
+	/// default.
+	pub fn set_durability(&self, durability: BokkenDurability) {
+		*self.durability.write().unwrap() = durability;
+	}
+	/// The full logs of a committed transaction, from the log archive if they had to be truncated in its block. If the
+	/// archive has been removed since, the truncated logs are returned instead.
+	pub async fn transaction_logs(&self, tx_sig: [u8; 64]) -> Result<Option<Vec<String>>, BokkenDetailedError> {
+		let Some(entry) = self.get_bokken_entry_by_tx(tx_sig).await? else {
+			return Ok(None);
+		};
+		if entry.tx_logs_archived {
+			if let Some(logs) = read_log_archive(&self.base_path, entry.slot).await? {
+				return Ok(Some(logs));
+			}
+		}
+		Ok(Some(entry.tx_logs))
+	}
+	/// Archives the full logs of the block for `slot` if they won't fit in it, returning whether they were archived
+	async fn archive_logs_if_truncated(&self, slot: u64, logs: &[String]) -> bool {
+		if logs_fit_in_block(logs) {
+			return false;
+		}
+		if let Err(e) = write_log_archive(&self.base_path, slot, logs).await {
+			eprintln!("Warning: Full logs of the block at slot {} couldn't be archived: {}", slot, e);
+			return false;
+		}
+		let retention = self.log_archive_retention.load(Ordering::Relaxed);
+		if retention > 0 {
+			if let Err(e) = prune_log_archive(&self.base_path, slot.saturating_sub(retention)).await {
+				eprintln!("Warning: Old archived logs couldn't be removed: {}", e);
+			}
+		}
+		true
+	}
+	pub async fn get_bokken_entry_by_tx(&self, tx_sig: [u8; 64]) -> Result<Option<BokkenLedgerFileSlotEntry>, BokkenDetailedError> {
+		let tx_slot = self.transaction_index.lock().await.get(&tx_sig).await?;
+		if let Some(tx_slot) = tx_slot {
+			return Ok(
+				self.state.read().await.read_block_at_slot(tx_slot).await?
+			);
+		}
+		Ok(None)
+	}
+	/// Waits until the transaction with the specified signature has been committed and is visible to every snapshot
+	/// taken from then on. Returns the slot it was committed in.
+	pub async fn wait_for_transaction(&self, signature: [u8; 64]) -> Result<u64, BokkenDetailedError> {
+		// Listen for changes before looking at the ledger, so that a commit in-between can't be missed
+		let mut changes = self.subscribe_changes();
+		loop {
+			if let Some(entry) = self.get_bokken_entry_by_tx(signature).await? {
+				if entry.slot <= self.slot().await {
+					return Ok(entry.slot);
+				}
+			}
+			if let Err(broadcast::error::RecvError::Closed) = changes.recv().await {
+				return Err(BokkenError::Stopping.into());
+			}
+		}
+	}
+	/// Returns the block committed at the specified slot, along with the hash of its parent block
+	pub async fn get_block(&self, slot: u64) -> Result<Option<(BokkenLedgerFileSlotEntry, [u8; 32])>, BokkenDetailedError> {
+		let state = self.state.read().await;
+		let Some(entry) = state.read_block_at_slot(slot).await? else {
+			return Ok(None);
+		};
+		// The first block's parent is the (empty) genesis state
+		let previous_blockhash = if slot > 1 {
+			state.read_block_at_slot(slot - 1).await?.map(|parent| parent.block_hash).unwrap_or_default()
+		}else{
+			<[u8; 32]>::default()
+		};
+		Ok(Some((entry, previous_blockhash)))
+	}
+	/// Adds lamports to the account as of the current slot. Accounts which don't exist are created as system accounts.
+	pub async fn fund_account(&self, pubkey: &Pubkey, lamports: u64) -> Result<(), BokkenDetailedError> {
+		// Don't want a transaction to overwrite this
+		let _commit_guard = self.commit_lock.lock().await;
+		let mut account = self.read_account(pubkey, None).await?;
+		account.lamports = account.lamports.saturating_add(lamports);
+		self.save_account(pubkey, &account).await
+	}
+	/// Commits an empty transaction paid for by the validator identity, for when something needs the slot to advance.
+	/// Returns the new slot.
+	pub async fn advance_slot(&self) -> Result<u64, BokkenDetailedError> {
+		self.fund_account(&self.identity.pubkey(), LAMPORTS_PER_SIGNATURE).await?;
+		let tx = Transaction::new_signed_with_payer(
+			&[],
+			Some(&self.identity.pubkey()),
+			&[&self.identity],
+			Hash::new_from_array(self.blockhash().await)
+		);
+		self.execute_transaction(tx, true).await?;
+		Ok(self.slot().await)
+	}
+	/// Keeps transactions from being committed until the guard is dropped, so that accounts can be read, modified, and
+	/// saved without a transaction overwriting them in-between
+	pub async fn lock_commits(&self) -> tokio::sync::MutexGuard<'_, ()> {
+		self.commit_lock.lock().await
+	}
+	/// Saves the account state as of the current slot
+	pub async fn save_account(&self, pubkey: &Pubkey, data: &BokkenAccountData) -> Result<(), BokkenDetailedError> {
+		self.save_account_at_slot(pubkey, data, self.slot().await).await
+	}
+	async fn save_account_at_slot(
+		&self,
+		pubkey: &Pubkey,
+		data: &BokkenAccountData,
+		slot: u64
+	) -> Result<(), BokkenDetailedError> {
+		self.write_account_file(pubkey, data, slot).await?;
+		self.sync_writes().await?;
+		self.notify_account_saved(pubkey, data, slot);
+		Ok(())
+	}
+	async fn write_account_file(
+		&self,
+		pubkey: &Pubkey,
+		data: &BokkenAccountData,
+		slot: u64
+	) -> Result<(), BokkenDetailedError> {
+		// TODO: This is terrible, replace with IndexableFile
+		let account_dir_path = account_dir(&self.base_path, pubkey);
+		fs::create_dir_all(&account_dir_path).await?;
+		let account_path = account_dir_path.join(slot.to_string());
+		let file_data = if is_closed_account(data) {
+			// Tombstone
+			Vec::new()
+		}else{
+			data.try_to_vec()?
+		};
+		match self.durability() {
+			BokkenDurability::Always => {
+				let mut file = fs::File::create(&account_path).await?;
+				file.write_all(&file_data).await?;
+				file.sync_all().await?;
+				// The file can only be found after a crash if the directory entries leading to it are synced too
+				for dir in account_dir_path.ancestors().take(3) {
+					sync_dir(dir).await?;
+				}
+			},
+			BokkenDurability::PerSlot => {
+				fs::write(&account_path, file_data).await?;
+				self.unsynced_account_files.lock().unwrap().push(account_path);
+			},
+			BokkenDurability::Never => {
+				fs::write(&account_path, file_data).await?;
+			}
+		}
+		Ok(())
+	}
+	/// Syncs everything written since the last time this was called, unless nothing is to be synced. Account states
+	/// are already synced as they're written if everything is to be synced straight away.
+	async fn sync_writes(&self) -> Result<(), BokkenDetailedError> {
+		if self.durability() == BokkenDurability::Never {
+			return Ok(());
+		}
+		let account_files = std::mem::take(&mut *self.unsynced_account_files.lock().unwrap());
+		// Each account's directory, its shard, and the accounts directory, so that new files and directories are found
+		// after a crash
+		let mut dirs = HashSet::new();
+		for account_file in account_files {
+			match fs::File::open(&account_file).await {
+				Ok(file) => file.sync_all().await?,
+				// Rolled back or compacted since
+				Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+				Err(e) => return Err(e.into())
+			}
+			dirs.extend(account_file.ancestors().skip(1).take(3).map(Path::to_path_buf));
+		}
+		for dir in dirs {
+			sync_dir(&dir).await?;
+		}
+		self.state.read().await.sync().await?;
+		self.transaction_index.lock().await.sync().await?;
+		self.log_index.lock().await.sync().await?;
+		Ok(())
+	}
+	/// Only used to undo writes for a slot which hasn't been created yet, so no one could have seen them
+	async fn remove_account_files(&self, pubkeys: &[Pubkey], slot: u64) {
+		for pubkey in pubkeys.iter() {
+			let account_path = account_dir(&self.base_path, pubkey).join(slot.to_string());
+			if let Err(e) = fs::remove_file(&account_path).await {
+				eprintln!("Warning: Couldn't roll back account state at {:?}: {}", account_path, e);
+			}
+		}
+	}
+	fn notify_account_saved(&self, pubkey: &Pubkey, data: &BokkenAccountData, slot: u64) {
+		self.state_version.fetch_add(1, Ordering::Relaxed);
+		// An error here only means that nobody is listening
+		let _ = self.change_sender.send(BokkenLedgerChange::Account {
+			pubkey: *pubkey,
+			slot,
+			data: normalize_closed_account(data.clone())
+		});
+	}
+	/// Reads the account from the overlay if it was written to it, otherwise reads it from the overlay's base slot
+	pub async fn read_account_in_overlay(
+		&self,
+		pubkey: &Pubkey,
+		overlay: &AccountsOverlay,
+		clock_time_override_hack: Option<(u64, i64)>
+	) -> Result<BokkenAccountData, BokkenError> {
+		if let Some(data) = overlay.get(pubkey) {
+			return Ok(data.clone());
+		}
+		self.read_account_at_slot(pubkey, overlay.base_slot(), clock_time_override_hack).await
+	}
+	/// Reads the latest state of the specified account
+	pub async fn read_account(
+		&self,
+		pubkey: &Pubkey,
+		clock_time_override_hack: Option<(u64, i64)>
+	) -> Result<BokkenAccountData, BokkenError> {
+		self.read_account_at_slot(pubkey, self.slot().await, clock_time_override_hack).await
+	}
+	/// Reads the state of the specified account as it was at the specified slot.
+	/// 
+	/// Account states committed by transactions in later slots are ignored, so this can be used for a consistent
+	/// view of the ledger while new transactions are being committed.
+	pub async fn read_account_at_slot(
+		&self,
+		pubkey: &Pubkey,
+		slot: u64,
+		clock_time_override_hack: Option<(u64, i64)>
+	) -> Result<BokkenAccountData, BokkenError> {
+		if self.program_caller.has_program_id(pubkey).await {
+			let layout = self.program_account_layouts.read().unwrap().get(pubkey);
+			return Ok(program_account(pubkey, layout, &GHOST_DATA, 0xf09f91bb));
+		}
+		let programdata_owner = self.program_account_layouts.read().unwrap().programdata_owner(pubkey);
+		if let Some(program_id) = programdata_owner {
+			if self.program_caller.has_program_id(&program_id).await {
+				return Ok(programdata_account(&GHOST_DATA, 0xf09f91bb));
+			}
+		}
+
+		// TODO: This is terrible
+		if *pubkey == solana_sdk::sysvar::clock::id() {
+			let (slot, unix_timestamp) = clock_time_override_hack.unwrap_or_else(||{
+				(slot, self.unix_timestamp_at_slot(slot))
+			});
+			let epoch_schedule = self.epoch_schedule();
+			return Ok(
+				BokkenAccountData {
+					lamports: 0xf09f91bb,
+					data: bincode::serialize(
+						&solana_sdk::sysvar::clock::Clock {
+							slot,
+							epoch_start_timestamp: self.epoch_start_timestamp(slot, unix_timestamp).await?,
+							epoch: epoch_schedule.get_epoch(slot),
+							leader_schedule_epoch: epoch_schedule.get_leader_schedule_epoch(slot),
+							unix_timestamp
+						}
+					).expect("clock sysvar couln't be serialized"),
+					owner: pubkey!("Sysvar1111111111111111111111111111111111111"),
+					executable: false,
+					rent_epoch: 0
+				}
+			)
+		}
+		
+		if *pubkey == solana_sdk::sysvar::rent::id() {
+			return Ok(
+				BokkenAccountData {
+					lamports: 0xf09f91bb,
+					data: bincode::serialize(&self.rent().to_rent()).expect("Rent sysvar couln't be serialized"),
+					owner: pubkey!("Sysvar1111111111111111111111111111111111111"),
+					executable: false,
+					rent_epoch: 0
+				}
+			)
+		}
+
+		if *pubkey == solana_sdk::sysvar::epoch_schedule::id() {
+			return Ok(
+				BokkenAccountData {
+					lamports: 0xf09f91bb,
+					data: bincode::serialize(&self.epoch_schedule()).expect("EpochSchedule sysvar couln't be serialized"),
+					owner: pubkey!("Sysvar1111111111111111111111111111111111111"),
+					executable: false,
+					rent_epoch: 0
+				}
+			)
+		}
+
+		if *pubkey == solana_sdk::sysvar::stake_history::id() {
+			// Nothing can be staked here, but every finished epoch still gets an (empty) entry, newest first
+			let slot = clock_time_override_hack.map_or(slot, |(slot, _)| slot);
+			let epoch = self.epoch_schedule().get_epoch(slot);
+			let mut stake_history = StakeHistory::default();
+			for past_epoch in epoch.saturating_sub(solana_sdk::stake_history::MAX_ENTRIES as u64)..epoch {
+				stake_history.add(past_epoch, StakeHistoryEntry::default());
+			}
+			return Ok(
+				BokkenAccountData {
+					lamports: 0xf09f91bb,
+					data: bincode::serialize(&stake_history).expect("StakeHistory sysvar couln't be serialized"),
+					owner: pubkey!("Sysvar1111111111111111111111111111111111111"),
+					executable: false,
+					rent_epoch: 0
+				}
+			)
+		}
+
+		if let Some(account) = self.feature_set.read().unwrap().feature_account(pubkey, slot) {
+			return Ok(account);
+		}
+
+		if let Some(idl_account) = self.anchor_idl_account(pubkey) {
+			// An IDL account which was actually created takes precedence, e.g. after `anchor idl init`
+			let stored_account = self.read_stored_account_at_slot(pubkey, slot).await?;
+			return Ok(if is_closed_account(&stored_account) { idl_account } else { stored_account });
+		}
+
+		if *pubkey == self.identity_vote_address() {
+			// Stays synthesized until the vote program changes it
+			let stored_account = self.read_stored_account_at_slot(pubkey, slot).await?;
+			if !is_closed_account(&stored_account) {
+				return Ok(stored_account);
+			}
+			return Ok(
+				identity_vote_account(
+					&self.identity.pubkey(),
+					slot,
+					&self.epoch_schedule(),
+					self.calc_min_balance_for_rent_exemption(VoteState::size_of() as u64)
+				)
+			);
+		}
+
+		self.read_stored_account_at_slot(pubkey, slot).await
+	}
+	/// Reads the state of the account as it was saved at or before the specified slot, without any of the accounts
+	/// which are synthesized on the fly
+	async fn read_stored_account_at_slot(&self, pubkey: &Pubkey, slot: u64) -> Result<BokkenAccountData, BokkenError> {
+		let Some(stored_slot) = self.latest_stored_slot(pubkey, slot).await? else {
+			// The account was never saved, or was created after the requested slot
+			return Ok(BokkenAccountData::default());
+		};
+		match fs::read(account_dir(&self.base_path, pubkey).join(stored_slot.to_string())).await {
+			Ok(file_data) => {
+				let file_data_parsed = parse_account_file(&file_data)?;
+				Ok(file_data_parsed)
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+				Ok(BokkenAccountData::default())
+			},
+			Err(e) => {
+				return Err(e.into())
+			}
+		}
+	}
+	/// Whether the account exists in the overlay or was saved at or before its base slot. Sysvars and programs always
+	/// count as created.
+	async fn account_ever_created(&self, pubkey: &Pubkey, overlay: &AccountsOverlay) -> Result<bool, BokkenError> {
+		if overlay.get(pubkey).is_some() || solana_sdk::sdk_ids::SDK_IDS.contains(pubkey) {
+			return Ok(true);
+		}
+		Ok(self.latest_stored_slot(pubkey, overlay.base_slot()).await?.is_some())
+	}
+	/// The latest slot at or before the specified one which the account was saved in, None if it wasn't saved by then
+	async fn latest_stored_slot(&self, pubkey: &Pubkey, slot: u64) -> Result<Option<u64>, BokkenError> {
+		// TODO: This is terrible, replace with IndexableFile
+		match fs::read_dir(account_dir(&self.base_path, pubkey)).await {
+			Ok(mut files) => {
+				let mut max_slot = None;
+				while let Some(file) = files.next_entry().await? {
+					let file_slot = file.file_name().to_str().unwrap_or_default().parse::<u64>().unwrap_or_default();
+					if file_slot <= slot && max_slot.map_or(true, |max_slot| file_slot > max_slot) {
+						max_slot = Some(file_slot);
+					}
+				}
+				Ok(max_slot)
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+				Ok(None)
+			},
+			Err(e) => {
+				return Err(e.into())
+			}
+		}
+	}
+	#[tracing::instrument(skip_all, fields(program_id = %instruction.program_id))]
+	async fn execute_instruction(
+		&self,
+		instruction: BokkenLedgerInstruction,
+		call_depth: u8,
+		state: &mut HashMap<Pubkey, BokkenAccountData>,
+		return_data: Option<(Pubkey, Vec<u8>)>
+	) -> Result<(u64, Vec<String>, Option<(Pubkey, Vec<u8>)>), BokkenDetailedError> {
+		// Only send ixs required to the child process (this probably wastes more perf than it saves)
+		let account_datas_for_ix = {
+		 	let mut account_datas_for_ix = HashMap::new();
+			// Insert rent sysvar
+			account_datas_for_ix.insert(
+				solana_sdk::sysvar::rent::id(),
+				state.get(&solana_sdk::sysvar::rent::id()).unwrap().clone()
+			);
+			// insert clock sysvar
+			account_datas_for_ix.insert(
+				solana_sdk::sysvar::clock::id(),
+				state.get(&solana_sdk::sysvar::clock::id()).unwrap().clone()
+			);
+			// insert epoch schedule sysvar
+			account_datas_for_ix.insert(
+				solana_sdk::sysvar::epoch_schedule::id(),
+				state.get(&solana_sdk::sysvar::epoch_schedule::id()).unwrap().clone()
+			);
+			for meta in instruction.account_metas.iter() {
+				if !account_datas_for_ix.contains_key(&meta.pubkey) {
+					account_datas_for_ix.insert(
+						meta.pubkey.clone(),
+						state.remove(&meta.pubkey).ok_or(TransactionError::AccountNotFound)?
+					);
+				}
+			}
+			account_datas_for_ix
+		};
+
+		let (return_code, logs, account_datas_for_ix, return_data) = self.program_caller.call_program(
+			instruction.program_id,
+			instruction.data,
+			instruction.account_metas,
+			account_datas_for_ix,
+			call_depth,
+			return_data
+		).await?;
+
+		// do stuff
+		for (pubkey, account_data) in account_datas_for_ix.into_iter() {
+			// re-insert edited state back in
+			state.insert(pubkey, account_data);
+		}
+		Ok((return_code, logs, return_data))
+	}
+	#[tracing::instrument(skip_all, fields(signature = %tx.signatures.first().copied().unwrap_or_default()))]
+	pub async fn execute_transaction(
+		&self,
+		tx: Transaction,
+		commit_changes: bool
+	) -> Result<(), BokkenDetailedError> {
+		// Taken before waiting for our turn, so that a conflicting transaction which is already waiting or executing
+		// makes this one fail straight away
+		let _account_lock_guard = if self.account_in_use_errors.load(Ordering::Relaxed) {
+			Some(self.account_locks.try_lock(&tx.message)?)
+		}else{
+			None
+		};
+		// Slots only advance when a transaction is committed, which sweeps waiting transactions with expired blockhashes,
+		// see `drop_expired_transactions`
+		let (pending_guard, expired) = self.pending_transactions.add(&tx);
+		let commit_guard = tokio::select! {
+			commit_guard = self.commit_lock.lock() => Some(commit_guard),
+			_ = expired => None
+		};
+		// Held until the end so that no other transaction can commit while we're reading and writing state
+		let _commit_guard = match commit_guard {
+			// The sweep may have dropped the transaction right as it was its turn
+			Some(commit_guard) if pending_guard.finish() => commit_guard,
+			// Subscribers have been told already, and nothing was executed
+			_ => return Err(TransactionError::BlockhashNotFound.into())
+		};
+		let cur_slot = self.slot().await;
+		let new_slot = cur_slot + 1;
+		// The block's timestamp is the same one the transaction's instructions see
+		let cur_time = self.unix_timestamp_at_slot(new_slot);
+		// The blockhash may never have been valid to begin with, or the max age may have been lowered since
+		if let Err(tx_error) = self.check_transaction_age(&tx, cur_slot) {
+			let _ = self.change_sender.send(BokkenLedgerChange::TransactionDropped {
+				signature: tx.signatures[0],
+				tx_error: tx_error.clone()
+			});
+			let error = tx_error.into();
+			self.save_failure_artifacts(cur_slot, &tx, &error, None).await;
+			return Err(error);
+		}
+
+		// Changes are only written to disk once the entire transaction succeeds, an error simply discards the overlay
+		let mut overlay = AccountsOverlay::new(cur_slot);
+		let mut failed_execution = None;
+		let instructions = BokkenLedgerInstruction::from_transaction(&tx);
+		// Fails the same way executing the instructions would, so it's only checked once they succeeded
+		let fee = BokkenLedgerInstruction::transaction_fee(&tx.message.account_keys[0], &instructions);
+		let (_, logs, return_data) = match self.execute_instructions_inner(
+			&tx.message.account_keys[0],
+			instructions,
+			BokkenLedgerAccountReturnChoice::None,
+			&mut overlay,
+			Some((new_slot, cur_time)),
+			&mut failed_execution
+		).await {
+			Ok(result) => result,
+			Err(error) => {
+				self.save_failure_artifacts(cur_slot, &tx, &error, failed_execution.as_ref()).await;
+				if let Some(failed_execution) = failed_execution.as_ref().filter(|execution| !execution.panics.is_empty()) {
+					self.save_crash_report(cur_slot, &tx, &error, failed_execution).await;
+				}
+				let pre_state_limit = self.preflight_pre_state_limit.load(Ordering::Relaxed);
+				return match failed_execution {
+					Some(failed_execution) if pre_state_limit > 0 => Err(
+						error.map_source(|error| BokkenError::PreflightFailure {
+							error: Box::new(error),
+							pre_state: capture_pre_state(cur_slot, &failed_execution.pre, pre_state_limit),
+							logs: failed_execution.logs
+						})
+					),
+					_ => Err(error)
+				};
+			}
+		};
+		if commit_changes {
+			let fee = fee?;
+			let (fee_burned, rewards) = self.distribute_fee(fee, &mut overlay, (new_slot, cur_time)).await?;
+			self.commit_transaction(tx, overlay, cur_time, new_slot, logs, return_data, fee, fee_burned, rewards).await?;
+		}
+		// Otherwise the overlay is dropped here, and the transaction's changes along with it
+		
+		Ok(())
+	}
+	/// Burns part of the transaction's fee, and credits the rest to the identity account in `overlay`, like a real
+	/// validator does for the block's leader. Returns how much was burned, and the rewards to record in the block.
+	async fn distribute_fee(
+		&self,
+		fee: u64,
+		overlay: &mut AccountsOverlay,
+		clock: (u64, i64)
+	) -> Result<(u64, Vec<BokkenBlockReward>), BokkenDetailedError> {
+		let fee_burned = fee * FEE_BURN_PERCENT / 100;
+		let fee_collected = fee - fee_burned;
+		if fee_collected == 0 {
+			return Ok((fee_burned, Vec::new()));
+		}
+		let identity = self.identity.pubkey();
+		let mut identity_account = self.read_account_in_overlay(&identity, overlay, Some(clock)).await?;
+		identity_account.lamports = identity_account.lamports.saturating_add(fee_collected);
+		let post_balance = identity_account.lamports;
+		overlay.write(identity, identity_account);
+		Ok((
+			fee_burned,
+			vec![
+				BokkenBlockReward {
+					pubkey: identity,
+					lamports: fee_collected as i64,
+					post_balance,
+					reward_type: BokkenRewardType::Fee
+				}
+			]
+		))
+	}
+	/// Commits a successfully executed transaction as the block for `new_slot`.
+	/// 
+	/// Account states are written as part of `new_slot` first, which nobody reads from until the block for it exists.
+	/// If anything fails before the block is appended, the account states are removed again so the ledger is left
+	/// exactly as it was. Change notifications are only sent once the block exists.
+	async fn commit_transaction(
+		&self,
+		tx: Transaction,
+		overlay: AccountsOverlay,
+		cur_time: i64,
+		new_slot: u64,
+		logs: Vec<String>,
+		return_data: Option<(Pubkey, Vec<u8>)>,
+		fee: u64,
+		fee_burned: u64,
+		rewards: Vec<BokkenBlockReward>
+	) -> Result<(), BokkenDetailedError> {
+		let writes = overlay.into_writes();
+		let mut written = Vec::with_capacity(writes.len());
+		for (pubkey, data) in writes.iter() {
+			if let Err(e) = self.write_account_file(pubkey, data, new_slot).await {
+				self.remove_account_files(&written, new_slot).await;
+				return Err(e);
+			}
+			written.push(*pubkey);
+		}
+		let logs_archived = self.archive_logs_if_truncated(new_slot, &logs).await;
+		if let Err(e) = self.state.write().await.append_new_block(
+			cur_time,
+			tx.clone(),
+			// We simply don't save txs with errors for now
+			None,
+			return_data,
+			logs.clone(),
+			fee,
+			fee_burned,
+			rewards,
+			logs_archived
+		).await {
+			self.remove_account_files(&written, new_slot).await;
+			return Err(e);
+		}
+
+		// The transaction has been committed at this point, so there's no going back
+		self.remember_blockhash(self.blockhash().await, new_slot);
+		self.drop_expired_transactions(new_slot);
+		let signature = tx.signatures[0];
+		if let Err(e) = self.transaction_index.lock().await.insert(&signature.into(), new_slot).await {
+			eprintln!("Warning: Transaction {} was committed but couldn't be indexed: {}", signature, e);
+		}
+		if let Err(e) = self.log_index.lock().await.insert(new_slot, &program_ids_from_logs(&logs)).await {
+			eprintln!("Warning: Logs of transaction {} couldn't be indexed: {}", signature, e);
+		}
+		if let Err(e) = self.sync_writes().await {
+			eprintln!("Warning: Slot {} was committed but couldn't be synced to disk: {}", new_slot, e);
+		}
+		for (pubkey, data) in writes.iter() {
+			self.notify_account_saved(pubkey, data, new_slot);
+		}
+		self.program_caller.notify_new_slot(new_slot, self.epoch_schedule().get_epoch(new_slot));
+		self.state_version.fetch_add(1, Ordering::Relaxed);
+		let _ = self.change_sender.send(BokkenLedgerChange::Transaction {
+			slot: new_slot,
+			signature,
+			transaction: tx,
+			tx_error: None,
+			logs
+		});
+		Ok(())
+	}
+	/// Removes every account whose latest state is deleted (0 lamports) from disk, along with all of its earlier states.
+	/// Returns the amount of accounts removed and the amount of bytes freed.
+	/// 
+	/// The account's history is lost: reads of a removed account at a slot before it was deleted will see it as
+	/// nonexistent, and so will account diffs of earlier transactions. Accounts deleted after an open fork was created
+	/// are left alone, so the fork can still read them.
+	/// 
+	/// Accounts are compacted a chunk at a time, and transactions can be committed in-between, so compacting a large
+	/// ledger doesn't hold up everything else until it's done. Its progress is available through
+	/// `compaction_progress`, and only one compaction can run at a time.
+	pub async fn compact_accounts(&self) -> Result<(usize, u64), BokkenDetailedError> {
+		{
+			let mut compaction = self.compaction.lock().unwrap();
+			if compaction.running {
+				return Err(BokkenError::CompactionInProgress.into());
+			}
+			*compaction = BokkenCompactionProgress::start(0);
+		}
+		let result = self.compact_account_chunks().await;
+		let mut compaction = self.compaction.lock().unwrap();
+		compaction.finish(result.as_ref().err().map(|e| e.to_string()));
+		println!(
+			"Account compaction {}: removed {} of {} accounts, freeing {} bytes",
+			if result.is_ok() { "finished" } else { "failed" },
+			compaction.removed_accounts,
+			compaction.total_accounts,
+			compaction.freed_bytes
+		);
+		result
+	}
+	async fn compact_account_chunks(&self) -> Result<(usize, u64), BokkenDetailedError> {
+		// Accounts created while compacting are left for next time, they're unlikely to be deleted already anyway
+		let account_dirs: Vec<_> = list_account_dirs(&self.base_path).await?.into_iter().flatten().collect();
+		self.compaction.lock().unwrap().total_accounts = account_dirs.len() as u64;
+		let mut removed_accounts = 0;
+		let mut freed_bytes = 0;
+		let mut last_logged_percent = 0;
+		for chunk in account_dirs.chunks(COMPACTION_CHUNK_SIZE) {
+			{
+				let _commit_guard = self.commit_lock.lock().await;
+				let min_fork_slot = self.forks.lock().await.values().map(|overlay| overlay.base_slot()).min();
+				for account_dir in chunk.iter() {
+					if let Some(dir_bytes) = compact_account_dir(account_dir, min_fork_slot).await? {
+						removed_accounts += 1;
+						freed_bytes += dir_bytes;
+					}
+				}
+			}
+			let percent_done = {
+				let mut compaction = self.compaction.lock().unwrap();
+				compaction.processed_accounts += chunk.len() as u64;
+				compaction.removed_accounts = removed_accounts as u64;
+				compaction.freed_bytes = freed_bytes;
+				compaction.percent_done()
+			};
+			if percent_done / 10 > last_logged_percent / 10 && percent_done < 100 {
+				println!("Account compaction: {}% done", percent_done);
+				last_logged_percent = percent_done;
+			}
+			tokio::task::yield_now().await;
+		}
+		Ok((removed_accounts, freed_bytes))
+	}
+	pub fn compaction_progress(&self) -> BokkenCompactionProgress {
+		self.compaction.lock().unwrap().clone()
+	}
+	/// Creates a fork of the current ledger state. Transactions executed on the fork don't affect the ledger until the
+	/// fork is committed.
+	pub async fn create_fork(&self) -> u64 {
+		let fork_id = self.next_fork_id.fetch_add(1, Ordering::Relaxed);
+		let base_slot = self.slot().await;
+		self.forks.lock().await.insert(fork_id, AccountsOverlay::new(base_slot));
+		fork_id
+	}
+	/// Returns false if the fork didn't exist
+	pub async fn discard_fork(&self, fork_id: u64) -> bool {
+		self.forks.lock().await.remove(&fork_id).is_some()
+	}
+	/// Commits all account states changed on the fork as a new slot. Its block holds an empty transaction paid for by
+	/// the validator identity, the same as `advance_slot`. Returns the amount of accounts written.
+	/// 
+	/// Committing fails if a transaction was committed to the ledger since the fork was created, as its changes would
+	/// be overwritten. The fork is kept in that case, so it can still be inspected or discarded.
+	pub async fn commit_fork(&self, fork_id: u64) -> Result<usize, BokkenDetailedError> {
+		let _commit_guard = self.commit_lock.lock().await;
+		let cur_slot = self.slot().await;
+		let mut overlay = {
+			let mut forks = self.forks.lock().await;
+			let overlay = forks.remove(&fork_id).ok_or(BokkenError::ForkNotFound(fork_id))?;
+			if overlay.base_slot() != cur_slot {
+				let base_slot = overlay.base_slot();
+				forks.insert(fork_id, overlay);
+				return Err(BokkenError::ForkOutdated(fork_id, base_slot, cur_slot).into());
+			}
+			overlay
+		};
+		let account_count = overlay.len();
+		let new_slot = cur_slot + 1;
+		let cur_time = self.unix_timestamp_at_slot(new_slot);
+		let identity = self.identity.pubkey();
+		let tx = Transaction::new_signed_with_payer(
+			&[],
+			Some(&identity),
+			&[&self.identity],
+			Hash::new_from_array(self.blockhash().await)
+		);
+		// Like with `advance_slot`, the identity is funded with the fee before paying it, so only the reward remains
+		let fee = BokkenLedgerInstruction::signature_fee(&identity, &[]);
+		let (fee_burned, rewards) = self.distribute_fee(fee, &mut overlay, (new_slot, cur_time)).await?;
+		self.commit_transaction(tx, overlay, cur_time, new_slot, Vec::new(), None, fee, fee_burned, rewards).await?;
+		Ok(account_count)
+	}
+	pub async fn read_account_on_fork(&self, fork_id: u64, pubkey: &Pubkey) -> Result<BokkenAccountData, BokkenDetailedError> {
+		let forks = self.forks.lock().await;
+		let overlay = forks.get(&fork_id).ok_or(BokkenError::ForkNotFound(fork_id))?;
+		Ok(self.read_account_in_overlay(pubkey, overlay, None).await?)
+	}
+	/// Executes the transaction against the fork's state, the fork is left untouched if the transaction fails
+	pub async fn execute_transaction_on_fork(
+		&self,
+		fork_id: u64,
+		tx: &Transaction
+	) -> Result<Vec<String>, BokkenDetailedError> {
+		let mut forks = self.forks.lock().await;
+		let overlay = forks.get_mut(&fork_id).ok_or(BokkenError::ForkNotFound(fork_id))?;
+		let (_, logs, _) = self.execute_instructions(
+			&tx.message.account_keys[0],
+			BokkenLedgerInstruction::from_transaction(tx),
+			BokkenLedgerAccountReturnChoice::None,
+			overlay,
+			None
+		).await?;
+		Ok(logs)
+	}
+
+
+	/// Execute the specified data as a transaction instruction
+	/// 
+	/// All accounts are read through the overlay. Changes are only written to the overlay if every instruction
+	/// succeeded, it is up to the caller to commit or discard it.
+	/// 
+	/// `clock` is the slot and unix timestamp of the clock sysvar throughout the transaction, by default that's the
+	/// overlay's base slot and the current time. Like within a real bank, it doesn't change between instructions.
+	/// 
+	/// Along with the requested accounts and the logs, the transaction's return data is returned. Return data is cleared
+	/// at the start of every instruction, so that's whatever the last instruction or its CPIs set most recently, or None
+	/// if it's empty, same as a real validator reports it.
+	#[tracing::instrument(skip_all, fields(instruction_count = instructions.len()))]
+	pub async fn execute_instructions(
+		&self,
+		fee_payer: &Pubkey,
+		instructions: Vec<BokkenLedgerInstruction>,
+		return_choice: BokkenLedgerAccountReturnChoice,
+		overlay: &mut AccountsOverlay,
+		clock: Option<(u64, i64)>
+	) -> Result<(HashMap<Pubkey, BokkenAccountData>, Vec<String>, Option<(Pubkey, Vec<u8>)>), BokkenDetailedError> {
+		self.execute_instructions_inner(fee_payer, instructions, return_choice, overlay, clock, &mut None).await
+	}
+	/// Same as `execute_instructions`, except that if an instruction fails, the account states as of the failure are
+	/// stored in `failed_execution`
+	async fn execute_instructions_inner(
+		&self,
+		fee_payer: &Pubkey,
+		instructions: Vec<BokkenLedgerInstruction>,
+		return_choice: BokkenLedgerAccountReturnChoice,
+		overlay: &mut AccountsOverlay,
+		clock: Option<(u64, i64)>,
+		failed_execution: &mut Option<BokkenFailedExecution>
+	) -> Result<(HashMap<Pubkey, BokkenAccountData>, Vec<String>, Option<(Pubkey, Vec<u8>)>), BokkenDetailedError> {
+		let mut the_big_log = Vec::new();
+		let mut return_data = None;
+		// The clock is only read once here, every instruction and CPI gets the same copy of it
+		let clock_time_override_hack = Some(
+			clock.unwrap_or_else(|| (overlay.base_slot(), self.unix_timestamp_at_slot(overlay.base_slot())))
+		);
+		let fee = BokkenLedgerInstruction::transaction_fee(fee_payer, &instructions)?;
+		// Checked as accounts are loaded, so that a transaction referencing lots of huge accounts fails before all of
+		// them are in memory, and before they're sent to a debuggable program
+		let loaded_data_size_limit = loaded_accounts_data_size_limit(&instructions)?;
+		let mut loaded_data_size: u64 = 0;
+		let mut count_loaded_data = |account: &BokkenAccountData| -> Result<(), BokkenError> {
+			loaded_data_size += account.data.len() as u64;
+			if loaded_data_size > loaded_data_size_limit as u64 {
+				return Err(BokkenError::MaxLoadedAccountsDataSizeExceeded(loaded_data_size, loaded_data_size_limit));
+			}
+			Ok(())
+		};
+		let account_datas = {
+			let mut account_datas = HashMap::new();
+			// Fee payer
+			let fee_payer_data = self.read_account_in_overlay(fee_payer, overlay, clock_time_override_hack).await?;
+			count_loaded_data(&fee_payer_data)?;
+			account_datas.insert(fee_payer.clone(), fee_payer_data);
+			// rent sysvar (needed for Rent::get to work)
+			account_datas.insert(
+				solana_sdk::sysvar::rent::id(),
+				self.read_account_in_overlay(&solana_sdk::sysvar::rent::id(), overlay, clock_time_override_hack).await?
+			);
+			// clock sysvar (needed for Clock::get to work)
+			account_datas.insert(
+				solana_sdk::sysvar::clock::id(),
+				self.read_account_in_overlay(&solana_sdk::sysvar::clock::id(), overlay, clock_time_override_hack).await?
+			);
+			// epoch schedule sysvar (needed for EpochSchedule::get to work)
+			account_datas.insert(
+				solana_sdk::sysvar::epoch_schedule::id(),
+				self.read_account_in_overlay(&solana_sdk::sysvar::epoch_schedule::id(), overlay, clock_time_override_hack).await?
+			);
+			let strict_accounts = self.strict_accounts.load(Ordering::Relaxed);
+			for ix in instructions.iter() {
+				for meta in ix.account_metas.iter() {
+					if !account_datas.contains_key(&meta.pubkey) {
+						let account_data = self.read_account_in_overlay(&meta.pubkey, overlay, clock_time_override_hack).await?;
+						if
+							strict_accounts &&
+							!meta.is_writable &&
+							is_closed_account(&account_data) &&
+							!self.account_ever_created(&meta.pubkey, overlay).await?
+						{
+							return Err(BokkenError::StrictAccountNotFound(meta.pubkey).into());
+						}
+						count_loaded_data(&account_data)?;
+						account_datas.insert(meta.pubkey, account_data);
+					}
+				}
+			}
+			account_datas
+		};
+		// Everything from here on only modifies this working copy. Returning early rolls back every instruction *and*
+		// the fee, as failed transactions aren't recorded in a block which the fee could be charged in. This is what
+		// happens on a real validator when a transaction fails during preflight.
+		let mut account_datas_changed = account_datas.clone();
+		{
+			// Take the fee away!
+			let fee_payer = account_datas_changed.get_mut(fee_payer)
+				.expect("For the fee payer data to be where we put it");
+			// TODO: care about about the 128 bytes for rent
+			fee_payer.lamports = fee_payer.lamports.checked_sub(fee).ok_or(TransactionError::InsufficientFundsForFee)?;
+			// fee_payer gets dropped
+		}
+
+		{
+			let _execution_guard = self.execution_lock.lock().await;
+			// Anything left over is from an invocation which was given up on, and has nothing to do with this one
+			self.program_caller.take_panics();
+			let mut exec_result: Result<(), BokkenDetailedError> = Ok(());
+			for (i, ix) in instructions.into_iter().enumerate() {
+				// These were already applied when the accounts were loaded
+				if is_compute_budget_instruction(&ix) {
+					the_big_log.push(format!("Program {} invoke [1]", ix.program_id));
+					the_big_log.push(format!("Program {} success", ix.program_id));
+					return_data = None;
+					continue;
+				}
+				// Every instruction starts without return data, only CPIs see what their caller left behind
+				let (return_code, logs, new_return_data) = match self.execute_instruction(
+					ix,
+					1,
+					&mut account_datas_changed,
+					None
+				).await {
+					Ok(result) => result,
+					Err(e) => {
+						exec_result = Err(e);
+						break;
+					}
+				};
+				return_data = new_return_data;
+				the_big_log.extend(logs);
+				if return_code != 0 {
+					exec_result = Err(BokkenError::InstructionExecError(i, return_code.into(), the_big_log.clone()).into());
+					break;
+				}
+			}
+			// Failed invocations are counted too, so the stats are saved regardless of the outcome
+			if let Err(e) = write_program_stats_file(
+				&self.program_stats_path,
+				&self.program_caller.program_stats().await
+			).await {
+				eprintln!("Warning: Program stats couldn't be saved: {}", e);
+			}
+			if exec_result.is_err() {
+				*failed_execution = Some(
+					BokkenFailedExecution {
+						pre: account_datas.clone(),
+						post: account_datas_changed.clone(),
+						logs: the_big_log.clone(),
+						panics: self.program_caller.take_panics()
+					}
+				);
+			}
+			exec_result?;
+			// _execution_guard gets dropped and unlocked
+		}
+		// Checked after every instruction has run, as lamports may be moved around between them
+		if let Err(e) = check_lamports_balanced(&account_datas, &account_datas_changed, fee) {
+			*failed_execution = Some(
+				BokkenFailedExecution {
+					pre: account_datas.clone(),
+					post: account_datas_changed.clone(),
+					logs: the_big_log.clone(),
+					panics: Vec::new()
+				}
+			);
+			return Err(e.into());
+		}
+		// Closed accounts are deleted at the end of the transaction, whatever the program left in them
+		for account in account_datas_changed.values_mut().filter(|account| is_closed_account(account)) {
+			*account = BokkenAccountData::default();
+		}
+		// All instructions succeeded, the changes can now be written to the overlay
+		let edited_accounts = {
+			let mut result = HashMap::new();
+			for (pubkey, old_data) in account_datas.into_iter() {
+				let new_data = account_datas_changed.get(&pubkey).unwrap().clone();
+				if new_data != old_data {
+					overlay.write(pubkey, new_data.clone());
+					result.insert(pubkey, new_data);
+				}
+			}
+			result
+		};
+		let account_data_result = match return_choice {
+			BokkenLedgerAccountReturnChoice::None => {
+				HashMap::new()
+			}
+			BokkenLedgerAccountReturnChoice::All => {
+				account_datas_changed
+			},
+			BokkenLedgerAccountReturnChoice::Edited => {
+				edited_accounts
+			},
+			BokkenLedgerAccountReturnChoice::Only(pubkeys) => {
+				let mut result = HashMap::new();
+				for pubkey in pubkeys.into_iter() {
+					result.insert(pubkey, account_datas_changed.get(&pubkey).unwrap().clone());
+				}
+				result
+			}
+		};
+		Ok((account_data_result, the_big_log, return_data.filter(|(_, data)| !data.is_empty())))
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use std::{collections::HashMap, sync::{Arc, Mutex as StdMutex, atomic::{AtomicUsize, Ordering}}};
+
+	use bokken_runtime::{debug_env::{BokkenProgramIdentity, BokkenRuntimeMessage, BokkenValidatorMessage}, ipc_comm::{IPCComm, connect_ipc_socket}};
+	use solana_sdk::{compute_budget::ComputeBudgetInstruction, hash::Hash, instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction, transaction::Transaction};
+
+	use crate::program_caller::{ProgramCaller, ProgramCallerConfig, bind_ipc_socket};
+	use super::{
+		AccountsOverlay,
+		BokkenLedger,
+		BokkenLedgerAccountReturnChoice,
+		BokkenLedgerInitConfig,
+		BokkenLedgerInstruction,
+		DEFAULT_MAX_TRANSACTION_AGE,
+		LAMPORTS_PER_SIGNATURE,
+		account_close::is_closed_account
+	};
+
+	const PAYER_LAMPORTS: u64 = 10_000_000_000;
+	const TRANSFER_LAMPORTS: u64 = 1_000_000;
+
+	type ReturnData = Option<(Pubkey, Vec<u8>)>;
+	/// The return data a fake program was invoked with, and what each of its CPIs returned, in order
+	type FakeProgramLog = Arc<StdMutex<Vec<(&'static str, ReturnData)>>>;
+
+	static TEST_LEDGER_ID: AtomicUsize = AtomicUsize::new(0);
+
+	/// A new ledger in a temporary directory, removed again when this is dropped
+	pub(crate) struct TestLedger {
+		pub ledger: BokkenLedger,
+		pub payer: Keypair,
+		ledger_path: std::path::PathBuf,
+		socket_path: std::path::PathBuf
+	}
+	impl TestLedger {
+		pub async fn new() -> Self {
+			let name = format!("bokken-test-{}-{}", std::process::id(), TEST_LEDGER_ID.fetch_add(1, Ordering::Relaxed));
+			let ledger_path = std::env::temp_dir().join(&name);
+			let socket_path = std::env::temp_dir().join(format!("{}.sock", name));
+			let payer = Keypair::new();
+			let ledger = BokkenLedger::new(
+				ledger_path.clone(),
+				ProgramCaller::new(bind_ipc_socket(&socket_path).unwrap(), ProgramCallerConfig::default()),
+				Some(
+					BokkenLedgerInitConfig {
+						initial_mint: payer.pubkey(),
+						initial_mint_lamports: PAYER_LAMPORTS,
+						identity: None
+					}
+				)
+			).await.unwrap();
+			Self { ledger, payer, ledger_path, socket_path }
+		}
+		async fn lamports(&self, pubkey: &Pubkey) -> u64 {
+			self.ledger.read_account(pubkey, None).await.unwrap().lamports
+		}
+		/// A transfer which can never succeed, as the payer doesn't have that much
+		fn failing_transfer(&self) -> solana_sdk::instruction::Instruction {
+			system_instruction::transfer(&self.payer.pubkey(), &Pubkey::new_unique(), PAYER_LAMPORTS * 2)
+		}
+		async fn transaction(&self, instructions: &[solana_sdk::instruction::Instruction]) -> Transaction {
+			Transaction::new_signed_with_payer(
+				instructions,
+				Some(&self.payer.pubkey()),
+				&[&self.payer],
+				Hash::new_from_array(self.ledger.blockhash().await)
+			)
+		}
+		/// Connects a stand-in for a debuggable program, which does what the first byte of each instruction says:
+		/// * 0: Sets the rest of the instruction as return data
+		/// * 1: Leaves return data as it is
+		/// * 2: Invokes `cpi_program_id` with the rest of the instruction, and leaves return data as the CPI did
+		/// * 3: Same as 2, then sets "caller" as return data
+		async fn connect_fake_program(&self, program_id: Pubkey, cpi_program_id: Pubkey) -> FakeProgramLog {
+			let log = FakeProgramLog::default();
+			let mut comm = IPCComm::new(connect_ipc_socket(&self.socket_path).await.unwrap());
+			comm.send_msg(BokkenProgramIdentity { program_id, name: None }).await.unwrap();
+			let task_log = log.clone();
+			tokio::spawn(async move {
+				while let Ok(Some(msg)) = comm.until_recv_msg::<BokkenValidatorMessage>().await {
+					let (nonce, instruction, account_datas, call_depth, return_data) = match msg {
+						BokkenValidatorMessage::Invoke { nonce, instruction, account_datas, call_depth, return_data, .. } => {
+							(nonce, instruction, account_datas, call_depth, return_data)
+						},
+						BokkenValidatorMessage::Ping => {
+							let _ = comm.send_msg(BokkenRuntimeMessage::Pong).await;
+							continue;
+						},
+						_ => continue
+					};
+					task_log.lock().unwrap().push(("invoked with", return_data.clone()));
+					let return_data = match instruction[0] {
+						0 => Some((program_id, instruction[1..].to_vec())),
+						1 => return_data,
+						_ => {
+							comm.send_msg(BokkenRuntimeMessage::CrossProgramInvoke {
+								nonce,
+								program_id: cpi_program_id,
+								instruction: instruction[1..].to_vec(),
+								account_metas: Vec::new(),
+								account_datas: HashMap::new(),
+								call_depth,
+								return_data
+							}).await.unwrap();
+							let cpi_return_data = loop {
+								match comm.until_recv_msg::<BokkenValidatorMessage>().await {
+									Ok(Some(BokkenValidatorMessage::CrossProgramInvokeResult { return_data, .. })) => break return_data,
+									Ok(Some(_)) => {},
+									_ => return
+								}
+							};
+							task_log.lock().unwrap().push(("cpi returned", cpi_return_data.clone()));
+							if instruction[0] == 3 {
+								Some((program_id, b"caller".to_vec()))
+							}else{
+								cpi_return_data
+							}
+						}
+					};
+					comm.send_msg(BokkenRuntimeMessage::Executed { nonce, return_code: 0, account_datas, return_data }).await.unwrap();
+				}
+			});
+			self.ledger.wait_for_debuggable_programs(0, &[program_id]).await.unwrap();
+			log
+		}
+		/// Two fake programs which CPI into each other, see `connect_fake_program`
+		async fn connect_fake_programs(&self) -> (Pubkey, FakeProgramLog, Pubkey, FakeProgramLog) {
+			let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+			let a_log = self.connect_fake_program(a, b).await;
+			let b_log = self.connect_fake_program(b, a).await;
+			(a, a_log, b, b_log)
+		}
+		/// The return data reported after simulating `instructions`
+		async fn simulated_return_data(&self, instructions: &[Instruction]) -> ReturnData {
+			let tx = self.transaction(instructions).await;
+			let (_, _, return_data) = self.ledger.execute_instructions(
+				&self.payer.pubkey(),
+				BokkenLedgerInstruction::from_transaction(&tx),
+				BokkenLedgerAccountReturnChoice::None,
+				&mut AccountsOverlay::new(self.ledger.slot().await),
+				None
+			).await.unwrap();
+			return_data
+		}
+	}
+	impl Drop for TestLedger {
+		fn drop(&mut self) {
+			self.ledger.stop();
+			let _ = std::fs::remove_dir_all(&self.ledger_path);
+			let _ = std::fs::remove_file(&self.socket_path);
+		}
+	}
+
+	#[tokio::test]
+	async fn failed_instruction_discards_earlier_writes() {
+		let test = TestLedger::new().await;
+		let recipient = Pubkey::new_unique();
+		test.ledger.fund_account(&recipient, TRANSFER_LAMPORTS).await.unwrap();
+		let tx = test.transaction(&[
+			system_instruction::transfer(&test.payer.pubkey(), &recipient, TRANSFER_LAMPORTS),
+			test.failing_transfer()
+		]).await;
+		assert!(test.ledger.execute_transaction(tx, true).await.is_err());
+		assert_eq!(test.lamports(&recipient).await, TRANSFER_LAMPORTS);
+		assert_eq!(test.lamports(&test.payer.pubkey()).await, PAYER_LAMPORTS);
+	}
+
+	#[tokio::test]
+	async fn failed_transaction_charges_no_fee_and_records_no_signature() {
+		let test = TestLedger::new().await;
+		let identity_lamports = test.lamports(&test.ledger.identity().pubkey()).await;
+		let slot = test.ledger.slot().await;
+		let tx = test.transaction(&[test.failing_transfer()]).await;
+		let signature: [u8; 64] = tx.signatures[0].as_ref().try_into().unwrap();
+		assert!(test.ledger.execute_transaction(tx, true).await.is_err());
+		assert_eq!(test.lamports(&test.payer.pubkey()).await, PAYER_LAMPORTS);
+		assert_eq!(test.lamports(&test.ledger.identity().pubkey()).await, identity_lamports);
+		assert_eq!(test.ledger.slot().await, slot);
+		assert!(test.ledger.get_bokken_entry_by_tx(signature).await.unwrap().is_none());
+	}
+
+	#[tokio::test]
+	async fn failed_transaction_rolls_back_created_accounts() {
+		let test = TestLedger::new().await;
+		let new_account = Keypair::new();
+		let tx = Transaction::new_signed_with_payer(
+			&[
+				system_instruction::create_account(
+					&test.payer.pubkey(),
+					&new_account.pubkey(),
+					test.ledger.calc_min_balance_for_rent_exemption(16),
+					16,
+					&solana_sdk::system_program::id()
+				),
+				test.failing_transfer()
+			],
+			Some(&test.payer.pubkey()),
+			&[&test.payer, &new_account],
+			Hash::new_from_array(test.ledger.blockhash().await)
+		);
+		assert!(test.ledger.execute_transaction(tx, true).await.is_err());
+		assert!(is_closed_account(&test.ledger.read_account(&new_account.pubkey(), None).await.unwrap()));
+		assert_eq!(test.lamports(&test.payer.pubkey()).await, PAYER_LAMPORTS);
+	}
+
+	#[tokio::test]
+	async fn prioritization_fee_is_charged_and_recorded() {
+		let test = TestLedger::new().await;
+		let recipient = Pubkey::new_unique();
+		let tx = test.transaction(&[
+			ComputeBudgetInstruction::set_compute_unit_limit(300_000),
+			ComputeBudgetInstruction::set_compute_unit_price(1_500_000),
+			system_instruction::transfer(&test.payer.pubkey(), &recipient, TRANSFER_LAMPORTS)
+		]).await;
+		test.ledger.execute_transaction(tx, true).await.unwrap();
+		// 300,000 compute units at 1.5 lamports each
+		let fee = LAMPORTS_PER_SIGNATURE + 450_000;
+		assert_eq!(test.lamports(&test.payer.pubkey()).await, PAYER_LAMPORTS - TRANSFER_LAMPORTS - fee);
+		let (block, _) = test.ledger.get_block(test.ledger.slot().await).await.unwrap().unwrap();
+		assert_eq!(block.tx_fee, fee);
+	}
+
+	#[tokio::test]
+	async fn committed_fork_gets_its_own_block() {
+		let test = TestLedger::new().await;
+		let recipient = Pubkey::new_unique();
+		let fork_id = test.ledger.create_fork().await;
+		let tx = test.transaction(&[system_instruction::transfer(&test.payer.pubkey(), &recipient, TRANSFER_LAMPORTS)]).await;
+		test.ledger.execute_transaction_on_fork(fork_id, &tx).await.unwrap();
+		let slot = test.ledger.slot().await;
+		test.ledger.commit_fork(fork_id).await.unwrap();
+		assert_eq!(test.ledger.slot().await, slot + 1);
+		assert!(test.ledger.get_block(slot + 1).await.unwrap().is_some());
+		assert_eq!(test.lamports(&recipient).await, TRANSFER_LAMPORTS);
+		// The slot the fork was created at is left as it was
+		assert!(is_closed_account(&test.ledger.read_account_at_slot(&recipient, slot, None).await.unwrap()));
+	}
+
+	#[tokio::test]
+	async fn outdated_fork_is_not_committed() {
+		let test = TestLedger::new().await;
+		let recipient = Pubkey::new_unique();
+		let fork_id = test.ledger.create_fork().await;
+		let tx = test.transaction(&[system_instruction::transfer(&test.payer.pubkey(), &recipient, TRANSFER_LAMPORTS)]).await;
+		test.ledger.execute_transaction_on_fork(fork_id, &tx).await.unwrap();
+		test.ledger.advance_slot().await.unwrap();
+		assert!(test.ledger.commit_fork(fork_id).await.is_err());
+		assert!(is_closed_account(&test.ledger.read_account(&recipient, None).await.unwrap()));
+		assert_eq!(test.ledger.read_account_on_fork(fork_id, &recipient).await.unwrap().lamports, TRANSFER_LAMPORTS);
+	}
+
+	#[tokio::test]
+	async fn return_data_is_cleared_for_every_instruction() {
+		let test = TestLedger::new().await;
+		let (a, a_log, _, _) = test.connect_fake_programs().await;
+		let return_data = test.simulated_return_data(&[
+			Instruction::new_with_bytes(a, &[0, 1, 2, 3], Vec::new()),
+			Instruction::new_with_bytes(a, &[1], Vec::new())
+		]).await;
+		assert_eq!(*a_log.lock().unwrap(), vec![("invoked with", None), ("invoked with", None)]);
+		assert_eq!(return_data, None);
+	}
+
+	#[tokio::test]
+	async fn cpi_return_data_is_visible_to_the_caller() {
+		let test = TestLedger::new().await;
+		let (a, a_log, b, b_log) = test.connect_fake_programs().await;
+		let return_data = test.simulated_return_data(&[
+			Instruction::new_with_bytes(a, &[2, 0, 7, 7], Vec::new())
+		]).await;
+		assert_eq!(*b_log.lock().unwrap(), vec![("invoked with", None)]);
+		assert_eq!(
+			*a_log.lock().unwrap(),
+			vec![("invoked with", None), ("cpi returned", Some((b, vec![7, 7])))]
+		);
+		assert_eq!(return_data, Some((b, vec![7, 7])));
+	}
+
+	#[tokio::test]
+	async fn last_return_data_set_is_reported() {
+		let test = TestLedger::new().await;
+		let (a, _, b, b_log) = test.connect_fake_programs().await;
+		let return_data = test.simulated_return_data(&[
+			Instruction::new_with_bytes(a, &[0, 1], Vec::new()),
+			Instruction::new_with_bytes(a, &[3, 0, 2], Vec::new())
+		]).await;
+		assert_eq!(*b_log.lock().unwrap(), vec![("invoked with", None)]);
+		assert_eq!(return_data, Some((a, b"caller".to_vec())));
+		// The CPI's return data is what the instruction ends with if the caller doesn't replace it
+		let return_data = test.simulated_return_data(&[
+			Instruction::new_with_bytes(a, &[0, 1], Vec::new()),
+			Instruction::new_with_bytes(a, &[2, 0, 2], Vec::new())
+		]).await;
+		assert_eq!(return_data, Some((b, vec![2])));
+	}
+
+	#[tokio::test]
+	async fn last_valid_block_height_is_bounded() {
+		let test = TestLedger::new().await;
+		assert_eq!(test.ledger.last_valid_block_height(100), 100 + DEFAULT_MAX_TRANSACTION_AGE);
+		test.ledger.set_max_transaction_age(10);
+		assert_eq!(test.ledger.last_valid_block_height(100), 110);
+		// Ages aren't enforced, but clients still need a height they can count towards
+		test.ledger.set_max_transaction_age(0);
+		assert_eq!(test.ledger.last_valid_block_height(100), 100 + DEFAULT_MAX_TRANSACTION_AGE);
+	}
+}
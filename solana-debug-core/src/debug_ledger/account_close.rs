@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::BokkenError;
+
+/// An account is closed once it holds no lamports, whatever is left in it is garbage collected after the transaction
+pub fn is_closed_account(account: &BokkenAccountData) -> bool {
+	account.lamports == 0
+}
+
+/// The state a closed account is kept as: no data, owned by the system program, and not executable. Accounts which
+/// aren't closed are returned as-is.
+///
+/// Programs usually close accounts by moving all of their lamports elsewhere, and may or may not clear the data or
+/// reassign the account to the system program. Normalizing them means every reader, such as overlays, change
+/// notifications, and the account files, sees a closed account the same way as one which never existed.
+pub fn normalize_closed_account(account: BokkenAccountData) -> BokkenAccountData {
+	if is_closed_account(&account) {
+		BokkenAccountData::default()
+	}else{
+		account
+	}
+}
+
+/// Makes sure the lamports of closed accounts were moved somewhere rather than vanishing, and that nothing was
+/// created out of thin air. The only lamports which may disappear from the transaction's accounts are its `fee`.
+pub(super) fn check_lamports_balanced(
+	pre: &HashMap<Pubkey, BokkenAccountData>,
+	post: &HashMap<Pubkey, BokkenAccountData>,
+	fee: u64
+) -> Result<(), BokkenError> {
+	let pre_lamports: u128 = pre.values().map(|account| account.lamports as u128).sum();
+	let post_lamports: u128 = post.values().map(|account| account.lamports as u128).sum();
+	let expected_lamports = pre_lamports.saturating_sub(fee as u128);
+	if post_lamports != expected_lamports {
+		return Err(BokkenError::UnbalancedTransaction(expected_lamports, post_lamports));
+	}
+	Ok(())
+}
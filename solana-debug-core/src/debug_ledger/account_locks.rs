@@ -0,0 +1,89 @@
+use std::{collections::{HashMap, HashSet}, sync::Mutex};
+
+use solana_sdk::{message::Message, pubkey::Pubkey, transaction::TransactionError};
+
+#[derive(Debug, Default)]
+struct AccountLock {
+	writer: bool,
+	readers: usize
+}
+
+/// The accounts the message would lock for writing, in the order they're listed
+pub fn writable_accounts(message: &Message) -> Vec<Pubkey> {
+	message.account_keys.iter().enumerate()
+		.filter(|(index, _)| message.is_writable(*index))
+		.map(|(_, pubkey)| *pubkey)
+		.collect()
+}
+
+/// Keeps track of which accounts are in use by transactions which haven't finished yet, like a real bank does.
+///
+/// Writable accounts can only be used by one transaction at a time, while read-only accounts can be shared by any
+/// amount of transactions as long as none of them are writing to it.
+#[derive(Debug, Default)]
+pub struct BokkenAccountLocks {
+	locks: Mutex<HashMap<Pubkey, AccountLock>>
+}
+impl BokkenAccountLocks {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	/// Locks every account the message uses until the returned guard is dropped.
+	///
+	/// Fails with `AccountInUse` if another transaction is holding a conflicting lock, or `AccountLoadedTwice` if the
+	/// message lists the same account more than once. Nothing is locked if this fails.
+	pub fn try_lock(&self, message: &Message) -> Result<BokkenAccountLockGuard<'_>, TransactionError> {
+		let mut unique_keys = HashSet::with_capacity(message.account_keys.len());
+		if !message.account_keys.iter().all(|pubkey| unique_keys.insert(pubkey)) {
+			return Err(TransactionError::AccountLoadedTwice);
+		}
+		let mut locks = self.locks.lock().unwrap();
+		for (index, pubkey) in message.account_keys.iter().enumerate() {
+			if let Some(lock) = locks.get(pubkey) {
+				if lock.writer || (message.is_writable(index) && lock.readers > 0) {
+					return Err(TransactionError::AccountInUse);
+				}
+			}
+		}
+		let mut guard = BokkenAccountLockGuard {
+			locks: self,
+			writable: Vec::new(),
+			readonly: Vec::new()
+		};
+		for (index, pubkey) in message.account_keys.iter().enumerate() {
+			let lock = locks.entry(*pubkey).or_default();
+			if message.is_writable(index) {
+				lock.writer = true;
+				guard.writable.push(*pubkey);
+			}else{
+				lock.readers += 1;
+				guard.readonly.push(*pubkey);
+			}
+		}
+		Ok(guard)
+	}
+}
+
+/// Releases the transaction's account locks when dropped
+#[derive(Debug)]
+pub struct BokkenAccountLockGuard<'a> {
+	locks: &'a BokkenAccountLocks,
+	writable: Vec<Pubkey>,
+	readonly: Vec<Pubkey>
+}
+impl Drop for BokkenAccountLockGuard<'_> {
+	fn drop(&mut self) {
+		let mut locks = self.locks.locks.lock().unwrap();
+		for pubkey in self.writable.iter() {
+			locks.remove(pubkey);
+		}
+		for pubkey in self.readonly.iter() {
+			if let Some(lock) = locks.get_mut(pubkey) {
+				lock.readers -= 1;
+				if lock.readers == 0 && !lock.writer {
+					locks.remove(pubkey);
+				}
+			}
+		}
+	}
+}
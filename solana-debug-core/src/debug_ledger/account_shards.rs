@@ -0,0 +1,223 @@
+//! Accounts are saved as `accounts/<shard>/<pubkey>/<slot>`, sharded by the first byte of their pubkey, so that no
+//! directory has to hold every account and scans over every account can be spread across cores.
+//!
+//! Ledgers saved before accounts were sharded have every account directly in `accounts/`, they're moved into their
+//! shards when the ledger is opened. Until then, they're still found by everything which reads the ledger's files.
+
+use std::{io, path::{Path, PathBuf}, str::FromStr};
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::pubkey::Pubkey;
+use tokio::fs;
+
+use crate::error::BokkenDetailedError;
+
+use super::parse_account_file;
+
+const ACCOUNTS_DIR_NAME: &str = "accounts";
+
+/// The latest saved state of an account, along with how much of its history is on disk
+#[derive(Debug)]
+pub(crate) struct BokkenStoredAccount {
+	pub pubkey: Pubkey,
+	pub latest: BokkenAccountData,
+	/// Saved states across all slots
+	pub version_count: u64,
+	pub disk_bytes: u64
+}
+
+pub(crate) fn accounts_dir(ledger_path: &Path) -> PathBuf {
+	ledger_path.join(ACCOUNTS_DIR_NAME)
+}
+
+fn shard_name(pubkey: &Pubkey) -> String {
+	format!("{:02x}", pubkey.as_ref()[0])
+}
+
+fn is_shard_name(name: &str) -> bool {
+	name.len() == 2 && name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Where the account's state at every slot is saved
+pub(crate) fn account_dir(ledger_path: &Path, pubkey: &Pubkey) -> PathBuf {
+	accounts_dir(ledger_path).join(shard_name(pubkey)).join(pubkey.to_string())
+}
+
+/// Where the account was saved before accounts were sharded
+fn unsharded_account_dir(ledger_path: &Path, pubkey: &Pubkey) -> PathBuf {
+	accounts_dir(ledger_path).join(pubkey.to_string())
+}
+
+/// Lists the account's saved states, also looking in its unsharded directory if the ledger hasn't been opened since
+/// accounts were sharded. None if the account was never saved.
+pub(crate) async fn read_account_dir(
+	ledger_path: &Path,
+	pubkey: &Pubkey
+) -> Result<Option<(PathBuf, fs::ReadDir)>, BokkenDetailedError> {
+	for account_dir in [account_dir(ledger_path, pubkey), unsharded_account_dir(ledger_path, pubkey)] {
+		match fs::read_dir(&account_dir).await {
+			Ok(files) => return Ok(Some((account_dir, files))),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+			Err(e) => return Err(e.into())
+		}
+	}
+	Ok(None)
+}
+
+/// Moves every account saved directly in `accounts/` into its shard, returning how many were moved
+pub(super) async fn shard_unsharded_accounts(ledger_path: &Path) -> Result<usize, BokkenDetailedError> {
+	let mut moved = 0;
+	let mut dir_entries = fs::read_dir(accounts_dir(ledger_path)).await?;
+	while let Some(entry) = dir_entries.next_entry().await? {
+		let Ok(pubkey) = Pubkey::from_str(entry.file_name().to_str().unwrap_or_default()) else {
+			continue;
+		};
+		let sharded_dir = account_dir(ledger_path, &pubkey);
+		fs::create_dir_all(sharded_dir.parent().expect("account directories are in a shard")).await?;
+		match fs::rename(entry.path(), &sharded_dir).await {
+			Ok(()) => {},
+			// Renaming onto a directory which isn't empty fails, e.g. if moving accounts was interrupted after
+			// some of them were saved in their shard again
+			Err(_) if fs::try_exists(&sharded_dir).await? => merge_account_dir(&entry.path(), &sharded_dir).await?,
+			Err(e) => return Err(e.into())
+		}
+		moved += 1;
+	}
+	Ok(moved)
+}
+
+/// Moves the saved states in `from` into `into`, then removes `from`. States saved in `into` are kept over ones for
+/// the same slot in `from`, as they were written more recently.
+async fn merge_account_dir(from: &Path, into: &Path) -> Result<(), BokkenDetailedError> {
+	let mut files = fs::read_dir(from).await?;
+	while let Some(file) = files.next_entry().await? {
+		let merged_file = into.join(file.file_name());
+		if fs::try_exists(&merged_file).await? {
+			fs::remove_file(file.path()).await?;
+		}else{
+			fs::rename(file.path(), merged_file).await?;
+		}
+	}
+	fs::remove_dir(from).await?;
+	Ok(())
+}
+
+/// Every directory an account is saved in, shard by shard. Unsharded accounts are grouped together at the end.
+pub(crate) async fn list_account_dirs(ledger_path: &Path) -> Result<Vec<Vec<PathBuf>>, BokkenDetailedError> {
+	let mut shards = Vec::new();
+	let mut unsharded = Vec::new();
+	let mut dir_entries = fs::read_dir(accounts_dir(ledger_path)).await?;
+	while let Some(entry) = dir_entries.next_entry().await? {
+		let file_name = entry.file_name();
+		let file_name = file_name.to_str().unwrap_or_default();
+		if is_shard_name(file_name) {
+			let mut account_dirs = Vec::new();
+			let mut shard_entries = fs::read_dir(entry.path()).await?;
+			while let Some(account_dir) = shard_entries.next_entry().await? {
+				account_dirs.push(account_dir.path());
+			}
+			shards.push(account_dirs);
+		}else if Pubkey::from_str(file_name).is_ok() {
+			unsharded.push(entry.path());
+		}
+	}
+	if !unsharded.is_empty() {
+		shards.push(unsharded);
+	}
+	Ok(shards)
+}
+
+/// None if no state of the account is saved, e.g. as it was compacted after its directory was listed
+async fn read_stored_account(account_dir: PathBuf) -> Result<Option<BokkenStoredAccount>, BokkenDetailedError> {
+	let Ok(pubkey) = Pubkey::from_str(
+		account_dir.file_name().and_then(|file_name| file_name.to_str()).unwrap_or_default()
+	) else {
+		return Ok(None);
+	};
+	let mut files = match fs::read_dir(&account_dir).await {
+		Ok(files) => files,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(e) => return Err(e.into())
+	};
+	let mut max_slot = None;
+	let mut version_count = 0;
+	let mut disk_bytes = 0;
+	while let Some(file) = files.next_entry().await? {
+		let Ok(file_slot) = file.file_name().to_str().unwrap_or_default().parse::<u64>() else {
+			continue;
+		};
+		version_count += 1;
+		disk_bytes += file.metadata().await?.len();
+		if max_slot.map_or(true, |max_slot| file_slot > max_slot) {
+			max_slot = Some(file_slot);
+		}
+	}
+	let Some(latest_slot) = max_slot else {
+		return Ok(None);
+	};
+	let latest = match fs::read(account_dir.join(latest_slot.to_string())).await {
+		Ok(file_data) => parse_account_file(&file_data)?,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(e) => return Err(e.into())
+	};
+	Ok(Some(
+		BokkenStoredAccount {
+			pubkey,
+			latest,
+			version_count,
+			disk_bytes
+		}
+	))
+}
+
+/// Reads the latest state of every account saved in the ledger, each shard being scanned in its own task. Accounts
+/// aren't in any particular order.
+pub(crate) async fn scan_stored_accounts(ledger_path: &Path) -> Result<Vec<BokkenStoredAccount>, BokkenDetailedError> {
+	let shard_scans: Vec<_> = list_account_dirs(ledger_path).await?.into_iter().map(|account_dirs| {
+		tokio::spawn(async move {
+			let mut accounts = Vec::with_capacity(account_dirs.len());
+			for account_dir in account_dirs {
+				if let Some(account) = read_stored_account(account_dir).await? {
+					accounts.push(account);
+				}
+			}
+			Ok::<_, BokkenDetailedError>(accounts)
+		})
+	}).collect();
+	let mut accounts = Vec::new();
+	for shard_scan in shard_scans {
+		accounts.extend(shard_scan.await.map_err(io::Error::from)??);
+	}
+	Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn unsharded_account_is_merged_into_existing_shard() {
+		let ledger_path = std::env::temp_dir().join(format!("bokken-account-shards-test-{}", std::process::id()));
+		let pubkey = Pubkey::new_unique();
+		let unsharded_dir = unsharded_account_dir(&ledger_path, &pubkey);
+		let sharded_dir = account_dir(&ledger_path, &pubkey);
+		fs::create_dir_all(&unsharded_dir).await.unwrap();
+		fs::create_dir_all(&sharded_dir).await.unwrap();
+		fs::write(unsharded_dir.join("1"), b"unsharded 1").await.unwrap();
+		fs::write(unsharded_dir.join("2"), b"unsharded 2").await.unwrap();
+		fs::write(sharded_dir.join("2"), b"sharded 2").await.unwrap();
+		fs::write(sharded_dir.join("3"), b"sharded 3").await.unwrap();
+
+		let result = shard_unsharded_accounts(&ledger_path).await;
+		let files = [1, 2, 3].map(|slot| std::fs::read(sharded_dir.join(slot.to_string())).ok());
+		let unsharded_dir_exists = unsharded_dir.exists();
+		let _ = std::fs::remove_dir_all(&ledger_path);
+
+		assert_eq!(result.unwrap(), 1);
+		assert_eq!(
+			files,
+			[Some(b"unsharded 1".to_vec()), Some(b"sharded 2".to_vec()), Some(b"sharded 3".to_vec())]
+		);
+		assert!(!unsharded_dir_exists);
+	}
+}
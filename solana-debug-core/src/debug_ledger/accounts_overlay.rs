@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::pubkey::Pubkey;
+
+use super::account_close::normalize_closed_account;
+
+/// In-memory account writes layered on top of the ledger as it was at `base_slot`.
+///
+/// Nothing is written to disk until the overlay is committed as part of a transaction's block, or with
+/// `BokkenLedger::commit_fork`. Dropping the overlay discards all of its changes.
+#[derive(Debug, Clone)]
+pub struct AccountsOverlay {
+	base_slot: u64,
+	writes: HashMap<Pubkey, BokkenAccountData>
+}
+impl AccountsOverlay {
+	pub fn new(base_slot: u64) -> Self {
+		Self {
+			base_slot,
+			writes: HashMap::new()
+		}
+	}
+	/// The slot of the ledger state which accounts not written to this overlay are read from
+	pub fn base_slot(&self) -> u64 {
+		self.base_slot
+	}
+	/// Returns the pending state of the account, if it has been written to this overlay
+	pub fn get(&self, pubkey: &Pubkey) -> Option<&BokkenAccountData> {
+		self.writes.get(pubkey)
+	}
+	/// Closed accounts are written as deleted, see `normalize_closed_account`
+	pub fn write(&mut self, pubkey: Pubkey, data: BokkenAccountData) {
+		self.writes.insert(pubkey, normalize_closed_account(data));
+	}
+	pub fn len(&self) -> usize {
+		self.writes.len()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.writes.is_empty()
+	}
+	pub fn into_writes(self) -> HashMap<Pubkey, BokkenAccountData> {
+		self.writes
+	}
+}
@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use tokio::fs;
+
+use crate::error::BokkenDetailedError;
+
+use super::{parse_account_file, account_close::is_closed_account, unix_timestamp_now};
+
+/// Accounts looked at per chunk. Transactions can't be committed while a chunk is being compacted, so this is kept
+/// small enough that they're only held up for a moment.
+pub(super) const COMPACTION_CHUNK_SIZE: usize = 256;
+
+/// How far along the latest account compaction is
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BokkenCompactionProgress {
+	pub running: bool,
+	/// Accounts which existed on disk when the compaction started
+	pub total_accounts: u64,
+	pub processed_accounts: u64,
+	pub removed_accounts: u64,
+	pub freed_bytes: u64,
+	/// Unix timestamps, None if no compaction has been started yet, or it hasn't finished
+	pub started_at: Option<i64>,
+	pub finished_at: Option<i64>,
+	/// Why the compaction stopped early, if it did
+	pub error: Option<String>
+}
+impl BokkenCompactionProgress {
+	pub(super) fn start(total_accounts: u64) -> Self {
+		Self {
+			running: true,
+			total_accounts,
+			started_at: Some(unix_timestamp_now()),
+			..Default::default()
+		}
+	}
+	pub(super) fn finish(&mut self, error: Option<String>) {
+		self.running = false;
+		self.finished_at = Some(unix_timestamp_now());
+		self.error = error;
+	}
+	/// 0 to 100
+	pub fn percent_done(&self) -> u64 {
+		if self.total_accounts == 0 {
+			return 100;
+		}
+		self.processed_accounts.saturating_mul(100) / self.total_accounts
+	}
+}
+
+/// Removes the account's directory if its latest state is deleted and it isn't newer than `min_fork_slot`. Returns the
+/// amount of bytes freed if it was removed.
+pub(super) async fn compact_account_dir(
+	account_dir: &Path,
+	min_fork_slot: Option<u64>
+) -> Result<Option<u64>, BokkenDetailedError> {
+	let mut max_slot = None;
+	let mut dir_bytes = 0;
+	let mut files = match fs::read_dir(account_dir).await {
+		Ok(files) => files,
+		// Removed since the account directories were listed
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+		Err(e) => return Err(e.into())
+	};
+	while let Some(file) = files.next_entry().await? {
+		let Ok(file_slot) = file.file_name().to_str().unwrap_or_default().parse::<u64>() else {
+			continue;
+		};
+		dir_bytes += file.metadata().await?.len();
+		if max_slot.map_or(true, |max_slot| file_slot > max_slot) {
+			max_slot = Some(file_slot);
+		}
+	}
+	let Some(max_slot) = max_slot else {
+		return Ok(None);
+	};
+	if min_fork_slot.map_or(false, |min_fork_slot| max_slot > min_fork_slot) {
+		return Ok(None);
+	}
+	let account = parse_account_file(&fs::read(account_dir.join(max_slot.to_string())).await?)?;
+	if !is_closed_account(&account) {
+		return Ok(None);
+	}
+	fs::remove_dir_all(account_dir).await?;
+	Ok(Some(dir_bytes))
+}
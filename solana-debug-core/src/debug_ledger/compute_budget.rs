@@ -0,0 +1,77 @@
+use solana_sdk::{compute_budget, instruction::InstructionError, transaction::TransactionError};
+
+use super::BokkenLedgerInstruction;
+
+/// How much account data a transaction may load unless it asks for less, same as on a real validator
+pub const MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES: u32 = 64 * 1024 * 1024;
+/// Borsh discriminants of the `ComputeBudgetInstruction`s which are applied
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+/// Borsh discriminant of `ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit`, which the version of solana-sdk
+/// we use doesn't know about yet
+const SET_LOADED_ACCOUNTS_DATA_SIZE_LIMIT: u8 = 4;
+/// Compute units each instruction may use unless the transaction sets a limit, same as on a real validator
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+/// The most compute units a transaction may use
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Compute unit prices are in micro-lamports
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Whether the instruction is for the compute budget program, which isn't invoked like other programs, as its
+/// instructions only configure the transaction
+pub(super) fn is_compute_budget_instruction(ix: &BokkenLedgerInstruction) -> bool {
+	ix.program_id == compute_budget::id()
+}
+
+/// The index and argument of the compute budget instruction with the specified discriminant, if the transaction has
+/// one. Fails like on a real validator if there's more than one, or the argument isn't `N` bytes long.
+fn compute_budget_arg<const N: usize>(
+	instructions: &[BokkenLedgerInstruction],
+	discriminant: u8
+) -> Result<Option<(usize, [u8; N])>, TransactionError> {
+	let mut arg = None;
+	for (i, ix) in instructions.iter().enumerate().filter(|(_, ix)| is_compute_budget_instruction(ix)) {
+		if ix.data.first() != Some(&discriminant) {
+			continue;
+		}
+		let bytes: [u8; N] = ix.data.get(1..).and_then(|bytes| bytes.try_into().ok())
+			.ok_or(TransactionError::InstructionError(i as u8, InstructionError::InvalidInstructionData))?;
+		if arg.is_some() {
+			return Err(TransactionError::DuplicateInstruction(i as u8));
+		}
+		arg = Some((i, bytes));
+	}
+	Ok(arg)
+}
+
+/// The most account data the transaction may load, as set by a `SetLoadedAccountsDataSizeLimit` instruction. Values
+/// above `MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES` are capped to it.
+pub(super) fn loaded_accounts_data_size_limit(instructions: &[BokkenLedgerInstruction]) -> Result<u32, TransactionError> {
+	let Some((i, bytes)) = compute_budget_arg::<4>(instructions, SET_LOADED_ACCOUNTS_DATA_SIZE_LIMIT)? else {
+		return Ok(MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES);
+	};
+	let requested_limit = u32::from_le_bytes(bytes);
+	if requested_limit == 0 {
+		return Err(TransactionError::InstructionError(i as u8, InstructionError::InvalidInstructionData));
+	}
+	Ok(requested_limit.min(MAX_LOADED_ACCOUNTS_DATA_SIZE_BYTES))
+}
+
+/// The fee the transaction pays for priority on top of its signature fee, as set by a `SetComputeUnitPrice`
+/// instruction. Like on a real validator, the price is paid for every compute unit the transaction may use, whether it
+/// uses them or not, and the fee is rounded up to the next lamport.
+pub(super) fn prioritization_fee(instructions: &[BokkenLedgerInstruction]) -> Result<u64, TransactionError> {
+	let compute_unit_limit = match compute_budget_arg::<4>(instructions, SET_COMPUTE_UNIT_LIMIT)? {
+		Some((_, bytes)) => u32::from_le_bytes(bytes),
+		None => {
+			let instruction_count = instructions.iter().filter(|ix| !is_compute_budget_instruction(ix)).count();
+			DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT.saturating_mul(instruction_count as u32)
+		}
+	}.min(MAX_COMPUTE_UNIT_LIMIT);
+	let Some((_, bytes)) = compute_budget_arg::<8>(instructions, SET_COMPUTE_UNIT_PRICE)? else {
+		return Ok(0);
+	};
+	let micro_lamports = u64::from_le_bytes(bytes) as u128 * compute_unit_limit as u128;
+	let lamports = (micro_lamports + MICRO_LAMPORTS_PER_LAMPORT - 1) / MICRO_LAMPORTS_PER_LAMPORT;
+	Ok(u64::try_from(lamports).unwrap_or(u64::MAX))
+}
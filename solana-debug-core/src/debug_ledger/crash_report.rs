@@ -0,0 +1,50 @@
+use std::{io, path::{Path, PathBuf}};
+
+use solana_sdk::signature::Signature;
+use tokio::fs;
+
+use crate::{error::BokkenDetailedError, program_caller::BokkenProgramPanic};
+
+const CRASH_REPORTS_DIR_NAME: &str = "crash_reports";
+
+/// What's known about a transaction which failed because a debuggable program panicked. Failed transactions aren't
+/// saved in a block, so this is kept next to the ledger instead.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BokkenCrashReport {
+	/// The slot the transaction was executed against
+	pub slot: u64,
+	/// Why the transaction failed
+	pub error: String,
+	/// The program which panicked first comes first
+	pub panics: Vec<BokkenProgramPanic>,
+	/// The logs of every instruction up to and including the failing one
+	pub logs: Vec<String>
+}
+
+fn crash_report_path(ledger_path: &Path, signature: &Signature) -> PathBuf {
+	ledger_path.join(CRASH_REPORTS_DIR_NAME).join(format!("{}.json", signature))
+}
+
+/// Saves the crash report as `crash_reports/<signature>.json` in the ledger directory, replacing any earlier one of
+/// the same transaction
+pub(super) async fn write_crash_report(
+	ledger_path: &Path,
+	signature: &Signature,
+	crash_report: &BokkenCrashReport
+) -> Result<(), BokkenDetailedError> {
+	fs::create_dir_all(ledger_path.join(CRASH_REPORTS_DIR_NAME)).await?;
+	fs::write(crash_report_path(ledger_path, signature), serde_json::to_vec_pretty(crash_report).map_err(io::Error::from)?).await?;
+	Ok(())
+}
+
+pub(super) async fn read_crash_report(
+	ledger_path: &Path,
+	signature: &Signature
+) -> Result<Option<BokkenCrashReport>, BokkenDetailedError> {
+	match fs::read(crash_report_path(ledger_path, signature)).await {
+		Ok(data) => Ok(Some(serde_json::from_slice(&data).map_err(io::Error::from)?)),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+		Err(e) => Err(e.into())
+	}
+}
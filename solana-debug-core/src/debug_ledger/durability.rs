@@ -0,0 +1,40 @@
+use std::{io, path::Path, str::FromStr};
+
+use tokio::fs;
+
+/// When what's written to the ledger is synced from the OS's page cache to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BokkenDurability {
+	/// Every account state is synced as soon as it's written, along with the directory entries leading to it. Blocks
+	/// and index entries are synced once the slot is committed, same as `PerSlot`.
+	Always,
+	/// Everything written for a slot, including new directory entries, is synced together once the slot is committed,
+	/// before anyone is notified of it
+	#[default]
+	PerSlot,
+	/// Nothing is synced, the OS writes it to disk whenever it gets around to it. If the machine (not only Bokken)
+	/// crashes, the latest slots may be lost or only partially saved.
+	Never
+}
+impl FromStr for BokkenDurability {
+	type Err = String;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"always" => Ok(Self::Always),
+			"per-slot" => Ok(Self::PerSlot),
+			"never" => Ok(Self::Never),
+			_ => Err(format!("Expected always, per-slot, or never, got {}", s))
+		}
+	}
+}
+
+/// Syncs a directory, so that entries added to it are still there after a crash. A directory which no longer exists
+/// has nothing to sync.
+pub(super) async fn sync_dir(path: &Path) -> io::Result<()> {
+	match fs::File::open(path).await {
+		Ok(dir) => dir.sync_all().await,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+		Err(e) => Err(e)
+	}
+}
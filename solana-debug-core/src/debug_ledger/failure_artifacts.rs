@@ -0,0 +1,135 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, fmt::Write};
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+use tokio::fs;
+
+use crate::{account_diff::diff_byte_ranges, error::BokkenDetailedError, program_caller::BokkenProgramPanic};
+
+/// Account states of a transaction whose instructions failed
+#[derive(Debug, Clone, Default)]
+pub(super) struct BokkenFailedExecution {
+	/// Every account the transaction used, as it was before the transaction
+	pub pre: HashMap<Pubkey, BokkenAccountData>,
+	/// The accounts as they were left by the failing instruction. An account is missing if the failing instruction
+	/// used it but never returned its state, e.g. because its program disconnected.
+	pub post: HashMap<Pubkey, BokkenAccountData>,
+	pub logs: Vec<String>,
+	/// Every debuggable program which panicked during the transaction
+	pub panics: Vec<BokkenProgramPanic>
+}
+
+/// Copies the directory recursively, except for `skip_path` and anything in it
+async fn copy_dir(from: &Path, to: &Path, skip_path: &Path) -> Result<(), BokkenDetailedError> {
+	let mut dirs = vec![(from.to_path_buf(), to.to_path_buf())];
+	while let Some((from_dir, to_dir)) = dirs.pop() {
+		fs::create_dir_all(&to_dir).await?;
+		let mut entries = fs::read_dir(&from_dir).await?;
+		while let Some(entry) = entries.next_entry().await? {
+			let entry_path = entry.path();
+			if entry_path.starts_with(skip_path) {
+				continue;
+			}
+			let to_path = to_dir.join(entry.file_name());
+			if entry.metadata().await?.is_dir() {
+				dirs.push((entry_path, to_path));
+			}else{
+				fs::copy(&entry_path, &to_path).await?;
+			}
+		}
+	}
+	Ok(())
+}
+
+fn describe_account_diffs(failed_execution: &BokkenFailedExecution) -> String {
+	let mut pubkeys: Vec<&Pubkey> = failed_execution.pre.keys().collect();
+	pubkeys.sort();
+	let mut result = String::new();
+	for pubkey in pubkeys {
+		let pre = &failed_execution.pre[pubkey];
+		let Some(post) = failed_execution.post.get(pubkey) else {
+			let _ = writeln!(result, "{}: state unknown", pubkey);
+			continue;
+		};
+		if pre == post {
+			continue;
+		}
+		let _ = writeln!(result, "{}:", pubkey);
+		if pre.lamports != post.lamports {
+			let _ = writeln!(result, "  lamports: {} -> {}", pre.lamports, post.lamports);
+		}
+		if pre.owner != post.owner {
+			let _ = writeln!(result, "  owner: {} -> {}", pre.owner, post.owner);
+		}
+		if pre.data.len() != post.data.len() {
+			let _ = writeln!(result, "  data length: {} -> {}", pre.data.len(), post.data.len());
+		}
+		for range in diff_byte_ranges(&pre.data, &post.data) {
+			let _ = writeln!(
+				result,
+				"  data[{}..{}]: {:02x?} -> {:02x?}",
+				range.start,
+				range.end,
+				pre.data.get(range.start..range.end.min(pre.data.len())).unwrap_or_default(),
+				post.data.get(range.start..range.end.min(post.data.len())).unwrap_or_default()
+			);
+		}
+	}
+	result
+}
+
+fn describe_panics(panics: &[BokkenProgramPanic]) -> String {
+	let mut result = String::new();
+	for panic in panics {
+		let _ = writeln!(result, "Program {} [{}] panicked: {}", panic.program_id, panic.call_depth, panic.message);
+		let _ = writeln!(result, "{}", panic.backtrace);
+	}
+	result
+}
+
+/// Saves everything needed to investigate a failed transaction into a new directory in `artifacts_path`, returning
+/// the new directory:
+///
+/// * `ledger/`: A copy of the ledger as of the slot the transaction was executed against, which Bokken can be started
+///   with to replay the transaction
+/// * `transaction.bin`: The bincode-encoded transaction
+/// * `transaction.txt`: The transaction in a human-readable form
+/// * `error.txt`: Why the transaction failed
+/// * `logs.txt`: The logs of every instruction up to and including the failing one
+/// * `account_diff.txt`: How the failing instruction and the ones before it changed the accounts
+/// * `backtrace.txt`: Where debuggable programs panicked, if any did
+///
+/// The commit lock must be held, so that the ledger doesn't change while it's being copied.
+pub(super) async fn write_failure_artifacts(
+	artifacts_path: &Path,
+	ledger_path: &Path,
+	slot: u64,
+	tx: &Transaction,
+	error: &BokkenDetailedError,
+	failed_execution: Option<&BokkenFailedExecution>
+) -> Result<PathBuf, BokkenDetailedError> {
+	fs::create_dir_all(artifacts_path).await?;
+	// Canonicalized so that it can be skipped if it's inside the ledger directory
+	let artifacts_path = fs::canonicalize(artifacts_path).await?;
+	let timestamp = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.expect("We're in 1970")
+		.as_millis();
+	let artifact_path = artifacts_path.join(format!("{}-{}", timestamp, tx.signatures[0]));
+	fs::create_dir_all(&artifact_path).await?;
+
+	copy_dir(&fs::canonicalize(ledger_path).await?, &artifact_path.join("ledger"), &artifacts_path).await?;
+	fs::write(artifact_path.join("transaction.bin"), bincode::serialize(tx)?).await?;
+	fs::write(artifact_path.join("transaction.txt"), format!("{:#?}\n", tx)).await?;
+	fs::write(artifact_path.join("error.txt"), format!("Slot {}: {}\n", slot, error)).await?;
+	if let Some(failed_execution) = failed_execution {
+		let mut logs = failed_execution.logs.join("\n");
+		logs.push('\n');
+		fs::write(artifact_path.join("logs.txt"), logs).await?;
+		fs::write(artifact_path.join("account_diff.txt"), describe_account_diffs(failed_execution)).await?;
+		if !failed_execution.panics.is_empty() {
+			fs::write(artifact_path.join("backtrace.txt"), describe_panics(&failed_execution.panics)).await?;
+		}
+	}
+	Ok(artifact_path)
+}
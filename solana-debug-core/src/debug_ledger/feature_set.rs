@@ -0,0 +1,101 @@
+use std::{collections::{BTreeMap, BTreeSet}, io, path::Path};
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use serde_with::{serde_as, DisplayFromStr};
+use solana_sdk::{feature::{self, Feature}, feature_set::{FEATURE_NAMES, ID as FEATURE_SET_ID}, pubkey::Pubkey};
+use tokio::fs;
+
+use crate::error::BokkenDetailedError;
+
+const FEATURE_SET_FILE_NAME: &str = "feature_set.json";
+
+/// Which runtime features the ledger reports as active, so clients which check for them can be tested against
+/// clusters with or without them. Nothing is actually feature-gated here, this only changes what clients see.
+///
+/// Every feature known to the solana-sdk Bokken is built with is active from slot 0 unless deactivated, like on a
+/// fresh test validator.
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BokkenFeatureSet {
+	/// Reported as the `feature-set` by getVersion, derived from the known features if not set
+	#[serde(default)]
+	pub feature_set_id: Option<u32>,
+	/// Features activated at a specific slot, which may include ones solana-sdk doesn't know about
+	#[serde(default)]
+	#[serde_as(as = "BTreeMap<DisplayFromStr, _>")]
+	pub activated: BTreeMap<Pubkey, u64>,
+	/// Known features which are reported as inactive
+	#[serde(default)]
+	#[serde_as(as = "BTreeSet<DisplayFromStr>")]
+	pub deactivated: BTreeSet<Pubkey>
+}
+impl BokkenFeatureSet {
+	/// Same as what a real validator built with the same solana-sdk would report
+	pub fn default_feature_set_id() -> u32 {
+		let bytes = FEATURE_SET_ID.to_bytes();
+		u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+	}
+	pub fn feature_set_id(&self) -> u32 {
+		self.feature_set_id.unwrap_or_else(Self::default_feature_set_id)
+	}
+	/// The slot the feature was activated at, or None if it's inactive
+	pub fn activation_slot(&self, feature_id: &Pubkey) -> Option<u64> {
+		if self.deactivated.contains(feature_id) {
+			return None;
+		}
+		self.activated.get(feature_id).copied()
+			.or_else(|| FEATURE_NAMES.contains_key(feature_id).then_some(0))
+	}
+	pub fn activate(&mut self, feature_id: Pubkey, slot: u64) {
+		self.deactivated.remove(&feature_id);
+		self.activated.insert(feature_id, slot);
+	}
+	pub fn deactivate(&mut self, feature_id: Pubkey) {
+		self.activated.remove(&feature_id);
+		if FEATURE_NAMES.contains_key(&feature_id) {
+			self.deactivated.insert(feature_id);
+		}
+	}
+	/// Every known or explicitly activated feature, sorted by ID
+	pub fn feature_ids(&self) -> Vec<Pubkey> {
+		let mut feature_ids: BTreeSet<Pubkey> = FEATURE_NAMES.keys().copied().collect();
+		feature_ids.extend(self.activated.keys().copied());
+		feature_ids.into_iter().collect()
+	}
+	/// The feature's on-chain account as it would be at the specified slot, which doesn't exist if the feature is
+	/// inactive. Features activated at a later slot are reported as pending.
+	pub(super) fn feature_account(&self, feature_id: &Pubkey, slot: u64) -> Option<BokkenAccountData> {
+		let activation_slot = self.activation_slot(feature_id)?;
+		Some(
+			BokkenAccountData {
+				lamports: 0xf09f91bb,
+				data: bincode::serialize(
+					&Feature {
+						activated_at: (activation_slot <= slot).then_some(activation_slot)
+					}
+				).expect("Feature couldn't be serialized"),
+				owner: feature::id(),
+				executable: false,
+				rent_epoch: 0
+			}
+		)
+	}
+}
+
+/// Loads the ledger's feature set, which is the default one if it was never changed
+pub(super) async fn read_feature_set(base_path: &Path) -> Result<BokkenFeatureSet, BokkenDetailedError> {
+	match fs::read(base_path.join(FEATURE_SET_FILE_NAME)).await {
+		Ok(data) => Ok(serde_json::from_slice(&data).map_err(io::Error::from)?),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BokkenFeatureSet::default()),
+		Err(e) => Err(e.into())
+	}
+}
+
+pub(super) async fn write_feature_set(base_path: &Path, feature_set: &BokkenFeatureSet) -> Result<(), BokkenDetailedError> {
+	fs::write(
+		base_path.join(FEATURE_SET_FILE_NAME),
+		serde_json::to_vec_pretty(feature_set).map_err(io::Error::from)?
+	).await?;
+	Ok(())
+}
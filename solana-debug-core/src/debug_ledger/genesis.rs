@@ -0,0 +1,76 @@
+use std::{io, path::Path};
+
+use solana_sdk::{hash::hashv, signature::{Keypair, read_keypair_file}, signer::Signer};
+use tokio::fs;
+
+use crate::{error::{BokkenError, BokkenDetailedError}, keystore::keypair_to_json};
+
+use super::unix_timestamp_now;
+
+const IDENTITY_FILE_NAME: &str = "identity.json";
+const GENESIS_HASH_FILE_NAME: &str = "genesis_hash.blob";
+
+/// Identifiers which stay the same for as long as the ledger exists
+#[derive(Debug)]
+pub(super) struct BokkenLedgerGenesis {
+	pub identity: Keypair,
+	pub genesis_hash: [u8; 32]
+}
+
+async fn read_file_if_exists(path: &Path) -> Result<Option<Vec<u8>>, BokkenDetailedError> {
+	match fs::read(path).await {
+		Ok(data) => Ok(Some(data)),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+		Err(e) => Err(e.into())
+	}
+}
+
+/// Loads the ledger's identity and genesis hash, creating any of them which don't exist yet, e.g. because the ledger
+/// is new, or was created before these were saved.
+///
+/// `identity` is only used if the ledger doesn't have an identity yet.
+pub(super) async fn load_or_create_genesis(
+	base_path: &Path,
+	identity: Option<&Keypair>
+) -> Result<BokkenLedgerGenesis, BokkenDetailedError> {
+	let identity_path = base_path.join(IDENTITY_FILE_NAME);
+	let identity = if fs::try_exists(&identity_path).await? {
+		let saved_identity = read_keypair_file(&identity_path).map_err(|_| BokkenError::InvalidKeypair)?;
+		if let Some(identity) = identity.filter(|identity| identity.pubkey() != saved_identity.pubkey()) {
+			eprintln!(
+				"Ledger already has identity {}, ignoring {}",
+				saved_identity.pubkey(),
+				identity.pubkey()
+			);
+		}
+		saved_identity
+	}else{
+		let identity = match identity {
+			Some(identity) => Keypair::from_bytes(&identity.to_bytes()).expect("keypair bytes to round-trip"),
+			None => Keypair::new()
+		};
+		fs::write(&identity_path, keypair_to_json(&identity)).await?;
+		identity
+	};
+
+	let genesis_hash_path = base_path.join(GENESIS_HASH_FILE_NAME);
+	let genesis_hash = match read_file_if_exists(&genesis_hash_path).await? {
+		Some(data) => <[u8; 32]>::try_from(data.as_slice()).map_err(|_| BokkenError::UnexpectedEOF)?,
+		None => {
+			// Only has to be unique, the identity and creation time make sure of that
+			let genesis_hash = hashv(&[
+				identity.pubkey().as_ref(),
+				&unix_timestamp_now().to_le_bytes(),
+				&std::process::id().to_le_bytes()
+			]).to_bytes();
+			fs::write(&genesis_hash_path, genesis_hash).await?;
+			genesis_hash
+		}
+	};
+	Ok(
+		BokkenLedgerGenesis {
+			identity,
+			genesis_hash
+		}
+	)
+}
@@ -0,0 +1,356 @@
+use std::{path::{Path, PathBuf}, io::{self, SeekFrom}, mem::size_of};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+use bytemuck::{Zeroable, Pod};
+use solana_sdk::{pubkey::Pubkey, transaction::{Transaction, TransactionError}, signature::Signature, program::MAX_RETURN_DATA, hash::hashv};
+use tokio::{fs, io::{AsyncReadExt, AsyncSeekExt}};
+
+use crate::{error::BokkenDetailedError, utils::indexable_file::IndexableFile};
+
+const MAX_TRANSACTION_SIZE: usize = 1232;
+const DEFAULT_MAX_LOG_SIZE: usize = 50 * 1000; // 5 times more than original
+
+/// Used for ledgers created before the exemption threshold was saved
+const DEFAULT_EXEMPTION_THRESHOLD: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+#[repr(C)]
+struct BokkenLedgerFileHeader {
+	rent_per_byte_year: u64,
+	/// 0 if the ledger was created before this was saved
+	exemption_threshold: f64
+}
+impl Default for BokkenLedgerFileHeader {
+	fn default() -> Self {
+		Self { rent_per_byte_year: 3480, exemption_threshold: DEFAULT_EXEMPTION_THRESHOLD }
+	}
+}
+
+/// Why a block reward was paid, same as Solana's `RewardType`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum BokkenRewardType {
+	Fee,
+	Rent,
+	Staking,
+	Voting
+}
+
+/// Lamports credited to an account when a block was produced
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BokkenBlockReward {
+	pub pubkey: Pubkey,
+	pub lamports: i64,
+	/// The account's balance after the reward was credited
+	pub post_balance: u64,
+	pub reward_type: BokkenRewardType
+}
+/// Size of a Borsh-encoded `BokkenBlockReward`
+const BLOCK_REWARD_SIZE: usize = size_of::<Pubkey>() + size_of::<i64>() + size_of::<u64>() + 1;
+/// Currently the only reward is the leader's share of the fee
+const MAX_BLOCK_REWARDS: usize = 1;
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+struct BokkenLedgerFileSlotEntryRaw {
+	// Currently these are the same value, 
+	slot: u64,
+	block_height: u64,
+	timestamp: i64,
+	block_hash: [u8; 32],
+	// Currently there's 1 transaction per block
+	tx_data: Vec<u8>, // Transaction (legacy) w/ bincode
+	tx_error: Vec<u8>, // TransactionError w/ bincode
+	tx_return_data: Option<(Pubkey, Vec<u8>)>,
+	tx_logs: Vec<String>,
+	// Blocks saved before fees were recorded have zeroes here, as entries are zero-padded to `SLOT_ENTRY_SIZE`
+	tx_fee: u64,
+	fee_burned: u64,
+	rewards: Vec<BokkenBlockReward>,
+	tx_logs_archived: bool
+}
+/// A block as it was saved to the ledger
+#[derive(Debug)]
+pub struct BokkenLedgerFileSlotEntry {
+	pub slot: u64,
+	pub block_height: u64,
+	pub timestamp: i64,
+	pub block_hash: [u8; 32],
+	// Currently there's 1 transaction per block
+	pub tx_data: Transaction, // Transaction (legacy) w/ bincode
+	pub tx_error: Option<TransactionError>, // TransactionError w/ bincode
+	pub tx_return_data: Option<(Pubkey, Vec<u8>)>,
+	pub tx_logs: Vec<String>,
+	/// 0 if the block was saved before fees were recorded
+	pub tx_fee: u64,
+	/// The part of `tx_fee` which was burned, the rest was paid out as `rewards`
+	pub fee_burned: u64,
+	pub rewards: Vec<BokkenBlockReward>,
+	/// Whether `tx_logs` had to be truncated, and the full logs were archived next to the ledger (see
+	/// `BokkenLedger::transaction_logs`). False for blocks saved before logs were archived.
+	pub tx_logs_archived: bool
+}
+impl From<BokkenLedgerFileSlotEntryRaw> for BokkenLedgerFileSlotEntry {
+    fn from(value: BokkenLedgerFileSlotEntryRaw) -> Self {
+        Self {
+			slot: value.slot,
+			block_height: value.block_height,
+			timestamp: value.timestamp,
+			block_hash: value.block_hash,
+			tx_data: bincode::deserialize(&value.tx_data).expect("tx_data deserialization"),
+			tx_error: if value.tx_error.len() == 0 {
+				None
+			}else{
+				Some(bincode::deserialize(&value.tx_error).expect("tx_error deserialization"))
+			},
+			tx_return_data: value.tx_return_data,
+			tx_logs: value.tx_logs,
+			tx_fee: value.tx_fee,
+			fee_burned: value.fee_burned,
+			rewards: value.rewards,
+			tx_logs_archived: value.tx_logs_archived
+		}
+    }
+}
+impl From<BokkenLedgerFileSlotEntry> for BokkenLedgerFileSlotEntryRaw {
+    fn from(value: BokkenLedgerFileSlotEntry) -> Self {
+        Self {
+			slot: value.slot,
+			block_height: value.block_height,
+			timestamp: value.timestamp,
+			block_hash: value.block_hash,
+			tx_data: bincode::serialize(&value.tx_data).expect("tx_sig deserialization"),
+			tx_error: if let Some(tx_error) = value.tx_error {
+				bincode::serialize(&tx_error).expect("tx_error deserialization")
+			}else{
+				Vec::new()
+			},
+			tx_return_data: value.tx_return_data,
+			tx_logs: value.tx_logs,
+			tx_fee: value.tx_fee,
+			fee_burned: value.fee_burned,
+			rewards: value.rewards,
+			tx_logs_archived: value.tx_logs_archived
+		}
+    }
+}
+
+/// The SHA-256 hash of the parent block's hash, the slot, and the signatures of the block's transactions.
+/// 
+/// Each block's hash depends on every block before it, so the chain can be verified, and blocks with different
+/// contents get different hashes.
+pub fn block_hash(parent_block_hash: &[u8; 32], slot: u64, signatures: &[Signature]) -> [u8; 32] {
+	let slot_bytes = slot.to_le_bytes();
+	let mut hash_data: Vec<&[u8]> = vec![parent_block_hash, &slot_bytes];
+	hash_data.extend(signatures.iter().map(|signature| signature.as_ref()));
+	hashv(&hash_data).to_bytes()
+}
+
+const LEDGER_FILE_HEADER_SIZE: usize = 16;
+const SLOT_ENTRY_SIZE: usize = size_of::<u64>() + // slot
+	size_of::<u64>() +
+	size_of::<u64>() + 
+	32 +
+	64 + 
+	MAX_TRANSACTION_SIZE + 4 +
+	size_of::<TransactionError>() + 1 +
+	size_of::<Pubkey>() + MAX_RETURN_DATA + 4 + 1 +
+	DEFAULT_MAX_LOG_SIZE + 4;
+/// Room the fee and rewards take up in an entry. `SLOT_ENTRY_SIZE` can't change without breaking existing ledgers, so
+/// this is taken away from the room for logs instead.
+const BLOCK_FEES_SIZE: usize = size_of::<u64>() + size_of::<u64>() + 4 + MAX_BLOCK_REWARDS * BLOCK_REWARD_SIZE;
+/// Room the log archival flag takes up in an entry, also taken away from the room for logs
+const LOGS_ARCHIVED_SIZE: usize = 1;
+/// Room for logs in an entry
+const MAX_LOG_SIZE: usize = DEFAULT_MAX_LOG_SIZE - BLOCK_FEES_SIZE - LOGS_ARCHIVED_SIZE;
+
+/// Amount of blocks saved in a ledger file of the specified size, so that it can be known without opening the file
+pub fn block_count_from_file_len(file_len: u64) -> u64 {
+	file_len.saturating_sub(LEDGER_FILE_HEADER_SIZE as u64) / SLOT_ENTRY_SIZE as u64
+}
+
+/// Reads up to `limit` blocks starting at `first_slot` straight from a ledger file, without opening it for writing, so
+/// the blocks of a ledger which is in use can be read from another process
+pub async fn read_blocks_from_file(
+	path: &Path,
+	first_slot: u64,
+	limit: usize
+) -> Result<Vec<BokkenLedgerFileSlotEntry>, BokkenDetailedError> {
+	let mut file = match fs::File::open(path).await {
+		Ok(file) => file,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(e.into())
+	};
+	let block_count = block_count_from_file_len(file.metadata().await?.len());
+	// The first block is for slot 1, slot 0 only has the genesis accounts
+	let first_index = first_slot.max(1) - 1;
+	let end_index = block_count.min(first_index.saturating_add(limit as u64));
+	let mut blocks = Vec::new();
+	if first_index >= end_index {
+		return Ok(blocks);
+	}
+	file.seek(SeekFrom::Start(LEDGER_FILE_HEADER_SIZE as u64 + first_index * SLOT_ENTRY_SIZE as u64)).await?;
+	let mut entry_bytes = vec![0u8; SLOT_ENTRY_SIZE];
+	for _ in first_index..end_index {
+		file.read_exact(&mut entry_bytes).await?;
+		blocks.push(BokkenLedgerFileSlotEntryRaw::deserialize(&mut entry_bytes.as_slice())?.into());
+	}
+	Ok(blocks)
+}
+
+const LOG_TRUNCATED_MSG: &str = "Log truncated";
+/// How many of the logs fit in a block entry, if not all of them do. The logs after that are replaced with
+/// "Log truncated".
+fn truncated_log_count(logs: &[String]) -> Option<usize> {
+	let mut total_log_len = 0;
+	for (i, log) in logs.iter().enumerate() {
+		if (total_log_len + 4 + log.len() + LOG_TRUNCATED_MSG.len()) > MAX_LOG_SIZE {
+			return Some(i);
+		}
+		total_log_len += log.len();
+	}
+	None
+}
+/// Whether the logs will be saved in a block entry as-is, without being truncated
+pub fn logs_fit_in_block(logs: &[String]) -> bool {
+	truncated_log_count(logs).is_none()
+}
+/// Global state for the Bokken ledger
+#[derive(Debug)]
+pub struct BokkenLedgerFile {
+	slot: u64,
+	blockhash: [u8; 32],
+	rent_per_byte_year: u64,
+	exemption_threshold: f64,
+	indexed_file_ref: IndexableFile<LEDGER_FILE_HEADER_SIZE, 8, u64, BokkenLedgerFileSlotEntryRaw>
+}
+impl BokkenLedgerFile {
+	pub async fn new(path: PathBuf) -> Result<Self, color_eyre::eyre::Error> {
+		let mut indexed_file_ref: IndexableFile<LEDGER_FILE_HEADER_SIZE, 8, u64, BokkenLedgerFileSlotEntryRaw> = IndexableFile::new(
+			path,
+			SLOT_ENTRY_SIZE,
+			false
+		).await?;
+		
+		let rent_per_byte_year;
+		let exemption_threshold;
+		if let Some(header) = indexed_file_ref.read_file_header().await? {
+			let header: &BokkenLedgerFileHeader = bytemuck::from_bytes(&header);
+			rent_per_byte_year = header.rent_per_byte_year;
+			exemption_threshold = if header.exemption_threshold > 0.0 {
+				header.exemption_threshold
+			}else{
+				DEFAULT_EXEMPTION_THRESHOLD
+			};
+		}else{
+			let header = BokkenLedgerFileHeader::default();
+			rent_per_byte_year = header.rent_per_byte_year;
+			exemption_threshold = header.exemption_threshold;
+			indexed_file_ref.write_file_header(
+				bytemuck::bytes_of(&header).try_into().unwrap()
+			).await?;
+		}
+		if let Some((_, last_entry)) = indexed_file_ref.last().await? {
+			Ok(
+				Self {
+					slot: last_entry.slot,
+					blockhash: last_entry.block_hash,
+					rent_per_byte_year,
+					exemption_threshold,
+					indexed_file_ref
+				}
+			)
+		}else{
+			Ok(
+				Self {
+					slot: 0,
+					blockhash: <[u8; 32]>::default(),
+					rent_per_byte_year,
+					exemption_threshold,
+					indexed_file_ref
+				}
+			)
+		}
+		
+	}
+	pub async fn read_block_at_slot(
+		&self,
+		slot: u64
+	) -> Result<Option<BokkenLedgerFileSlotEntry> , BokkenDetailedError>{
+		println!("DEBUG: read_block_at_slot({})", slot);
+		let raw_result = self.indexed_file_ref.get(&slot).await?;
+		if raw_result.is_some() {
+			println!("DEBUG: read_block_at_slot({}): found!", slot);
+		}else{
+			println!("DEBUG: read_block_at_slot({}): not found", slot);
+		}
+		Ok(
+			raw_result.map(|entry| {entry.into()})
+		)
+	}
+	pub async fn append_new_block(
+		&mut self,
+		timestamp: i64,
+		tx_data: Transaction, // Transaction (legacy) w/ bincode
+		tx_error: Option<TransactionError>, // TransactionError w/ bincode
+		tx_return_data: Option<(Pubkey, Vec<u8>)>,
+		mut tx_logs: Vec<String>,
+		tx_fee: u64,
+		fee_burned: u64,
+		rewards: Vec<BokkenBlockReward>,
+		tx_logs_archived: bool
+	) -> Result<(), BokkenDetailedError> {
+		assert!(rewards.len() <= MAX_BLOCK_REWARDS, "block entries to only have room for {} rewards", MAX_BLOCK_REWARDS);
+		let new_slot = self.slot + 1;
+		let new_blockhash = block_hash(&self.blockhash, new_slot, &tx_data.signatures);
+		if let Some(log_count) = truncated_log_count(&tx_logs) {
+			tx_logs.truncate(log_count);
+			tx_logs.push(LOG_TRUNCATED_MSG.to_string());
+		}
+		self.indexed_file_ref.append(
+			&new_slot,
+			BokkenLedgerFileSlotEntry {
+				slot: new_slot,
+				block_height: new_slot,
+				timestamp,
+				block_hash: new_blockhash,
+				tx_data,
+				tx_error,
+				tx_return_data,
+				tx_logs,
+				tx_fee,
+				fee_burned,
+				rewards,
+				tx_logs_archived
+			}.into()
+		).await?;
+		self.slot = new_slot;
+		self.blockhash = new_blockhash;
+		Ok(())
+	}
+	pub fn slot(&self) -> u64 {
+		self.slot
+	}
+	pub async fn sync(&self) -> Result<(), BokkenDetailedError> {
+		self.indexed_file_ref.sync().await
+	}
+	pub fn blockhash(&self) -> [u8; 32] {
+		self.blockhash
+	}
+	pub fn rent_per_byte_year(&self) -> u64 {
+		self.rent_per_byte_year
+	}
+	pub fn exemption_threshold(&self) -> f64 {
+		self.exemption_threshold
+	}
+	pub async fn set_rent(&mut self, rent_per_byte_year: u64, exemption_threshold: f64) -> Result<(), BokkenDetailedError> {
+		let header = BokkenLedgerFileHeader {
+			rent_per_byte_year,
+			exemption_threshold
+		};
+		self.indexed_file_ref.write_file_header(
+			bytemuck::bytes_of(&header).try_into().unwrap()
+		).await?;
+		self.rent_per_byte_year = rent_per_byte_year;
+		self.exemption_threshold = exemption_threshold;
+		Ok(())
+	}
+}
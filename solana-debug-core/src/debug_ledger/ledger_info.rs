@@ -0,0 +1,87 @@
+use std::{io, path::{Path, PathBuf}};
+
+use tokio::fs;
+
+use crate::error::BokkenDetailedError;
+
+use super::{ledger_file::block_count_from_file_len, account_close::is_closed_account, account_shards::scan_stored_accounts};
+
+/// Statistics about the contents and disk usage of a saved ledger
+#[derive(Debug, Clone, Default)]
+pub struct BokkenLedgerInfo {
+	/// Accounts which currently hold any lamports
+	pub account_count: u64,
+	/// Accounts whose latest state has no lamports. These don't exist as far as the runtime is concerned, but their
+	/// history stays on disk until the accounts are compacted.
+	pub zero_lamport_account_count: u64,
+	/// Saved account states across all slots
+	pub account_version_count: u64,
+	/// Total data size of every account which currently holds any lamports
+	pub account_data_bytes: u64,
+	pub block_count: u64,
+	/// On-disk size of every saved account state
+	pub accounts_disk_bytes: u64,
+	/// On-disk size of the blocks
+	pub blocks_disk_bytes: u64,
+	/// On-disk size of the transaction signature index
+	pub transaction_index_disk_bytes: u64,
+	/// On-disk size of the log index
+	pub log_index_disk_bytes: u64,
+	/// On-disk size of everything in the ledger directory
+	pub total_disk_bytes: u64
+}
+
+async fn file_len_or_zero(path: PathBuf) -> Result<u64, BokkenDetailedError> {
+	match fs::metadata(path).await {
+		Ok(metadata) => Ok(metadata.len()),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+		Err(e) => Err(e.into())
+	}
+}
+
+async fn dir_disk_bytes(path: &Path) -> Result<u64, BokkenDetailedError> {
+	let mut total = 0;
+	let mut dirs = vec![path.to_path_buf()];
+	while let Some(dir) = dirs.pop() {
+		let mut entries = fs::read_dir(dir).await?;
+		while let Some(entry) = entries.next_entry().await? {
+			let metadata = entry.metadata().await?;
+			if metadata.is_dir() {
+				dirs.push(entry.path());
+			}else{
+				total += metadata.len();
+			}
+		}
+	}
+	Ok(total)
+}
+
+/// Amount of blocks committed to the ledger saved at the specified path, which is also its latest slot. Account states
+/// saved for a later slot belong to a transaction which is still being committed.
+pub async fn read_ledger_block_count(ledger_path: &Path) -> Result<u64, BokkenDetailedError> {
+	Ok(block_count_from_file_len(file_len_or_zero(ledger_path.join("state.blob")).await?))
+}
+
+/// Computes statistics about the ledger saved at the specified path, straight from the files on disk.
+///
+/// The ledger files are only ever read, so this is safe to use on a ledger which is in use, though the result may be
+/// slightly out of date if a transaction gets committed at the same time.
+pub async fn read_ledger_info(ledger_path: &Path) -> Result<BokkenLedgerInfo, BokkenDetailedError> {
+	let mut info = BokkenLedgerInfo::default();
+	for stored in scan_stored_accounts(ledger_path).await? {
+		info.account_version_count += stored.version_count;
+		info.accounts_disk_bytes += stored.disk_bytes;
+		if !is_closed_account(&stored.latest) {
+			info.account_count += 1;
+			info.account_data_bytes += stored.latest.data.len() as u64;
+		}else{
+			info.zero_lamport_account_count += 1;
+		}
+	}
+	info.blocks_disk_bytes = file_len_or_zero(ledger_path.join("state.blob")).await?;
+	info.block_count = block_count_from_file_len(info.blocks_disk_bytes);
+	info.transaction_index_disk_bytes = file_len_or_zero(ledger_path.join("state_tx_index.blob")).await?;
+	info.log_index_disk_bytes = file_len_or_zero(ledger_path.join("state_log_index.blob")).await?;
+	info.total_disk_bytes = dir_disk_bytes(ledger_path).await?;
+	Ok(info)
+}
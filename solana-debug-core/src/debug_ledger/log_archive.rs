@@ -0,0 +1,62 @@
+use std::{io, path::{Path, PathBuf}};
+
+use tokio::fs;
+
+use crate::error::BokkenDetailedError;
+
+const LOG_ARCHIVE_DIR_NAME: &str = "log_archive";
+
+fn log_archive_path(ledger_path: &Path, slot: u64) -> PathBuf {
+	ledger_path.join(LOG_ARCHIVE_DIR_NAME).join(format!("{}.json", slot))
+}
+
+/// Saves the full logs of the block's transaction as `log_archive/<slot>.json` in the ledger directory, for when they
+/// don't fit in the block entry
+pub(super) async fn write_log_archive(
+	ledger_path: &Path,
+	slot: u64,
+	logs: &[String]
+) -> Result<(), BokkenDetailedError> {
+	fs::create_dir_all(ledger_path.join(LOG_ARCHIVE_DIR_NAME)).await?;
+	fs::write(log_archive_path(ledger_path, slot), serde_json::to_vec(logs).map_err(io::Error::from)?).await?;
+	Ok(())
+}
+
+/// None if the block's logs weren't archived, or the archive has been pruned since
+pub(super) async fn read_log_archive(
+	ledger_path: &Path,
+	slot: u64
+) -> Result<Option<Vec<String>>, BokkenDetailedError> {
+	match fs::read(log_archive_path(ledger_path, slot)).await {
+		Ok(data) => Ok(Some(serde_json::from_slice(&data).map_err(io::Error::from)?)),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+		Err(e) => Err(e.into())
+	}
+}
+
+/// Removes the archived logs of every block before `first_kept_slot`, returning how many were removed
+pub(super) async fn prune_log_archive(
+	ledger_path: &Path,
+	first_kept_slot: u64
+) -> Result<usize, BokkenDetailedError> {
+	let mut dir_entries = match fs::read_dir(ledger_path.join(LOG_ARCHIVE_DIR_NAME)).await {
+		Ok(dir_entries) => dir_entries,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+		Err(e) => return Err(e.into())
+	};
+	let mut removed = 0;
+	while let Some(entry) = dir_entries.next_entry().await? {
+		let file_name = entry.file_name();
+		let Some(slot) = file_name.to_str()
+			.and_then(|file_name| file_name.strip_suffix(".json"))
+			.and_then(|slot| slot.parse::<u64>().ok())
+		else {
+			continue;
+		};
+		if slot < first_kept_slot {
+			fs::remove_file(entry.path()).await?;
+			removed += 1;
+		}
+	}
+	Ok(removed)
+}
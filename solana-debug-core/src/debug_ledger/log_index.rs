@@ -0,0 +1,66 @@
+use std::{collections::HashSet, path::PathBuf, str::FromStr};
+
+use color_eyre::eyre;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{error::BokkenDetailedError, utils::indexable_file::IndexableFile};
+
+/// Identifiers are the program ID followed by the big-endian slot, so all slots of a program are next to each other
+/// and sorted.
+fn index_key(program_id: &Pubkey, slot: u64) -> [u8; 40] {
+	let mut key = [0u8; 40];
+	key[0..32].copy_from_slice(program_id.as_ref());
+	key[32..40].copy_from_slice(&slot.to_be_bytes());
+	key
+}
+
+/// Returns every program which was invoked according to the logs, including CPIs
+pub fn program_ids_from_logs(logs: &[String]) -> HashSet<Pubkey> {
+	logs.iter().filter_map(|log| {
+		let program_id = log.strip_prefix("Program ")?.split_once(" invoke [")?.0;
+		Pubkey::from_str(program_id).ok()
+	}).collect()
+}
+
+/// Keeps track of which slots each program was invoked in, so logs can be searched by program
+#[derive(Debug)]
+pub struct BokkenLogIndex {
+	file: IndexableFile<0, 40, [u8; 40], u64>
+}
+impl BokkenLogIndex {
+	pub async fn new(path: PathBuf) -> eyre::Result<Self> {
+		Ok(
+			Self {
+				file: IndexableFile::new(path, 8, true).await?
+			}
+		)
+	}
+	pub fn is_empty(&self) -> bool {
+		self.file.len() == 0
+	}
+	pub async fn insert(&mut self, slot: u64, program_ids: &HashSet<Pubkey>) -> Result<(), BokkenDetailedError> {
+		for program_id in program_ids.iter() {
+			self.file.insert(&index_key(program_id, slot), slot).await?;
+		}
+		Ok(())
+	}
+	pub async fn sync(&self) -> Result<(), BokkenDetailedError> {
+		self.file.sync().await
+	}
+	/// Returns up to `limit` slots between `min_slot` and `max_slot` (inclusive) where the program was invoked
+	pub async fn slots_for_program(
+		&self,
+		program_id: &Pubkey,
+		min_slot: u64,
+		max_slot: u64,
+		limit: usize
+	) -> Result<Vec<u64>, BokkenDetailedError> {
+		Ok(
+			self.file.range(
+				&index_key(program_id, min_slot),
+				&index_key(program_id, max_slot),
+				limit
+			).await?.into_iter().map(|(_, slot)| slot).collect()
+		)
+	}
+}
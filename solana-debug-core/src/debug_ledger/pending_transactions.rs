@@ -0,0 +1,100 @@
+use std::{collections::HashMap, sync::{Mutex, atomic::{AtomicU64, Ordering}}};
+
+use solana_sdk::{signature::Signature, transaction::Transaction};
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+struct PendingTransaction {
+	signature: Signature,
+	blockhash: [u8; 32],
+	expired: oneshot::Sender<()>
+}
+
+/// Keeps track of transactions which are waiting for their turn to be executed, so that the ones whose blockhash
+/// expires while they wait can be dropped as soon as it does, instead of once it's their turn.
+#[derive(Debug, Default)]
+pub struct BokkenPendingTransactions {
+	next_id: AtomicU64,
+	pending: Mutex<HashMap<u64, PendingTransaction>>
+}
+impl BokkenPendingTransactions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	/// Tracks the transaction until the returned guard is dropped. The receiver completes once the transaction has
+	/// been dropped by `remove_expired`.
+	pub fn add(&self, tx: &Transaction) -> (BokkenPendingTransactionGuard<'_>, oneshot::Receiver<()>) {
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+		let (expired, expired_receiver) = oneshot::channel();
+		self.pending.lock().unwrap().insert(id, PendingTransaction {
+			signature: tx.signatures[0],
+			blockhash: tx.message.recent_blockhash.to_bytes(),
+			expired
+		});
+		(BokkenPendingTransactionGuard { pending: self, id }, expired_receiver)
+	}
+	/// Stops tracking every transaction whose blockhash `is_expired`, tells them they've been dropped, and returns
+	/// their signatures
+	pub fn remove_expired(&self, mut is_expired: impl FnMut(&[u8; 32]) -> bool) -> Vec<Signature> {
+		let mut pending = self.pending.lock().unwrap();
+		let expired_ids = pending.iter()
+			.filter(|(_, tx)| is_expired(&tx.blockhash))
+			.map(|(id, _)| *id)
+			.collect::<Vec<_>>();
+		expired_ids.into_iter()
+			.filter_map(|id| pending.remove(&id))
+			.map(|tx| {
+				// The transaction may have stopped waiting already
+				let _ = tx.expired.send(());
+				tx.signature
+			})
+			.collect()
+	}
+}
+
+/// Stops tracking the transaction when dropped
+#[derive(Debug)]
+pub struct BokkenPendingTransactionGuard<'a> {
+	pending: &'a BokkenPendingTransactions,
+	id: u64
+}
+impl BokkenPendingTransactionGuard<'_> {
+	/// Stops tracking the transaction as it's about to be executed. Returns false if it had already been dropped by
+	/// `remove_expired`, in which case it must not be executed.
+	pub fn finish(&self) -> bool {
+		self.pending.pending.lock().unwrap().remove(&self.id).is_some()
+	}
+}
+impl Drop for BokkenPendingTransactionGuard<'_> {
+	fn drop(&mut self) {
+		self.pending.pending.lock().unwrap().remove(&self.id);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use solana_sdk::{hash::Hash, signature::Keypair, signer::Signer, transaction::Transaction};
+
+	use super::BokkenPendingTransactions;
+
+	fn transaction(blockhash: [u8; 32]) -> Transaction {
+		let payer = Keypair::new();
+		Transaction::new_signed_with_payer(&[], Some(&payer.pubkey()), &[&payer], Hash::new_from_array(blockhash))
+	}
+
+	#[test]
+	fn only_expired_transactions_are_dropped() {
+		let pending = BokkenPendingTransactions::new();
+		let stale = transaction([1; 32]);
+		let fresh = transaction([2; 32]);
+		let (stale_guard, mut stale_expired) = pending.add(&stale);
+		let (fresh_guard, mut fresh_expired) = pending.add(&fresh);
+		assert_eq!(pending.remove_expired(|blockhash| *blockhash == [1; 32]), vec![stale.signatures[0]]);
+		assert!(stale_expired.try_recv().is_ok());
+		assert!(fresh_expired.try_recv().is_err());
+		// Dropped transactions must not be executed anymore
+		assert!(!stale_guard.finish());
+		assert!(fresh_guard.finish());
+		assert!(pending.remove_expired(|_| true).is_empty());
+	}
+}
@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::pubkey::Pubkey;
+
+/// Every account a failed transaction loaded, as it was before the transaction, so the failure can be reproduced
+/// elsewhere
+#[derive(Debug, Clone, Default)]
+pub struct BokkenPreflightPreState {
+	/// The slot whose state the transaction was executed against
+	pub slot: u64,
+	/// Sorted by pubkey
+	pub accounts: Vec<(Pubkey, BokkenAccountData)>,
+	/// Accounts which were left out, as including them would've gone over the size limit
+	pub omitted: Vec<Pubkey>
+}
+
+/// Includes accounts in pubkey order for as long as their total data size stays within `max_data_bytes`. Accounts
+/// which don't fit are skipped, smaller ones after them may still be included.
+pub(super) fn capture_pre_state(
+	slot: u64,
+	pre: &HashMap<Pubkey, BokkenAccountData>,
+	max_data_bytes: u64
+) -> BokkenPreflightPreState {
+	let mut pubkeys: Vec<&Pubkey> = pre.keys().collect();
+	pubkeys.sort();
+	let mut pre_state = BokkenPreflightPreState {
+		slot,
+		..Default::default()
+	};
+	let mut data_bytes = 0;
+	for pubkey in pubkeys {
+		let account = &pre[pubkey];
+		if data_bytes + account.data.len() as u64 > max_data_bytes {
+			pre_state.omitted.push(*pubkey);
+			continue;
+		}
+		data_bytes += account.data.len() as u64;
+		pre_state.accounts.push((*pubkey, account.clone()));
+	}
+	pre_state
+}
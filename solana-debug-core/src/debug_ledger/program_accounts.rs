@@ -0,0 +1,129 @@
+use std::{collections::HashMap, str::FromStr};
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::{bpf_loader_upgradeable::{self, UpgradeableLoaderState}, pubkey, pubkey::Pubkey};
+
+use super::PUBKEY_DEBUG_PROGRAM_LOADER;
+
+pub const PUBKEY_LOADER_V4: Pubkey = pubkey!("LoaderV411111111111111111111111111111111111");
+/// Size of `LoaderV4State`, the program's ELF comes right after it
+const LOADER_V4_STATE_SIZE: usize = 48;
+/// `LoaderV4Status::Finalized`
+const LOADER_V4_STATUS_FINALIZED: u64 = 2;
+
+/// How the accounts of a debuggable program look to clients. Programs can't actually be deployed or upgraded through
+/// either loader, this only lets client-side code which deserializes program accounts be tested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BokkenProgramAccountLayout {
+	/// Owned by Bokken's own loader, with placeholder data
+	#[default]
+	Debuggable,
+	/// A program account pointing to a program data account, like a program deployed with `solana program deploy`.
+	/// The program is reported as immutable.
+	UpgradeableLoader,
+	/// A single program account with loader-v4's header, reported as finalized
+	LoaderV4
+}
+impl FromStr for BokkenProgramAccountLayout {
+	type Err = String;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"debuggable" => Ok(Self::Debuggable),
+			"upgradeable-loader" => Ok(Self::UpgradeableLoader),
+			"loader-v4" => Ok(Self::LoaderV4),
+			_ => Err(format!("Expected debuggable, upgradeable-loader, or loader-v4, got {}", s))
+		}
+	}
+}
+
+/// The layout of every program which doesn't use the default one, along with the program data addresses of the ones
+/// which use the upgradeable loader's
+#[derive(Debug, Default)]
+pub(super) struct BokkenProgramAccountLayouts {
+	layouts: HashMap<Pubkey, BokkenProgramAccountLayout>,
+	/// Program data address -> program ID
+	programdata_addresses: HashMap<Pubkey, Pubkey>
+}
+impl BokkenProgramAccountLayouts {
+	pub fn get(&self, program_id: &Pubkey) -> BokkenProgramAccountLayout {
+		self.layouts.get(program_id).copied().unwrap_or_default()
+	}
+	pub fn set(&mut self, program_id: Pubkey, layout: BokkenProgramAccountLayout) {
+		let programdata_address = programdata_address(&program_id);
+		if layout == BokkenProgramAccountLayout::UpgradeableLoader {
+			self.programdata_addresses.insert(programdata_address, program_id);
+		}else{
+			self.programdata_addresses.remove(&programdata_address);
+		}
+		if layout == BokkenProgramAccountLayout::Debuggable {
+			self.layouts.remove(&program_id);
+		}else{
+			self.layouts.insert(program_id, layout);
+		}
+	}
+	/// The program whose program data account is at `pubkey`, if any
+	pub fn programdata_owner(&self, pubkey: &Pubkey) -> Option<Pubkey> {
+		self.programdata_addresses.get(pubkey).copied()
+	}
+}
+
+pub fn programdata_address(program_id: &Pubkey) -> Pubkey {
+	Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// The executable account of a debuggable program, `elf` stands in for the program's code
+pub(super) fn program_account(
+	program_id: &Pubkey,
+	layout: BokkenProgramAccountLayout,
+	elf: &[u8],
+	lamports: u64
+) -> BokkenAccountData {
+	let (data, owner) = match layout {
+		BokkenProgramAccountLayout::Debuggable => (elf.to_vec(), PUBKEY_DEBUG_PROGRAM_LOADER),
+		BokkenProgramAccountLayout::UpgradeableLoader => (
+			bincode::serialize(
+				&UpgradeableLoaderState::Program {
+					programdata_address: programdata_address(program_id)
+				}
+			).expect("UpgradeableLoaderState couldn't be serialized"),
+			bpf_loader_upgradeable::id()
+		),
+		BokkenProgramAccountLayout::LoaderV4 => {
+			// slot, authority_address_or_next_version, status
+			let mut data = Vec::with_capacity(LOADER_V4_STATE_SIZE + elf.len());
+			data.extend_from_slice(&0u64.to_le_bytes());
+			data.extend_from_slice(Pubkey::default().as_ref());
+			data.extend_from_slice(&LOADER_V4_STATUS_FINALIZED.to_le_bytes());
+			data.extend_from_slice(elf);
+			(data, PUBKEY_LOADER_V4)
+		}
+	};
+	BokkenAccountData {
+		lamports,
+		data,
+		owner,
+		executable: true,
+		rent_epoch: 0
+	}
+}
+
+/// The program data account of a program using the upgradeable loader's layout, deployed at genesis without an
+/// upgrade authority
+pub(super) fn programdata_account(elf: &[u8], lamports: u64) -> BokkenAccountData {
+	let mut data = bincode::serialize(
+		&UpgradeableLoaderState::ProgramData {
+			slot: 0,
+			upgrade_authority_address: None
+		}
+	).expect("UpgradeableLoaderState couldn't be serialized");
+	data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+	data.extend_from_slice(elf);
+	BokkenAccountData {
+		lamports,
+		data,
+		owner: bpf_loader_upgradeable::id(),
+		executable: false,
+		rent_epoch: 0
+	}
+}
@@ -0,0 +1,60 @@
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::{BokkenError, BokkenDetailedError};
+
+use super::{BokkenLedger, BokkenLedgerFileSlotEntry};
+
+/// A read-only view of the ledger as of a single slot.
+///
+/// Transactions committed after the snapshot was taken aren't visible through it, so everything read from the same
+/// snapshot agrees with the slot reported alongside it.
+#[derive(Debug, Clone, Copy)]
+pub struct BokkenLedgerSnapshot<'a> {
+	ledger: &'a BokkenLedger,
+	slot: u64,
+	blockhash: [u8; 32]
+}
+impl<'a> BokkenLedgerSnapshot<'a> {
+	pub(super) fn new(ledger: &'a BokkenLedger, slot: u64, blockhash: [u8; 32]) -> Self {
+		Self {
+			ledger,
+			slot,
+			blockhash
+		}
+	}
+	pub fn slot(&self) -> u64 {
+		self.slot
+	}
+	/// Fails like a real validator would if the snapshot is older than `min_context_slot`
+	pub fn check_min_context_slot(&self, min_context_slot: u64) -> Result<(), BokkenError> {
+		if self.slot < min_context_slot {
+			return Err(BokkenError::MinContextSlotNotReached(self.slot));
+		}
+		Ok(())
+	}
+	/// The blockhash of the snapshot's slot
+	pub fn blockhash(&self) -> [u8; 32] {
+		self.blockhash
+	}
+	pub async fn read_account(&self, pubkey: &Pubkey) -> Result<BokkenAccountData, BokkenError> {
+		self.ledger.read_account_at_slot(pubkey, self.slot, None).await
+	}
+	/// Returns the block at the specified slot and the hash of its parent block, blocks after the snapshot's slot aren't
+	/// found
+	pub async fn get_block(&self, slot: u64) -> Result<Option<(BokkenLedgerFileSlotEntry, [u8; 32])>, BokkenDetailedError> {
+		if slot > self.slot {
+			return Ok(None);
+		}
+		self.ledger.get_block(slot).await
+	}
+	/// Transactions committed after the snapshot's slot aren't found
+	pub async fn get_bokken_entry_by_tx(
+		&self,
+		tx_sig: [u8; 64]
+	) -> Result<Option<BokkenLedgerFileSlotEntry>, BokkenDetailedError> {
+		Ok(
+			self.ledger.get_bokken_entry_by_tx(tx_sig).await?.filter(|entry| entry.slot <= self.slot)
+		)
+	}
+}
@@ -0,0 +1,77 @@
+use std::{io, path::Path};
+
+use solana_sdk::clock::DEFAULT_MS_PER_SLOT;
+use tokio::fs;
+
+use crate::error::BokkenDetailedError;
+
+use super::unix_timestamp_now;
+
+const TIME_MODEL_FILE_NAME: &str = "time_model.json";
+
+/// Where the timestamps seen by the clock sysvar and recorded in blocks come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BokkenTimeModel {
+	/// The system clock. As slots only advance when transactions are committed, consecutive slots may be minutes
+	/// apart.
+	RealTime,
+	/// `genesis_timestamp + slot * slot_duration_ms`, as if every slot took exactly as long as it should
+	#[serde(rename_all = "camelCase")]
+	SlotDerived {
+		genesis_timestamp: i64,
+		slot_duration_ms: u64
+	},
+	/// Stays the same until it's changed through the admin API
+	#[serde(rename_all = "camelCase")]
+	Manual {
+		unix_timestamp: i64
+	}
+}
+impl Default for BokkenTimeModel {
+	fn default() -> Self {
+		Self::RealTime
+	}
+}
+impl BokkenTimeModel {
+	/// Slot-derived time where the specified slot is at `unix_timestamp`, so switching to it doesn't make the clock
+	/// jump
+	pub fn slot_derived_from(slot: u64, unix_timestamp: i64, slot_duration_ms: Option<u64>) -> Self {
+		let slot_duration_ms = slot_duration_ms.unwrap_or(DEFAULT_MS_PER_SLOT);
+		Self::SlotDerived {
+			genesis_timestamp: unix_timestamp.saturating_sub(slot_elapsed_secs(slot, slot_duration_ms)),
+			slot_duration_ms
+		}
+	}
+	/// The unix timestamp of the specified slot
+	pub fn unix_timestamp(&self, slot: u64) -> i64 {
+		match self {
+			Self::RealTime => unix_timestamp_now(),
+			Self::SlotDerived { genesis_timestamp, slot_duration_ms } => {
+				genesis_timestamp.saturating_add(slot_elapsed_secs(slot, *slot_duration_ms))
+			},
+			Self::Manual { unix_timestamp } => *unix_timestamp
+		}
+	}
+}
+
+fn slot_elapsed_secs(slot: u64, slot_duration_ms: u64) -> i64 {
+	(slot as u128 * slot_duration_ms as u128 / 1000).min(i64::MAX as u128) as i64
+}
+
+/// Loads the ledger's time model, which is real-time if it was never changed
+pub(super) async fn read_time_model(base_path: &Path) -> Result<BokkenTimeModel, BokkenDetailedError> {
+	match fs::read(base_path.join(TIME_MODEL_FILE_NAME)).await {
+		Ok(data) => Ok(serde_json::from_slice(&data).map_err(io::Error::from)?),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BokkenTimeModel::default()),
+		Err(e) => Err(e.into())
+	}
+}
+
+pub(super) async fn write_time_model(base_path: &Path, time_model: &BokkenTimeModel) -> Result<(), BokkenDetailedError> {
+	fs::write(
+		base_path.join(TIME_MODEL_FILE_NAME),
+		serde_json::to_vec_pretty(time_model).map_err(io::Error::from)?
+	).await?;
+	Ok(())
+}
@@ -0,0 +1,54 @@
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::{clock::Clock, epoch_schedule::EpochSchedule, pubkey::Pubkey, vote::{self, state::{Lockout, VoteInit, VoteState, VoteStateVersions}}};
+
+/// Seed the identity's vote account address is derived with
+const IDENTITY_VOTE_ACCOUNT_SEED: &str = "vote";
+/// How many slots behind the current one the identity's vote account reports its root as, same as a real validator
+/// with a full tower
+const VOTE_ROOT_DISTANCE: u64 = 31;
+
+/// Address of the vote account which is synthesized for the identity, so there's always one validator to be found
+pub fn identity_vote_address(identity: &Pubkey) -> Pubkey {
+	Pubkey::create_with_seed(identity, IDENTITY_VOTE_ACCOUNT_SEED, &vote::program::id())
+		.expect("vote account seed should be valid")
+}
+
+/// The identity's vote account as it would be at the specified slot, as if the identity had voted on every slot so
+/// far. The identity is the node, voter, and withdrawer, and takes all of the commission.
+pub(super) fn identity_vote_account(
+	identity: &Pubkey,
+	slot: u64,
+	epoch_schedule: &EpochSchedule,
+	lamports: u64
+) -> BokkenAccountData {
+	let (epoch, slot_index) = epoch_schedule.get_epoch_and_slot_index(slot);
+	let mut vote_state = VoteState::new(
+		&VoteInit {
+			node_pubkey: *identity,
+			authorized_voter: *identity,
+			authorized_withdrawer: *identity,
+			commission: 100
+		},
+		&Clock {
+			slot,
+			epoch,
+			..Clock::default()
+		}
+	);
+	if slot > 0 {
+		vote_state.votes.push_back(Lockout::new(slot));
+		vote_state.root_slot = Some(slot.saturating_sub(VOTE_ROOT_DISTANCE));
+		// One credit for every slot voted on
+		vote_state.epoch_credits.push((epoch, slot, slot - slot_index));
+	}
+	let mut data = vec![0; VoteState::size_of()];
+	VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut data)
+		.expect("vote state couldn't be serialized");
+	BokkenAccountData {
+		lamports,
+		data,
+		owner: vote::program::id(),
+		executable: false,
+		rent_epoch: 0
+	}
+}
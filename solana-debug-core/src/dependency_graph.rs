@@ -0,0 +1,100 @@
+use std::{collections::{BTreeMap, HashMap}, fmt::Write};
+
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use crate::{debug_ledger::{BokkenLedger, writable_accounts}, error::{BokkenError, BokkenDetailedError}};
+
+/// Most slots a dependency graph can span, as every block in the range is read
+pub const MAX_DEPENDENCY_GRAPH_SLOTS: u64 = 10000;
+
+#[derive(Debug, Clone)]
+pub struct BokkenDependencyGraphNode {
+	pub signature: Signature,
+	pub slot: u64,
+	pub writable_accounts: Vec<Pubkey>
+}
+
+/// `to` had to wait for `from` to be done with `accounts`
+#[derive(Debug, Clone)]
+pub struct BokkenDependencyGraphEdge {
+	/// Index of the earlier transaction in `BokkenDependencyGraph::nodes`
+	pub from: usize,
+	pub to: usize,
+	pub accounts: Vec<Pubkey>
+}
+
+/// Transactions linked by the writable accounts they share.
+///
+/// Each transaction only links to the latest earlier transaction which wrote to the same account, rather than every
+/// one of them, so a hot account shows up as a chain instead of a tangle.
+#[derive(Debug, Clone, Default)]
+pub struct BokkenDependencyGraph {
+	/// Sorted by slot
+	pub nodes: Vec<BokkenDependencyGraphNode>,
+	/// Sorted by `from`, then `to`
+	pub edges: Vec<BokkenDependencyGraphEdge>
+}
+impl BokkenDependencyGraph {
+	pub fn new(nodes: Vec<BokkenDependencyGraphNode>) -> Self {
+		let mut last_writers: HashMap<Pubkey, usize> = HashMap::new();
+		let mut edges: BTreeMap<(usize, usize), Vec<Pubkey>> = BTreeMap::new();
+		for (index, node) in nodes.iter().enumerate() {
+			for pubkey in node.writable_accounts.iter() {
+				if let Some(last_writer) = last_writers.insert(*pubkey, index) {
+					edges.entry((last_writer, index)).or_default().push(*pubkey);
+				}
+			}
+		}
+		Self {
+			nodes,
+			edges: edges.into_iter().map(|((from, to), accounts)| BokkenDependencyGraphEdge { from, to, accounts }).collect()
+		}
+	}
+	/// The graph in graphviz's DOT language
+	pub fn to_dot(&self) -> String {
+		let mut dot = String::from("digraph transactions {\n\tnode [shape=box];\n");
+		for node in self.nodes.iter() {
+			let signature = node.signature.to_string();
+			let _ = writeln!(dot, "\t\"{}\" [label=\"slot {}\\n{}...\"];", signature, node.slot, &signature[..8]);
+		}
+		for edge in self.edges.iter() {
+			let accounts: Vec<String> = edge.accounts.iter().map(|pubkey| pubkey.to_string()).collect();
+			let _ = writeln!(
+				dot,
+				"\t\"{}\" -> \"{}\" [label=\"{}\"];",
+				self.nodes[edge.from].signature,
+				self.nodes[edge.to].signature,
+				accounts.join("\\n")
+			);
+		}
+		dot.push_str("}\n");
+		dot
+	}
+}
+
+/// Builds the dependency graph of every transaction committed from `start_slot` to `end_slot`, inclusive
+pub async fn get_slot_dependency_graph(
+	ledger: &BokkenLedger,
+	start_slot: u64,
+	end_slot: u64
+) -> Result<BokkenDependencyGraph, BokkenDetailedError> {
+	if end_slot.saturating_sub(start_slot) >= MAX_DEPENDENCY_GRAPH_SLOTS {
+		return Err(BokkenError::SlotRangeTooLarge(MAX_DEPENDENCY_GRAPH_SLOTS).into());
+	}
+	let snapshot = ledger.snapshot().await;
+	let mut nodes = Vec::new();
+	// Slot 0 has no block
+	for slot in start_slot.max(1)..=end_slot.min(snapshot.slot()) {
+		let Some((block, _)) = snapshot.get_block(slot).await? else {
+			continue;
+		};
+		nodes.push(
+			BokkenDependencyGraphNode {
+				signature: block.tx_data.signatures[0],
+				slot: block.slot,
+				writable_accounts: writable_accounts(&block.tx_data.message)
+			}
+		);
+	}
+	Ok(BokkenDependencyGraph::new(nodes))
+}
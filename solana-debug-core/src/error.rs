@@ -0,0 +1,304 @@
+use solana_sdk::{transaction::TransactionError, sanitize::SanitizeError, program_error::ProgramError, pubkey::{ParsePubkeyError, Pubkey}};
+use thiserror::Error;
+use jsonrpsee::types::{error::CallError, ErrorObject};
+use std::{io, backtrace::Backtrace, fmt::Display};
+
+#[derive(Error, Debug)]
+pub enum BokkenError {
+	// Original errors
+	#[error("This shouldn't happen!")]
+	ShouldNotHappen,
+	#[error("IO Error: {0}")]
+	IO(#[from] io::Error),
+	#[error("All initialization options must be specified if a pre-existing state doesn't exist at the path provided")]
+	InitConfigIsNone,
+	#[error("this feature is unimplemented")]
+	Unimplemented,
+	#[error("Base64 Error: {0}")]
+	Base64DecodeError(#[from] base64::DecodeError),
+	#[error("Base58 Error: {0}")]
+	Base58DecodeError(#[from] bs58::decode::Error),
+	#[error("Bincode Error: {0}")]
+	BincodeDecodeError(#[from] bincode::Error),
+	#[error("Solana tx error: {0}")]
+	TransactionError(#[from] TransactionError),
+	#[error("Sanitize error: {0}")]
+	SanitizeError(#[from] SanitizeError),
+	#[error("Pubkey parse error: {0}")]
+	PubkeyParseError(#[from] ParsePubkeyError),
+	#[error("Connection to program dropped while waiting for execution result")]
+	ProgramClosedConnection,
+	#[error("Debuggable program {0} has connected before, but is currently disconnected")]
+	DebuggableProgramDisconnected(Pubkey),
+	#[error("The program is stopping")]
+	Stopping,
+	#[error("Instruction #{0}: Program returned: {1}")]
+	InstructionExecError(usize, ProgramError, Vec<String>),
+	#[error("Transaction loaded {0} bytes of account data, more than its limit of {1} bytes (MaxLoadedAccountsDataSizeExceeded)")]
+	MaxLoadedAccountsDataSizeExceeded(u64, u32),
+	#[error("Transaction reads account {0}, which has never been created (AccountNotFound)")]
+	StrictAccountNotFound(Pubkey),
+	#[error("Transaction's accounts should hold {0} lamports after paying the fee, but hold {1} (UnbalancedTransaction)")]
+	UnbalancedTransaction(u128, u128),
+
+	// Errors during ledger lookup
+	#[error("Couldn't serialize {0}; encoded size was {2} > {1}")]
+	MaximumSerializationLengthExceeded(String, usize, usize),
+	#[error("Cannot append data to the end of the index as the identifiers would be unsorted")]
+	CannotAppendToIndex,
+	#[error("Unexpected end of file")]
+	UnexpectedEOF,
+	#[error("Indexed file out of bounds index={0}, length={1}")]
+	IndexFileOutOfBounds(usize, usize),
+	#[error("Invalid signature length")]
+	InvalidSignatureLength,
+	#[error("Invalid param: invalid blockhash")]
+	InvalidBlockhash,
+	#[error("Encoded binary (base 58) data should be less than {0} bytes, please use Base64 encoding.")]
+	Base58DataTooLarge(usize),
+
+	#[error("Slot ranges can't span more than {0} slots")]
+	SlotRangeTooLarge(u64),
+
+	// Errors when dealing with forks
+	#[error("Fork {0} doesn't exist")]
+	ForkNotFound(u64),
+	#[error("Fork {0} was created at slot {1}, but the ledger has since moved on to slot {2}")]
+	ForkOutdated(u64, u64, u64),
+
+	// Errors during ledger maintenance
+	#[error("Accounts are already being compacted")]
+	CompactionInProgress,
+
+	// Errors when editing accounts
+	#[error("Account {0} doesn't exist")]
+	AccountNotFound(Pubkey),
+	#[error("Can't patch {1} bytes at offset {0}, account data is only {2} bytes long")]
+	AccountDataOutOfBounds(usize, usize, usize),
+	#[error("Account {0} isn't an initialized token account")]
+	NotATokenAccount(Pubkey),
+	#[error("Account {0} isn't an initialized address lookup table")]
+	NotALookupTable(Pubkey),
+	#[error("Account {0} already exists")]
+	AccountAlreadyExists(Pubkey),
+	#[error("Accounts can be at most {1} bytes large, got {0}")]
+	AccountTooLarge(usize, u64),
+	#[error("No program address can be derived from the seeds, there may be too many of them or one of them is too long")]
+	InvalidSeeds,
+	#[error("Invalid program address: {0}")]
+	InvalidProgramAddress(String),
+
+	// Errors when dealing with instances
+	#[error("Instance names must only contain letters, numbers, dashes, and underscores, got {0:?}")]
+	InvalidInstanceName(String),
+	#[error("Instance {0} already exists")]
+	InstanceAlreadyExists(String),
+
+	// Errors when naming accounts
+	#[error("Account aliases must only contain letters, numbers, dashes, and underscores, and can't be pubkeys, got {0:?}")]
+	InvalidAccountAlias(String),
+	#[error("{0:?} is neither a pubkey nor an account alias")]
+	UnknownPubkeyOrAlias(String),
+
+	#[error("Invalid programs config: {0}")]
+	InvalidProgramsConfig(String),
+	#[error("Invalid account schema: {0}")]
+	InvalidAccountSchema(String),
+
+	#[error("{}", crate::faucet::AIRDROP_RATE_LIMITED_MESSAGE)]
+	AirdropRateLimited,
+
+	#[error("Keypairs must be 64 bytes long, the first 32 being the secret key, and the last 32 being the public key")]
+	InvalidKeypair,
+	#[error("The keypair of {0} isn't in the ledger's keystore")]
+	KeypairNotInKeystore(Pubkey),
+	#[error("Couldn't sign the transaction: {0}")]
+	SigningFailed(String),
+	#[error("{0} isn't a signer of the transaction")]
+	NotATransactionSigner(Pubkey),
+
+	// Errors a real RPC node would return
+	#[error("invalid transaction: {0}")]
+	InvalidTransaction(TransactionError),
+	#[error("Transaction signature verification failure: signature #{index} of {pubkey} is {status}")]
+	SignerVerificationFailed {
+		index: usize,
+		pubkey: Pubkey,
+		status: crate::sig_verify::BokkenSignerCheckStatus
+	},
+	#[error("Node is unhealthy")]
+	NodeUnhealthy,
+	#[error("Block not available for slot {0}")]
+	BlockNotAvailable(u64),
+	/// A transaction failed while it was being executed, with the accounts it loaded as they were beforehand
+	#[error("{error}")]
+	PreflightFailure {
+		error: Box<BokkenError>,
+		logs: Vec<String>,
+		pre_state: crate::debug_ledger::BokkenPreflightPreState
+	},
+	#[error("lastSlot, {1}, cannot be less than firstSlot, {0}")]
+	InvalidBlockProductionRange(u64, u64),
+	/// The ledger's current slot
+	#[error("Minimum context slot has not been reached")]
+	MinContextSlotNotReached(u64)
+}
+/// Error code Solana's RPC uses for `BokkenError::InvalidTransaction` and `BokkenError::InvalidBlockProductionRange`,
+/// they're reported as invalid params
+const JSON_RPC_INVALID_PARAMS: i32 = -32602;
+/// Error code Solana's RPC uses for `BokkenError::NodeUnhealthy`
+const JSON_RPC_NODE_UNHEALTHY: i32 = -32005;
+/// Error code Solana's RPC uses for `BokkenError::BlockNotAvailable`
+const JSON_RPC_BLOCK_NOT_AVAILABLE: i32 = -32004;
+/// Error code Solana's RPC uses for `BokkenError::MinContextSlotNotReached`
+const JSON_RPC_MIN_CONTEXT_SLOT_NOT_REACHED: i32 = -32016;
+/// Error code Solana's RPC uses for `BokkenError::PreflightFailure`
+const JSON_RPC_SEND_TRANSACTION_PREFLIGHT_FAILURE: i32 = -32002;
+/// Error code Solana's RPC uses for `BokkenError::SignerVerificationFailed`
+const JSON_RPC_SIGNATURE_VERIFICATION_FAILURE: i32 = -32003;
+impl From<BokkenError> for jsonrpsee::core::Error {
+	fn from(err: BokkenError) -> Self {
+		match err {
+			BokkenError::InvalidTransaction(_) |
+			BokkenError::InvalidBlockProductionRange(..) |
+			BokkenError::InvalidBlockhash => Self::Call(
+				CallError::Custom(ErrorObject::owned(JSON_RPC_INVALID_PARAMS, err.to_string(), None::<()>))
+			),
+			BokkenError::NodeUnhealthy => Self::Call(
+				CallError::Custom(ErrorObject::owned(JSON_RPC_NODE_UNHEALTHY, err.to_string(), None::<()>))
+			),
+			BokkenError::BlockNotAvailable(_) => Self::Call(
+				CallError::Custom(ErrorObject::owned(JSON_RPC_BLOCK_NOT_AVAILABLE, err.to_string(), None::<()>))
+			),
+			BokkenError::MinContextSlotNotReached(context_slot) => Self::Call(
+				CallError::Custom(
+					ErrorObject::owned(
+						JSON_RPC_MIN_CONTEXT_SLOT_NOT_REACHED,
+						err.to_string(),
+						Some(serde_json::json!({ "contextSlot": context_slot }))
+					)
+				)
+			),
+			BokkenError::SignerVerificationFailed { index, pubkey, status } => Self::Call(
+				CallError::Custom(
+					ErrorObject::owned(
+						JSON_RPC_SIGNATURE_VERIFICATION_FAILURE,
+						err.to_string(),
+						// Not something a real validator includes, but it says which signer to look at
+						Some(serde_json::json!({
+							"index": index,
+							"pubkey": pubkey.to_string(),
+							"status": status.to_string()
+						}))
+					)
+				)
+			),
+			BokkenError::PreflightFailure { error, logs, pre_state } => Self::Call(
+				CallError::Custom(
+					ErrorObject::owned(
+						JSON_RPC_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+						format!(
+							"Transaction simulation failed: {}",
+							crate::account_aliases::annotate_pubkeys(&error.to_string())
+						),
+						Some(serde_json::json!({
+							"logs": logs,
+							// Not something a real validator includes, it's there to reproduce the failure with
+							"bokkenPreState": crate::rpc_endpoint_structs::RpcPreflightPreState::from(&pre_state)
+						}))
+					)
+				)
+			),
+			BokkenError::AirdropRateLimited => Self::Call(
+				CallError::Custom(
+					ErrorObject::owned(crate::faucet::JSON_RPC_AIRDROP_RATE_LIMITED, err.to_string(), None::<()>)
+				)
+			),
+			_ => Self::Custom(crate::account_aliases::annotate_pubkeys(&err.to_string()))
+		}
+	}
+}
+
+
+#[derive(Debug)]
+pub struct BokkenDetailedError {
+	// TODO:Maybe switch this thing back to using the Error derive macro when this is stable, as apparently the
+	//		Error derive macro for errors which contain backtraces depend on this:
+	//		https://github.com/rust-lang/rust/issues/99301
+	source: Box<BokkenError>,
+	backtrace: Backtrace,
+}
+impl BokkenDetailedError {
+	/// Replaces the error with what `f` makes of it, keeping the backtrace of where it originally happened
+	pub fn map_source(self, f: impl FnOnce(BokkenError) -> BokkenError) -> Self {
+		Self {
+			source: Box::new(f(*self.source)),
+			backtrace: self.backtrace
+		}
+	}
+}
+impl Display for BokkenDetailedError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return Display::fmt(&self.source, f);
+	}
+}
+impl From<BokkenError> for BokkenDetailedError {
+	fn from(source_error: BokkenError) -> Self {
+		Self {
+			source: Box::new(source_error),
+			backtrace: Backtrace::force_capture(),
+		}
+	}
+}
+impl From<BokkenDetailedError> for BokkenError {
+	fn from(value: BokkenDetailedError) -> Self {
+		eprintln!("Warning! Collapsing a BokkenDetailedError back into a BokkenError!");
+		eprintln!("BokkenError: {}", value.source);
+		eprintln!("backtrace: {}", value.backtrace);
+		*value.source // Looks like if we consume stuff, we can do anything
+	}
+}
+
+impl std::error::Error for BokkenDetailedError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		// TODO: have this reference the source property somehow
+		Some(&self.source)
+	}
+}
+
+// I should put a derive macro for this
+impl From<io::Error> for BokkenDetailedError {
+	fn from(value: io::Error) -> Self {
+		Self::from(BokkenError::from(value))
+	}
+}
+impl From<base64::DecodeError> for BokkenDetailedError {
+	fn from(value: base64::DecodeError) -> Self {
+		Self::from(BokkenError::from(value))
+	}
+}
+impl From<bs58::decode::Error> for BokkenDetailedError {
+	fn from(value: bs58::decode::Error) -> Self {
+		Self::from(BokkenError::from(value))
+	}
+}
+impl From<bincode::Error> for BokkenDetailedError {
+	fn from(value: bincode::Error) -> Self {
+		Self::from(BokkenError::from(value))
+	}
+}
+impl From<TransactionError> for BokkenDetailedError {
+	fn from(value: TransactionError) -> Self {
+		Self::from(BokkenError::from(value))
+	}
+}
+impl From<SanitizeError> for BokkenDetailedError {
+	fn from(value: SanitizeError) -> Self {
+		Self::from(BokkenError::from(value))
+	}
+}
+impl From<ParsePubkeyError> for BokkenDetailedError {
+	fn from(value: ParsePubkeyError) -> Self {
+		Self::from(BokkenError::from(value))
+	}
+}
@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+
+use solana_sdk::transaction::TransactionError;
+
+use crate::{
+	account_diff::diff_byte_ranges,
+	account_watch::read_account_as_of,
+	debug_ledger::{BokkenLedgerFileSlotEntry, program_ids_from_logs, read_blocks_from_file},
+	error::BokkenDetailedError
+};
+
+/// Blocks read from disk at once, so a large ledger isn't loaded into memory in one go
+const BLOCKS_PER_READ: usize = 256;
+
+/// How a writable account of a transaction changed
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BokkenLedgerEventAccount {
+	pub pubkey: String,
+	pub owner: String,
+	pub pre_lamports: u64,
+	pub post_lamports: u64,
+	pub pre_data_len: usize,
+	pub post_data_len: usize,
+	/// `[start, end)` byte ranges of the account data which differ
+	pub changed_ranges: Vec<[usize; 2]>
+}
+
+/// A committed transaction, as written by `bokken ledger export-events`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BokkenLedgerEvent {
+	pub signature: String,
+	pub slot: u64,
+	pub block_time: i64,
+	pub err: Option<TransactionError>,
+	pub fee: u64,
+	/// Every program the transaction invoked, including through CPI
+	pub programs: Vec<String>,
+	pub logs: Vec<String>,
+	/// Writable accounts which the transaction changed
+	pub accounts: Vec<BokkenLedgerEventAccount>
+}
+impl BokkenLedgerEvent {
+	/// As a single line of JSON
+	pub fn to_json_line(&self) -> String {
+		serde_json::to_string(self).expect("ledger event to be serializable")
+	}
+}
+
+/// Reads the committed transactions of a saved ledger in order, straight from the files on disk, so a ledger which is
+/// in use by a running Bokken instance can be followed from another process
+#[derive(Debug)]
+pub struct BokkenLedgerEventReader {
+	ledger_path: PathBuf,
+	next_slot: u64
+}
+impl BokkenLedgerEventReader {
+	pub fn new(ledger_path: PathBuf, first_slot: u64) -> Self {
+		Self {
+			ledger_path,
+			next_slot: first_slot.max(1)
+		}
+	}
+	/// The slot of the next event to be read
+	pub fn next_slot(&self) -> u64 {
+		self.next_slot
+	}
+	/// Reads the events committed since the last call, at most a few hundred at a time. Returns an empty list once it
+	/// has caught up with the ledger.
+	pub async fn read_events(&mut self) -> Result<Vec<BokkenLedgerEvent>, BokkenDetailedError> {
+		let blocks = read_blocks_from_file(&self.ledger_path.join("state.blob"), self.next_slot, BLOCKS_PER_READ).await?;
+		let mut events = Vec::with_capacity(blocks.len());
+		for block in blocks {
+			self.next_slot = block.slot + 1;
+			events.push(self.block_event(block).await?);
+		}
+		Ok(events)
+	}
+	async fn block_event(&self, block: BokkenLedgerFileSlotEntry) -> Result<BokkenLedgerEvent, BokkenDetailedError> {
+		let message = &block.tx_data.message;
+		let mut accounts = Vec::new();
+		for (i, pubkey) in message.account_keys.iter().enumerate() {
+			if !message.is_writable(i) {
+				continue;
+			}
+			// Transactions save their changes as part of the slot they were committed in
+			let pre = read_account_as_of(&self.ledger_path, pubkey, block.slot - 1).await?;
+			let post = read_account_as_of(&self.ledger_path, pubkey, block.slot).await?;
+			if pre == post {
+				continue;
+			}
+			accounts.push(
+				BokkenLedgerEventAccount {
+					pubkey: pubkey.to_string(),
+					owner: post.owner.to_string(),
+					pre_lamports: pre.lamports,
+					post_lamports: post.lamports,
+					pre_data_len: pre.data.len(),
+					post_data_len: post.data.len(),
+					changed_ranges: diff_byte_ranges(&pre.data, &post.data).into_iter()
+						.map(|range| [range.start, range.end])
+						.collect()
+				}
+			);
+		}
+		let mut programs: Vec<String> = program_ids_from_logs(&block.tx_logs).into_iter()
+			.chain(message.instructions.iter().map(|instruction| *instruction.program_id(&message.account_keys)))
+			.map(|program_id| program_id.to_string())
+			.collect();
+		programs.sort();
+		programs.dedup();
+		Ok(
+			BokkenLedgerEvent {
+				signature: block.tx_data.signatures[0].to_string(),
+				slot: block.slot,
+				block_time: block.timestamp,
+				err: block.tx_error,
+				fee: block.tx_fee,
+				programs,
+				logs: block.tx_logs,
+				accounts
+			}
+		)
+	}
+}
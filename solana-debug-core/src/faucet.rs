@@ -0,0 +1,414 @@
+//! `requestAirdrop` support, with optional quotas for when Bokken is exposed on a shared server.
+
+use std::{collections::{HashMap, VecDeque}, error::Error as StdError, future::Future, net::{IpAddr, SocketAddr}, pin::Pin, sync::{Arc, Mutex}, task::{Context, Poll}, time::{Duration, Instant}};
+
+use hyper::{Body, HeaderMap, Request, Response, header::CONTENT_TYPE};
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::{Keypair, Signature, read_keypair_file}, signer::Signer, system_instruction, transaction::Transaction};
+use tokio::{fs, sync::OnceCell};
+use tower::{Layer, Service};
+
+use crate::{debug_ledger::{BokkenLedger, LAMPORTS_PER_SIGNATURE}, error::{BokkenError, BokkenDetailedError}, keystore::keypair_to_json};
+
+/// Error message the public faucets respond with when an airdrop is rate limited
+pub const AIRDROP_RATE_LIMITED_MESSAGE: &str = "You've either reached your airdrop limit today or the airdrop faucet has run dry.";
+/// JSON-RPC error code the public faucets respond with when an airdrop is rate limited
+pub const JSON_RPC_AIRDROP_RATE_LIMITED: i32 = 429;
+
+/// Funds airdrops with transfers from a keypair saved alongside the ledger, so that airdrops are regular transactions
+/// with a signature which can be confirmed
+#[derive(Debug, Default)]
+pub struct BokkenFaucet {
+	keypair: OnceCell<Keypair>
+}
+impl BokkenFaucet {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	/// Loads the faucet keypair of the ledger, creating it if it doesn't exist yet
+	async fn keypair(&self, ledger: &BokkenLedger) -> Result<&Keypair, BokkenDetailedError> {
+		self.keypair.get_or_try_init(|| async {
+			let keypair_path = ledger.base_path().join("faucet.json");
+			if fs::try_exists(&keypair_path).await? {
+				return read_keypair_file(&keypair_path).map_err(|_| BokkenError::InvalidKeypair.into());
+			}
+			let keypair = Keypair::new();
+			fs::write(&keypair_path, keypair_to_json(&keypair)).await?;
+			Ok::<_, BokkenDetailedError>(keypair)
+		}).await
+	}
+	/// Sends `lamports` to `pubkey` from the faucet, returning the signature of the transfer. The faucet is topped up
+	/// beforehand, so it never runs dry.
+	pub async fn request_airdrop(
+		&self,
+		ledger: &BokkenLedger,
+		pubkey: &Pubkey,
+		lamports: u64
+	) -> Result<Signature, BokkenDetailedError> {
+		let keypair = self.keypair(ledger).await?;
+		ledger.fund_account(&keypair.pubkey(), lamports.saturating_add(LAMPORTS_PER_SIGNATURE)).await?;
+		let tx = Transaction::new_signed_with_payer(
+			&[system_instruction::transfer(&keypair.pubkey(), pubkey, lamports)],
+			Some(&keypair.pubkey()),
+			&[keypair],
+			Hash::new_from_array(ledger.blockhash().await)
+		);
+		let signature = tx.signatures[0];
+		ledger.execute_transaction(tx, true).await?;
+		Ok(signature)
+	}
+}
+
+/// How many lamports can be airdropped within a sliding time window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BokkenAirdropQuota {
+	pub lamports: u64,
+	pub window: Duration
+}
+
+/// Keeps track of airdrops by recipient and client IP. Nothing is limited by default.
+#[derive(Debug, Default)]
+pub struct BokkenAirdropLimiter {
+	per_pubkey: Option<BokkenAirdropQuota>,
+	per_ip: Option<BokkenAirdropQuota>,
+	/// Reverse proxies whose forwarding headers are believed
+	trusted_proxies: Vec<IpAddr>,
+	/// Time and amount of recent airdrops to each recipient
+	pubkey_usage: Mutex<HashMap<Pubkey, VecDeque<(Instant, u64)>>>,
+	/// Time and amount of recent airdrops requested by each IP
+	ip_usage: Mutex<HashMap<IpAddr, VecDeque<(Instant, u64)>>>
+}
+impl BokkenAirdropLimiter {
+	/// Clients are identified by the IP they connect from. When that's one of `trusted_proxies`, the client IP is taken
+	/// from the proxy's `X-Forwarded-For` or `X-Real-IP` header instead.
+	pub fn new(
+		per_pubkey: Option<BokkenAirdropQuota>,
+		per_ip: Option<BokkenAirdropQuota>,
+		trusted_proxies: Vec<IpAddr>
+	) -> Self {
+		Self {
+			per_pubkey,
+			per_ip,
+			trusted_proxies,
+			..Default::default()
+		}
+	}
+	/// Records the airdrop if it fits in the quota, returns false otherwise
+	fn try_take<K: Eq + std::hash::Hash>(
+		quota: Option<BokkenAirdropQuota>,
+		usage: &Mutex<HashMap<K, VecDeque<(Instant, u64)>>>,
+		key: K,
+		lamports: u64
+	) -> bool {
+		let Some(quota) = quota else {
+			return true;
+		};
+		let now = Instant::now();
+		let mut usage = usage.lock().unwrap();
+		let airdrops = usage.entry(key).or_default();
+		while airdrops.front().map_or(false, |(time, _)| now.duration_since(*time) >= quota.window) {
+			airdrops.pop_front();
+		}
+		let used: u64 = airdrops.iter().map(|(_, lamports)| *lamports).sum();
+		if used.saturating_add(lamports) > quota.lamports {
+			return false;
+		}
+		airdrops.push_back((now, lamports));
+		true
+	}
+	/// Takes back `lamports` recorded by `try_take` for airdrops which didn't go through, newest first
+	fn refund<K: Eq + std::hash::Hash>(usage: &Mutex<HashMap<K, VecDeque<(Instant, u64)>>>, key: &K, mut lamports: u64) {
+		let mut usage = usage.lock().unwrap();
+		let Some(airdrops) = usage.get_mut(key) else {
+			return;
+		};
+		while lamports > 0 {
+			let Some((_, amount)) = airdrops.back_mut() else {
+				break;
+			};
+			let refunded = lamports.min(*amount);
+			*amount -= refunded;
+			lamports -= refunded;
+			if *amount == 0 {
+				airdrops.pop_back();
+			}
+		}
+	}
+	/// Records an airdrop of `lamports` to `pubkey`, or fails with `AirdropRateLimited` if it would exceed the
+	/// per-recipient quota
+	pub fn take_pubkey_quota(&self, pubkey: &Pubkey, lamports: u64) -> Result<(), BokkenError> {
+		if Self::try_take(self.per_pubkey, &self.pubkey_usage, *pubkey, lamports) {
+			Ok(())
+		}else{
+			Err(BokkenError::AirdropRateLimited)
+		}
+	}
+	/// Gives back quota taken by `take_pubkey_quota` for an airdrop which failed
+	pub fn refund_pubkey_quota(&self, pubkey: &Pubkey, lamports: u64) {
+		Self::refund(&self.pubkey_usage, pubkey, lamports);
+	}
+	/// Records an airdrop of `lamports` requested from `ip`, returns false if it would exceed the per-IP quota
+	pub fn take_ip_quota(&self, ip: IpAddr, lamports: u64) -> bool {
+		Self::try_take(self.per_ip, &self.ip_usage, ip, lamports)
+	}
+	/// Gives back quota taken by `take_ip_quota` for airdrops which failed
+	pub fn refund_ip_quota(&self, ip: IpAddr, lamports: u64) {
+		Self::refund(&self.ip_usage, &ip, lamports);
+	}
+	pub fn limits_ips(&self) -> bool {
+		self.per_ip.is_some()
+	}
+}
+
+/// The address of the connection an HTTP request came in on, which jsonrpsee only tells its logger about. Inserted
+/// into a request's extensions so that `BokkenRpcLogger` can fill it in.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConnectionPeerAddr(Arc<Mutex<Option<SocketAddr>>>);
+impl ConnectionPeerAddr {
+	pub fn set(&self, addr: SocketAddr) {
+		*self.0.lock().unwrap() = Some(addr);
+	}
+	pub fn get(&self) -> Option<SocketAddr> {
+		*self.0.lock().unwrap()
+	}
+}
+
+/// The IP of the client which sent a request with `headers` over a connection from `peer`.
+///
+/// Forwarding headers are only believed if `peer` is a trusted proxy. Then the client is the right-most address in
+/// `X-Forwarded-For` which isn't a trusted proxy itself, as anything left of that could have been made up by the
+/// client. `X-Real-IP` is used if there's no `X-Forwarded-For`.
+fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+	if !trusted_proxies.contains(&peer) {
+		return peer;
+	}
+	let forwarded_for: Vec<&str> = headers.get_all("x-forwarded-for").iter()
+		.filter_map(|value| value.to_str().ok())
+		.flat_map(|value| value.split(','))
+		.collect();
+	if forwarded_for.is_empty() {
+		return headers.get("x-real-ip")
+			.and_then(|value| value.to_str().ok())
+			.and_then(|value| value.trim().parse().ok())
+			.unwrap_or(peer);
+	}
+	let mut client = peer;
+	for hop in forwarded_for.into_iter().rev() {
+		let Ok(hop) = hop.trim().parse() else {
+			// Whoever added this isn't a proxy we know of, so this is as far back as can be trusted
+			break;
+		};
+		client = hop;
+		if !trusted_proxies.contains(&hop) {
+			break;
+		}
+	}
+	client
+}
+
+/// The ID and lamports of each `requestAirdrop` call in a JSON-RPC request body, which may be a batch. Notifications
+/// don't have an ID.
+fn requested_airdrops(body: &[u8]) -> Vec<(Option<serde_json::Value>, u64)> {
+	let Ok(request) = serde_json::from_slice::<serde_json::Value>(body) else {
+		return Vec::new();
+	};
+	let calls = match &request {
+		serde_json::Value::Array(calls) => calls.iter().collect(),
+		call => vec![call]
+	};
+	calls.into_iter()
+		.filter(|call| call.get("method").and_then(|method| method.as_str()) == Some("requestAirdrop"))
+		.filter_map(|call| Some((call.get("id").cloned(), call.get("params")?.get(1)?.as_u64()?)))
+		.collect()
+}
+
+/// Total lamports of the `airdrops` which the JSON-RPC response `body` doesn't report a result for. Notifications
+/// aren't answered, so they're assumed to have gone through.
+fn failed_airdrop_lamports(airdrops: &[(Option<serde_json::Value>, u64)], body: &[u8]) -> u64 {
+	let response = serde_json::from_slice::<serde_json::Value>(body).unwrap_or(serde_json::Value::Null);
+	let responses = match &response {
+		serde_json::Value::Array(responses) => responses.iter().collect(),
+		response => vec![response]
+	};
+	airdrops.iter()
+		.filter(|(id, _)| id.as_ref().map_or(false, |id| {
+			!responses.iter().any(|response| response.get("id") == Some(id) && response.get("result").is_some())
+		}))
+		.map(|(_, lamports)| *lamports)
+		.fold(0, u64::saturating_add)
+}
+
+/// Applies the per-IP airdrop quota to HTTP requests, see `BokkenAirdropLimiter::new` for how clients are identified.
+/// Airdrops which fail are taken off the quota again.
+///
+/// A request which would exceed the quota is rejected as a whole, including any other calls batched with it.
+///
+/// This has to wrap jsonrpsee's service directly, as that's what reports the peer address.
+#[derive(Debug, Clone)]
+pub struct BokkenAirdropLimitLayer {
+	limiter: Arc<BokkenAirdropLimiter>
+}
+impl BokkenAirdropLimitLayer {
+	pub fn new(limiter: Arc<BokkenAirdropLimiter>) -> Self {
+		Self {
+			limiter
+		}
+	}
+}
+impl<S> Layer<S> for BokkenAirdropLimitLayer {
+	type Service = BokkenAirdropLimit<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		BokkenAirdropLimit {
+			inner,
+			limiter: self.limiter.clone()
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct BokkenAirdropLimit<S> {
+	inner: S,
+	limiter: Arc<BokkenAirdropLimiter>
+}
+impl<S> Service<Request<Body>> for BokkenAirdropLimit<S>
+where
+	S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+	S::Error: Into<Box<dyn StdError + Send + Sync>> + 'static,
+	S::Future: Send + 'static,
+{
+	type Response = Response<Body>;
+	type Error = Box<dyn StdError + Send + Sync + 'static>;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx).map_err(Into::into)
+	}
+
+	fn call(&mut self, req: Request<Body>) -> Self::Future {
+		// The service which was polled ready has to be the one which gets called
+		let clone = self.inner.clone();
+		let mut inner = std::mem::replace(&mut self.inner, clone);
+		let limiter = self.limiter.clone();
+		Box::pin(async move {
+			if !limiter.limits_ips() {
+				return inner.call(req).await.map_err(Into::into);
+			}
+			let (mut parts, body) = req.into_parts();
+			let body = hyper::body::to_bytes(body).await?;
+			let airdrops = requested_airdrops(&body);
+			let lamports = airdrops.iter().map(|(_, lamports)| *lamports).fold(0, u64::saturating_add);
+			let headers = parts.headers.clone();
+			let peer_addr = ConnectionPeerAddr::default();
+			parts.extensions.insert(peer_addr.clone());
+			// jsonrpsee reports the peer address to its logger as soon as it's called, but doesn't do anything else
+			// until the response is polled, so the request can still be turned away after this
+			let response = inner.call(Request::from_parts(parts, Body::from(body.clone())));
+			let Some(peer_addr) = peer_addr.get().filter(|_| lamports > 0) else {
+				return response.await.map_err(Into::into);
+			};
+			let client_ip = client_ip(peer_addr.ip(), &headers, &limiter.trusted_proxies);
+			if !limiter.take_ip_quota(client_ip, lamports) {
+				drop(response);
+				let error = serde_json::json!({
+					"jsonrpc": "2.0",
+					"error": {
+						"code": JSON_RPC_AIRDROP_RATE_LIMITED,
+						"message": AIRDROP_RATE_LIMITED_MESSAGE
+					},
+					"id": serde_json::from_slice::<serde_json::Value>(&body).ok()
+						.and_then(|request| request.get("id").cloned())
+						.unwrap_or(serde_json::Value::Null)
+				});
+				return Ok(
+					Response::builder()
+						.header(CONTENT_TYPE, "application/json")
+						.body(Body::from(error.to_string()))?
+				);
+			}
+			let response = match response.await {
+				Ok(response) => response,
+				Err(e) => {
+					limiter.refund_ip_quota(client_ip, lamports);
+					return Err(e.into());
+				}
+			};
+			let (parts, body) = response.into_parts();
+			let body = hyper::body::to_bytes(body).await?;
+			limiter.refund_ip_quota(client_ip, failed_airdrop_lamports(&airdrops, &body));
+			Ok(Response::from_parts(parts, Body::from(body)))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hyper::header::HeaderValue;
+
+	use super::*;
+
+	fn ip(ip: &str) -> IpAddr {
+		ip.parse().unwrap()
+	}
+	fn request_headers(forwarded_for: &[&str], real_ip: Option<&str>) -> HeaderMap {
+		let mut headers = HeaderMap::new();
+		for value in forwarded_for {
+			headers.append("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+		}
+		if let Some(real_ip) = real_ip {
+			headers.insert("x-real-ip", HeaderValue::from_str(real_ip).unwrap());
+		}
+		headers
+	}
+
+	#[test]
+	fn forwarding_headers_are_ignored_without_a_trusted_proxy() {
+		let headers = request_headers(&["1.1.1.1"], Some("2.2.2.2"));
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &[]), ip("10.0.0.1"));
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &[ip("10.0.0.2")]), ip("10.0.0.1"));
+	}
+
+	#[test]
+	fn client_is_the_right_most_untrusted_hop() {
+		let proxies = [ip("10.0.0.1"), ip("10.0.0.2")];
+		// The client made up the first address, the proxies appended the rest
+		let headers = request_headers(&["6.6.6.6, 1.1.1.1", "10.0.0.2"], None);
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &proxies), ip("1.1.1.1"));
+		let headers = request_headers(&["garbage, 1.1.1.1"], None);
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &proxies), ip("1.1.1.1"));
+		let headers = request_headers(&["1.1.1.1, garbage"], None);
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &proxies), ip("10.0.0.1"));
+		let headers = request_headers(&[], Some("2.2.2.2"));
+		assert_eq!(client_ip(ip("10.0.0.1"), &headers, &proxies), ip("2.2.2.2"));
+	}
+
+	#[test]
+	fn only_airdrops_with_a_result_count() {
+		let airdrops = requested_airdrops(serde_json::json!([
+			{"jsonrpc": "2.0", "id": 1, "method": "requestAirdrop", "params": ["11111111111111111111111111111111", 10]},
+			{"jsonrpc": "2.0", "id": 2, "method": "requestAirdrop", "params": ["11111111111111111111111111111111", 20]},
+			{"jsonrpc": "2.0", "id": 3, "method": "getSlot"},
+			{"jsonrpc": "2.0", "method": "requestAirdrop", "params": ["11111111111111111111111111111111", 40]}
+		]).to_string().as_bytes());
+		assert_eq!(airdrops, vec![(Some(1.into()), 10), (Some(2.into()), 20), (None, 40)]);
+		let response = serde_json::json!([
+			{"jsonrpc": "2.0", "id": 1, "result": "signature"},
+			{"jsonrpc": "2.0", "id": 2, "error": {"code": -32602, "message": "Invalid params"}},
+			{"jsonrpc": "2.0", "id": 3, "result": 1}
+		]);
+		assert_eq!(failed_airdrop_lamports(&airdrops, response.to_string().as_bytes()), 20);
+		assert_eq!(failed_airdrop_lamports(&airdrops, b"Payload too large"), 30);
+	}
+
+	#[test]
+	fn refunded_quota_can_be_used_again() {
+		let quota = BokkenAirdropQuota { lamports: 100, window: Duration::from_secs(60) };
+		let limiter = BokkenAirdropLimiter::new(None, Some(quota), Vec::new());
+		let client = ip("1.1.1.1");
+		assert!(limiter.take_ip_quota(client, 60));
+		assert!(limiter.take_ip_quota(client, 40));
+		assert!(!limiter.take_ip_quota(client, 1));
+		limiter.refund_ip_quota(client, 50);
+		assert!(limiter.take_ip_quota(client, 50));
+		assert!(!limiter.take_ip_quota(client, 1));
+	}
+}
@@ -0,0 +1,229 @@
+use std::{error::Error as StdError, future::Future, pin::Pin, sync::{Arc, Mutex, RwLock}, task::{Context, Poll}, time::Duration};
+
+use http_body::{LengthLimitError, Limited};
+use hyper::{Body, Request, Response, StatusCode, header::CONTENT_TYPE};
+use jsonrpsee::types::{ErrorResponse, Id, error::reject_too_big_request};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use solana_sdk::transaction::TransactionError;
+use tower::{Layer, Service};
+
+use crate::{error::BokkenError, rpc_endpoint_structs::RpcFaultInjectionConfig};
+
+/// Requests only calling methods starting with this are never faulted, so that fault injection can always be turned
+/// off again
+const ADMIN_METHOD_PREFIX: &str = "bokken_";
+
+/// Makes the JSON-RPC endpoint misbehave like a real, overloaded, RPC provider would, so that client retry logic
+/// actually gets exercised. Everything is disabled by default.
+#[derive(Debug, Default)]
+pub struct BokkenFaultInjector {
+	config: RwLock<RpcFaultInjectionConfig>,
+	chaos: Option<BokkenChaos>
+}
+impl BokkenFaultInjector {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	/// Also fails some transactions sent with `sendTransaction` before they're executed, see `BokkenChaos`
+	pub fn with_chaos(chaos: BokkenChaos) -> Self {
+		Self {
+			chaos: Some(chaos),
+			..Default::default()
+		}
+	}
+	/// Returns the error a transaction should fail with before it gets executed, if any
+	pub fn transaction_failure(&self) -> Option<BokkenError> {
+		if let Some(error) = self.chaos.as_ref().and_then(|chaos| chaos.transaction_failure()) {
+			return Some(error);
+		}
+		if Self::roll(self.config.read().unwrap().blockhash_expired_probability) {
+			return Some(TransactionError::BlockhashNotFound.into());
+		}
+		None
+	}
+	pub fn config(&self) -> RpcFaultInjectionConfig {
+		self.config.read().unwrap().clone()
+	}
+	pub fn set_config(&self, config: RpcFaultInjectionConfig) {
+		*self.config.write().unwrap() = config;
+	}
+	fn roll(probability: f64) -> bool {
+		probability > 0.0 && rand::random::<f64>() < probability
+	}
+	/// How long to wait before responding to a request
+	pub fn latency(&self) -> Duration {
+		let config = self.config.read().unwrap();
+		let jitter = if config.latency_jitter_ms == 0 {
+			0
+		}else{
+			rand::random::<u64>() % (config.latency_jitter_ms + 1)
+		};
+		Duration::from_millis(config.latency_ms + jitter)
+	}
+	pub fn should_rate_limit(&self) -> bool {
+		Self::roll(self.config.read().unwrap().rate_limit_probability)
+	}
+	pub fn should_drop_notification(&self) -> bool {
+		Self::roll(self.config.read().unwrap().dropped_notification_probability)
+	}
+}
+
+/// Fails a percentage of transactions with errors which a client should retry on. Unlike the rest of the fault
+/// injection, the failures are decided by a seeded RNG, so the same sequence of transactions fails the same way every
+/// time.
+#[derive(Debug)]
+pub struct BokkenChaos {
+	failure_probability: f64,
+	rng: Mutex<StdRng>
+}
+impl BokkenChaos {
+	/// `failure_percent` is clamped to 0-100
+	pub fn new(failure_percent: f64, seed: u64) -> Self {
+		Self {
+			failure_probability: failure_percent.clamp(0.0, 100.0) / 100.0,
+			rng: Mutex::new(StdRng::seed_from_u64(seed))
+		}
+	}
+	fn transaction_failure(&self) -> Option<BokkenError> {
+		let mut rng = self.rng.lock().unwrap();
+		if !rng.gen_bool(self.failure_probability) {
+			return None;
+		}
+		if rng.gen_bool(0.5) {
+			Some(TransactionError::BlockhashNotFound.into())
+		}else{
+			Some(BokkenError::NodeUnhealthy)
+		}
+	}
+}
+
+/// Only the part of a JSON-RPC call which decides whether it's faulted
+#[derive(serde::Deserialize)]
+struct RequestMethod {
+	method: String
+}
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum RequestMethods {
+	Single(RequestMethod),
+	Batch(Vec<RequestMethod>)
+}
+
+/// Whether every call in the JSON-RPC request `body`, which may be a batch, is to an admin method. Requests which
+/// can't be parsed aren't admin requests.
+fn is_admin_request(body: &[u8]) -> bool {
+	match serde_json::from_slice::<RequestMethods>(body) {
+		Ok(RequestMethods::Single(call)) => call.method.starts_with(ADMIN_METHOD_PREFIX),
+		Ok(RequestMethods::Batch(calls)) => {
+			!calls.is_empty() && calls.iter().all(|call| call.method.starts_with(ADMIN_METHOD_PREFIX))
+		},
+		Err(_) => false
+	}
+}
+
+/// The same response jsonrpsee gives when a request is larger than `max_request_size`
+fn request_too_large(max_request_size: u32) -> Result<Response<Body>, hyper::http::Error> {
+	let error = serde_json::to_string(&ErrorResponse::borrowed(reject_too_big_request(max_request_size), Id::Null))
+		.expect("error responses to serialize");
+	Response::builder()
+		.status(StatusCode::PAYLOAD_TOO_LARGE)
+		.header(CONTENT_TYPE, "application/json")
+		.body(Body::from(error))
+}
+
+/// Adds latency and rate-limit responses to HTTP requests.
+/// 
+/// Request bodies are read into memory to check which methods they call, so `max_request_size` should be the same
+/// limit the server has. Larger requests are turned away before they're read any further.
+#[derive(Debug, Clone)]
+pub struct BokkenFaultInjectionLayer {
+	injector: Arc<BokkenFaultInjector>,
+	max_request_size: u32
+}
+impl BokkenFaultInjectionLayer {
+	pub fn new(injector: Arc<BokkenFaultInjector>, max_request_size: u32) -> Self {
+		Self {
+			injector,
+			max_request_size
+		}
+	}
+}
+impl<S> Layer<S> for BokkenFaultInjectionLayer {
+	type Service = BokkenFaultInjection<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		BokkenFaultInjection {
+			inner,
+			injector: self.injector.clone(),
+			max_request_size: self.max_request_size
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct BokkenFaultInjection<S> {
+	inner: S,
+	injector: Arc<BokkenFaultInjector>,
+	max_request_size: u32
+}
+impl<S> Service<Request<Body>> for BokkenFaultInjection<S>
+where
+	S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+	S::Error: Into<Box<dyn StdError + Send + Sync>> + 'static,
+	S::Future: Send + 'static,
+{
+	type Response = Response<Body>;
+	type Error = Box<dyn StdError + Send + Sync + 'static>;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx).map_err(Into::into)
+	}
+
+	fn call(&mut self, req: Request<Body>) -> Self::Future {
+		// The service which was polled ready has to be the one which gets called
+		let clone = self.inner.clone();
+		let mut inner = std::mem::replace(&mut self.inner, clone);
+		let injector = self.injector.clone();
+		let max_request_size = self.max_request_size;
+		Box::pin(async move {
+			let (parts, body) = req.into_parts();
+			let body = match hyper::body::to_bytes(Limited::new(body, max_request_size as usize)).await {
+				Ok(body) => body,
+				Err(e) if e.is::<LengthLimitError>() => return Ok(request_too_large(max_request_size)?),
+				Err(e) => return Err(e)
+			};
+			let is_admin_request = is_admin_request(&body);
+			let req = Request::from_parts(parts, Body::from(body));
+			if is_admin_request {
+				return inner.call(req).await.map_err(Into::into);
+			}
+			tokio::time::sleep(injector.latency()).await;
+			if injector.should_rate_limit() {
+				return Ok(
+					Response::builder()
+						.status(StatusCode::TOO_MANY_REQUESTS)
+						.body(Body::from("Too many requests for a specific RPC call (injected by Bokken)"))?
+				);
+			}
+			inner.call(req).await.map_err(Into::into)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::is_admin_request;
+
+	#[test]
+	fn only_admin_calls_are_admin_requests() {
+		assert!(is_admin_request(br#"{"jsonrpc":"2.0","id":1,"method":"bokken_setFaultInjection","params":[{}]}"#));
+		assert!(is_admin_request(br#"[{"jsonrpc":"2.0","id":1,"method":"bokken_getMetrics"},{"jsonrpc":"2.0","id":2,"method":"bokken_createFork"}]"#));
+		// A batch with a single admin call in it
+		assert!(!is_admin_request(br#"[{"jsonrpc":"2.0","id":1,"method":"bokken_getMetrics"},{"jsonrpc":"2.0","id":2,"method":"getSlot"}]"#));
+		// Admin method names in params
+		assert!(!is_admin_request(br#"{"jsonrpc":"2.0","id":1,"method":"getAccountInfo","params":["\"bokken_"]}"#));
+		assert!(!is_admin_request(br#"[]"#));
+		assert!(!is_admin_request(br#"{"method":"bokken_getMetrics""#));
+	}
+}
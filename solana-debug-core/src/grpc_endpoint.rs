@@ -0,0 +1,173 @@
+use std::{net::SocketAddr, sync::Arc, collections::HashSet};
+
+use color_eyre::eyre;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::{mpsc, broadcast};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, transport::Server};
+
+use crate::debug_ledger::{BokkenLedger, BokkenLedgerChange};
+
+pub mod proto {
+	tonic::include_proto!("bokken");
+}
+use proto::{
+	bokken_grpc_server::{BokkenGrpc, BokkenGrpcServer},
+	Account,
+	GetAccountRequest,
+	SubscribeAccountsRequest,
+	SubscribeTransactionsRequest,
+	TransactionUpdate
+};
+
+/// How many messages can be waiting to be sent to a gRPC subscriber
+const SUBSCRIBER_QUEUE_SIZE: usize = 128;
+
+fn pubkey_from_bytes(bytes: &[u8]) -> Result<Pubkey, Status> {
+	Pubkey::try_from(bytes).map_err(|_| {
+		Status::invalid_argument(format!("Public keys must be 32 bytes long, got {}", bytes.len()))
+	})
+}
+fn pubkey_set_from_bytes(list: &Vec<Vec<u8>>) -> Result<HashSet<Pubkey>, Status> {
+	list.iter().map(|bytes| pubkey_from_bytes(bytes)).collect()
+}
+
+pub struct BokkenGrpcImpl {
+	ledger: Arc<BokkenLedger>
+}
+impl BokkenGrpcImpl {
+	fn new(ledger: Arc<BokkenLedger>) -> Self {
+		Self {
+			ledger
+		}
+	}
+	fn subscribe_changes(&self) -> broadcast::Receiver<BokkenLedgerChange> {
+		self.ledger.subscribe_changes()
+	}
+}
+
+#[tonic::async_trait]
+impl BokkenGrpc for BokkenGrpcImpl {
+	async fn get_account(&self, request: Request<GetAccountRequest>) -> Result<Response<Account>, Status> {
+		let pubkey = pubkey_from_bytes(&request.get_ref().pubkey)?;
+		let snapshot = self.ledger.snapshot().await;
+		let slot = snapshot.slot();
+		let data = snapshot.read_account(&pubkey).await
+			.map_err(|e| Status::internal(e.to_string()))?;
+		Ok(Response::new(
+			Account {
+				pubkey: pubkey.to_bytes().to_vec(),
+				slot,
+				lamports: data.lamports,
+				owner: data.owner.to_bytes().to_vec(),
+				executable: data.executable,
+				rent_epoch: data.rent_epoch,
+				data: data.data
+			}
+		))
+	}
+
+	type SubscribeAccountsStream = ReceiverStream<Result<Account, Status>>;
+	async fn subscribe_accounts(
+		&self,
+		request: Request<SubscribeAccountsRequest>
+	) -> Result<Response<Self::SubscribeAccountsStream>, Status> {
+		let accounts = pubkey_set_from_bytes(&request.get_ref().accounts)?;
+		let owners = pubkey_set_from_bytes(&request.get_ref().owners)?;
+		let mut changes = self.subscribe_changes();
+		let (sender, receiver) = mpsc::channel(SUBSCRIBER_QUEUE_SIZE);
+		tokio::task::spawn(async move {
+			loop {
+				let change = match changes.recv().await {
+					Ok(change) => change,
+					Err(broadcast::error::RecvError::Lagged(_)) => {
+						// Better to tell the client they missed something than to silently skip updates
+						let _ = sender.send(Err(Status::data_loss("Subscriber lagged behind"))).await;
+						break;
+					},
+					Err(broadcast::error::RecvError::Closed) => break
+				};
+				if let BokkenLedgerChange::Account { pubkey, slot, data } = change {
+					if
+						(!accounts.is_empty() || !owners.is_empty()) &&
+						!accounts.contains(&pubkey) &&
+						!owners.contains(&data.owner)
+					{
+						continue;
+					}
+					let msg = Account {
+						pubkey: pubkey.to_bytes().to_vec(),
+						slot,
+						lamports: data.lamports,
+						owner: data.owner.to_bytes().to_vec(),
+						executable: data.executable,
+						rent_epoch: data.rent_epoch,
+						data: data.data
+					};
+					if sender.send(Ok(msg)).await.is_err() {
+						// Client went away
+						break;
+					}
+				}
+			}
+		});
+		Ok(Response::new(ReceiverStream::new(receiver)))
+	}
+
+	type SubscribeTransactionsStream = ReceiverStream<Result<TransactionUpdate, Status>>;
+	async fn subscribe_transactions(
+		&self,
+		request: Request<SubscribeTransactionsRequest>
+	) -> Result<Response<Self::SubscribeTransactionsStream>, Status> {
+		let account_include = pubkey_set_from_bytes(&request.get_ref().account_include)?;
+		let include_failed = request.get_ref().include_failed;
+		let mut changes = self.subscribe_changes();
+		let (sender, receiver) = mpsc::channel(SUBSCRIBER_QUEUE_SIZE);
+		tokio::task::spawn(async move {
+			loop {
+				let change = match changes.recv().await {
+					Ok(change) => change,
+					Err(broadcast::error::RecvError::Lagged(_)) => {
+						let _ = sender.send(Err(Status::data_loss("Subscriber lagged behind"))).await;
+						break;
+					},
+					Err(broadcast::error::RecvError::Closed) => break
+				};
+				if let BokkenLedgerChange::Transaction { slot, signature, transaction, tx_error, logs } = change {
+					if tx_error.is_some() && !include_failed {
+						continue;
+					}
+					if
+						!account_include.is_empty() &&
+						!transaction.message.account_keys.iter().any(|key| account_include.contains(key))
+					{
+						continue;
+					}
+					let msg = TransactionUpdate {
+						slot,
+						signature: signature.as_ref().to_vec(),
+						transaction: bincode::serialize(&transaction).expect("transaction to serialize"),
+						error: tx_error.map(|err| err.to_string()).unwrap_or_default(),
+						logs
+					};
+					if sender.send(Ok(msg)).await.is_err() {
+						break;
+					}
+				}
+			}
+		});
+		Ok(Response::new(ReceiverStream::new(receiver)))
+	}
+}
+
+pub async fn start_grpc_endpoint(
+	addr: SocketAddr,
+	ledger: Arc<BokkenLedger>
+) -> eyre::Result<()> {
+	println!("gRPC endpoint listening on {}", addr);
+	Server::builder()
+		.add_service(BokkenGrpcServer::new(BokkenGrpcImpl::new(ledger)))
+		.serve(addr)
+		.await?;
+	Ok(())
+}
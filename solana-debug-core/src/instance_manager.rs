@@ -0,0 +1,241 @@
+use std::{collections::HashMap, error::Error as StdError, future::Future, net::SocketAddr, path::PathBuf, pin::Pin, sync::Arc, task::{Context, Poll}, time::Duration};
+
+use color_eyre::eyre;
+use hyper::{Body, Client, Request, Response, StatusCode, Uri, client::HttpConnector};
+use jsonrpsee::server::ServerHandle;
+use bokken_runtime::ipc_comm::{bind_ipc_socket, is_abstract_socket_path, remove_ipc_socket};
+use tokio::{fs, sync::RwLock};
+use tower::{Layer, Service};
+
+use crate::{debug_ledger::{BokkenLedger, BokkenLedgerInitConfig}, error::BokkenError, program_caller::{ProgramCaller, ProgramCallerConfig}, rpc_endpoint::{BokkenRpcServerLimits, start_instance_endpoint}};
+
+const INSTANCE_PATH_PREFIX: &str = "/instances/";
+
+/// A ledger hosted alongside the main one, with its own save directory, unix socket, and set of programs
+#[derive(Debug)]
+pub struct BokkenInstance {
+	name: String,
+	ledger: Arc<BokkenLedger>,
+	save_path: PathBuf,
+	socket_path: PathBuf,
+	rpc_addr: SocketAddr,
+	server_handle: ServerHandle
+}
+impl BokkenInstance {
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+	pub fn ledger(&self) -> Arc<BokkenLedger> {
+		self.ledger.clone()
+	}
+	/// Debuggable programs for this instance should connect to this socket
+	pub fn socket_path(&self) -> &PathBuf {
+		&self.socket_path
+	}
+	/// The instance's own JSON-RPC server, which also accepts subscriptions
+	pub fn rpc_addr(&self) -> SocketAddr {
+		self.rpc_addr
+	}
+	/// The path which requests to the main JSON-RPC server are forwarded to this instance from
+	pub fn url_path(&self) -> String {
+		format!("{}{}", INSTANCE_PATH_PREFIX, self.name)
+	}
+}
+
+/// Creates and destroys named ledger instances
+#[derive(Debug)]
+pub struct BokkenInstanceManager {
+	/// Each instance is saved in a sub-directory named after it
+	save_path: PathBuf,
+	/// Socket paths are derived from this, e.g. `bokken.sock` becomes `bokken.<name>.sock`, and `@bokken` becomes
+	/// `@bokken.<name>`
+	socket_path: PathBuf,
+	default_init_config: Option<BokkenLedgerInitConfig>,
+	slow_call_threshold: Duration,
+	simulation_cache_size: usize,
+	limits: BokkenRpcServerLimits,
+	program_caller_config: ProgramCallerConfig,
+	instances: RwLock<HashMap<String, Arc<BokkenInstance>>>
+}
+impl BokkenInstanceManager {
+	pub fn new(
+		save_path: PathBuf,
+		socket_path: PathBuf,
+		default_init_config: Option<BokkenLedgerInitConfig>,
+		slow_call_threshold: Duration,
+		simulation_cache_size: usize,
+		limits: BokkenRpcServerLimits,
+		program_caller_config: ProgramCallerConfig
+	) -> Self {
+		Self {
+			save_path,
+			socket_path,
+			default_init_config,
+			slow_call_threshold,
+			simulation_cache_size,
+			limits,
+			program_caller_config,
+			instances: RwLock::new(HashMap::new())
+		}
+	}
+	fn instance_socket_path(&self, name: &str) -> PathBuf {
+		if is_abstract_socket_path(&self.socket_path) {
+			return PathBuf::from(format!("{}.{}", self.socket_path.to_string_lossy(), name));
+		}
+		self.socket_path.with_file_name(format!(
+			"{}.{}.sock",
+			self.socket_path.file_stem().unwrap_or_default().to_string_lossy(),
+			name
+		))
+	}
+	pub async fn get_instance(&self, name: &str) -> Option<Arc<BokkenInstance>> {
+		self.instances.read().await.get(name).cloned()
+	}
+	pub async fn list_instances(&self) -> Vec<Arc<BokkenInstance>> {
+		let mut instances: Vec<Arc<BokkenInstance>> = self.instances.read().await.values().cloned().collect();
+		instances.sort_by(|a, b| a.name.cmp(&b.name));
+		instances
+	}
+	/// Starts a new instance. If an instance with the same name existed previously and its data wasn't deleted, its
+	/// state is resumed and `init_config` is ignored.
+	pub async fn create_instance(
+		&self,
+		name: String,
+		init_config: Option<BokkenLedgerInitConfig>
+	) -> eyre::Result<Arc<BokkenInstance>> {
+		if
+			name.is_empty() ||
+			!name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+		{
+			return Err(BokkenError::InvalidInstanceName(name).into());
+		}
+		// Held until the instance is inserted so the same instance can't be created twice at the same time
+		let mut instances = self.instances.write().await;
+		if instances.contains_key(&name) {
+			return Err(BokkenError::InstanceAlreadyExists(name).into());
+		}
+		fs::create_dir_all(&self.save_path).await?;
+		let save_path = self.save_path.join(&name);
+		let socket_path = self.instance_socket_path(&name);
+		let ledger = Arc::new(BokkenLedger::new(
+			save_path.clone(),
+			ProgramCaller::new(bind_ipc_socket(&socket_path)?, self.program_caller_config.clone()),
+			init_config.or_else(|| self.default_init_config.clone())
+		).await?);
+		let (rpc_addr, server_handle) = start_instance_endpoint(
+			ledger.clone(),
+			self.slow_call_threshold,
+			self.simulation_cache_size,
+			self.limits
+		).await?;
+		println!("Created instance {} listening on {}, programs connect to {:?}", name, rpc_addr, socket_path);
+		let instance = Arc::new(BokkenInstance {
+			name: name.clone(),
+			ledger,
+			save_path,
+			socket_path,
+			rpc_addr,
+			server_handle
+		});
+		instances.insert(name, instance.clone());
+		Ok(instance)
+	}
+	/// Stops the instance, returns false if it didn't exist
+	pub async fn destroy_instance(&self, name: &str, delete_data: bool) -> eyre::Result<bool> {
+		let Some(instance) = self.instances.write().await.remove(name) else {
+			return Ok(false);
+		};
+		// An error only means the server has stopped already
+		let _ = instance.server_handle.stop();
+		instance.ledger.stop();
+		remove_ipc_socket(&instance.socket_path).await?;
+		if delete_data {
+			fs::remove_dir_all(&instance.save_path).await?;
+		}
+		println!("Destroyed instance {}", name);
+		Ok(true)
+	}
+}
+
+/// Returns the instance name if the path is in the form of `/instances/<name>`
+fn instance_name_from_path(path: &str) -> Option<String> {
+	let name = path.strip_prefix(INSTANCE_PATH_PREFIX)?.trim_end_matches('/');
+	if name.is_empty() || name.contains('/') {
+		return None;
+	}
+	Some(name.to_string())
+}
+
+/// Forwards JSON-RPC requests sent to `/instances/<name>` to that instance's server.
+///
+/// Only plain HTTP requests are forwarded, websocket clients must connect to the instance's server directly.
+#[derive(Debug, Clone)]
+pub struct BokkenInstanceRouterLayer {
+	manager: Option<Arc<BokkenInstanceManager>>
+}
+impl BokkenInstanceRouterLayer {
+	/// Nothing is forwarded if `manager` is None
+	pub fn new(manager: Option<Arc<BokkenInstanceManager>>) -> Self {
+		Self {
+			manager
+		}
+	}
+}
+impl<S> Layer<S> for BokkenInstanceRouterLayer {
+	type Service = BokkenInstanceRouter<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		BokkenInstanceRouter {
+			inner,
+			manager: self.manager.clone(),
+			client: Client::new()
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct BokkenInstanceRouter<S> {
+	inner: S,
+	manager: Option<Arc<BokkenInstanceManager>>,
+	client: Client<HttpConnector>
+}
+impl<S> Service<Request<Body>> for BokkenInstanceRouter<S>
+where
+	S: Service<Request<Body>, Response = Response<Body>>,
+	S::Error: Into<Box<dyn StdError + Send + Sync>> + 'static,
+	S::Future: Send + 'static,
+{
+	type Response = Response<Body>;
+	type Error = Box<dyn StdError + Send + Sync + 'static>;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx).map_err(Into::into)
+	}
+
+	fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+		let (Some(manager), Some(instance_name)) = (
+			self.manager.clone(),
+			instance_name_from_path(req.uri().path())
+		) else {
+			let fut = self.inner.call(req);
+			return Box::pin(async move {
+				fut.await.map_err(Into::into)
+			});
+		};
+		let client = self.client.clone();
+		Box::pin(async move {
+			let Some(instance) = manager.get_instance(&instance_name).await else {
+				return Ok(
+					Response::builder()
+						.status(StatusCode::NOT_FOUND)
+						.body(Body::from(format!("Instance {} doesn't exist", instance_name)))?
+				);
+			};
+			*req.uri_mut() = Uri::try_from(format!("http://{}/", instance.rpc_addr()))?;
+			// Let hyper fill this in with the instance's address
+			req.headers_mut().remove(hyper::header::HOST);
+			Ok(client.request(req).await?)
+		})
+	}
+}
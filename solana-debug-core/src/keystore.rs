@@ -0,0 +1,61 @@
+use std::{io, path::PathBuf};
+
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use tokio::fs;
+
+use crate::{debug_ledger::BokkenLedger, error::{BokkenError, BokkenDetailedError}};
+
+/// Keeps a copy of every keypair created by Bokken, so that tests don't lose access to accounts they funded
+#[derive(Debug)]
+pub struct BokkenKeystore {
+	path: PathBuf
+}
+impl BokkenKeystore {
+	pub fn new(path: PathBuf) -> Self {
+		Self {
+			path
+		}
+	}
+	/// The keystore saved alongside the ledger's state
+	pub fn for_ledger(ledger: &BokkenLedger) -> Self {
+		Self::new(ledger.base_path().join("keystore"))
+	}
+	/// Saves the keypair as `<pubkey>.json`, in the same format as `solana-keygen` uses
+	pub async fn save_keypair(&self, keypair: &Keypair) -> Result<PathBuf, BokkenDetailedError> {
+		fs::create_dir_all(&self.path).await?;
+		let keypair_path = self.path.join(format!("{}.json", keypair.pubkey()));
+		fs::write(&keypair_path, keypair_to_json(keypair)).await?;
+		Ok(keypair_path)
+	}
+	/// Loads the keypair of the specified pubkey, which must have been saved in the keystore before
+	pub async fn read_keypair(&self, pubkey: &Pubkey) -> Result<Keypair, BokkenDetailedError> {
+		let keypair_json = match fs::read_to_string(self.path.join(format!("{}.json", pubkey))).await {
+			Ok(keypair_json) => keypair_json,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => {
+				return Err(BokkenError::KeypairNotInKeystore(*pubkey).into());
+			},
+			Err(e) => return Err(e.into())
+		};
+		let keypair_bytes: Vec<u8> = serde_json::from_str(&keypair_json).map_err(|_| BokkenError::InvalidKeypair)?;
+		Ok(Keypair::from_bytes(&keypair_bytes).map_err(|_| BokkenError::InvalidKeypair)?)
+	}
+	/// Creates a new keypair, saves it, and funds it with the specified amount of lamports
+	pub async fn create_funded_keypair(
+		&self,
+		ledger: &BokkenLedger,
+		lamports: u64
+	) -> Result<Keypair, BokkenDetailedError> {
+		let keypair = Keypair::new();
+		self.save_keypair(&keypair).await?;
+		ledger.fund_account(&keypair.pubkey(), lamports).await?;
+		Ok(keypair)
+	}
+}
+
+/// Encodes the keypair as a JSON array of bytes, like `solana-keygen` keypair files
+pub fn keypair_to_json(keypair: &Keypair) -> String {
+	format!(
+		"[{}]",
+		keypair.to_bytes().iter().map(|byte| byte.to_string()).collect::<Vec<String>>().join(",")
+	)
+}
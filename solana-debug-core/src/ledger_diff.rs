@@ -0,0 +1,66 @@
+use std::{collections::{BTreeMap, BTreeSet}, path::Path};
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{debug_ledger::{is_closed_account, scan_stored_accounts}, error::BokkenDetailedError};
+
+/// What happened to an account between two ledger snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BokkenLedgerAccountChangeKind {
+	Created,
+	Modified,
+	Deleted
+}
+
+#[derive(Debug)]
+pub struct BokkenLedgerAccountChange {
+	pub pubkey: Pubkey,
+	pub kind: BokkenLedgerAccountChangeKind,
+	pub lamports_delta: i128,
+	pub data_size_delta: i64,
+	pub owner_changed: bool
+}
+
+/// Reads the latest state of every account in a saved ledger, without starting the ledger itself.
+///
+/// Accounts without any lamports don't exist as far as the runtime is concerned, so they're left out.
+pub async fn read_ledger_accounts(
+	ledger_path: &Path
+) -> Result<BTreeMap<Pubkey, BokkenAccountData>, BokkenDetailedError> {
+	let mut accounts = BTreeMap::new();
+	for stored in scan_stored_accounts(ledger_path).await? {
+		if !is_closed_account(&stored.latest) {
+			accounts.insert(stored.pubkey, stored.latest);
+		}
+	}
+	Ok(accounts)
+}
+
+/// Lists every account which was created, modified, or deleted going from snapshot `a` to snapshot `b`, sorted by
+/// pubkey.
+pub fn diff_ledger_accounts(
+	a: &BTreeMap<Pubkey, BokkenAccountData>,
+	b: &BTreeMap<Pubkey, BokkenAccountData>
+) -> Vec<BokkenLedgerAccountChange> {
+	let pubkeys: BTreeSet<&Pubkey> = a.keys().chain(b.keys()).collect();
+	pubkeys.into_iter().filter_map(|pubkey| {
+		let (kind, pre, post) = match (a.get(pubkey), b.get(pubkey)) {
+			(None, Some(post)) => (BokkenLedgerAccountChangeKind::Created, None, Some(post)),
+			(Some(pre), None) => (BokkenLedgerAccountChangeKind::Deleted, Some(pre), None),
+			(Some(pre), Some(post)) if pre != post => (BokkenLedgerAccountChangeKind::Modified, Some(pre), Some(post)),
+			_ => return None
+		};
+		let lamports = |account: Option<&BokkenAccountData>| account.map_or(0, |account| account.lamports as i128);
+		let data_size = |account: Option<&BokkenAccountData>| account.map_or(0, |account| account.data.len() as i64);
+		Some(
+			BokkenLedgerAccountChange {
+				pubkey: *pubkey,
+				kind,
+				lamports_delta: lamports(post) - lamports(pre),
+				data_size_delta: data_size(post) - data_size(pre),
+				owner_changed: matches!((pre, post), (Some(pre), Some(post)) if pre.owner != post.owner)
+			}
+		)
+	}).collect()
+}
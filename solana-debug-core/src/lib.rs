@@ -0,0 +1,44 @@
+//! The guts of the Bokken validator, for tools which want to run it programmatically.
+//! 
+//! A minimal setup looks like what the `bokken` binary does: create a `ProgramCaller` listening on a unix socket,
+//! give it to `BokkenLedger::new`, and then either use the ledger directly or serve it with
+//! `rpc_endpoint::start_endpoint`.
+
+pub mod error;
+mod utils;
+pub mod debug_ledger;
+pub mod rpc_endpoint_structs;
+pub mod rpc_endpoint;
+pub mod native_program_stubs;
+pub mod program_caller;
+pub mod rpc_admin_endpoint;
+pub mod rpc_metrics;
+pub mod rpc_instance_endpoint;
+pub mod instance_manager;
+pub mod scheduler;
+pub mod keystore;
+pub mod tx_lint;
+pub mod account_diff;
+pub mod program_stats;
+pub mod fault_injection;
+pub mod ledger_diff;
+pub mod subscription_manager;
+pub mod bench;
+pub mod account_surgery;
+pub mod faucet;
+pub mod program_launcher;
+pub mod sig_verify;
+pub mod account_schema;
+pub mod account_watch;
+pub mod event_export;
+pub mod dependency_graph;
+pub mod account_aliases;
+pub mod tx_sanitize;
+mod simulation_cache;
+#[cfg(feature = "grpc")]
+pub mod grpc_endpoint;
+#[cfg(feature = "client")]
+pub mod client;
+
+pub use debug_ledger::{BokkenLedger, BokkenLedgerInitConfig};
+pub use program_caller::ProgramCaller;
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use bokken_runtime::debug_env::{BorshAccountMeta, BokkenAccountData};
-use solana_sdk::{pubkey::Pubkey, program_error::ProgramError};
+use solana_sdk::{pubkey::Pubkey, program_error::ProgramError, rent::Rent};
 
 pub fn assert_account_meta(
 	metas: &Vec<BorshAccountMeta>,
@@ -22,6 +22,10 @@ pub fn assert_account_meta(
 }
 
 pub mod system_program;
+pub mod address_lookup_table;
+pub mod vote_program;
+/// A native program implemented within the validator itself, as opposed to a debuggable program connected through the
+/// unix socket.
 pub trait NativeProgramStub: Send + Sync + std::fmt::Debug {
 	fn clear_logs(&mut self);
 	fn logs(&self) -> &Vec<String>;
@@ -32,6 +36,22 @@ pub trait NativeProgramStub: Send + Sync + std::fmt::Debug {
 	fn msg_str(&mut self, msg: &str) {
 		self.logs_mut().push(format!("Program logged: {}", msg))
 	}
+	/// Data set with `set_return_data` during the current execution, None if none was set
+	fn return_data(&self) -> &Option<Vec<u8>>;
+	fn return_data_mut(&mut self) -> &mut Option<Vec<u8>>;
+	fn clear_return_data(&mut self) {
+		*self.return_data_mut() = None
+	}
+	/// Equivalent of `solana_program::program::set_return_data`, replaces the transaction's return data
+	fn set_return_data(&mut self, data: &[u8]) {
+		*self.return_data_mut() = Some(data.to_vec())
+	}
+	/// Called once the ledger has advanced to `slot`, which is in `epoch`, for stubs which keep bookkeeping that depends
+	/// on time passing. Slots only advance when a block is committed, so there can be a long time between calls.
+	fn on_new_slot(&mut self, _slot: u64, _epoch: u64) {}
+	/// Called on startup and whenever the ledger's rent parameters are changed
+	fn on_rent_change(&mut self, _rent: &Rent) {}
+	/// Executes an instruction. `account_datas` contains the state of every account in `account_metas`.
 	fn exec(
 		&mut self,
 		instruction: Vec<u8>,
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use bokken_runtime::debug_env::{BorshAccountMeta, BokkenAccountData};
+use solana_sdk::{program_error::ProgramError, pubkey, pubkey::Pubkey, rent::Rent, slot_hashes::MAX_ENTRIES, system_program};
+
+use super::{NativeProgramStub, assert_account_meta};
+
+pub const PUBKEY_ADDRESS_LOOKUP_TABLE_PROGRAM: Pubkey = pubkey!("AddressLookupTab1e1111111111111111111111111");
+/// Size of the table's metadata, the addresses come right after it
+pub const LOOKUP_TABLE_META_SIZE: usize = 56;
+pub const LOOKUP_TABLE_MAX_ADDRESSES: usize = 256;
+
+/// Same layout as `solana_address_lookup_table_program::instruction::ProgramInstruction`
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+enum ProgramInstruction {
+	CreateLookupTable {
+		recent_slot: u64,
+		bump_seed: u8
+	},
+	FreezeLookupTable,
+	ExtendLookupTable {
+		new_addresses: Vec<Pubkey>
+	},
+	DeactivateLookupTable,
+	CloseLookupTable
+}
+
+/// Same layout as `solana_address_lookup_table_program::state::LookupTableMeta`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LookupTableMeta {
+	/// `u64::MAX` unless the table has been deactivated
+	pub deactivation_slot: u64,
+	pub last_extended_slot: u64,
+	/// Addresses from this index onwards were added in `last_extended_slot`, and can't be used until the slot after
+	pub last_extended_slot_start_index: u8,
+	/// None once the table is frozen
+	pub authority: Option<Pubkey>,
+	_padding: u16
+}
+impl LookupTableMeta {
+	fn new(authority: Pubkey) -> Self {
+		Self {
+			deactivation_slot: u64::MAX,
+			last_extended_slot: 0,
+			last_extended_slot_start_index: 0,
+			authority: Some(authority),
+			_padding: 0
+		}
+	}
+	pub fn is_deactivated(&self) -> bool {
+		self.deactivation_slot != u64::MAX
+	}
+	/// Like a real validator, a table is only fully deactivated once the slot it was deactivated in is no longer in
+	/// the slot hashes sysvar
+	pub fn closable_at_slot(&self) -> Option<u64> {
+		self.is_deactivated().then(|| self.deactivation_slot.saturating_add(MAX_ENTRIES as u64 + 1))
+	}
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+enum ProgramState {
+	Uninitialized,
+	LookupTable(LookupTableMeta)
+}
+
+/// The table's metadata and addresses, None if `data` isn't an initialized lookup table
+pub fn deserialize_lookup_table(data: &[u8]) -> Option<(LookupTableMeta, Vec<Pubkey>)> {
+	if data.len() < LOOKUP_TABLE_META_SIZE {
+		return None;
+	}
+	let ProgramState::LookupTable(meta) = bincode::deserialize(&data[..LOOKUP_TABLE_META_SIZE]).ok()? else {
+		return None;
+	};
+	let addresses = data[LOOKUP_TABLE_META_SIZE..].chunks_exact(32)
+		.map(|bytes| Pubkey::new_from_array(bytes.try_into().expect("chunks are 32 bytes")))
+		.collect();
+	Some((meta, addresses))
+}
+
+fn serialize_lookup_table(meta: &LookupTableMeta, addresses: &[Pubkey]) -> Vec<u8> {
+	let mut data = bincode::serialize(&ProgramState::LookupTable(meta.clone()))
+		.expect("lookup table meta couldn't be serialized");
+	data.resize(LOOKUP_TABLE_META_SIZE, 0);
+	for address in addresses {
+		data.extend_from_slice(address.as_ref());
+	}
+	data
+}
+
+/// Emulates the address lookup table program, with activation and deactivation delays driven by the ledger's slot.
+///
+/// Bokken only runs legacy transactions, so tables can be created and managed, but not used by transactions.
+#[derive(Debug)]
+pub struct BokkenAddressLookupTableProgram {
+	logs: Vec<String>,
+	return_data: Option<Vec<u8>>,
+	/// The latest committed slot, instructions are executed as part of the slot after it
+	slot: u64,
+	rent: Rent
+}
+impl BokkenAddressLookupTableProgram {
+	pub fn new() -> Self {
+		Self {
+			logs: Vec::new(),
+			return_data: None,
+			slot: 0,
+			rent: Rent::default()
+		}
+	}
+	fn current_slot(&self) -> u64 {
+		self.slot + 1
+	}
+	/// Makes sure the authority (2nd account) signed and matches the table's (1st account), returning the table's
+	/// account, metadata, and addresses
+	fn assert_table_authority(
+		&mut self,
+		account_metas: &Vec<BorshAccountMeta>,
+		account_datas: &mut HashMap<Pubkey, BokkenAccountData>
+	) -> Result<(Pubkey, BokkenAccountData, LookupTableMeta, Vec<Pubkey>), ProgramError> {
+		let (table_key, table_account) = assert_account_meta(account_metas, account_datas, 0, true, false)?;
+		if table_account.owner != PUBKEY_ADDRESS_LOOKUP_TABLE_PROGRAM {
+			return Err(ProgramError::IllegalOwner);
+		}
+		let (meta, addresses) = deserialize_lookup_table(&table_account.data)
+			.ok_or(ProgramError::UninitializedAccount)?;
+		let authority_meta = account_metas.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+		let Some(authority) = meta.authority else {
+			self.msg_str("Lookup table is frozen");
+			return Err(ProgramError::InvalidAccountData);
+		};
+		if authority != authority_meta.pubkey {
+			self.msg_str("Incorrect lookup table authority");
+			return Err(ProgramError::InvalidArgument);
+		}
+		if !authority_meta.is_signer {
+			self.msg_str("Authority account must be a signer");
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+		Ok((table_key, table_account, meta, addresses))
+	}
+	/// Tops the table up to the rent-exempt minimum for its current size from the payer at `payer_index`
+	fn fund_table(
+		&self,
+		table_account: &mut BokkenAccountData,
+		account_metas: &Vec<BorshAccountMeta>,
+		account_datas: &mut HashMap<Pubkey, BokkenAccountData>,
+		payer_index: usize
+	) -> Result<(), ProgramError> {
+		let required_lamports = self.rent.minimum_balance(table_account.data.len()).max(1)
+			.saturating_sub(table_account.lamports);
+		if required_lamports == 0 {
+			return Ok(());
+		}
+		let (payer_key, mut payer_account) = assert_account_meta(account_metas, account_datas, payer_index, true, true)?;
+		payer_account.move_lamports(table_account, required_lamports)?;
+		account_datas.insert(payer_key, payer_account);
+		Ok(())
+	}
+}
+impl Default for BokkenAddressLookupTableProgram {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl NativeProgramStub for BokkenAddressLookupTableProgram {
+	fn clear_logs(&mut self) {
+		self.logs.clear()
+	}
+
+	fn logs(&self) -> &Vec<String> {
+		&self.logs
+	}
+
+	fn logs_mut(&mut self) -> &mut Vec<String> {
+		&mut self.logs
+	}
+
+	fn return_data(&self) -> &Option<Vec<u8>> {
+		&self.return_data
+	}
+
+	fn return_data_mut(&mut self) -> &mut Option<Vec<u8>> {
+		&mut self.return_data
+	}
+
+	fn on_new_slot(&mut self, slot: u64, _epoch: u64) {
+		self.slot = slot;
+	}
+
+	fn on_rent_change(&mut self, rent: &Rent) {
+		self.rent = rent.clone();
+	}
+
+	fn exec(
+		&mut self,
+		instruction: Vec<u8>,
+		account_metas: Vec<BorshAccountMeta>,
+		account_datas: &mut HashMap<Pubkey, BokkenAccountData>
+	) -> Result<(), ProgramError> {
+		match bincode::deserialize::<ProgramInstruction>(&instruction).map_err(|_|{ProgramError::InvalidInstructionData})? {
+			ProgramInstruction::CreateLookupTable { recent_slot, bump_seed } => {
+				let (
+					table_key,
+					mut table_account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, false)?;
+				let authority_meta = account_metas.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+				if !authority_meta.is_signer {
+					self.msg_str("Authority account must be a signer");
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				let authority_key = authority_meta.pubkey;
+
+				if table_account.owner == PUBKEY_ADDRESS_LOOKUP_TABLE_PROGRAM {
+					return Err(ProgramError::AccountAlreadyInitialized);
+				}
+				if table_account.owner != system_program::id() || table_account.data.len() > 0 {
+					return Err(ProgramError::IllegalOwner);
+				}
+				// Slots with a block are the only ones in the slot hashes sysvar
+				let current_slot = self.current_slot();
+				if recent_slot >= current_slot || current_slot - recent_slot > MAX_ENTRIES as u64 {
+					self.msg(format!("{} is not a recent slot", recent_slot));
+					return Err(ProgramError::InvalidInstructionData);
+				}
+				let derived_table_key = Pubkey::create_program_address(
+					&[authority_key.as_ref(), &recent_slot.to_le_bytes(), &[bump_seed]],
+					&PUBKEY_ADDRESS_LOOKUP_TABLE_PROGRAM
+				)?;
+				if table_key != derived_table_key {
+					self.msg(format!("Table address must match derived address: {}", derived_table_key));
+					return Err(ProgramError::InvalidArgument);
+				}
+
+				table_account.data = serialize_lookup_table(&LookupTableMeta::new(authority_key), &[]);
+				self.fund_table(&mut table_account, &account_metas, account_datas, 2)?;
+				table_account.owner = PUBKEY_ADDRESS_LOOKUP_TABLE_PROGRAM;
+				account_datas.insert(table_key, table_account);
+			},
+			ProgramInstruction::FreezeLookupTable => {
+				let (
+					table_key,
+					mut table_account,
+					mut meta,
+					addresses
+				) = self.assert_table_authority(&account_metas, account_datas)?;
+				if meta.is_deactivated() {
+					self.msg_str("Deactivated tables cannot be frozen");
+					return Err(ProgramError::InvalidArgument);
+				}
+				if addresses.is_empty() {
+					self.msg_str("Empty lookup tables cannot be frozen");
+					return Err(ProgramError::InvalidInstructionData);
+				}
+
+				meta.authority = None;
+				table_account.data = serialize_lookup_table(&meta, &addresses);
+				account_datas.insert(table_key, table_account);
+			},
+			ProgramInstruction::ExtendLookupTable { new_addresses } => {
+				let (
+					table_key,
+					mut table_account,
+					mut meta,
+					mut addresses
+				) = self.assert_table_authority(&account_metas, account_datas)?;
+				if meta.is_deactivated() {
+					self.msg_str("Deactivated tables cannot be extended");
+					return Err(ProgramError::InvalidArgument);
+				}
+				if new_addresses.is_empty() {
+					self.msg_str("Must extend with at least one address");
+					return Err(ProgramError::InvalidInstructionData);
+				}
+				if addresses.len() + new_addresses.len() > LOOKUP_TABLE_MAX_ADDRESSES {
+					self.msg(format!(
+						"Extended lookup table length {} would exceed max capacity of {}",
+						addresses.len() + new_addresses.len(),
+						LOOKUP_TABLE_MAX_ADDRESSES
+					));
+					return Err(ProgramError::InvalidInstructionData);
+				}
+
+				let current_slot = self.current_slot();
+				if meta.last_extended_slot != current_slot {
+					meta.last_extended_slot = current_slot;
+					meta.last_extended_slot_start_index = addresses.len() as u8;
+				}
+				addresses.extend(new_addresses);
+				table_account.data = serialize_lookup_table(&meta, &addresses);
+				self.fund_table(&mut table_account, &account_metas, account_datas, 2)?;
+				account_datas.insert(table_key, table_account);
+			},
+			ProgramInstruction::DeactivateLookupTable => {
+				let (
+					table_key,
+					mut table_account,
+					mut meta,
+					addresses
+				) = self.assert_table_authority(&account_metas, account_datas)?;
+				if meta.is_deactivated() {
+					self.msg_str("Lookup table is already deactivated");
+					return Err(ProgramError::InvalidArgument);
+				}
+
+				meta.deactivation_slot = self.current_slot();
+				table_account.data = serialize_lookup_table(&meta, &addresses);
+				account_datas.insert(table_key, table_account);
+			},
+			ProgramInstruction::CloseLookupTable => {
+				let (
+					table_key,
+					mut table_account,
+					meta,
+					_
+				) = self.assert_table_authority(&account_metas, account_datas)?;
+				let Some(closable_at_slot) = meta.closable_at_slot() else {
+					self.msg_str("Lookup table is not deactivated");
+					return Err(ProgramError::InvalidArgument);
+				};
+				let current_slot = self.current_slot();
+				if current_slot < closable_at_slot {
+					self.msg(format!(
+						"Table cannot be closed until it's fully deactivated in {} blocks",
+						closable_at_slot - current_slot
+					));
+					return Err(ProgramError::InvalidArgument);
+				}
+				let (
+					recipient_key,
+					mut recipient_account
+				) = assert_account_meta(&account_metas, account_datas, 2, true, false)?;
+				if recipient_key == table_key {
+					self.msg_str("Lookup table cannot be the recipient of reclaimed lamports");
+					return Err(ProgramError::InvalidArgument);
+				}
+
+				let lamports = table_account.lamports;
+				table_account.move_lamports(&mut recipient_account, lamports)?;
+				table_account.data = Vec::new();
+				table_account.owner = system_program::id();
+				account_datas.insert(table_key, table_account);
+				account_datas.insert(recipient_key, recipient_account);
+			}
+		}
+		Ok(())
+	}
+}
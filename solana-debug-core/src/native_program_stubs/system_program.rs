@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use bokken_runtime::debug_env::{BorshAccountMeta, BokkenAccountData};
+use solana_sdk::{program_error::ProgramError, system_instruction::SystemInstruction, pubkey::Pubkey, system_program};
+
+use super::{NativeProgramStub, assert_account_meta};
+
+#[derive(Debug)]
+pub struct BokkenSystemProgram {
+	logs: Vec<String>,
+	return_data: Option<Vec<u8>>,
+	max_account_size: u64
+}
+impl BokkenSystemProgram {
+	pub fn new(max_account_size: u64) -> Self {
+		Self {
+			logs: Vec::new(),
+			return_data: None,
+			max_account_size
+		}
+	}
+}
+impl NativeProgramStub for BokkenSystemProgram {
+	fn clear_logs(&mut self) {
+		self.logs.clear()
+	}
+
+	fn logs(&self) -> &Vec<String> {
+		&self.logs
+	}
+
+	fn logs_mut(&mut self) -> &mut Vec<String> {
+		&mut self.logs
+	}
+
+	fn return_data(&self) -> &Option<Vec<u8>> {
+		&self.return_data
+	}
+
+	fn return_data_mut(&mut self) -> &mut Option<Vec<u8>> {
+		&mut self.return_data
+	}
+
+	fn exec(
+		&mut self,
+		instruction: Vec<u8>,
+		account_metas: Vec<BorshAccountMeta>,
+		account_datas: &mut HashMap<Pubkey, BokkenAccountData>
+	) -> Result<(), ProgramError> {
+		match bincode::deserialize::<SystemInstruction>(&instruction).map_err(|_|{ProgramError::InvalidInstructionData})? {
+			SystemInstruction::CreateAccount {lamports, space, owner } => {
+				let (
+					funding_account_key,
+					mut funding_account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, true)?;
+				let (
+					new_account_key,
+					mut new_account
+				) = assert_account_meta(&account_metas, account_datas, 1, true, true)?;
+
+				if new_account.data.len() > 0 || new_account.owner != system_program::id() {
+					return Err(ProgramError::AccountAlreadyInitialized);
+				}
+				if space > self.max_account_size {
+					self.msg(format!("{} > {}", space, self.max_account_size));
+					return Err(ProgramError::InvalidRealloc);
+				}
+				funding_account.move_lamports(&mut new_account, lamports)?;
+				new_account.owner = owner;
+				new_account.data = vec![0; space as usize];
+				
+				account_datas.insert(funding_account_key, funding_account);
+				account_datas.insert(new_account_key, new_account);
+			},
+			SystemInstruction::Assign { owner } => {
+				let (
+					account_key,
+					mut account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, true)?;
+				if account.owner != system_program::id() && account.owner != owner {
+					return Err(ProgramError::IllegalOwner);
+				}
+				account.owner = owner;
+				account_datas.insert(account_key, account);
+			},
+			SystemInstruction::Transfer { lamports } => {
+				let (
+					from_account_key,
+					mut from_account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, true)?;
+				let (
+					to_account_key,
+					mut to_account
+				) = assert_account_meta(&account_metas, account_datas, 1, true, false)?;
+				from_account.move_lamports(&mut to_account, lamports)?;
+
+				account_datas.insert(from_account_key, from_account);
+				account_datas.insert(to_account_key, to_account);
+			},
+			SystemInstruction::CreateAccountWithSeed { base, seed, lamports, space, owner } => {
+				let (
+					funding_account_key,
+					mut funding_account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, true)?;
+				let (
+					new_account_key,
+					mut new_account
+				) = assert_account_meta(&account_metas, account_datas, 1, true, false)?;
+				if base != funding_account_key && !account_metas.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?.is_signer {
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				if new_account_key != Pubkey::create_with_seed(&base, &seed, &owner)? {
+					self.msg_str("Provided new account and derived seed don't match");
+					return Err(ProgramError::InvalidSeeds);
+				}
+
+				if new_account.data.len() > 0 || new_account.owner != system_program::id() {
+					return Err(ProgramError::AccountAlreadyInitialized);
+				}
+				if space > self.max_account_size {
+					self.msg(format!("{} > {}", space, self.max_account_size));
+					return Err(ProgramError::InvalidRealloc);
+				}
+				funding_account.move_lamports(&mut new_account, lamports)?;
+				new_account.owner = owner;
+				new_account.data = vec![0; space as usize];
+				
+				account_datas.insert(funding_account_key, funding_account);
+				account_datas.insert(new_account_key, new_account);
+			},
+			SystemInstruction::Allocate { space } => {
+				let (
+					new_account_key,
+					mut new_account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, true)?;
+
+				if new_account.data.len() > 0 || new_account.owner != system_program::id() {
+					return Err(ProgramError::AccountAlreadyInitialized);
+				}
+				if space > self.max_account_size {
+					self.msg(format!("{} > {}", space, self.max_account_size));
+					return Err(ProgramError::InvalidRealloc);
+				}
+				new_account.data = vec![0; space as usize];
+				
+				account_datas.insert(new_account_key, new_account);
+			},
+			SystemInstruction::AllocateWithSeed { base, seed, space, owner } => {
+				let (
+					new_account_key,
+					mut new_account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, false)?;
+
+				if new_account.data.len() > 0 || new_account.owner != system_program::id() {
+					return Err(ProgramError::AccountAlreadyInitialized);
+				}
+				if space > self.max_account_size {
+					self.msg(format!("{} > {}", space, self.max_account_size));
+					return Err(ProgramError::InvalidRealloc);
+				}
+
+				if !account_metas.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?.is_signer {
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				if account_metas.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?.pubkey != base {
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				if new_account_key != Pubkey::create_with_seed(&base, &seed, &owner)? {
+					self.msg_str("Provided new account and derived seed don't match");
+					return Err(ProgramError::InvalidSeeds);
+				}
+
+				new_account.data = vec![0; space as usize];
+				
+				account_datas.insert(new_account_key, new_account);
+			},
+			SystemInstruction::AssignWithSeed { base, seed, owner } => {
+				let (
+					account_key,
+					mut account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, false)?;
+
+				if !account_metas.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?.is_signer {
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				if account_metas.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?.pubkey != base {
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				if account_key != Pubkey::create_with_seed(&base, &seed, &owner)? {
+					self.msg_str("Provided new account and derived seed don't match");
+					return Err(ProgramError::InvalidSeeds);
+				}
+				if account.owner != system_program::id() && account.owner != owner {
+					return Err(ProgramError::IllegalOwner);
+				}
+
+				account.owner = owner;
+				account_datas.insert(account_key, account);
+			},
+			SystemInstruction::TransferWithSeed { lamports, from_seed, from_owner } => {
+				let (
+					from_account_key,
+					mut from_account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, false)?;
+
+				let base_meta = account_metas.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+				if !base_meta.is_signer {
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				if from_account_key != Pubkey::create_with_seed(&base_meta.pubkey, &from_seed, &from_owner)? {
+					self.msg_str("Provided new account and derived seed don't match");
+					return Err(ProgramError::InvalidSeeds);
+				}
+
+				let (
+					to_account_key,
+					mut to_account
+				) = assert_account_meta(&account_metas, account_datas, 2, true, false)?;
+				from_account.move_lamports(&mut to_account, lamports)?;
+
+				account_datas.insert(from_account_key, from_account);
+				account_datas.insert(to_account_key, to_account);
+			},
+			_ => {
+				self.msg_str("Unknown/Unimplemented SystemInstruction");
+				return Err(ProgramError::InvalidInstructionData);
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use bokken_runtime::debug_env::{BorshAccountMeta, BokkenAccountData};
+	use proptest::prelude::*;
+	use solana_sdk::{program_error::ProgramError, pubkey::Pubkey, system_instruction::SystemInstruction, system_program};
+
+	use super::{BokkenSystemProgram, NativeProgramStub};
+
+	const MAX_ACCOUNT_SIZE: u64 = 64;
+	const SEED: &str = "seed";
+
+	fn other_program() -> Pubkey {
+		Pubkey::new_from_array([0xee; 32])
+	}
+	/// Every account the generated instructions may refer to. The last two are derived from the first, so that seeded
+	/// instructions get a chance to succeed.
+	fn pubkeys() -> Vec<Pubkey> {
+		let base = Pubkey::new_from_array([1; 32]);
+		vec![
+			base,
+			Pubkey::new_from_array([2; 32]),
+			Pubkey::new_from_array([3; 32]),
+			Pubkey::create_with_seed(&base, SEED, &system_program::id()).unwrap(),
+			Pubkey::create_with_seed(&base, SEED, &other_program()).unwrap()
+		]
+	}
+
+	fn owner() -> impl Strategy<Value = Pubkey> {
+		prop_oneof![Just(system_program::id()), Just(other_program())]
+	}
+	fn pubkey() -> impl Strategy<Value = Pubkey> {
+		prop::sample::select(pubkeys())
+	}
+	fn seed() -> impl Strategy<Value = String> {
+		prop_oneof![Just(SEED.to_string()), Just("other".to_string())]
+	}
+	fn accounts() -> impl Strategy<Value = HashMap<Pubkey, BokkenAccountData>> {
+		let account = (0..1_000u64, prop::collection::vec(any::<u8>(), 0..4), owner()).prop_map(|(lamports, data, owner)| {
+			BokkenAccountData { lamports, data, owner, executable: false, rent_epoch: 0 }
+		});
+		prop::collection::vec(account, pubkeys().len()).prop_map(|accounts| {
+			pubkeys().into_iter().zip(accounts).collect()
+		})
+	}
+	fn system_instruction() -> impl Strategy<Value = SystemInstruction> {
+		let lamports = 0..1_500u64;
+		let space = 0..MAX_ACCOUNT_SIZE * 2;
+		prop_oneof![
+			(lamports.clone(), space.clone(), owner()).prop_map(|(lamports, space, owner)| {
+				SystemInstruction::CreateAccount { lamports, space, owner }
+			}),
+			owner().prop_map(|owner| SystemInstruction::Assign { owner }),
+			lamports.clone().prop_map(|lamports| SystemInstruction::Transfer { lamports }),
+			(pubkey(), seed(), lamports.clone(), space.clone(), owner()).prop_map(|(base, seed, lamports, space, owner)| {
+				SystemInstruction::CreateAccountWithSeed { base, seed, lamports, space, owner }
+			}),
+			space.clone().prop_map(|space| SystemInstruction::Allocate { space }),
+			(pubkey(), seed(), space, owner()).prop_map(|(base, seed, space, owner)| {
+				SystemInstruction::AllocateWithSeed { base, seed, space, owner }
+			}),
+			(pubkey(), seed(), owner()).prop_map(|(base, seed, owner)| SystemInstruction::AssignWithSeed { base, seed, owner }),
+			(lamports, seed(), owner()).prop_map(|(lamports, from_seed, from_owner)| {
+				SystemInstruction::TransferWithSeed { lamports, from_seed, from_owner }
+			})
+		]
+	}
+	fn account_metas() -> impl Strategy<Value = Vec<BorshAccountMeta>> {
+		prop::collection::vec((pubkey(), any::<bool>(), any::<bool>()), 0..4).prop_map(|metas| {
+			metas.into_iter().map(|(pubkey, is_signer, is_writable)| {
+				BorshAccountMeta { pubkey, is_signer, is_writable }
+			}).collect()
+		})
+	}
+
+	/// Executes the instruction on a copy of `accounts`, as the ledger discards whatever a failed instruction did
+	fn exec(
+		accounts: &HashMap<Pubkey, BokkenAccountData>,
+		instruction: &SystemInstruction,
+		account_metas: &[BorshAccountMeta]
+	) -> Result<HashMap<Pubkey, BokkenAccountData>, ProgramError> {
+		let mut account_datas = accounts.clone();
+		BokkenSystemProgram::new(MAX_ACCOUNT_SIZE).exec(
+			bincode::serialize(instruction).unwrap(),
+			account_metas.to_vec(),
+			&mut account_datas
+		)?;
+		Ok(account_datas)
+	}
+	fn total_lamports(accounts: &HashMap<Pubkey, BokkenAccountData>) -> u128 {
+		accounts.values().map(|account| account.lamports as u128).sum()
+	}
+
+	proptest! {
+		#[test]
+		fn instruction_sequences_hold_invariants(
+			mut accounts in accounts(),
+			instructions in prop::collection::vec((system_instruction(), account_metas()), 1..8)
+		) {
+			for (instruction, account_metas) in instructions.iter() {
+				let result = exec(&accounts, instruction, account_metas);
+				prop_assert_eq!(&exec(&accounts, instruction, account_metas), &result, "the outcome isn't deterministic");
+				let new_accounts = match result {
+					Ok(new_accounts) => new_accounts,
+					Err(_) => continue
+				};
+				prop_assert_eq!(new_accounts.len(), accounts.len(), "accounts went missing or appeared");
+				prop_assert!(accounts.keys().all(|pubkey| new_accounts.contains_key(pubkey)), "accounts went missing");
+				prop_assert_eq!(total_lamports(&new_accounts), total_lamports(&accounts), "lamports weren't conserved");
+				for (pubkey, account) in accounts.iter() {
+					let new_account = &new_accounts[pubkey];
+					if account.owner != system_program::id() {
+						prop_assert_eq!(&new_account.data, &account.data, "data of {} changed, it isn't owned by the system program", pubkey);
+						prop_assert_eq!(new_account.owner, account.owner, "owner of {} changed, it isn't owned by the system program", pubkey);
+					}
+				}
+				accounts = new_accounts;
+			}
+		}
+
+		#[test]
+		fn transfers_move_exactly_the_amount(
+			mut accounts in accounts(),
+			from in 0..3usize,
+			to in 0..3usize,
+			lamports in 0..1_500u64
+		) {
+			prop_assume!(from != to);
+			let (from, to) = (pubkeys()[from], pubkeys()[to]);
+			let from_account = accounts.get_mut(&from).unwrap();
+			from_account.owner = system_program::id();
+			from_account.data.clear();
+			let account_metas = vec![
+				BorshAccountMeta { pubkey: from, is_signer: true, is_writable: true },
+				BorshAccountMeta { pubkey: to, is_signer: false, is_writable: true }
+			];
+			let result = exec(&accounts, &SystemInstruction::Transfer { lamports }, &account_metas);
+			if lamports > accounts[&from].lamports {
+				prop_assert_eq!(result, Err(ProgramError::InsufficientFunds));
+			}else{
+				let new_accounts = result.unwrap();
+				prop_assert_eq!(new_accounts[&from].lamports, accounts[&from].lamports - lamports);
+				prop_assert_eq!(new_accounts[&to].lamports, accounts[&to].lamports + lamports);
+			}
+		}
+	}
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use bokken_runtime::debug_env::{BorshAccountMeta, BokkenAccountData};
+use solana_sdk::{clock::Clock, instruction::InstructionError, program_error::ProgramError, pubkey::Pubkey, rent::Rent, vote::{instruction::VoteInstruction, state::{VoteAuthorize, VoteState, VoteStateVersions}}};
+
+use super::{NativeProgramStub, assert_account_meta};
+
+fn program_error(error: InstructionError) -> ProgramError {
+	ProgramError::try_from(error).unwrap_or(ProgramError::InvalidAccountData)
+}
+
+fn write_vote_state(vote_account: &mut BokkenAccountData, vote_state: VoteState) -> Result<(), ProgramError> {
+	VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut vote_account.data).map_err(program_error)
+}
+
+fn is_signer(account_metas: &[BorshAccountMeta], pubkey: &Pubkey) -> bool {
+	account_metas.iter().any(|meta| meta.is_signer && meta.pubkey == *pubkey)
+}
+
+/// A minimal vote program, for managing vote accounts. Bokken has no other validators, so votes themselves aren't
+/// processed.
+#[derive(Debug)]
+pub struct BokkenVoteProgram {
+	logs: Vec<String>,
+	return_data: Option<Vec<u8>>,
+	/// The latest committed slot and its epoch, instructions are executed as part of the slot after it
+	slot: u64,
+	epoch: u64,
+	rent: Rent
+}
+impl BokkenVoteProgram {
+	pub fn new() -> Self {
+		Self {
+			logs: Vec::new(),
+			return_data: None,
+			slot: 0,
+			epoch: 0,
+			rent: Rent::default()
+		}
+	}
+	/// Reads the initialized vote account which is the first account of the instruction
+	fn vote_account(
+		&self,
+		account_metas: &Vec<BorshAccountMeta>,
+		account_datas: &mut HashMap<Pubkey, BokkenAccountData>
+	) -> Result<(Pubkey, BokkenAccountData, VoteState), ProgramError> {
+		let (vote_key, vote_account) = assert_account_meta(account_metas, account_datas, 0, true, false)?;
+		if vote_account.owner != solana_sdk::vote::program::id() {
+			return Err(ProgramError::IncorrectProgramId);
+		}
+		let vote_state = VoteState::deserialize(&vote_account.data).map_err(program_error)?;
+		if vote_state.authorized_voters().is_empty() {
+			return Err(ProgramError::UninitializedAccount);
+		}
+		Ok((vote_key, vote_account, vote_state))
+	}
+	fn assert_withdrawer_signed(
+		&mut self,
+		account_metas: &[BorshAccountMeta],
+		vote_state: &VoteState
+	) -> Result<(), ProgramError> {
+		if !is_signer(account_metas, &vote_state.authorized_withdrawer) {
+			self.msg_str("Authorized withdrawer must be a signer");
+			return Err(ProgramError::MissingRequiredSignature);
+		}
+		Ok(())
+	}
+	fn authorize(
+		&mut self,
+		vote_state: &mut VoteState,
+		account_metas: &[BorshAccountMeta],
+		new_authority: Pubkey,
+		vote_authorize: VoteAuthorize
+	) -> Result<(), ProgramError> {
+		match vote_authorize {
+			VoteAuthorize::Voter => {
+				// Like on a real validator, the new voter takes over from the next epoch
+				let withdrawer_signed = is_signer(account_metas, &vote_state.authorized_withdrawer);
+				let current_epoch = self.epoch;
+				vote_state.set_new_authorized_voter(
+					&new_authority,
+					current_epoch,
+					current_epoch + 1,
+					|voter| {
+						if withdrawer_signed || is_signer(account_metas, &voter) {
+							Ok(())
+						}else{
+							Err(InstructionError::MissingRequiredSignature)
+						}
+					}
+				).map_err(program_error)?;
+			},
+			VoteAuthorize::Withdrawer => {
+				self.assert_withdrawer_signed(account_metas, vote_state)?;
+				vote_state.authorized_withdrawer = new_authority;
+			}
+		}
+		Ok(())
+	}
+}
+impl Default for BokkenVoteProgram {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+impl NativeProgramStub for BokkenVoteProgram {
+	fn clear_logs(&mut self) {
+		self.logs.clear()
+	}
+
+	fn logs(&self) -> &Vec<String> {
+		&self.logs
+	}
+
+	fn logs_mut(&mut self) -> &mut Vec<String> {
+		&mut self.logs
+	}
+
+	fn return_data(&self) -> &Option<Vec<u8>> {
+		&self.return_data
+	}
+
+	fn return_data_mut(&mut self) -> &mut Option<Vec<u8>> {
+		&mut self.return_data
+	}
+
+	fn on_new_slot(&mut self, slot: u64, epoch: u64) {
+		self.slot = slot;
+		self.epoch = epoch;
+	}
+
+	fn on_rent_change(&mut self, rent: &Rent) {
+		self.rent = rent.clone();
+	}
+
+	fn exec(
+		&mut self,
+		instruction: Vec<u8>,
+		account_metas: Vec<BorshAccountMeta>,
+		account_datas: &mut HashMap<Pubkey, BokkenAccountData>
+	) -> Result<(), ProgramError> {
+		match bincode::deserialize::<VoteInstruction>(&instruction).map_err(|_|{ProgramError::InvalidInstructionData})? {
+			VoteInstruction::InitializeAccount(vote_init) => {
+				let (
+					vote_key,
+					mut vote_account
+				) = assert_account_meta(&account_metas, account_datas, 0, true, false)?;
+				if vote_account.owner != solana_sdk::vote::program::id() {
+					return Err(ProgramError::IncorrectProgramId);
+				}
+				if vote_account.data.len() != VoteState::size_of() {
+					return Err(ProgramError::InvalidAccountData);
+				}
+				if vote_account.data.iter().any(|byte| *byte != 0) {
+					return Err(ProgramError::AccountAlreadyInitialized);
+				}
+				if !self.rent.is_exempt(vote_account.lamports, vote_account.data.len()) {
+					return Err(ProgramError::AccountNotRentExempt);
+				}
+				if !is_signer(&account_metas, &vote_init.node_pubkey) {
+					self.msg_str("Node identity must be a signer");
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				let vote_state = VoteState::new(
+					&vote_init,
+					&Clock {
+						slot: self.slot + 1,
+						epoch: self.epoch,
+						..Clock::default()
+					}
+				);
+				write_vote_state(&mut vote_account, vote_state)?;
+				account_datas.insert(vote_key, vote_account);
+			},
+			VoteInstruction::Authorize(new_authority, vote_authorize) => {
+				let (vote_key, mut vote_account, mut vote_state) = self.vote_account(&account_metas, account_datas)?;
+				self.authorize(&mut vote_state, &account_metas, new_authority, vote_authorize)?;
+				write_vote_state(&mut vote_account, vote_state)?;
+				account_datas.insert(vote_key, vote_account);
+			},
+			VoteInstruction::AuthorizeChecked(vote_authorize) => {
+				let (vote_key, mut vote_account, mut vote_state) = self.vote_account(&account_metas, account_datas)?;
+				let new_authority_meta = account_metas.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+				if !new_authority_meta.is_signer {
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				self.authorize(&mut vote_state, &account_metas, new_authority_meta.pubkey, vote_authorize)?;
+				write_vote_state(&mut vote_account, vote_state)?;
+				account_datas.insert(vote_key, vote_account);
+			},
+			VoteInstruction::UpdateValidatorIdentity => {
+				let (vote_key, mut vote_account, mut vote_state) = self.vote_account(&account_metas, account_datas)?;
+				let node_meta = account_metas.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+				if !node_meta.is_signer {
+					self.msg_str("Node identity must be a signer");
+					return Err(ProgramError::MissingRequiredSignature);
+				}
+				self.assert_withdrawer_signed(&account_metas, &vote_state)?;
+				vote_state.node_pubkey = node_meta.pubkey;
+				write_vote_state(&mut vote_account, vote_state)?;
+				account_datas.insert(vote_key, vote_account);
+			},
+			VoteInstruction::UpdateCommission(commission) => {
+				let (vote_key, mut vote_account, mut vote_state) = self.vote_account(&account_metas, account_datas)?;
+				self.assert_withdrawer_signed(&account_metas, &vote_state)?;
+				vote_state.commission = commission;
+				write_vote_state(&mut vote_account, vote_state)?;
+				account_datas.insert(vote_key, vote_account);
+			},
+			VoteInstruction::Withdraw(lamports) => {
+				let (vote_key, mut vote_account, vote_state) = self.vote_account(&account_metas, account_datas)?;
+				self.assert_withdrawer_signed(&account_metas, &vote_state)?;
+				let (
+					recipient_key,
+					mut recipient_account
+				) = assert_account_meta(&account_metas, account_datas, 1, true, false)?;
+				vote_account.move_lamports(&mut recipient_account, lamports)?;
+				if vote_account.lamports == 0 {
+					// Closing the account, like a real validator this is allowed even if it has voted recently
+					vote_account.data.fill(0);
+				}else if !self.rent.is_exempt(vote_account.lamports, vote_account.data.len()) {
+					self.msg_str("Vote account would no longer be rent-exempt");
+					return Err(ProgramError::InsufficientFunds);
+				}
+				account_datas.insert(vote_key, vote_account);
+				account_datas.insert(recipient_key, recipient_account);
+			},
+			_ => {
+				self.msg_str("Unknown/Unimplemented VoteInstruction, Bokken doesn't process votes");
+				return Err(ProgramError::InvalidInstructionData);
+			}
+		}
+		Ok(())
+	}
+}
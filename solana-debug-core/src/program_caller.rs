@@ -0,0 +1,849 @@
+
+use std::{sync::{atomic::{AtomicU64, AtomicBool, Ordering}, Arc}, collections::{HashMap, HashSet}, time::{Duration, Instant}};
+use std::sync::Mutex as StdMutex;
+use async_recursion::async_recursion;
+use color_eyre::eyre;
+use bokken_runtime::{ipc_comm::IPCComm, debug_env::{BokkenValidatorMessage, BokkenRuntimeMessage, BokkenAccountData, BorshAccountMeta, BokkenProgramIdentity}};
+use solana_sdk::{pubkey::Pubkey, rent::Rent, transaction::TransactionError, system_program, program_error::ProgramError, bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable, native_loader, compute_budget, ed25519_program, secp256k1_program, stake, vote, sysvar};
+use tokio::{net::UnixListener, task, sync::{Mutex, watch, broadcast}};
+use serde_with::{serde_as, DisplayFromStr};
+use tracing::Instrument;
+
+pub use bokken_runtime::debug_env::{BokkenLogLevel, BokkenAccountSizeLimits};
+pub use bokken_runtime::ipc_comm::{bind_ipc_socket, remove_ipc_socket, is_abstract_socket_path};
+
+use crate::{error::BokkenError, account_aliases::display_pubkey, native_program_stubs::{NativeProgramStub, system_program::BokkenSystemProgram, address_lookup_table::{BokkenAddressLookupTableProgram, PUBKEY_ADDRESS_LOOKUP_TABLE_PROGRAM}, vote_program::BokkenVoteProgram}, program_stats::BokkenProgramStats};
+/// Logs are carried along with each status, containing everything the program logged since the previous status for
+/// the same invocation. As a program's messages are received in the order they were sent, this keeps logs in the order
+/// they were emitted, even around CPIs.
+#[derive(Debug)]
+enum ProgramCallerExecStatus {
+	Executed {
+		return_code: u64,
+		account_datas: HashMap<Pubkey, BokkenAccountData>,
+		return_data: Option<(Pubkey, Vec<u8>)>,
+		logs: Vec<String>,
+		/// The panic message and backtrace, if the program panicked
+		panic: Option<(String, String)>
+	},
+	CPI {
+		program_id: Pubkey,
+		instruction: Vec<u8>,
+		account_metas: Vec<BorshAccountMeta>,
+		account_datas: HashMap<Pubkey, BokkenAccountData>,
+		call_depth: u8,
+		return_data: Option<(Pubkey, Vec<u8>)>,
+		logs: Vec<String>
+	}
+}
+
+/// Return code, logs, resulting account states and return data of a program invocation
+pub type ProgramCallResult = (u64, Vec<String>, HashMap<Pubkey, BokkenAccountData>, Option<(Pubkey, Vec<u8>)>);
+
+/// Each solana program invoke is tied with a nonce so that nested CPIs can be properly handeled 
+static COMM_NONCE: AtomicU64 = AtomicU64::new(0);
+/// Every debuggable program connection gets its own ID, so that a reconnected program can be told apart
+static CONNECTION_ID: AtomicU64 = AtomicU64::new(0);
+/// How many log messages can be queued up for each log subscriber before they start lagging behind
+const LOG_BUS_CAPACITY: usize = 4096;
+/// How often debuggable programs are pinged
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// Debuggable programs are considered unhealthy if nothing has been received from them for this long
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Most log data kept in memory for a single invocation, anything logged past this is dropped
+const MAX_INVOCATION_LOG_BYTES: usize = 10 * 1024 * 1024;
+const INVOCATION_LOG_TRUNCATED_MSG: &str = "Log truncated, the invocation logged more than Bokken keeps in memory";
+
+/// Programs which are built into a real validator, debuggable programs can't take their place unless they're
+/// configured to override them
+fn is_builtin_program_id(program_id: &Pubkey) -> bool {
+	[
+		system_program::id(),
+		bpf_loader::id(),
+		bpf_loader_deprecated::id(),
+		bpf_loader_upgradeable::id(),
+		native_loader::id(),
+		compute_budget::id(),
+		ed25519_program::id(),
+		secp256k1_program::id(),
+		stake::program::id(),
+		vote::program::id()
+	].contains(program_id) || sysvar::is_sysvar_id(program_id)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProgramCallerConfig {
+	/// Debuggable programs are told not to send any logs below this level
+	pub min_log_level: BokkenLogLevel,
+	/// If specified, only these programs may connect
+	pub allowed_programs: Option<HashSet<Pubkey>>,
+	/// How long invoking a program which has disconnected waits for it to reconnect before failing. Useful for
+	/// rebuilding and restarting a program between transactions.
+	pub reconnect_wait: Duration,
+	/// Overrides `reconnect_wait` for specific programs
+	pub program_reconnect_wait: HashMap<Pubkey, Duration>,
+	/// Limits held to by both the system program and debuggable programs
+	pub account_size_limits: BokkenAccountSizeLimits,
+	/// Builtin programs, including ones Bokken has a native stub for, which a debuggable program is allowed to take the
+	/// place of. Once a debuggable program with one of these IDs has connected, it's invoked instead of the native stub,
+	/// otherwise the native stub (if any) is. Sysvars can't be overridden.
+	pub program_overrides: HashSet<Pubkey>
+}
+impl ProgramCallerConfig {
+	pub fn reconnect_wait_for(&self, program_id: &Pubkey) -> Duration {
+		self.program_reconnect_wait.get(program_id).copied().unwrap_or(self.reconnect_wait)
+	}
+	/// Whether a debuggable program may take the place of the builtin program with this ID. Overridden programs may
+	/// connect even if they aren't in `allowed_programs`.
+	pub fn is_program_override(&self, program_id: &Pubkey) -> bool {
+		self.program_overrides.contains(program_id) && !sysvar::is_sysvar_id(program_id)
+	}
+}
+
+#[derive(Debug)]
+struct DebuggableProgramConnection {
+	id: u64,
+	name: Option<String>,
+	connected_at: Instant,
+	last_message_at: Instant,
+	pending_invokes: u64
+}
+
+/// A message logged by a debuggable program, see `ProgramCaller::subscribe_logs`
+#[derive(Debug, Clone)]
+pub struct BokkenProgramLog {
+	pub program_id: Pubkey,
+	pub level: BokkenLogLevel,
+	pub message: String
+}
+
+/// A debuggable program panicking during an invocation, see `ProgramCaller::take_panics`
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BokkenProgramPanic {
+	#[serde_as(as = "DisplayFromStr")]
+	pub program_id: Pubkey,
+	pub call_depth: u8,
+	pub message: String,
+	/// As captured by the program's runtime, it points at source lines if the program was built with debug info
+	pub backtrace: String
+}
+
+/// Connection status of a debuggable program, see `ProgramCaller::debuggable_programs`
+#[derive(Debug, Clone)]
+pub struct BokkenDebuggableProgramStatus {
+	pub program_id: Pubkey,
+	pub name: Option<String>,
+	/// How the program is connected, currently always "unix"
+	pub transport: &'static str,
+	pub connected_for: Duration,
+	/// Invocations which were sent to the program but haven't finished yet, including ones waiting on a CPI
+	pub pending_invokes: u64,
+	/// Time since anything, including a heartbeat, was received from the program
+	pub last_seen: Duration,
+	pub healthy: bool
+}
+
+/// Keeps the pending invoke count of a debuggable program up to date, however the invocation ends
+struct PendingInvokeGuard<'a> {
+	connections: &'a StdMutex<HashMap<Pubkey, DebuggableProgramConnection>>,
+	program_id: Pubkey
+}
+impl<'a> PendingInvokeGuard<'a> {
+	fn new(connections: &'a StdMutex<HashMap<Pubkey, DebuggableProgramConnection>>, program_id: Pubkey) -> Self {
+		if let Some(connection) = connections.lock().unwrap().get_mut(&program_id) {
+			connection.pending_invokes += 1;
+		}
+		Self {
+			connections,
+			program_id
+		}
+	}
+}
+impl Drop for PendingInvokeGuard<'_> {
+	fn drop(&mut self) {
+		if let Some(connection) = self.connections.lock().unwrap().get_mut(&self.program_id) {
+			connection.pending_invokes = connection.pending_invokes.saturating_sub(1);
+		}
+	}
+}
+
+/// Marks an invocation's nonce as being waited on for as long as this lives. Messages for any other nonce are from an
+/// invocation which finished or was given up on, and are dropped.
+struct WaitingNonceGuard<'a> {
+	waiting_nonces: &'a StdMutex<HashSet<u64>>,
+	nonce: u64
+}
+impl<'a> WaitingNonceGuard<'a> {
+	fn new(waiting_nonces: &'a StdMutex<HashSet<u64>>, nonce: u64) -> Self {
+		waiting_nonces.lock().unwrap().insert(nonce);
+		Self {
+			waiting_nonces,
+			nonce
+		}
+	}
+}
+impl Drop for WaitingNonceGuard<'_> {
+	fn drop(&mut self) {
+		self.waiting_nonces.lock().unwrap().remove(&self.nonce);
+	}
+}
+
+/// Logs of an invocation which haven't been attached to an exec status yet
+#[derive(Debug, Default)]
+struct PendingLogs {
+	messages: Vec<String>,
+	bytes: usize,
+	truncated: bool
+}
+impl PendingLogs {
+	fn extend(&mut self, messages: impl IntoIterator<Item = String>) {
+		for message in messages {
+			if self.truncated {
+				return;
+			}
+			if self.bytes + message.len() > MAX_INVOCATION_LOG_BYTES {
+				self.messages.push(INVOCATION_LOG_TRUNCATED_MSG.to_string());
+				self.truncated = true;
+				return;
+			}
+			self.bytes += message.len();
+			self.messages.push(message);
+		}
+	}
+}
+/// Handles all requests to and from the debuggable programs
+#[derive(Debug)]
+pub struct ProgramCaller {
+	native_programs: HashMap<Pubkey, StdMutex<Box<dyn NativeProgramStub>>>,
+	listener_handle: task::JoinHandle<eyre::Result<()>>,
+	recieve_handle: task::JoinHandle<eyre::Result<()>>,
+	heartbeat_handle: task::JoinHandle<eyre::Result<()>>,
+	should_stop: Arc<AtomicBool>,
+	comms: Arc<Mutex<HashMap<Pubkey, IPCComm>>>,
+	/// Status of each program in `comms`
+	connections: Arc<StdMutex<HashMap<Pubkey, DebuggableProgramConnection>>>,
+	/// Every debuggable program which has ever connected, including ones which have disconnected since
+	known_programs: Arc<StdMutex<HashSet<Pubkey>>>,
+	/// Incremented whenever a debuggable program connects or disconnects, see `program_generation`
+	program_generation: Arc<AtomicU64>,
+	config: ProgramCallerConfig,
+	exec_notif: watch::Receiver<usize>,
+	exec_results: Arc<Mutex<HashMap<u64, ProgramCallerExecStatus>>>,
+	/// Nonces of the invocations which are waiting for their exec status, see `WaitingNonceGuard`
+	waiting_nonces: Arc<StdMutex<HashSet<u64>>>,
+	program_stats: Mutex<HashMap<Pubkey, BokkenProgramStats>>,
+	log_sender: broadcast::Sender<BokkenProgramLog>,
+	/// Panics which happened since the last `take_panics`
+	panics: StdMutex<Vec<BokkenProgramPanic>>
+}
+
+impl ProgramCaller {
+	/// Consumes the UnixListener for debuggable program communications, see `bokken_runtime::ipc_comm::bind_ipc_socket`
+	/// for creating one
+	/// 
+	/// Connections claiming to be a native or builtin program, or one not allowed by `config`, are rejected.
+	pub fn new(
+		listener: UnixListener,
+		config: ProgramCallerConfig
+	) -> Self {
+		let should_stop = Arc::new(AtomicBool::new(false));
+		let comms_mutex = Arc::new(Mutex::new(HashMap::new()));
+		let connections = Arc::new(StdMutex::new(HashMap::new()));
+		let known_programs = Arc::new(StdMutex::new(HashSet::new()));
+		let program_generation = Arc::new(AtomicU64::new(0));
+		let exec_results_mutex = Arc::new(Mutex::new(HashMap::new()));
+		let waiting_nonces = Arc::new(StdMutex::new(HashSet::new()));
+		let (exec_notif_sender, exec_notif) = watch::channel(0usize);
+		let log_sender = broadcast::channel(LOG_BUS_CAPACITY).0;
+
+		let mut native_programs = HashMap::new();
+		native_programs.insert(
+			system_program::id(),
+			StdMutex::new(
+				Box::new(BokkenSystemProgram::new(config.account_size_limits.max_account_size)) as Box<dyn NativeProgramStub>
+			)
+		);
+		native_programs.insert(
+			PUBKEY_ADDRESS_LOOKUP_TABLE_PROGRAM,
+			StdMutex::new(Box::new(BokkenAddressLookupTableProgram::new()) as Box<dyn NativeProgramStub>)
+		);
+		native_programs.insert(
+			vote::program::id(),
+			StdMutex::new(Box::new(BokkenVoteProgram::new()) as Box<dyn NativeProgramStub>)
+		);
+		let native_program_ids: HashSet<Pubkey> = native_programs.keys().cloned().collect();
+		for program_id in config.program_overrides.iter() {
+			if sysvar::is_sysvar_id(program_id) {
+				eprintln!("Ignoring override of {}, sysvars can't be overridden", display_pubkey(program_id));
+			}else if native_program_ids.contains(program_id) {
+				eprintln!(
+					"WARNING: Bokken's native {} will be shadowed by a debuggable program once one connects with its ID",
+					display_pubkey(program_id)
+				);
+			}else if is_builtin_program_id(program_id) {
+				eprintln!(
+					"WARNING: The builtin program {} will be shadowed by a debuggable program once one connects with its ID",
+					display_pubkey(program_id)
+				);
+			}
+		}
+		
+		let should_stop_clone = should_stop.clone();
+		let comms_mutex_clone = comms_mutex.clone();
+		let connections_clone = connections.clone();
+		let known_programs_clone = known_programs.clone();
+		let program_generation_clone = program_generation.clone();
+		let config_clone = config.clone();
+		let listener_handle: task::JoinHandle<eyre::Result<()>> = task::spawn(async move {
+			while !should_stop_clone.load(Ordering::Relaxed) {
+				match listener.accept().await {
+					Ok((stream, _addr)) => {
+						let (mut comm, identity) = IPCComm::new_with_identifier::<BokkenProgramIdentity>(stream).await?;
+						let is_builtin = native_program_ids.contains(&identity.program_id) ||
+							is_builtin_program_id(&identity.program_id);
+						let is_override = config_clone.is_program_override(&identity.program_id);
+						let rejection = if is_builtin && !is_override {
+							Some("it's a builtin program")
+						}else if !is_override && config_clone.allowed_programs.as_ref().map_or(false, |allowed| {
+							!allowed.contains(&identity.program_id)
+						}) {
+							Some("it isn't in the allowlist")
+						}else{
+							None
+						};
+						if let Some(reason) = rejection {
+							eprintln!("Rejected debuggable program {}, {}", identity, reason);
+							comm.stop();
+							continue;
+						}
+						comm.send_msg(BokkenValidatorMessage::SetLogLevel { min_level: config_clone.min_log_level }).await?;
+						comm.send_msg(
+							BokkenValidatorMessage::SetAccountSizeLimits { limits: config_clone.account_size_limits }
+						).await?;
+						if is_builtin {
+							eprintln!("WARNING: Debuggable program {} is overriding a builtin program", identity);
+						}
+						println!("Registered new debuggable program: {}", identity);
+						let mut comms = comms_mutex_clone.lock().await;
+						connections_clone.lock().unwrap().insert(
+							identity.program_id,
+							DebuggableProgramConnection {
+								id: CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+								name: identity.name,
+								connected_at: Instant::now(),
+								last_message_at: Instant::now(),
+								pending_invokes: 0
+							}
+						);
+						known_programs_clone.lock().unwrap().insert(identity.program_id);
+						if let Some(old_comm) = comms.insert(identity.program_id, comm) {
+							// The program restarted before we noticed it went away
+							old_comm.stop();
+						}
+						program_generation_clone.fetch_add(1, Ordering::Relaxed);
+					}
+					Err(_e) => { /* connection failed */ }
+				}
+			}
+			Ok(())
+		});
+		let should_stop_clone = should_stop.clone();
+		let comms_mutex_clone = comms_mutex.clone();
+		let connections_clone = connections.clone();
+		let program_generation_clone = program_generation.clone();
+		let exec_results_mutex_clone = exec_results_mutex.clone();
+		let waiting_nonces_clone = waiting_nonces.clone();
+		let log_sender_clone = log_sender.clone();
+		let recieve_handle: task::JoinHandle<eyre::Result<()>> = task::spawn(async move {
+			let mut pending_logs: HashMap<u64, PendingLogs> = HashMap::new();
+			// Panic message and backtrace of each invocation which panicked, until its `Executed` message arrives
+			let mut pending_panics: HashMap<u64, (String, String)> = HashMap::new();
+			while !should_stop_clone.load(Ordering::Relaxed) {
+				let mut stuff_executed = false;
+				let mut bad_program_ids = Vec::new();
+				let mut comms = comms_mutex_clone.lock().await;
+				for (program_id, comm) in comms.iter_mut() {
+					if comm.stopped() {
+						eprintln!("Debuggable program {} disconnected", display_pubkey(program_id));
+						bad_program_ids.push(*program_id);
+						continue;
+					}
+					let msg = match comm.recv_msg::<BokkenRuntimeMessage>().await {
+						Ok(msg) => msg,
+						Err(e) => {
+							// One misbehaving program shouldn't stop us from talking to all the others
+							eprintln!("Disconnecting debuggable program {}, it sent an undecodable message: {}", display_pubkey(program_id), e);
+							bad_program_ids.push(*program_id);
+							continue;
+						}
+					};
+					if let Some(msg) = msg {
+						if let Some(connection) = connections_clone.lock().unwrap().get_mut(program_id) {
+							connection.last_message_at = Instant::now();
+						}
+						match msg {
+							BokkenRuntimeMessage::Log { nonce, messages } => {
+								for log in messages.iter() {
+									// An error here only means that nobody is listening
+									let _ = log_sender_clone.send(
+										BokkenProgramLog {
+											program_id: *program_id,
+											level: log.level,
+											message: log.message.clone()
+										}
+									);
+								}
+								if waiting_nonces_clone.lock().unwrap().contains(&nonce) {
+									pending_logs.entry(nonce).or_default().extend(
+										messages.into_iter().map(|log| log.message)
+									);
+								}
+							},
+							// Only needed for updating last_message_at
+							BokkenRuntimeMessage::Pong => {},
+							BokkenRuntimeMessage::Panicked { nonce, message, backtrace } => {
+								if waiting_nonces_clone.lock().unwrap().contains(&nonce) {
+									pending_panics.insert(nonce, (message, backtrace));
+								}
+							},
+							BokkenRuntimeMessage::Executed {
+								nonce,
+								return_code,
+								account_datas,
+								return_data
+							} => {
+								let mut exec_results = exec_results_mutex_clone.lock().await;
+								exec_results.insert(
+									nonce,
+									ProgramCallerExecStatus::Executed {
+										return_code,
+										account_datas,
+										return_data,
+										logs: pending_logs.remove(&nonce).unwrap_or_default().messages,
+										panic: pending_panics.remove(&nonce)
+									}
+								);
+								stuff_executed = true;
+							},
+        					BokkenRuntimeMessage::CrossProgramInvoke {
+								nonce,
+								program_id,
+								instruction,
+								account_metas,
+								account_datas,
+								call_depth,
+								return_data
+							} => {
+								let mut exec_results = exec_results_mutex_clone.lock().await;
+								exec_results.insert(
+									nonce,
+									ProgramCallerExecStatus::CPI {
+										program_id,
+										instruction,
+										account_metas,
+										account_datas,
+										call_depth,
+										return_data,
+										logs: pending_logs.remove(&nonce).unwrap_or_default().messages
+									}
+								);
+								stuff_executed = true;
+							},
+						}
+					}
+				}
+				for program_id in bad_program_ids.iter() {
+					if let Some(comm) = comms.remove(program_id) {
+						comm.stop();
+					}
+					connections_clone.lock().unwrap().remove(program_id);
+					program_generation_clone.fetch_add(1, Ordering::Relaxed);
+				}
+				drop(comms); // unlock it!
+				// Whatever is left of invocations which finished or were given up on, e.g. logs which arrived after the
+				// program's result, or the result of an invocation whose caller stopped waiting
+				{
+					let waiting_nonces = waiting_nonces_clone.lock().unwrap();
+					pending_logs.retain(|nonce, _| waiting_nonces.contains(nonce));
+					pending_panics.retain(|nonce, _| waiting_nonces.contains(nonce));
+					if let Ok(mut exec_results) = exec_results_mutex_clone.try_lock() {
+						exec_results.retain(|nonce, _| waiting_nonces.contains(nonce));
+					}
+				}
+				// Invocations waiting on a disconnected program need to know that they'll never get their result
+				if stuff_executed || !bad_program_ids.is_empty() {
+					exec_notif_sender.send_modify(|val| {
+						(*val, _) = val.overflowing_add(1)
+					})
+				}
+			}
+			Ok(())
+		});
+		
+		let should_stop_clone = should_stop.clone();
+		let comms_mutex_clone = comms_mutex.clone();
+		let heartbeat_handle: task::JoinHandle<eyre::Result<()>> = task::spawn(async move {
+			while !should_stop_clone.load(Ordering::Relaxed) {
+				tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+				for (program_id, comm) in comms_mutex_clone.lock().await.iter_mut() {
+					if let Err(e) = comm.send_msg(BokkenValidatorMessage::Ping).await {
+						eprintln!("Couldn't ping debuggable program {}: {}", display_pubkey(program_id), e);
+					}
+				}
+			}
+			Ok(())
+		});
+
+		Self {
+			native_programs,
+			listener_handle,
+			recieve_handle,
+			heartbeat_handle,
+			should_stop,
+			comms: comms_mutex,
+			connections,
+			known_programs,
+			program_generation,
+			config,
+			exec_results: exec_results_mutex,
+			waiting_nonces,
+			exec_notif,
+			program_stats: Mutex::new(HashMap::new()),
+			log_sender,
+			panics: StdMutex::new(Vec::new())
+		}
+	}
+	/// Receives every message logged by debuggable programs as soon as it arrives, before the invocation which logged
+	/// it has finished
+	pub fn subscribe_logs(&self) -> broadcast::Receiver<BokkenProgramLog> {
+		self.log_sender.subscribe()
+	}
+
+	/// Returns every panic of a debuggable program since this was last called, in the order they happened. A panic in
+	/// a CPI comes before the one of the calling program, if the caller panicked as a result.
+	pub fn take_panics(&self) -> Vec<BokkenProgramPanic> {
+		std::mem::take(&mut *self.panics.lock().unwrap())
+	}
+
+	/// Returns a copy of the usage counters of every program which has been invoked
+	pub async fn program_stats(&self) -> HashMap<Pubkey, BokkenProgramStats> {
+		self.program_stats.lock().await.clone()
+	}
+	/// Replaces the usage counters, used when loading them from a saved ledger
+	pub async fn set_program_stats(&self, program_stats: HashMap<Pubkey, BokkenProgramStats>) {
+		*self.program_stats.lock().await = program_stats;
+	}
+	async fn record_invocation(&self, program_id: Pubkey, call_depth: u8, account_data_bytes: u64, return_code: u64) {
+		self.program_stats.lock().await
+			.entry(program_id)
+			.or_default()
+			.record_invocation(call_depth, account_data_bytes, return_code == 0);
+	}
+	/// The program's name followed by its ID, or just the ID (or its alias) if the program didn't give a name
+	pub fn program_display_name(&self, program_id: &Pubkey) -> String {
+		match self.connections.lock().unwrap().get(program_id).and_then(|connection| connection.name.as_ref()) {
+			Some(name) => format!("{} ({})", name, program_id),
+			None => display_pubkey(program_id)
+		}
+	}
+	/// Returns the status of every connected debuggable program, sorted by program ID
+	pub async fn debuggable_programs(&self) -> Vec<BokkenDebuggableProgramStatus> {
+		let comms = self.comms.lock().await;
+		let connections = self.connections.lock().unwrap();
+		let mut result: Vec<BokkenDebuggableProgramStatus> = connections.iter().map(|(program_id, connection)| {
+			let last_seen = connection.last_message_at.elapsed();
+			BokkenDebuggableProgramStatus {
+				program_id: *program_id,
+				name: connection.name.clone(),
+				transport: "unix",
+				connected_for: connection.connected_at.elapsed(),
+				pending_invokes: connection.pending_invokes,
+				last_seen,
+				healthy: last_seen < HEARTBEAT_TIMEOUT && comms.get(program_id).map_or(false, |comm| !comm.stopped())
+			}
+		}).collect();
+		result.sort_by_key(|status| status.program_id);
+		result
+	}
+	/// Waits until at least `min_count` debuggable programs are connected, including every one in `program_ids`
+	pub async fn wait_for_debuggable_programs(&self, min_count: usize, program_ids: &[Pubkey]) -> Result<(), BokkenError> {
+		loop {
+			if self.should_stop.load(Ordering::Relaxed) {
+				return Err(BokkenError::Stopping);
+			}
+			{
+				let comms = self.comms.lock().await;
+				if comms.len() >= min_count && program_ids.iter().all(|program_id| comms.contains_key(program_id)) {
+					return Ok(());
+				}
+				// comms gets dropped and unlocked
+			}
+			tokio::time::sleep(Duration::from_millis(100)).await;
+		}
+	}
+	/// Whether or not the program caller is able to call the program
+	pub async fn has_program_id(
+		&self,
+		program_id: &Pubkey
+	) -> bool {
+		self.native_programs.contains_key(program_id) ||
+		self.comms.lock().await.contains_key(program_id) ||
+		self.known_programs.lock().unwrap().contains(program_id)
+	}
+	/// The native stub to invoke for the program, unless a debuggable program has taken its place
+	fn native_program(&self, program_id: &Pubkey) -> Option<&StdMutex<Box<dyn NativeProgramStub>>> {
+		if
+			self.config.is_program_override(program_id) &&
+			self.known_programs.lock().unwrap().contains(program_id)
+		{
+			return None;
+		}
+		self.native_programs.get(program_id)
+	}
+	/// Changes whenever a debuggable program connects, reconnects or disconnects, as the same instruction may have a
+	/// different outcome afterwards
+	pub fn program_generation(&self) -> u64 {
+		self.program_generation.load(Ordering::Relaxed)
+	}
+	/// Whether the debuggable program is currently connected
+	pub fn is_connected(&self, program_id: &Pubkey) -> bool {
+		self.connection_id(program_id).is_some()
+	}
+	fn connection_id(&self, program_id: &Pubkey) -> Option<u64> {
+		self.connections.lock().unwrap().get(program_id).map(|connection| connection.id)
+	}
+	/// If the program has disconnected, waits for it to reconnect as configured. Returns the ID of the connection.
+	async fn wait_for_connection(&self, program_id: &Pubkey) -> Result<u64, BokkenError> {
+		let give_up_at = Instant::now() + self.config.reconnect_wait_for(program_id);
+		loop {
+			if let Some(connection_id) = self.connection_id(program_id) {
+				return Ok(connection_id);
+			}
+			if !self.known_programs.lock().unwrap().contains(program_id) {
+				return Err(TransactionError::AccountNotFound.into());
+			}
+			if self.should_stop.load(Ordering::Relaxed) {
+				return Err(BokkenError::Stopping);
+			}
+			if Instant::now() >= give_up_at {
+				return Err(BokkenError::DebuggableProgramDisconnected(*program_id));
+			}
+			tokio::time::sleep(Duration::from_millis(50)).await;
+		}
+	}
+
+	/// Wait until the specified execution ID (nonce) gets a response from the debuggable program
+	/// 
+	/// Fails if the program's connection (`connection_id`) goes away in the mean time, even if the program reconnects,
+	/// as the new connection won't know about the invocation.
+	async fn wait_for_exec_status(
+		&self,
+		nonce: u64,
+		program_id: &Pubkey,
+		connection_id: u64
+	) -> Result<ProgramCallerExecStatus, BokkenError> {
+		let mut exec_notif = self.exec_notif.clone();
+		loop {
+			if self.should_stop.load(Ordering::Relaxed) {
+				return Err(BokkenError::Stopping);
+			}
+			{
+				let mut exec_results = self.exec_results.lock().await;
+				
+				if let Some(status) = exec_results.remove(&nonce) {
+					return Ok(status);
+				}
+				// exec_results gets dropped and unlocked
+			}
+			if self.connection_id(program_id) != Some(connection_id) {
+				return Err(BokkenError::ProgramClosedConnection);
+			}
+			exec_notif.changed().await
+				.map_err(|_|{BokkenError::ProgramClosedConnection})?;
+		}
+	}
+	/// Lets every native program stub know the ledger has advanced to `slot`
+	pub fn notify_new_slot(&self, slot: u64, epoch: u64) {
+		for native_program in self.native_programs.values() {
+			native_program.lock().expect("native program stub to not have panicked").on_new_slot(slot, epoch);
+		}
+	}
+	/// Lets every native program stub know the rent parameters have changed
+	pub fn notify_rent_change(&self, rent: &Rent) {
+		for native_program in self.native_programs.values() {
+			native_program.lock().expect("native program stub to not have panicked").on_rent_change(rent);
+		}
+	}
+	/// Calls the specified program (emulated or debuggable)
+	/// 
+	/// `return_data` is the transaction's return data as it was before this invocation. Like on-chain, there's only
+	/// one return data buffer per transaction, which is cleared at the start of every instruction but not for CPIs. A
+	/// program sees whatever was last set, by itself or a CPI it made, until something else replaces it.
+	/// 
+	/// Returns Exist status, logs, edited state, return data
+	#[async_recursion]
+	pub async fn call_program(
+		&self,
+		program_id: Pubkey,
+		instruction: Vec<u8>,
+		account_metas: Vec<BorshAccountMeta>,
+		account_datas: HashMap<Pubkey, BokkenAccountData>,
+		call_depth: u8,
+		return_data: Option<(Pubkey, Vec<u8>)>
+	) -> Result<ProgramCallResult, BokkenError> {
+		let account_data_bytes: u64 = account_datas.values().map(|account| account.data.len() as u64).sum();
+		if let Some(native_program) = self.native_program(&program_id) {
+			let result: ProgramCallResult = {
+				let mut native_program = native_program.lock().expect("native program stub to not have panicked");
+				let mut account_datas = account_datas;
+				native_program.clear_logs();
+				native_program.clear_return_data();
+				native_program.logs_mut().push(format!("Program {} invoke [{}]", program_id, call_depth));
+				let return_code = match native_program.exec(instruction, account_metas, &mut account_datas) 	{
+					Ok(_) => {
+						native_program.logs_mut().push(format!("Program {} success", program_id));
+						0
+					},
+					Err(err) => {
+						native_program.logs_mut().push(format!("Program {} returned: {}", program_id, err));
+						err.into()
+					},
+				};
+				let return_data = match native_program.return_data() {
+					Some(data) => Some((program_id, data.clone())),
+					None => return_data
+				};
+				(return_code, native_program.logs().clone(), account_datas, return_data)
+				// native_program gets dropped and unlocked, as it can't be held across an await
+			};
+			self.record_invocation(program_id, call_depth, account_data_bytes, result.0).await;
+			return Ok(result);
+		}
+		self.call_debuggable_program(
+			program_id,
+			instruction,
+			account_metas,
+			account_datas,
+			call_depth,
+			return_data,
+			account_data_bytes
+		)
+			.instrument(tracing::info_span!("ipc_round_trip", %program_id, call_depth))
+			.await
+	}
+	async fn call_debuggable_program(
+		&self,
+		program_id: Pubkey,
+		instruction: Vec<u8>,
+		account_metas: Vec<BorshAccountMeta>,
+		account_datas: HashMap<Pubkey, BokkenAccountData>,
+		call_depth: u8,
+		return_data: Option<(Pubkey, Vec<u8>)>,
+		account_data_bytes: u64
+	) -> Result<ProgramCallResult, BokkenError> {
+		let connection_id = self.wait_for_connection(&program_id).await?;
+		let nonce = COMM_NONCE.fetch_add(1, Ordering::Relaxed);
+		println!("Invoking debuggable program {} [{}]", self.program_display_name(&program_id), call_depth);
+		let _pending_invoke_guard = PendingInvokeGuard::new(&self.connections, program_id);
+		let _waiting_nonce_guard = WaitingNonceGuard::new(&self.waiting_nonces, nonce);
+		let mut exec_logs = vec![format!("Program {} invoke [{}]", program_id, call_depth)];
+		{
+			let mut comms = self.comms.lock().await;
+			comms.get_mut(&program_id)
+				.ok_or(BokkenError::DebuggableProgramDisconnected(program_id))?
+				.send_msg(
+					BokkenValidatorMessage::Invoke {
+						nonce,
+						program_id,
+						instruction,
+						account_metas,
+						account_datas,
+						call_depth,
+						return_data
+					}
+				).await?;
+			// comms gets dropped and unlocked
+		}
+		loop {
+			if self.should_stop.load(Ordering::Relaxed) {
+				return Err(BokkenError::Stopping);
+			}
+			match self.wait_for_exec_status(nonce, &program_id, connection_id).await? {
+				ProgramCallerExecStatus::Executed {
+					return_code,
+					account_datas,
+					return_data,
+					logs,
+					panic
+				} => {
+					exec_logs.extend(logs);
+					if let Some((message, backtrace)) = panic {
+						self.panics.lock().unwrap().push(
+							BokkenProgramPanic {
+								program_id,
+								call_depth,
+								message,
+								backtrace
+							}
+						);
+					}
+						println!("TODO: Make sure lamports didn't get magically created or vanish");
+						println!("TODO: Also make sure that the program only edited accounts that it has access to edit");
+						println!("TODO: Maybe this could be done on the child process? (cuz CPI)");
+					if return_code == 0 {
+						exec_logs.push(format!("Program {} success", program_id));
+					}else{
+						exec_logs.push(format!("Program {} returned: {}", program_id, ProgramError::from(return_code)));
+					}
+					self.record_invocation(program_id, call_depth, account_data_bytes, return_code).await;
+					return Ok((return_code, exec_logs, account_datas, return_data));
+				},
+				ProgramCallerExecStatus::CPI {
+					program_id: sub_program_id,
+					instruction: sub_instruction,
+					account_metas: sub_account_metas,
+					account_datas: sub_account_datas,
+					call_depth: sub_call_depth,
+					return_data: sub_return_data,
+					logs
+				} => {
+					// Everything logged before the CPI comes before the CPI's logs
+					exec_logs.extend(logs);
+					let (sub_return_code, sub_logs, new_account_datas, sub_return_data) = self.call_program(
+						sub_program_id,
+						sub_instruction,
+						sub_account_metas,
+						sub_account_datas,
+						sub_call_depth + 1,
+						sub_return_data
+					).await?;
+					exec_logs.extend(sub_logs);
+					let mut comms = self.comms.lock().await;
+					comms.get_mut(&program_id)
+						.ok_or(BokkenError::ProgramClosedConnection)?
+						.send_msg(
+							BokkenValidatorMessage::CrossProgramInvokeResult {
+								nonce,
+								return_code: sub_return_code,
+								account_datas: new_account_datas,
+								return_data: sub_return_data
+							}
+						).await?;
+				},
+			}
+		}
+	}
+	/// Stops reading all connections and drops the unix listener
+	pub fn stop(&self) {
+		self.should_stop.store(true, Ordering::Relaxed);
+	}
+	/// Waits until all connections have been dropped
+	pub async fn wait_until_stopped(self) -> eyre::Result<()> {
+		self.recieve_handle.await??;
+		self.listener_handle.await??;
+		self.heartbeat_handle.await??;
+		Ok(())
+	}
+}
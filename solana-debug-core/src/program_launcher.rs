@@ -0,0 +1,355 @@
+//! Launching debuggable programs along with Bokken, as configured in a TOML file like this:
+//!
+//! ```toml
+//! [[program]]
+//! program-id = "YourAwesomeDebugab1eProgram1111111111111111"
+//! # `--socket-path` and `--program-id` are appended to this
+//! command = ["cargo", "run", "--"]
+//! # Relative to the config file (Default: the config file's directory)
+//! working-dir = "path/to/debuggable/program"
+//! # (Default: 1)
+//! rust-backtrace = "full"
+//! rust-log = "debug"
+//! # How the program's accounts look to clients: debuggable, upgradeable-loader, or loader-v4 (Default: debuggable)
+//! account-layout = "upgradeable-loader"
+//! # Launch the program again if it exits unsuccessfully (Default: false)
+//! restart-on-crash = true
+//! # Give up after restarting it this many times (Default: no limit)
+//! max-restarts = 5
+//! # Limits of the program's process, so a runaway program can't take the machine down with it (Default: no limit)
+//! # Address space in bytes (RLIMIT_AS)
+//! max-memory = 4294967296
+//! # CPU time in seconds, after which the process is killed (RLIMIT_CPU)
+//! max-cpu-seconds = 3600
+//!
+//! [program.env]
+//! SOME_VAR = "some value"
+//! ```
+//!
+//! Launched programs are killed when Bokken exits, even if it's killed without getting to clean up.
+
+use std::{collections::HashMap, io, path::{Path, PathBuf}, process::{ExitStatus, Stdio}, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use bokken_runtime::ipc_comm::is_abstract_socket_path;
+use serde_with::{serde_as, DisplayFromStr};
+use solana_sdk::pubkey::Pubkey;
+use tokio::{fs, process::{Child, Command}, task};
+
+use crate::{debug_ledger::BokkenProgramAccountLayout, error::{BokkenError, BokkenDetailedError}};
+
+/// `RUST_BACKTRACE` is set to this unless configured otherwise, so that panics and `Backtrace::force_capture` show
+/// where they came from
+const DEFAULT_RUST_BACKTRACE: &str = "1";
+/// How long a crashed program is given before it's launched again, so that one which crashes on startup doesn't spin
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// How to launch a single debuggable program
+#[serde_as]
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BokkenProgramLaunchConfig {
+	#[serde_as(as = "DisplayFromStr")]
+	pub program_id: Pubkey,
+	/// The program to run and its arguments, `--socket-path <PATH> --program-id <PUBKEY>` are appended to these
+	pub command: Vec<String>,
+	/// Relative paths are resolved against the directory of the config file
+	#[serde(default)]
+	pub working_dir: Option<PathBuf>,
+	/// Extra environment variables, these take precedence over `rust_backtrace` and `rust_log`
+	#[serde(default)]
+	pub env: HashMap<String, String>,
+	/// Value of `RUST_BACKTRACE` (Default: 1)
+	#[serde(default)]
+	pub rust_backtrace: Option<String>,
+	/// Value of `RUST_LOG`, left as Bokken's own if not specified
+	#[serde(default)]
+	pub rust_log: Option<String>,
+	/// How the program's accounts look to clients, see `BokkenLedger::set_program_account_layout`
+	#[serde(default)]
+	pub account_layout: BokkenProgramAccountLayout,
+	/// Launch the program again if it exits unsuccessfully, including being killed by a signal
+	#[serde(default)]
+	pub restart_on_crash: bool,
+	/// Most times the program is restarted, no limit if not specified
+	#[serde(default)]
+	pub max_restarts: Option<u64>,
+	/// Most bytes of address space the program's process can use (`RLIMIT_AS`), no limit if not specified
+	#[serde(default)]
+	pub max_memory: Option<u64>,
+	/// Most seconds of CPU time the program's process can use before it's killed (`RLIMIT_CPU`), no limit if not
+	/// specified
+	#[serde(default)]
+	pub max_cpu_seconds: Option<u64>
+}
+
+/// Every debuggable program to launch along with Bokken
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct BokkenProgramsConfig {
+	#[serde(default, rename = "program")]
+	pub programs: Vec<BokkenProgramLaunchConfig>
+}
+impl BokkenProgramsConfig {
+	/// Reads the TOML config file at `path`, relative working directories are resolved against its directory
+	pub async fn read(path: &Path) -> Result<Self, BokkenDetailedError> {
+		let mut config: Self = toml::from_str(&fs::read_to_string(path).await?)
+			.map_err(|e| BokkenError::InvalidProgramsConfig(e.to_string()))?;
+		let config_dir = path.parent().unwrap_or(Path::new(""));
+		for program in config.programs.iter_mut() {
+			if program.command.is_empty() {
+				return Err(BokkenError::InvalidProgramsConfig(
+					format!("The command of program {} is empty", program.program_id)
+				).into());
+			}
+			program.working_dir = Some(
+				config_dir.join(program.working_dir.as_deref().unwrap_or(Path::new("")))
+			);
+		}
+		Ok(config)
+	}
+}
+
+/// Applies the configured resource limits to the program's process, and has it killed when Bokken exits. Dropping the
+/// `Child` kills it too, but that doesn't happen if Bokken is killed with SIGKILL.
+fn limit_process(command: &mut Command, config: &BokkenProgramLaunchConfig) {
+	let max_memory = config.max_memory;
+	let max_cpu_seconds = config.max_cpu_seconds;
+	#[cfg(target_os = "linux")]
+	let parent_pid = std::process::id() as libc::pid_t;
+	// Runs in the forked process before the program is executed, where only async-signal-safe functions can be used
+	let limit = move || -> io::Result<()> {
+		#[cfg(target_os = "linux")]
+		unsafe {
+			// Sent once the thread which spawned the process exits. That's one of the tokio runtime's threads, which
+			// live as long as Bokken does.
+			if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) == -1 {
+				return Err(io::Error::last_os_error());
+			}
+			// Bokken may have exited before the signal was set up
+			if libc::getppid() != parent_pid {
+				return Err(io::Error::from_raw_os_error(libc::ESRCH));
+			}
+		}
+		if let Some(max_memory) = max_memory {
+			let rlimit = libc::rlimit { rlim_cur: max_memory as libc::rlim_t, rlim_max: max_memory as libc::rlim_t };
+			if unsafe { libc::setrlimit(libc::RLIMIT_AS, &rlimit) } == -1 {
+				return Err(io::Error::last_os_error());
+			}
+		}
+		if let Some(max_cpu_seconds) = max_cpu_seconds {
+			let rlimit = libc::rlimit {
+				rlim_cur: max_cpu_seconds as libc::rlim_t,
+				rlim_max: max_cpu_seconds as libc::rlim_t
+			};
+			if unsafe { libc::setrlimit(libc::RLIMIT_CPU, &rlimit) } == -1 {
+				return Err(io::Error::last_os_error());
+			}
+		}
+		Ok(())
+	};
+	// `limit` only calls async-signal-safe functions, and doesn't allocate
+	unsafe {
+		command.pre_exec(limit);
+	}
+}
+
+/// Starts the debuggable program, which connects to Bokken through `socket_path`. The program is killed when the
+/// returned `Child` is dropped or Bokken exits, and is limited as configured. See `BokkenProgramSupervisor` for keeping
+/// track of it after it's started.
+pub fn launch_program(config: &BokkenProgramLaunchConfig, socket_path: &Path) -> Result<Child, BokkenDetailedError> {
+	let (program, args) = config.command.split_first().ok_or(
+		BokkenError::InvalidProgramsConfig(format!("The command of program {} is empty", config.program_id))
+	)?;
+	// The program may be running in a different directory
+	let socket_path = if socket_path.is_relative() && !is_abstract_socket_path(socket_path) {
+		std::env::current_dir()?.join(socket_path)
+	}else{
+		socket_path.to_path_buf()
+	};
+	let mut command = Command::new(program);
+	command.args(args)
+		.arg("--socket-path")
+		.arg(&socket_path)
+		.arg("--program-id")
+		.arg(config.program_id.to_string())
+		.env("RUST_BACKTRACE", config.rust_backtrace.as_deref().unwrap_or(DEFAULT_RUST_BACKTRACE))
+		.stdin(Stdio::null())
+		.kill_on_drop(true);
+	if let Some(rust_log) = &config.rust_log {
+		command.env("RUST_LOG", rust_log);
+	}
+	command.envs(config.env.iter());
+	if let Some(working_dir) = config.working_dir.as_ref().filter(|dir| !dir.as_os_str().is_empty()) {
+		command.current_dir(working_dir);
+	}
+	limit_process(&mut command, config);
+	let child = command.spawn()?;
+	println!("Launched debuggable program {} ({})", config.program_id, config.command.join(" "));
+	Ok(child)
+}
+
+
+/// What a launched program's process is currently doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BokkenProgramProcessState {
+	/// Running, but not connected to Bokken (yet)
+	Starting,
+	/// Running and connected to Bokken
+	Ready,
+	/// Exited unsuccessfully or was killed, and may be waiting to be restarted
+	Crashed,
+	/// Exited successfully, it won't be restarted
+	Exited
+}
+
+/// The process of a program launched by Bokken, see `BokkenProgramSupervisor::statuses`
+#[derive(Debug, Clone)]
+pub struct BokkenProgramProcessStatus {
+	pub program_id: Pubkey,
+	pub pid: Option<u32>,
+	pub state: BokkenProgramProcessState,
+	pub restart_count: u64,
+	/// None if it hasn't exited yet, or was killed by a signal
+	pub last_exit_code: Option<i32>,
+	/// How long the current process has been running, None if it isn't
+	pub running_for: Option<Duration>
+}
+
+#[derive(Debug, Default)]
+struct LaunchedProcess {
+	pid: Option<u32>,
+	/// Set while the process is running
+	started_at: Option<Instant>,
+	last_exit: Option<ExitStatus>,
+	restart_count: u64
+}
+
+/// Keeps track of launched programs, restarting them if they're configured to. Every program is killed when this is
+/// dropped.
+#[derive(Debug)]
+pub struct BokkenProgramSupervisor {
+	processes: Arc<Mutex<HashMap<Pubkey, LaunchedProcess>>>,
+	handles: Vec<task::JoinHandle<()>>
+}
+impl BokkenProgramSupervisor {
+	/// Starts every configured program, failing if any of them couldn't be started
+	pub fn launch(config: &BokkenProgramsConfig, socket_path: &Path) -> Result<Self, BokkenDetailedError> {
+		let processes = Arc::new(Mutex::new(HashMap::new()));
+		let mut handles = Vec::with_capacity(config.programs.len());
+		for program in config.programs.iter() {
+			let child = launch_program(program, socket_path)?;
+			processes.lock().unwrap().insert(
+				program.program_id,
+				LaunchedProcess {
+					pid: child.id(),
+					started_at: Some(Instant::now()),
+					..Default::default()
+				}
+			);
+			handles.push(task::spawn(
+				supervise_program(child, program.clone(), socket_path.to_path_buf(), processes.clone())
+			));
+		}
+		Ok(
+			Self {
+				processes,
+				handles
+			}
+		)
+	}
+	/// Status of every launched program, sorted by program ID. `is_connected` tells whether a running program is ready.
+	pub fn statuses(&self, is_connected: impl Fn(&Pubkey) -> bool) -> Vec<BokkenProgramProcessStatus> {
+		let processes = self.processes.lock().unwrap();
+		let mut result: Vec<BokkenProgramProcessStatus> = processes.iter().map(|(program_id, process)| {
+			let state = match (process.started_at, process.last_exit) {
+				(Some(_), _) if is_connected(program_id) => BokkenProgramProcessState::Ready,
+				(Some(_), _) => BokkenProgramProcessState::Starting,
+				(None, Some(exit)) if exit.success() => BokkenProgramProcessState::Exited,
+				(None, _) => BokkenProgramProcessState::Crashed
+			};
+			BokkenProgramProcessStatus {
+				program_id: *program_id,
+				pid: process.pid.filter(|_| process.started_at.is_some()),
+				state,
+				restart_count: process.restart_count,
+				last_exit_code: process.last_exit.and_then(|exit| exit.code()),
+				running_for: process.started_at.map(|started_at| started_at.elapsed())
+			}
+		}).collect();
+		result.sort_by_key(|status| status.program_id);
+		result
+	}
+}
+impl Drop for BokkenProgramSupervisor {
+	fn drop(&mut self) {
+		// Aborting the tasks drops their `Child`, which kills the programs
+		for handle in self.handles.iter() {
+			handle.abort();
+		}
+	}
+}
+
+/// Logs a launched program's process changing state in a consistent format, so CI logs can be searched for them
+fn log_process_event(program_id: &Pubkey, event: &str, process: &LaunchedProcess) {
+	let message = format!(
+		"[Program Process]: program: {}, event: {}, pid: {}, restarts: {}, last_exit: {}",
+		program_id,
+		event,
+		process.pid.map_or("none".to_string(), |pid| pid.to_string()),
+		process.restart_count,
+		process.last_exit.map_or("none".to_string(), |exit| exit.to_string())
+	);
+	if event == "crashed" {
+		eprintln!("{}", message);
+	}else{
+		println!("{}", message);
+	}
+}
+
+/// Waits for the program to exit, launching it again for as long as its config says to
+async fn supervise_program(
+	mut child: Child,
+	config: BokkenProgramLaunchConfig,
+	socket_path: PathBuf,
+	processes: Arc<Mutex<HashMap<Pubkey, LaunchedProcess>>>
+) {
+	loop {
+		let exit = match child.wait().await {
+			Ok(exit) => exit,
+			Err(e) => {
+				eprintln!("Couldn't wait for debuggable program {} to exit: {}", config.program_id, e);
+				return;
+			}
+		};
+		let restart_count = {
+			let mut processes = processes.lock().unwrap();
+			let process = processes.entry(config.program_id).or_default();
+			process.started_at = None;
+			process.last_exit = Some(exit);
+			log_process_event(&config.program_id, if exit.success() { "exited" } else { "crashed" }, process);
+			process.restart_count
+		};
+		if
+			exit.success() ||
+			!config.restart_on_crash ||
+			config.max_restarts.map_or(false, |max_restarts| restart_count >= max_restarts)
+		{
+			return;
+		}
+		tokio::time::sleep(RESTART_DELAY).await;
+		child = match launch_program(&config, &socket_path) {
+			Ok(child) => child,
+			Err(e) => {
+				eprintln!("Couldn't restart debuggable program {}: {}", config.program_id, e);
+				return;
+			}
+		};
+		{
+			let mut processes = processes.lock().unwrap();
+			let process = processes.entry(config.program_id).or_default();
+			process.pid = child.id();
+			process.started_at = Some(Instant::now());
+			process.restart_count += 1;
+			log_process_event(&config.program_id, "restarted", process);
+		}
+	}
+}
@@ -0,0 +1,58 @@
+use std::{collections::HashMap, io, path::Path};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_sdk::pubkey::Pubkey;
+use tokio::fs;
+
+use crate::error::BokkenDetailedError;
+
+/// Usage counters for a single program. Every invocation is counted, including ones from simulated transactions and
+/// ones which were part of a transaction which failed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BokkenProgramStats {
+	pub invocations: u64,
+	/// Invocations which returned a non-zero exit code
+	pub failures: u64,
+	/// Sum of how deep in CPIs each invocation was, top-level instructions having a depth of 0
+	pub total_cpi_depth: u64,
+	/// Sum of the data lengths of every account passed to the program
+	pub account_data_bytes: u64
+}
+impl BokkenProgramStats {
+	pub fn record_invocation(&mut self, call_depth: u8, account_data_bytes: u64, success: bool) {
+		self.invocations += 1;
+		if !success {
+			self.failures += 1;
+		}
+		self.total_cpi_depth += call_depth.saturating_sub(1) as u64;
+		self.account_data_bytes += account_data_bytes;
+	}
+	pub fn failure_rate(&self) -> f64 {
+		if self.invocations == 0 {
+			return 0.0;
+		}
+		self.failures as f64 / self.invocations as f64
+	}
+	pub fn average_cpi_depth(&self) -> f64 {
+		if self.invocations == 0 {
+			return 0.0;
+		}
+		self.total_cpi_depth as f64 / self.invocations as f64
+	}
+}
+
+/// Returns empty stats if the file doesn't exist yet
+pub async fn read_program_stats_file(path: &Path) -> Result<HashMap<Pubkey, BokkenProgramStats>, BokkenDetailedError> {
+	match fs::read(path).await {
+		Ok(bytes) => Ok(HashMap::try_from_slice(&bytes)?),
+		Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+		Err(e) => Err(e.into())
+	}
+}
+pub async fn write_program_stats_file(
+	path: &Path,
+	stats: &HashMap<Pubkey, BokkenProgramStats>
+) -> Result<(), BokkenDetailedError> {
+	fs::write(path, stats.try_to_vec()?).await?;
+	Ok(())
+}
@@ -0,0 +1,1203 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use jsonrpsee::{proc_macros::rpc, core::async_trait, core::RpcResult};
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::feature_set::FEATURE_NAMES;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use solana_sdk::message::Message;
+use solana_sdk::hash::Hash;
+
+use crate::debug_ledger::{BokkenLedger, BokkenRentConfig, BokkenLedgerInstruction, BokkenFeatureSet, BokkenTimeModel, BokkenCompactionProgress, BokkenProgramAccountLayout};
+use crate::rpc_endpoint::{simulate_instructions, simulation_response, encode_account_data};
+use crate::error::BokkenError;
+use crate::rpc_metrics::BokkenRpcMetrics;
+use crate::scheduler::{BokkenScheduler, BokkenScheduledJob};
+use crate::keystore::BokkenKeystore;
+use crate::tx_lint::lint_transaction;
+use crate::program_launcher::{BokkenProgramProcessStatus, BokkenProgramProcessState};
+use crate::sig_verify::verify_signers;
+use crate::account_diff::get_transaction_account_diff;
+use crate::fault_injection::BokkenFaultInjector;
+use crate::account_schema::{BokkenAccountSchema, BokkenAccountSchemaFormat};
+use crate::dependency_graph::get_slot_dependency_graph;
+use crate::native_program_stubs::address_lookup_table::deserialize_lookup_table;
+use crate::tx_sanitize::{decode_transaction, sanitize_transaction};
+use crate::account_aliases::{resolve_pubkey, set_account_alias, remove_account_alias, account_aliases};
+use crate::account_surgery::{set_account_owner, patch_account_data, set_token_balance, create_program_owned_account};
+use crate::rpc_endpoint_structs::{RpcGetMetricsResponse, RpcForkSendTransactionResponse, RpcSendTransactionRequest, RpcBinaryEncoding, RpcGetAccountInfoRequest, RpcGetAccountInfoResponse, RpcResponseContext, RpcGetAccountInfoResponseValue, RPCBinaryEncodedString, RpcScheduleTransactionRequest, RpcScheduledTransactionInfo, RpcCreateFundedKeypairResponse, RpcLintTransactionRequest, RpcLintTransactionResponse, RpcGetTransactionAccountDiffRequest, RpcGetTransactionAccountDiffResponse, RpcAccountDiff, RpcAccountDataChange, RpcSearchLogsRequest, RpcSearchLogsResponse, RpcLogSearchResult, RpcProgramStats, RpcFaultInjectionConfig, RpcRentConfig, RpcDebuggableProgram, RpcProgramProcess, RpcProgramProcessState, RpcGetLedgerInfoResponse, RpcCompactAccountsResponse, RpcSimulateTransactionUncheckedRequest, RpcSimulateTransactionResponse, RpcPatchAccountDataRequest, RpcTransactionCrashReport, RpcProgramPanic, RpcFeatureActivation, RpcGetFeatureActivationResponse, RpcTimeModelConfig, RpcTimeModelType, RpcCreateProgramOwnedAccountRequest, RpcCreateProgramOwnedAccountResponse, RpcProgramAddress, RpcBuildAndSendTransactionRequest, RpcSignTransactionRequest, RpcSignTransactionResponse, RpcAccountSchemaFormat, RpcRegisterAccountSchemaRequest, RpcAccountSchemaInfo, RpcCompactionStatus, RpcProgramAccountLayout, RpcSlotDependencyGraph, RpcDependencyGraphNode, RpcDependencyGraphEdge, RpcAccountAlias};
+
+/// Maximum amount of transactions `bokken_searchLogs` returns at once
+const MAX_SEARCH_LOGS_LIMIT: usize = 1000;
+
+/// Bokken-specific methods which don't exist on a real validator. These are all prefixed with `bokken_`.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "bokken"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "bokken"))]
+pub trait BokkenAdminRpc {
+	#[method(name = "getMetrics")]
+	fn get_metrics(&self) -> RpcResult<RpcGetMetricsResponse>;
+	/// Returns the ID of a new fork of the current ledger state
+	#[method(name = "createFork")]
+	async fn create_fork(&self) -> RpcResult<u64>;
+	#[method(name = "discardFork")]
+	async fn discard_fork(&self, fork_id: u64) -> RpcResult<bool>;
+	/// Writes the fork's changes to the ledger as a new slot and removes the fork. Fails if a transaction was committed
+	/// since the fork was created. Returns the amount of accounts written.
+	#[method(name = "commitFork")]
+	async fn commit_fork(&self, fork_id: u64) -> RpcResult<usize>;
+	#[method(name = "forkGetAccountInfo")]
+	async fn fork_get_account_info(
+		&self,
+		fork_id: u64,
+		pubkey: String,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> RpcResult<RpcGetAccountInfoResponse>;
+	#[method(name = "forkSendTransaction")]
+	async fn fork_send_transaction(
+		&self,
+		fork_id: u64,
+		tx_data: String,
+		config: Option<RpcSendTransactionRequest>
+	) -> RpcResult<RpcForkSendTransactionResponse>;
+	/// Submits the instructions as a transaction every `everySlots` slots. Returns the ID of the scheduled transaction.
+	#[method(name = "scheduleTransaction")]
+	async fn schedule_transaction(&self, config: RpcScheduleTransactionRequest) -> RpcResult<u64>;
+	/// Returns false if the scheduled transaction didn't exist
+	#[method(name = "unscheduleTransaction")]
+	async fn unschedule_transaction(&self, id: u64) -> RpcResult<bool>;
+	#[method(name = "getScheduledTransactions")]
+	async fn get_scheduled_transactions(&self) -> RpcResult<Vec<RpcScheduledTransactionInfo>>;
+	/// Creates a new keypair funded with the specified amount of lamports. A copy is kept in the ledger's keystore.
+	#[method(name = "createFundedKeypair")]
+	async fn create_funded_keypair(&self, lamports: u64) -> RpcResult<RpcCreateFundedKeypairResponse>;
+	/// Assembles a transaction out of the instructions, signs it with keypairs from the ledger's keystore, and sends
+	/// it. Returns the transaction's signature. Saves having to use an SDK in simple scripts.
+	#[method(name = "buildAndSendTransaction")]
+	async fn build_and_send_transaction(&self, config: RpcBuildAndSendTransactionRequest) -> RpcResult<String>;
+	/// Adds signatures to a partially signed transaction using keypairs from the ledger's keystore, and returns it
+	/// without sending it. Signatures already on the transaction are kept, as its blockhash isn't changed.
+	#[method(name = "signTransaction")]
+	async fn sign_transaction(
+		&self,
+		tx_data: String,
+		config: Option<RpcSignTransactionRequest>
+	) -> RpcResult<RpcSignTransactionResponse>;
+	/// Checks the transaction for common construction mistakes without committing it
+	#[method(name = "lintTransaction")]
+	async fn lint_transaction(
+		&self,
+		tx_data: String,
+		config: Option<RpcLintTransactionRequest>
+	) -> RpcResult<RpcLintTransactionResponse>;
+	/// Returns how each writable account of a committed transaction changed. Accounts edited through admin methods
+	/// after the transaction, but before the next one was committed, include those edits in their post state.
+	#[method(name = "getTransactionAccountDiff")]
+	async fn get_transaction_account_diff(
+		&self,
+		signature: String,
+		config: Option<RpcGetTransactionAccountDiffRequest>
+	) -> RpcResult<RpcGetTransactionAccountDiffResponse>;
+	/// Returns where debuggable programs panicked during a failed transaction, null if the transaction didn't fail
+	/// because of a panic
+	#[method(name = "getTransactionCrashReport")]
+	async fn get_transaction_crash_report(&self, signature: String) -> RpcResult<Option<RpcTransactionCrashReport>>;
+	/// Returns every log of a committed transaction, including the ones past the point where its logs were truncated
+	/// like on a real validator, null if the transaction wasn't committed
+	#[method(name = "getTransactionLogs")]
+	async fn get_transaction_logs(&self, signature: String) -> RpcResult<Option<Vec<String>>>;
+	/// Searches the logs of committed transactions by program, slot range, and text
+	#[method(name = "searchLogs")]
+	async fn search_logs(&self, config: Option<RpcSearchLogsRequest>) -> RpcResult<RpcSearchLogsResponse>;
+	/// Returns the usage counters of every invoked program, or only the ones specified, sorted by invocation count
+	#[method(name = "getProgramStats")]
+	async fn get_program_stats(&self, program_ids: Option<Vec<String>>) -> RpcResult<Vec<RpcProgramStats>>;
+	/// Makes this endpoint randomly misbehave. `bokken_` methods are never affected.
+	#[method(name = "setFaultInjection")]
+	fn set_fault_injection(&self, config: RpcFaultInjectionConfig) -> RpcResult<RpcFaultInjectionConfig>;
+	#[method(name = "getFaultInjection")]
+	fn get_fault_injection(&self) -> RpcResult<RpcFaultInjectionConfig>;
+	/// Changes the rent parameters used from now on. Existing accounts aren't touched.
+	#[method(name = "setRent")]
+	async fn set_rent(&self, config: RpcRentConfig) -> RpcResult<RpcRentConfig>;
+	#[method(name = "getRent")]
+	fn get_rent(&self) -> RpcResult<RpcRentConfig>;
+	/// Changes where the clock sysvar's and new blocks' timestamps come from. With `manual`, this is also how the time
+	/// is changed.
+	#[method(name = "setTimeModel")]
+	async fn set_time_model(&self, config: RpcTimeModelConfig) -> RpcResult<RpcTimeModelConfig>;
+	#[method(name = "getTimeModel")]
+	async fn get_time_model(&self) -> RpcResult<RpcTimeModelConfig>;
+	/// Lists whether each of the specified features is active, or every known or activated feature if none are
+	/// specified. Feature accounts read the same way.
+	#[method(name = "getFeatureActivation")]
+	fn get_feature_activation(&self, feature_ids: Option<Vec<String>>) -> RpcResult<RpcGetFeatureActivationResponse>;
+	/// Reports the feature as active from `slot` (Default: the current slot) onwards, or as inactive. Nothing is
+	/// actually feature-gated, this only changes what clients see.
+	#[method(name = "setFeatureActivation")]
+	async fn set_feature_activation(
+		&self,
+		feature_id: String,
+		active: bool,
+		slot: Option<u64>
+	) -> RpcResult<RpcFeatureActivation>;
+	/// Changes the `feature-set` reported by getVersion, null reverts to the one of the solana-sdk Bokken was built with
+	#[method(name = "setFeatureSet")]
+	async fn set_feature_set(&self, feature_set: Option<u32>) -> RpcResult<u32>;
+	/// Lists the debuggable programs which are currently attached, sorted by program ID
+	#[method(name = "listPrograms")]
+	async fn list_programs(&self) -> RpcResult<Vec<RpcDebuggableProgram>>;
+	/// Changes whether the debuggable program's accounts look like they belong to Bokken's own loader, the upgradeable
+	/// BPF loader, or loader-v4, for testing client-side code which deserializes program accounts. Lasts until Bokken
+	/// is restarted.
+	#[method(name = "setProgramAccountLayout")]
+	fn set_program_account_layout(&self, program_id: String, layout: RpcProgramAccountLayout) -> RpcResult<RpcProgramAccountLayout>;
+	/// Account counts, block count, and disk usage of the ledger, useful for spotting runaway account growth
+	#[method(name = "getLedgerInfo")]
+	async fn get_ledger_info(&self) -> RpcResult<RpcGetLedgerInfoResponse>;
+	/// Simulates the transaction without verifying its signatures, treating `signers` as signers on top of the ones
+	/// the message specifies. Useful for seeing what would happen if someone else signed a transaction.
+	#[method(name = "simulateTransactionUnchecked")]
+	async fn simulate_transaction_unchecked(
+		&self,
+		tx_data: String,
+		config: Option<RpcSimulateTransactionUncheckedRequest>
+	) -> RpcResult<RpcSimulateTransactionResponse>;
+	/// Removes deleted (0 lamport) accounts and their history from disk.
+	/// 
+	/// This can't be undone, and the removed accounts will look like they never existed at any slot. Account diffs of
+	/// earlier transactions, `bokken ledger diff`, and reads at earlier slots will no longer show them.
+	#[method(name = "compactAccounts")]
+	async fn compact_accounts(&self) -> RpcResult<RpcCompactAccountsResponse>;
+	/// Same as `compactAccounts`, but returns right away instead of waiting for it to finish. Progress can be checked
+	/// with `getCompactionStatus`.
+	#[method(name = "startAccountCompaction")]
+	async fn start_account_compaction(&self) -> RpcResult<RpcCompactionStatus>;
+	/// Progress of the running or latest account compaction
+	#[method(name = "getCompactionStatus")]
+	fn get_compaction_status(&self) -> RpcResult<RpcCompactionStatus>;
+	/// Transactions committed from `start_slot` to `end_slot` (inclusive), linked to the latest earlier transaction
+	/// which wrote to any of the same accounts. Also returned as graphviz DOT, for seeing where a workload contends.
+	#[method(name = "getSlotDependencyGraph")]
+	async fn get_slot_dependency_graph(&self, start_slot: u64, end_slot: u64) -> RpcResult<RpcSlotDependencyGraph>;
+	/// Changes the owner of an existing account. Returns the account's new state.
+	#[method(name = "setAccountOwner")]
+	async fn set_account_owner(
+		&self,
+		pubkey: String,
+		owner: String,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> RpcResult<RpcGetAccountInfoResponse>;
+	/// Overwrites part of an existing account's data, without resizing it. Returns the account's new state.
+	#[method(name = "patchAccountData")]
+	async fn patch_account_data(
+		&self,
+		pubkey: String,
+		offset: usize,
+		bytes: String,
+		config: Option<RpcPatchAccountDataRequest>
+	) -> RpcResult<RpcGetAccountInfoResponse>;
+	/// Sets the amount held by an SPL token account, adjusting its mint's supply to match. Returns the token account's
+	/// new state.
+	#[method(name = "setTokenBalance")]
+	async fn set_token_balance(
+		&self,
+		token_account: String,
+		amount: u64,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> RpcResult<RpcGetAccountInfoResponse>;
+	/// Creates a rent-exempt account with zeroed data owned by the program, optionally at the program address derived
+	/// from the specified seeds. Saves sending system program transactions when setting up test fixtures.
+	#[method(name = "createProgramOwnedAccount")]
+	async fn create_program_owned_account(
+		&self,
+		program_id: String,
+		space: usize,
+		config: Option<RpcCreateProgramOwnedAccountRequest>
+	) -> RpcResult<RpcCreateProgramOwnedAccountResponse>;
+	/// Commits empty blocks until the lookup table's latest addresses can be used or, if it's been deactivated, until it
+	/// can be closed. Slots only advance when blocks are committed, so waiting for this wouldn't work. Returns the slot
+	/// which was reached.
+	#[method(name = "warmUpLookupTable")]
+	async fn warm_up_lookup_table(&self, address: String) -> RpcResult<u64>;
+	/// Derives the program address and bump seed from the base64-encoded seeds, same as `find_program_address`
+	#[method(name = "findProgramAddress")]
+	fn find_program_address(&self, seeds: Vec<String>, program_id: String) -> RpcResult<RpcProgramAddress>;
+	/// Derives the program address from the base64-encoded seeds, which must include the bump seed, same as
+	/// `create_program_address`
+	#[method(name = "createProgramAddress")]
+	fn create_program_address(&self, seeds: Vec<String>, program_id: String) -> RpcResult<String>;
+	/// Registers the schema of the program's accounts, written like an Anchor IDL, replacing any it already had.
+	/// `getAccountInfo` decodes the program's accounts with it when `jsonParsed` encoding is requested. Anchor schemas
+	/// are also served from the program's IDL account, unless one was actually created on the ledger.
+	#[method(name = "registerAccountSchema")]
+	async fn register_account_schema(
+		&self,
+		program_id: String,
+		schema: serde_json::Value,
+		config: Option<RpcRegisterAccountSchemaRequest>
+	) -> RpcResult<RpcAccountSchemaInfo>;
+	/// Returns whether the program had a schema
+	#[method(name = "unregisterAccountSchema")]
+	async fn unregister_account_schema(&self, program_id: String) -> RpcResult<bool>;
+	/// Lists the programs with a registered account schema, sorted by program ID
+	#[method(name = "listAccountSchemas")]
+	fn list_account_schemas(&self) -> RpcResult<Vec<RpcAccountSchemaInfo>>;
+	/// Gives the account a human-readable alias, which Bokken's output and error messages show it as, and which every
+	/// `bokken_` method accepts in place of a pubkey. Returns the pubkey the alias referred to before, if any.
+	#[method(name = "setAccountAlias")]
+	fn set_account_alias(&self, name: String, pubkey: String) -> RpcResult<Option<String>>;
+	/// Removes the alias, returning the pubkey it referred to
+	#[method(name = "removeAccountAlias")]
+	fn remove_account_alias(&self, name: String) -> RpcResult<Option<String>>;
+	/// Lists every account alias, sorted by alias
+	#[method(name = "getAccountAliases")]
+	fn get_account_aliases(&self) -> RpcResult<Vec<RpcAccountAlias>>;
+}
+
+fn account_schema_info(program_id: &Pubkey, schema: &BokkenAccountSchema) -> RpcAccountSchemaInfo {
+	RpcAccountSchemaInfo {
+		program_id: program_id.to_string(),
+		program_name: schema.program_name().map(str::to_string),
+		format: match schema.format() {
+			BokkenAccountSchemaFormat::Anchor => RpcAccountSchemaFormat::Anchor,
+			BokkenAccountSchemaFormat::Borsh => RpcAccountSchemaFormat::Borsh
+		},
+		account_types: schema.account_type_names()
+	}
+}
+
+fn decode_seeds(seeds: &[String], encoding: RpcBinaryEncoding) -> Result<Vec<Vec<u8>>, BokkenError> {
+	let mut decoded_seeds = Vec::with_capacity(seeds.len());
+	for seed in seeds.iter() {
+		decoded_seeds.push(encoding.decode_bytes(seed)?);
+	}
+	Ok(decoded_seeds)
+}
+
+fn feature_activation(feature_set: &BokkenFeatureSet, feature_id: &Pubkey) -> RpcFeatureActivation {
+	RpcFeatureActivation {
+		id: feature_id.to_string(),
+		description: FEATURE_NAMES.get(feature_id).map(|description| description.to_string()),
+		activated_at: feature_set.activation_slot(feature_id)
+	}
+}
+
+fn program_account_layout(layout: BokkenProgramAccountLayout) -> RpcProgramAccountLayout {
+	match layout {
+		BokkenProgramAccountLayout::Debuggable => RpcProgramAccountLayout::Debuggable,
+		BokkenProgramAccountLayout::UpgradeableLoader => RpcProgramAccountLayout::UpgradeableLoader,
+		BokkenProgramAccountLayout::LoaderV4 => RpcProgramAccountLayout::LoaderV4
+	}
+}
+
+fn program_process(status: BokkenProgramProcessStatus) -> RpcProgramProcess {
+	RpcProgramProcess {
+		program_id: status.program_id.to_string(),
+		pid: status.pid,
+		state: match status.state {
+			BokkenProgramProcessState::Starting => RpcProgramProcessState::Starting,
+			BokkenProgramProcessState::Ready => RpcProgramProcessState::Ready,
+			BokkenProgramProcessState::Crashed => RpcProgramProcessState::Crashed,
+			BokkenProgramProcessState::Exited => RpcProgramProcessState::Exited
+		},
+		restart_count: status.restart_count,
+		last_exit_code: status.last_exit_code,
+		uptime_ms: status.running_for.map(|running_for| running_for.as_millis() as u64)
+	}
+}
+
+fn compaction_status(progress: BokkenCompactionProgress) -> RpcCompactionStatus {
+	RpcCompactionStatus {
+		running: progress.running,
+		percent_done: progress.percent_done(),
+		total_accounts: progress.total_accounts,
+		processed_accounts: progress.processed_accounts,
+		removed_accounts: progress.removed_accounts,
+		freed_bytes: progress.freed_bytes,
+		started_at: progress.started_at,
+		finished_at: progress.finished_at,
+		error: progress.error
+	}
+}
+
+pub struct BokkenAdminRpcImpl {
+	metrics: Arc<BokkenRpcMetrics>,
+	ledger: Arc<BokkenLedger>,
+	scheduler: Arc<BokkenScheduler>,
+	fault_injector: Arc<BokkenFaultInjector>
+}
+impl BokkenAdminRpcImpl {
+	pub fn new(
+		metrics: Arc<BokkenRpcMetrics>,
+		ledger: Arc<BokkenLedger>,
+		scheduler: Arc<BokkenScheduler>,
+		fault_injector: Arc<BokkenFaultInjector>
+	) -> Self {
+		Self {
+			metrics,
+			ledger,
+			scheduler,
+			fault_injector
+		}
+	}
+	async fn _lint_transaction(
+		&self,
+		tx_data: String,
+		config: Option<RpcLintTransactionRequest>
+	) -> Result<RpcLintTransactionResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let tx: Transaction = bincode::deserialize(
+			&config.encoding.unwrap_or(RpcBinaryEncoding::Base58).decode_bytes(&tx_data)?
+		)?;
+		let slot = self.ledger.slot().await;
+		Ok(
+			RpcLintTransactionResponse {
+				context: RpcResponseContext { slot },
+				issues: lint_transaction(&self.ledger, &tx, !config.skip_simulation).await?
+			}
+		)
+	}
+	async fn _get_program_stats(&self, program_ids: Option<Vec<String>>) -> Result<Vec<RpcProgramStats>, BokkenError> {
+		let program_ids = program_ids.map(|program_ids| {
+			program_ids.iter().map(|program_id| resolve_pubkey(program_id)).collect::<Result<HashSet<_>, _>>()
+		}).transpose()?;
+		let mut result: Vec<RpcProgramStats> = self.ledger.program_stats().await.into_iter()
+			.filter(|(program_id, _)| {
+				program_ids.as_ref().map_or(true, |program_ids| program_ids.contains(program_id))
+			})
+			.map(|(program_id, stats)| {
+				RpcProgramStats {
+					program_id: program_id.to_string(),
+					invocations: stats.invocations,
+					failures: stats.failures,
+					failure_rate: stats.failure_rate(),
+					average_cpi_depth: stats.average_cpi_depth(),
+					account_data_bytes: stats.account_data_bytes
+				}
+			})
+			.collect();
+		result.sort_by(|a, b| b.invocations.cmp(&a.invocations));
+		Ok(result)
+	}
+	async fn _simulate_transaction_unchecked(
+		&self,
+		tx_data: String,
+		config: Option<RpcSimulateTransactionUncheckedRequest>
+	) -> Result<RpcSimulateTransactionResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let signers = config.signers.iter()
+			.map(|pubkey| resolve_pubkey(pubkey))
+			.collect::<Result<HashSet<_>, _>>()?;
+		let account_addresses = config.accounts.addresses.iter()
+			.map(|pubkey| resolve_pubkey(pubkey))
+			.collect::<Result<Vec<_>, _>>()?;
+		let tx = decode_transaction(
+			&config.encoding.unwrap_or(RpcBinaryEncoding::Base58).decode_bytes(&tx_data)?
+		)?;
+		let tx = sanitize_transaction(&self.ledger, tx, false).await?;
+		let mut ixs = BokkenLedgerInstruction::from_transaction(&tx);
+		for account_meta in ixs.iter_mut().flat_map(|ix| ix.account_metas.iter_mut()) {
+			if signers.contains(&account_meta.pubkey) {
+				account_meta.is_signer = true;
+			}
+		}
+		let slot = self.ledger.slot().await;
+		let simulation_result = simulate_instructions(&self.ledger, &tx.message.account_keys[0], ixs, slot).await?;
+		simulation_response(simulation_result, slot, &account_addresses, config.accounts.encoding)
+	}
+	async fn _get_ledger_info(&self) -> Result<RpcGetLedgerInfoResponse, BokkenError> {
+		let slot = self.ledger.slot().await;
+		let info = self.ledger.ledger_info().await?;
+		Ok(
+			RpcGetLedgerInfoResponse {
+				context: RpcResponseContext { slot },
+				account_count: info.account_count,
+				zero_lamport_account_count: info.zero_lamport_account_count,
+				account_version_count: info.account_version_count,
+				account_data_bytes: info.account_data_bytes,
+				block_count: info.block_count,
+				accounts_disk_bytes: info.accounts_disk_bytes,
+				blocks_disk_bytes: info.blocks_disk_bytes,
+				transaction_index_disk_bytes: info.transaction_index_disk_bytes,
+				log_index_disk_bytes: info.log_index_disk_bytes,
+				total_disk_bytes: info.total_disk_bytes
+			}
+		)
+	}
+	async fn _warm_up_lookup_table(&self, address: String) -> Result<u64, BokkenError> {
+		let address = resolve_pubkey(&address)?;
+		let account = self.ledger.read_account(&address, None).await?;
+		let (meta, _) = deserialize_lookup_table(&account.data).ok_or(BokkenError::NotALookupTable(address))?;
+		// Addresses added in a slot can be used from the next one. Transactions are executed in the slot after the
+		// current one, so a table can be closed once the current slot is one before it's closable.
+		let target_slot = match meta.closable_at_slot() {
+			Some(closable_at_slot) => closable_at_slot - 1,
+			None => meta.last_extended_slot + 1
+		};
+		let mut slot = self.ledger.slot().await;
+		while slot < target_slot {
+			slot = self.ledger.advance_slot().await?;
+		}
+		Ok(slot)
+	}
+	async fn _get_slot_dependency_graph(&self, start_slot: u64, end_slot: u64) -> Result<RpcSlotDependencyGraph, BokkenError> {
+		let slot = self.ledger.slot().await;
+		let graph = get_slot_dependency_graph(&self.ledger, start_slot, end_slot).await?;
+		Ok(
+			RpcSlotDependencyGraph {
+				context: RpcResponseContext { slot },
+				dot: graph.to_dot(),
+				nodes: graph.nodes.into_iter().map(|node| {
+					RpcDependencyGraphNode {
+						signature: node.signature.to_string(),
+						slot: node.slot,
+						writable_accounts: node.writable_accounts.iter().map(|pubkey| pubkey.to_string()).collect()
+					}
+				}).collect(),
+				edges: graph.edges.into_iter().map(|edge| {
+					RpcDependencyGraphEdge {
+						from: edge.from,
+						to: edge.to,
+						accounts: edge.accounts.iter().map(|pubkey| pubkey.to_string()).collect()
+					}
+				}).collect()
+			}
+		)
+	}
+	async fn _search_logs(&self, config: Option<RpcSearchLogsRequest>) -> Result<RpcSearchLogsResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let program_id = config.program_id.map(|program_id| resolve_pubkey(&program_id)).transpose()?;
+		let slot = self.ledger.slot().await;
+		let (results, next_min_slot) = self.ledger.search_logs(
+			program_id.as_ref(),
+			config.contains.as_deref(),
+			config.min_slot,
+			// Keeps the results consistent with the context slot
+			Some(config.max_slot.map_or(slot, |max_slot| max_slot.min(slot))),
+			config.limit.min(MAX_SEARCH_LOGS_LIMIT)
+		).await?;
+		Ok(
+			RpcSearchLogsResponse {
+				context: RpcResponseContext { slot },
+				value: results.into_iter().map(|result| {
+					RpcLogSearchResult {
+						slot: result.slot,
+						signature: result.signature.to_string(),
+						err: result.tx_error,
+						logs: result.logs
+					}
+				}).collect(),
+				next_min_slot
+			}
+		)
+	}
+	async fn _get_transaction_account_diff(
+		&self,
+		signature: String,
+		config: Option<RpcGetTransactionAccountDiffRequest>
+	) -> Result<RpcGetTransactionAccountDiffResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let encoding = config.encoding.unwrap_or_default();
+		let signature: [u8; 64] = bs58::decode(signature).into_vec()?
+			.try_into()
+			.map_err(|_| BokkenError::InvalidSignatureLength)?;
+		let Some((slot, diffs)) = get_transaction_account_diff(&self.ledger, signature).await? else {
+			return Ok(
+				RpcGetTransactionAccountDiffResponse {
+					context: RpcResponseContext { slot: self.ledger.slot().await },
+					value: None
+				}
+			);
+		};
+		Ok(
+			RpcGetTransactionAccountDiffResponse {
+				context: RpcResponseContext { slot },
+				value: Some(diffs.into_iter().map(|diff| {
+					RpcAccountDiff {
+						pubkey: diff.pubkey.to_string(),
+						pre_lamports: diff.pre.lamports,
+						post_lamports: diff.post.lamports,
+						pre_owner: diff.pre.owner.to_string(),
+						post_owner: diff.post.owner.to_string(),
+						pre_data_len: diff.pre.data.len(),
+						post_data_len: diff.post.data.len(),
+						data_changes: diff.changed_ranges.into_iter().map(|range| {
+							RpcAccountDataChange {
+								offset: range.start,
+								pre: RPCBinaryEncodedString::from_bytes(
+									diff.pre.data.get(range.start..range.end.min(diff.pre.data.len())).unwrap_or_default(),
+									encoding
+								),
+								post: RPCBinaryEncodedString::from_bytes(
+									diff.post.data.get(range.start..range.end.min(diff.post.data.len())).unwrap_or_default(),
+									encoding
+								)
+							}
+						}).collect()
+					}
+				}).collect())
+			}
+		)
+	}
+	async fn _get_transaction_logs(&self, signature: String) -> Result<Option<Vec<String>>, BokkenError> {
+		let signature: [u8; 64] = bs58::decode(signature).into_vec()?
+			.try_into()
+			.map_err(|_| BokkenError::InvalidSignatureLength)?;
+		Ok(self.ledger.transaction_logs(signature).await?)
+	}
+	async fn _get_transaction_crash_report(
+		&self,
+		signature: String
+	) -> Result<Option<RpcTransactionCrashReport>, BokkenError> {
+		let signature: [u8; 64] = bs58::decode(signature).into_vec()?
+			.try_into()
+			.map_err(|_| BokkenError::InvalidSignatureLength)?;
+		let Some(crash_report) = self.ledger.crash_report(&Signature::from(signature)).await? else {
+			return Ok(None);
+		};
+		Ok(
+			Some(
+				RpcTransactionCrashReport {
+					slot: crash_report.slot,
+					error: crash_report.error,
+					panics: crash_report.panics.into_iter().map(|panic| {
+						RpcProgramPanic {
+							program_id: panic.program_id.to_string(),
+							call_depth: panic.call_depth,
+							message: panic.message,
+							backtrace: panic.backtrace
+						}
+					}).collect(),
+					logs: crash_report.logs
+				}
+			)
+		)
+	}
+	async fn _schedule_transaction(&self, config: RpcScheduleTransactionRequest) -> Result<u64, BokkenError> {
+		let mut instructions = Vec::with_capacity(config.instructions.len());
+		for ix in config.instructions.iter() {
+			instructions.push(ix.to_instruction()?);
+		}
+		let signer = Keypair::from_bytes(&config.signer).map_err(|_| BokkenError::InvalidKeypair)?;
+		Ok(
+			self.scheduler.add_job(
+				BokkenScheduledJob::new(config.name, config.every_slots, instructions, signer)
+			).await
+		)
+	}
+	async fn _build_and_send_transaction(&self, config: RpcBuildAndSendTransactionRequest) -> Result<String, BokkenError> {
+		let mut instructions = Vec::with_capacity(config.instructions.len());
+		for ix in config.instructions.iter() {
+			instructions.push(ix.to_instruction()?);
+		}
+		let payer = resolve_pubkey(&config.payer)?;
+		let message = Message::new(&instructions, Some(&payer));
+		let keystore = BokkenKeystore::for_ledger(&self.ledger);
+		let mut signers = Vec::with_capacity(message.header.num_required_signatures as usize);
+		for signer in message.account_keys.iter().take(message.header.num_required_signatures as usize) {
+			signers.push(keystore.read_keypair(signer).await?);
+		}
+		let mut tx = Transaction::new_unsigned(message);
+		tx.try_sign(&signers.iter().collect::<Vec<&Keypair>>(), Hash::new_from_array(self.ledger.blockhash().await))
+			.map_err(|e| BokkenError::SigningFailed(e.to_string()))?;
+		let tx_sig = tx.signatures[0];
+		self.ledger.execute_transaction(tx, true).await?;
+		Ok(tx_sig.to_string())
+	}
+	async fn _sign_transaction(
+		&self,
+		tx_data: String,
+		config: Option<RpcSignTransactionRequest>
+	) -> Result<RpcSignTransactionResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let encoding = config.encoding.unwrap_or(RpcBinaryEncoding::Base58);
+		let mut tx: Transaction = bincode::deserialize(&encoding.decode_bytes(&tx_data)?)?;
+		let num_signers = tx.message.header.num_required_signatures as usize;
+		if tx.signatures.len() != num_signers {
+			// Unsigned transactions are sometimes serialized without any signatures
+			tx.signatures = vec![Signature::default(); num_signers];
+		}
+		let required_signers: Vec<Pubkey> = tx.message.account_keys.iter().take(num_signers).copied().collect();
+		let keystore = BokkenKeystore::for_ledger(&self.ledger);
+		let mut keypairs = Vec::new();
+		if let Some(signers) = config.signers.as_ref() {
+			for signer in signers.iter() {
+				let signer = resolve_pubkey(signer)?;
+				if !required_signers.contains(&signer) {
+					return Err(BokkenError::NotATransactionSigner(signer));
+				}
+				keypairs.push(keystore.read_keypair(&signer).await?);
+			}
+		}else{
+			for (signer, signature) in required_signers.iter().zip(tx.signatures.iter()) {
+				if *signature != Signature::default() {
+					continue;
+				}
+				match keystore.read_keypair(signer).await.map_err(BokkenError::from) {
+					Ok(keypair) => keypairs.push(keypair),
+					Err(BokkenError::KeypairNotInKeystore(_)) => {},
+					Err(e) => return Err(e)
+				}
+			}
+		}
+		let recent_blockhash = tx.message.recent_blockhash;
+		tx.try_partial_sign(&keypairs.iter().collect::<Vec<&Keypair>>(), recent_blockhash)
+			.map_err(|e| BokkenError::SigningFailed(e.to_string()))?;
+		Ok(
+			RpcSignTransactionResponse {
+				transaction: RPCBinaryEncodedString::from_bytes(&bincode::serialize(&tx)?, encoding),
+				signed: keypairs.iter().map(|keypair| keypair.pubkey().to_string()).collect(),
+				missing_signers: required_signers.iter().zip(tx.signatures.iter())
+					.filter(|(_, signature)| **signature == Signature::default())
+					.map(|(signer, _)| signer.to_string())
+					.collect()
+			}
+		)
+	}
+	async fn account_info_response(
+		&self,
+		data: BokkenAccountData,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> Result<RpcGetAccountInfoResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		Ok(
+			RpcGetAccountInfoResponse {
+				context: RpcResponseContext { slot: self.ledger.slot().await },
+				value: Some(
+					RpcGetAccountInfoResponseValue {
+						lamports: data.lamports,
+						owner: data.owner.to_string(),
+						data: encode_account_data(&self.ledger, &data, config.encoding)?,
+						executable: data.executable,
+						rent_epoch: data.rent_epoch,
+					}
+				)
+			}
+		)
+	}
+	async fn _set_account_owner(
+		&self,
+		pubkey: String,
+		owner: String,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> Result<RpcGetAccountInfoResponse, BokkenError> {
+		let data = set_account_owner(&self.ledger, &resolve_pubkey(&pubkey)?, &resolve_pubkey(&owner)?).await?;
+		self.account_info_response(data, config).await
+	}
+	async fn _set_time_model(&self, config: RpcTimeModelConfig) -> Result<RpcTimeModelConfig, BokkenError> {
+		let slot = self.ledger.slot().await;
+		let unix_timestamp = config.unix_timestamp.unwrap_or_else(|| self.ledger.unix_timestamp_at_slot(slot));
+		let time_model = match config.model_type {
+			RpcTimeModelType::RealTime => BokkenTimeModel::RealTime,
+			RpcTimeModelType::SlotDerived => {
+				BokkenTimeModel::slot_derived_from(slot, unix_timestamp, config.slot_duration_ms)
+			},
+			RpcTimeModelType::Manual => BokkenTimeModel::Manual { unix_timestamp }
+		};
+		self.ledger.set_time_model(time_model).await?;
+		Ok(self._get_time_model().await)
+	}
+	async fn _get_time_model(&self) -> RpcTimeModelConfig {
+		let slot = self.ledger.slot().await;
+		let time_model = self.ledger.time_model();
+		RpcTimeModelConfig {
+			model_type: match time_model {
+				BokkenTimeModel::RealTime => RpcTimeModelType::RealTime,
+				BokkenTimeModel::SlotDerived { .. } => RpcTimeModelType::SlotDerived,
+				BokkenTimeModel::Manual { .. } => RpcTimeModelType::Manual
+			},
+			slot_duration_ms: match time_model {
+				BokkenTimeModel::SlotDerived { slot_duration_ms, .. } => Some(slot_duration_ms),
+				_ => None
+			},
+			unix_timestamp: Some(time_model.unix_timestamp(slot))
+		}
+	}
+	fn _get_feature_activation(
+		&self,
+		feature_ids: Option<Vec<String>>
+	) -> Result<RpcGetFeatureActivationResponse, BokkenError> {
+		let feature_set = self.ledger.feature_set();
+		let feature_ids = match feature_ids {
+			Some(feature_ids) => {
+				let mut pubkeys = Vec::with_capacity(feature_ids.len());
+				for feature_id in feature_ids.iter() {
+					pubkeys.push(resolve_pubkey(feature_id)?);
+				}
+				pubkeys
+			},
+			None => feature_set.feature_ids()
+		};
+		Ok(
+			RpcGetFeatureActivationResponse {
+				feature_set: feature_set.feature_set_id(),
+				features: feature_ids.iter().map(|feature_id| feature_activation(&feature_set, feature_id)).collect()
+			}
+		)
+	}
+	async fn _set_feature_activation(
+		&self,
+		feature_id: String,
+		active: bool,
+		slot: Option<u64>
+	) -> Result<RpcFeatureActivation, BokkenError> {
+		let feature_id = resolve_pubkey(&feature_id)?;
+		let feature_set = if active {
+			self.ledger.activate_feature(feature_id, slot).await?
+		}else{
+			self.ledger.deactivate_feature(feature_id).await?
+		};
+		Ok(feature_activation(&feature_set, &feature_id))
+	}
+	async fn _patch_account_data(
+		&self,
+		pubkey: String,
+		offset: usize,
+		bytes: String,
+		config: Option<RpcPatchAccountDataRequest>
+	) -> Result<RpcGetAccountInfoResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let bytes = config.encoding.unwrap_or_default().decode_bytes(&bytes)?;
+		let data = patch_account_data(&self.ledger, &resolve_pubkey(&pubkey)?, offset, &bytes).await?;
+		self.account_info_response(data, None).await
+	}
+	async fn _set_token_balance(
+		&self,
+		token_account: String,
+		amount: u64,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> Result<RpcGetAccountInfoResponse, BokkenError> {
+		let data = set_token_balance(&self.ledger, &resolve_pubkey(&token_account)?, amount).await?;
+		self.account_info_response(data, config).await
+	}
+	async fn _create_program_owned_account(
+		&self,
+		program_id: String,
+		space: usize,
+		config: Option<RpcCreateProgramOwnedAccountRequest>
+	) -> Result<RpcCreateProgramOwnedAccountResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let seeds = match &config.seeds {
+			Some(seeds) => Some(decode_seeds(seeds, config.seed_encoding.unwrap_or_default())?),
+			None => None
+		};
+		let (pubkey, bump, data) = create_program_owned_account(
+			&self.ledger,
+			&resolve_pubkey(&program_id)?,
+			space,
+			config.lamports,
+			seeds.as_deref()
+		).await?;
+		Ok(
+			RpcCreateProgramOwnedAccountResponse {
+				pubkey: pubkey.to_string(),
+				bump,
+				lamports: data.lamports
+			}
+		)
+	}
+	fn _find_program_address(&self, seeds: Vec<String>, program_id: String) -> Result<RpcProgramAddress, BokkenError> {
+		let seeds = decode_seeds(&seeds, RpcBinaryEncoding::Base64)?;
+		let seeds: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+		let (pubkey, bump) = Pubkey::try_find_program_address(&seeds, &resolve_pubkey(&program_id)?)
+			.ok_or(BokkenError::InvalidSeeds)?;
+		Ok(
+			RpcProgramAddress {
+				pubkey: pubkey.to_string(),
+				bump
+			}
+		)
+	}
+	fn _create_program_address(&self, seeds: Vec<String>, program_id: String) -> Result<String, BokkenError> {
+		let seeds = decode_seeds(&seeds, RpcBinaryEncoding::Base64)?;
+		let seeds: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+		let pubkey = Pubkey::create_program_address(&seeds, &resolve_pubkey(&program_id)?)
+			.map_err(|e| BokkenError::InvalidProgramAddress(e.to_string()))?;
+		Ok(pubkey.to_string())
+	}
+	async fn _register_account_schema(
+		&self,
+		program_id: String,
+		schema: serde_json::Value,
+		config: Option<RpcRegisterAccountSchemaRequest>
+	) -> Result<RpcAccountSchemaInfo, BokkenError> {
+		let program_id = resolve_pubkey(&program_id)?;
+		let format = match config.unwrap_or_default().format.unwrap_or(RpcAccountSchemaFormat::Anchor) {
+			RpcAccountSchemaFormat::Anchor => BokkenAccountSchemaFormat::Anchor,
+			RpcAccountSchemaFormat::Borsh => BokkenAccountSchemaFormat::Borsh
+		};
+		let schema = BokkenAccountSchema::new(format, schema)?;
+		let info = account_schema_info(&program_id, &schema);
+		self.ledger.account_schemas().register(program_id, schema).await?;
+		Ok(info)
+	}
+	async fn _fork_get_account_info(
+		&self,
+		fork_id: u64,
+		pubkey: String,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> Result<RpcGetAccountInfoResponse, BokkenError> {
+		let pubkey = resolve_pubkey(&pubkey)?;
+		let config = config.unwrap_or_default();
+		let data = self.ledger.read_account_on_fork(fork_id, &pubkey).await?;
+		Ok(
+			RpcGetAccountInfoResponse {
+				context: RpcResponseContext { slot: self.ledger.slot().await },
+				value: if data.lamports == 0 {
+					None
+				}else{
+					Some(
+						RpcGetAccountInfoResponseValue {
+							lamports: data.lamports,
+							owner: data.owner.to_string(),
+							data: encode_account_data(&self.ledger, &data, config.encoding)?,
+							executable: data.executable,
+							rent_epoch: data.rent_epoch,
+						}
+					)
+				}
+			}
+		)
+	}
+	async fn _fork_send_transaction(
+		&self,
+		fork_id: u64,
+		tx_data: String,
+		config: Option<RpcSendTransactionRequest>
+	) -> Result<RpcForkSendTransactionResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let tx = decode_transaction(
+			&config.encoding.unwrap_or(RpcBinaryEncoding::Base58).decode_bytes(&tx_data)?
+		)?;
+		let tx = sanitize_transaction(&self.ledger, tx, true).await?;
+		verify_signers(&tx)?;
+		let logs = self.ledger.execute_transaction_on_fork(fork_id, &tx).await?;
+		Ok(
+			RpcForkSendTransactionResponse {
+				signature: bs58::encode(tx.signatures[0]).into_string(),
+				logs
+			}
+		)
+	}
+	fn _set_account_alias(&self, name: String, pubkey: String) -> Result<Option<String>, BokkenError> {
+		let pubkey = resolve_pubkey(&pubkey)?;
+		Ok(set_account_alias(name, pubkey)?.map(|old_pubkey| old_pubkey.to_string()))
+	}
+}
+
+#[async_trait]
+impl BokkenAdminRpcServer for BokkenAdminRpcImpl {
+	fn get_metrics(&self) -> RpcResult<RpcGetMetricsResponse> {
+		let mut metrics = self.metrics.snapshot();
+		metrics.program_processes = self.ledger.program_processes().into_iter().map(program_process).collect();
+		Ok(metrics)
+	}
+	async fn create_fork(&self) -> RpcResult<u64> {
+		Ok(self.ledger.create_fork().await)
+	}
+	async fn discard_fork(&self, fork_id: u64) -> RpcResult<bool> {
+		Ok(self.ledger.discard_fork(fork_id).await)
+	}
+	async fn commit_fork(&self, fork_id: u64) -> RpcResult<usize> {
+		Ok(self.ledger.commit_fork(fork_id).await.map_err(BokkenError::from)?)
+	}
+	async fn fork_get_account_info(
+		&self,
+		fork_id: u64,
+		pubkey: String,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> RpcResult<RpcGetAccountInfoResponse> {
+		Ok(self._fork_get_account_info(fork_id, pubkey, config).await?)
+	}
+	async fn fork_send_transaction(
+		&self,
+		fork_id: u64,
+		tx_data: String,
+		config: Option<RpcSendTransactionRequest>
+	) -> RpcResult<RpcForkSendTransactionResponse> {
+		Ok(self._fork_send_transaction(fork_id, tx_data, config).await?)
+	}
+	async fn schedule_transaction(&self, config: RpcScheduleTransactionRequest) -> RpcResult<u64> {
+		Ok(self._schedule_transaction(config).await?)
+	}
+	async fn unschedule_transaction(&self, id: u64) -> RpcResult<bool> {
+		Ok(self.scheduler.remove_job(id).await)
+	}
+	async fn get_scheduled_transactions(&self) -> RpcResult<Vec<RpcScheduledTransactionInfo>> {
+		let mut result = Vec::new();
+		self.scheduler.for_each_job(|id, job| {
+			result.push(
+				RpcScheduledTransactionInfo {
+					id,
+					name: job.name.clone(),
+					every_slots: job.every_slots,
+					signer: job.signer.pubkey().to_string(),
+					next_slot: job.next_slot(),
+					successes: job.successes(),
+					failures: job.failures()
+				}
+			);
+		}).await;
+		result.sort_by_key(|info| info.id);
+		Ok(result)
+	}
+	async fn create_funded_keypair(&self, lamports: u64) -> RpcResult<RpcCreateFundedKeypairResponse> {
+		let keypair = BokkenKeystore::for_ledger(&self.ledger)
+			.create_funded_keypair(&self.ledger, lamports).await
+			.map_err(BokkenError::from)?;
+		Ok(
+			RpcCreateFundedKeypairResponse {
+				pubkey: keypair.pubkey().to_string(),
+				secret_key: keypair.to_bytes().to_vec()
+			}
+		)
+	}
+	async fn build_and_send_transaction(&self, config: RpcBuildAndSendTransactionRequest) -> RpcResult<String> {
+		Ok(self._build_and_send_transaction(config).await?)
+	}
+	async fn sign_transaction(
+		&self,
+		tx_data: String,
+		config: Option<RpcSignTransactionRequest>
+	) -> RpcResult<RpcSignTransactionResponse> {
+		Ok(self._sign_transaction(tx_data, config).await?)
+	}
+	async fn lint_transaction(
+		&self,
+		tx_data: String,
+		config: Option<RpcLintTransactionRequest>
+	) -> RpcResult<RpcLintTransactionResponse> {
+		Ok(self._lint_transaction(tx_data, config).await?)
+	}
+	async fn get_transaction_account_diff(
+		&self,
+		signature: String,
+		config: Option<RpcGetTransactionAccountDiffRequest>
+	) -> RpcResult<RpcGetTransactionAccountDiffResponse> {
+		Ok(self._get_transaction_account_diff(signature, config).await?)
+	}
+	async fn get_transaction_crash_report(&self, signature: String) -> RpcResult<Option<RpcTransactionCrashReport>> {
+		Ok(self._get_transaction_crash_report(signature).await?)
+	}
+	async fn get_transaction_logs(&self, signature: String) -> RpcResult<Option<Vec<String>>> {
+		Ok(self._get_transaction_logs(signature).await?)
+	}
+	async fn search_logs(&self, config: Option<RpcSearchLogsRequest>) -> RpcResult<RpcSearchLogsResponse> {
+		Ok(self._search_logs(config).await?)
+	}
+	async fn get_program_stats(&self, program_ids: Option<Vec<String>>) -> RpcResult<Vec<RpcProgramStats>> {
+		Ok(self._get_program_stats(program_ids).await?)
+	}
+	fn set_fault_injection(&self, config: RpcFaultInjectionConfig) -> RpcResult<RpcFaultInjectionConfig> {
+		self.fault_injector.set_config(config);
+		Ok(self.fault_injector.config())
+	}
+	fn get_fault_injection(&self) -> RpcResult<RpcFaultInjectionConfig> {
+		Ok(self.fault_injector.config())
+	}
+	async fn set_rent(&self, config: RpcRentConfig) -> RpcResult<RpcRentConfig> {
+		self.ledger.set_rent(
+			BokkenRentConfig {
+				lamports_per_byte_year: config.lamports_per_byte_year,
+				exemption_threshold: config.exemption_threshold
+			}
+		).await.map_err(BokkenError::from)?;
+		self.get_rent()
+	}
+	fn get_rent(&self) -> RpcResult<RpcRentConfig> {
+		let rent = self.ledger.rent();
+		Ok(
+			RpcRentConfig {
+				lamports_per_byte_year: rent.lamports_per_byte_year,
+				exemption_threshold: rent.exemption_threshold
+			}
+		)
+	}
+	async fn set_time_model(&self, config: RpcTimeModelConfig) -> RpcResult<RpcTimeModelConfig> {
+		Ok(self._set_time_model(config).await?)
+	}
+	async fn get_time_model(&self) -> RpcResult<RpcTimeModelConfig> {
+		Ok(self._get_time_model().await)
+	}
+	fn get_feature_activation(&self, feature_ids: Option<Vec<String>>) -> RpcResult<RpcGetFeatureActivationResponse> {
+		Ok(self._get_feature_activation(feature_ids)?)
+	}
+	async fn set_feature_activation(
+		&self,
+		feature_id: String,
+		active: bool,
+		slot: Option<u64>
+	) -> RpcResult<RpcFeatureActivation> {
+		Ok(self._set_feature_activation(feature_id, active, slot).await?)
+	}
+	async fn set_feature_set(&self, feature_set: Option<u32>) -> RpcResult<u32> {
+		Ok(self.ledger.set_feature_set_id(feature_set).await.map_err(BokkenError::from)?.feature_set_id())
+	}
+	async fn list_programs(&self) -> RpcResult<Vec<RpcDebuggableProgram>> {
+		let mut processes: HashMap<Pubkey, BokkenProgramProcessStatus> = self.ledger.program_processes().into_iter()
+			.map(|process| (process.program_id, process))
+			.collect();
+		let mut result: Vec<RpcDebuggableProgram> = self.ledger.debuggable_programs().await.into_iter().map(|program| {
+			RpcDebuggableProgram {
+				program_id: program.program_id.to_string(),
+				name: program.name,
+				transport: program.transport.to_string(),
+				uptime_ms: program.connected_for.as_millis() as u64,
+				pending_invokes: program.pending_invokes,
+				last_seen_ms: program.last_seen.as_millis() as u64,
+				healthy: program.healthy,
+				account_layout: program_account_layout(self.ledger.program_account_layout(&program.program_id)),
+				process: processes.remove(&program.program_id).map(program_process)
+			}
+		}).collect();
+		// Launched programs which haven't connected, or have crashed
+		let mut disconnected: Vec<BokkenProgramProcessStatus> = processes.into_values().collect();
+		disconnected.sort_by_key(|process| process.program_id);
+		result.extend(disconnected.into_iter().map(|process| {
+			RpcDebuggableProgram {
+				program_id: process.program_id.to_string(),
+				name: None,
+				transport: "unix".to_string(),
+				uptime_ms: 0,
+				pending_invokes: 0,
+				last_seen_ms: 0,
+				healthy: false,
+				account_layout: program_account_layout(self.ledger.program_account_layout(&process.program_id)),
+				process: Some(program_process(process))
+			}
+		}));
+		Ok(result)
+	}
+	fn set_program_account_layout(&self, program_id: String, layout: RpcProgramAccountLayout) -> RpcResult<RpcProgramAccountLayout> {
+		let program_id = resolve_pubkey(&program_id)?;
+		self.ledger.set_program_account_layout(
+			program_id,
+			match layout {
+				RpcProgramAccountLayout::Debuggable => BokkenProgramAccountLayout::Debuggable,
+				RpcProgramAccountLayout::UpgradeableLoader => BokkenProgramAccountLayout::UpgradeableLoader,
+				RpcProgramAccountLayout::LoaderV4 => BokkenProgramAccountLayout::LoaderV4
+			}
+		);
+		Ok(layout)
+	}
+	async fn get_ledger_info(&self) -> RpcResult<RpcGetLedgerInfoResponse> {
+		Ok(self._get_ledger_info().await?)
+	}
+	async fn simulate_transaction_unchecked(
+		&self,
+		tx_data: String,
+		config: Option<RpcSimulateTransactionUncheckedRequest>
+	) -> RpcResult<RpcSimulateTransactionResponse> {
+		Ok(self._simulate_transaction_unchecked(tx_data, config).await?)
+	}
+	async fn compact_accounts(&self) -> RpcResult<RpcCompactAccountsResponse> {
+		let (removed_accounts, freed_bytes) = self.ledger.compact_accounts().await.map_err(BokkenError::from)?;
+		Ok(
+			RpcCompactAccountsResponse {
+				removed_accounts: removed_accounts as u64,
+				freed_bytes
+			}
+		)
+	}
+	async fn start_account_compaction(&self) -> RpcResult<RpcCompactionStatus> {
+		if self.ledger.compaction_progress().running {
+			return Err(BokkenError::CompactionInProgress.into());
+		}
+		let ledger = self.ledger.clone();
+		tokio::task::spawn(async move {
+			// Failures are recorded in the compaction status
+			let _ = ledger.compact_accounts().await;
+		});
+		// Let the compaction mark itself as running, so the status reflects that it has started
+		tokio::task::yield_now().await;
+		Ok(compaction_status(self.ledger.compaction_progress()))
+	}
+	fn get_compaction_status(&self) -> RpcResult<RpcCompactionStatus> {
+		Ok(compaction_status(self.ledger.compaction_progress()))
+	}
+	async fn warm_up_lookup_table(&self, address: String) -> RpcResult<u64> {
+		Ok(self._warm_up_lookup_table(address).await?)
+	}
+	async fn get_slot_dependency_graph(&self, start_slot: u64, end_slot: u64) -> RpcResult<RpcSlotDependencyGraph> {
+		Ok(self._get_slot_dependency_graph(start_slot, end_slot).await?)
+	}
+	async fn set_account_owner(
+		&self,
+		pubkey: String,
+		owner: String,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> RpcResult<RpcGetAccountInfoResponse> {
+		Ok(self._set_account_owner(pubkey, owner, config).await?)
+	}
+	async fn patch_account_data(
+		&self,
+		pubkey: String,
+		offset: usize,
+		bytes: String,
+		config: Option<RpcPatchAccountDataRequest>
+	) -> RpcResult<RpcGetAccountInfoResponse> {
+		Ok(self._patch_account_data(pubkey, offset, bytes, config).await?)
+	}
+	async fn set_token_balance(
+		&self,
+		token_account: String,
+		amount: u64,
+		config: Option<RpcGetAccountInfoRequest>
+	) -> RpcResult<RpcGetAccountInfoResponse> {
+		Ok(self._set_token_balance(token_account, amount, config).await?)
+	}
+	async fn create_program_owned_account(
+		&self,
+		program_id: String,
+		space: usize,
+		config: Option<RpcCreateProgramOwnedAccountRequest>
+	) -> RpcResult<RpcCreateProgramOwnedAccountResponse> {
+		Ok(self._create_program_owned_account(program_id, space, config).await?)
+	}
+	async fn register_account_schema(
+		&self,
+		program_id: String,
+		schema: serde_json::Value,
+		config: Option<RpcRegisterAccountSchemaRequest>
+	) -> RpcResult<RpcAccountSchemaInfo> {
+		Ok(self._register_account_schema(program_id, schema, config).await?)
+	}
+	async fn unregister_account_schema(&self, program_id: String) -> RpcResult<bool> {
+		let program_id = resolve_pubkey(&program_id)?;
+		Ok(self.ledger.account_schemas().unregister(&program_id).await.map_err(BokkenError::from)?)
+	}
+	fn list_account_schemas(&self) -> RpcResult<Vec<RpcAccountSchemaInfo>> {
+		let account_schemas = self.ledger.account_schemas();
+		Ok(
+			account_schemas.program_ids().iter().filter_map(|program_id| {
+				account_schemas.get(program_id).map(|schema| account_schema_info(program_id, &schema))
+			}).collect()
+		)
+	}
+	fn find_program_address(&self, seeds: Vec<String>, program_id: String) -> RpcResult<RpcProgramAddress> {
+		Ok(self._find_program_address(seeds, program_id)?)
+	}
+	fn create_program_address(&self, seeds: Vec<String>, program_id: String) -> RpcResult<String> {
+		Ok(self._create_program_address(seeds, program_id)?)
+	}
+	fn set_account_alias(&self, name: String, pubkey: String) -> RpcResult<Option<String>> {
+		Ok(self._set_account_alias(name, pubkey)?)
+	}
+	fn remove_account_alias(&self, name: String) -> RpcResult<Option<String>> {
+		Ok(remove_account_alias(&name).map(|pubkey| pubkey.to_string()))
+	}
+	fn get_account_aliases(&self) -> RpcResult<Vec<RpcAccountAlias>> {
+		Ok(
+			account_aliases().into_iter().map(|(name, pubkey)| {
+				RpcAccountAlias {
+					name,
+					pubkey: pubkey.to_string()
+				}
+			}).collect()
+		)
+	}
+}
@@ -0,0 +1,1001 @@
+use color_eyre::eyre;
+use jsonrpsee::RpcModule;
+use jsonrpsee::server::{ServerBuilder, ServerHandle};
+use jsonrpsee::types::error::CallError;
+use jsonrpsee::{proc_macros::rpc, core::async_trait, core::RpcResult};
+use solana_sdk::clock::MAX_RECENT_BLOCKHASHES;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::TransactionError;
+use solana_sdk::vote::state::{VoteState, MAX_LOCKOUT_HISTORY};
+use bokken_runtime::debug_env::BokkenAccountData;
+
+use std::net::{SocketAddr, Ipv4Addr};
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sig_verify::verify_signers;
+use crate::debug_ledger::{BokkenLedger, BokkenLedgerInstruction, BokkenLedgerAccountReturnChoice, AccountsOverlay, BokkenRewardType, LAMPORTS_PER_SIGNATURE};
+use crate::error::BokkenError;
+use crate::rpc_admin_endpoint::{BokkenAdminRpcImpl, BokkenAdminRpcServer};
+use crate::simulation_cache::{SimulationCache, SimulationCacheEntry};
+use crate::rpc_metrics::{BokkenRpcMetrics, BokkenRpcLogger};
+use crate::scheduler::BokkenScheduler;
+use crate::instance_manager::{BokkenInstanceManager, BokkenInstanceRouterLayer};
+use crate::rpc_instance_endpoint::{BokkenInstanceRpcImpl, BokkenInstanceRpcServer};
+use crate::fault_injection::{BokkenFaultInjector, BokkenFaultInjectionLayer};
+use crate::subscription_manager::BokkenSubscriptionManager;
+use crate::faucet::{BokkenFaucet, BokkenAirdropLimiter, BokkenAirdropLimitLayer};
+use crate::program_caller::BokkenLogLevel;
+use crate::account_aliases::resolve_pubkey;
+use crate::tx_sanitize::{decode_transaction, sanitize_transaction};
+use tower::ServiceBuilder;
+
+/// Large enough for a base64-encoded 10MiB account, the largest an account can be
+pub const DEFAULT_MAX_RESPONSE_SIZE: u32 = 32 * 1024 * 1024;
+/// Large enough for a base64-encoded transaction many times over, same as jsonrpsee's own default
+pub const DEFAULT_MAX_REQUEST_SIZE: u32 = 10 * 1024 * 1024;
+/// Account notifications can be as large as `getAccountInfo` responses
+pub const DEFAULT_MAX_WS_MESSAGE_SIZE: u32 = DEFAULT_MAX_RESPONSE_SIZE;
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 100;
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: u32 = 1024;
+
+/// Size and connection limits of the JSON-RPC servers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BokkenRpcServerLimits {
+	/// Maximum size of a JSON-RPC request body in bytes
+	pub max_request_size: u32,
+	/// Maximum size of a JSON-RPC response body in bytes
+	pub max_response_size: u32,
+	/// Maximum size of a message sent or received over the subscription websocket in bytes
+	pub max_ws_message_size: u32,
+	/// Maximum number of simultaneous connections to each server
+	pub max_connections: u32,
+	pub max_subscriptions_per_connection: u32
+}
+impl Default for BokkenRpcServerLimits {
+	fn default() -> Self {
+		Self {
+			max_request_size: DEFAULT_MAX_REQUEST_SIZE,
+			max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+			max_ws_message_size: DEFAULT_MAX_WS_MESSAGE_SIZE,
+			max_connections: DEFAULT_MAX_CONNECTIONS,
+			max_subscriptions_per_connection: DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION
+		}
+	}
+}
+/// Like a real validator's status cache, `getSignatureStatuses` only looks this many slots back unless
+/// `searchTransactionHistory` is set
+const SIGNATURE_STATUS_CACHE_SLOTS: u64 = MAX_RECENT_BLOCKHASHES as u64;
+/// How many slots behind a vote account's last vote can be before getVoteAccounts reports it as delinquent, same as a
+/// real validator
+const DELINQUENT_VALIDATOR_SLOT_DISTANCE: u64 = 128;
+/// Nothing can be staked, so `getBlockCommitment` reports the identity as having this much stake, all of it having
+/// voted on every block
+const BLOCK_COMMITMENT_TOTAL_STAKE: u64 = 1;
+
+use crate::rpc_endpoint_structs::{RpcGetLatestBlockhashRequest, RpcVersionResponse, RpcGetLatestBlockhashResponse, RpcGetLatestBlockhashResponseValue, RpcResponseContext, RpcSimulateTransactionRequest, RpcSimulateTransactionResponse, RpcBinaryEncoding, RpcSimulateTransactionResponseValue, RpcSimulateTransactionResponseAccounts, RpcSimulateTransactionResponseReturnData, RPCBinaryEncodedString, RpcGetAccountInfoRequest, RpcGetAccountInfoResponse, RpcGetBalanceResponse, RpcGetBalanceRequest, RpcGetAccountInfoResponseValue, RpcGenericConfigRequest, RpcSendTransactionRequest, RpcGetSignatureStatusesRequest, RpcGetSignatureStatusesResponse, RpcGetSignatureStatusesResponseValue, RpcCommitment, RpcGetBlockRequest, RpcGetBlockResponse, RpcReward, RpcRewardType, RpcSimulationDiagnostics, RpcSignerCheck, RpcBlockTransaction, RpcTransactionStatusMeta, RpcLoadedAddresses, RpcTransactionDetails, RpcGetEpochInfoResponse, RpcGetEpochScheduleResponse, RpcGetIdentityResponse, RpcProgramLogsTailRequest, RpcAccountData, RpcParsedAccountData, RpcParsedAccountInfo, RpcGetVoteAccountsRequest, RpcGetVoteAccountsResponse, RpcVoteAccountInfo, RpcGetBlockCommitmentResponse, RpcGetBlockProductionRequest, RpcGetBlockProductionResponse, RpcGetBlockProductionResponseValue, RpcBlockProductionRange, RpcIsBlockhashValidResponse};
+
+#[cfg_attr(not(feature = "client"), rpc(server))]
+#[cfg_attr(feature = "client", rpc(server, client))]
+pub trait SolanaDebuggerRpc {
+	#[method(name = "getAccountInfo")]
+	async fn get_account_info(&self, pubkey: String, config: Option<RpcGetAccountInfoRequest>) -> RpcResult<RpcGetAccountInfoResponse>;
+	#[method(name = "getBalance")]
+	async fn get_balance(&self, pubkey: String, config: Option<RpcGetBalanceRequest>) -> RpcResult<RpcGetBalanceResponse>;
+	#[method(name = "getBlock")]
+	async fn get_block(&self, slot: u64, config: Option<RpcGetBlockRequest>) -> RpcResult<RpcGetBlockResponse>;
+	/// Every block is rooted as soon as it's produced, so every produced block has full commitment
+	#[method(name = "getBlockCommitment")]
+	async fn get_block_commitment(&self, slot: u64) -> RpcResult<RpcGetBlockCommitmentResponse>;
+	/// The identity is the leader of every slot and produces a block in each of them
+	#[method(name = "getBlockProduction")]
+	async fn get_block_production(&self, config: Option<RpcGetBlockProductionRequest>) -> RpcResult<RpcGetBlockProductionResponse>;
+	#[method(name = "getBlockTime")]
+	async fn get_block_time(&self, slot: u64) -> RpcResult<i64>;
+	#[method(name = "getBlockHeight")]
+	async fn get_block_height(&self, _config: Option<RpcGetBalanceRequest>) -> RpcResult<u64>;
+	#[method(name = "getEpochInfo")]
+	async fn get_epoch_info(&self, _config: Option<RpcGenericConfigRequest>) -> RpcResult<RpcGetEpochInfoResponse>;
+	#[method(name = "getEpochSchedule")]
+	fn get_epoch_schedule(&self) -> RpcResult<RpcGetEpochScheduleResponse>;
+	#[method(name = "getGenesisHash")]
+	fn get_genesis_hash(&self) -> RpcResult<String>;
+	#[method(name = "getIdentity")]
+	fn get_identity(&self) -> RpcResult<RpcGetIdentityResponse>;
+	#[method(name = "getLatestBlockhash")]
+	async fn get_latest_blockhash(&self, config: Option<RpcGetLatestBlockhashRequest>) -> RpcResult<RpcGetLatestBlockhashResponse>;
+	#[method(name = "getMinimumBalanceForRentExemption")]
+	async fn get_min_balance_for_rent_exemption(&self, size: u64, config: Option<RpcGenericConfigRequest>) -> RpcResult<u64>;
+	#[method(name = "getSignatureStatuses")]
+	async fn get_signature_statuses(&self, sigs: Vec<String>, config: Option<RpcGetSignatureStatusesRequest>) -> RpcResult<RpcGetSignatureStatusesResponse>;
+	#[method(name = "getSlot")]
+	async fn get_slot(&self, config: Option<RpcGenericConfigRequest>) -> RpcResult<u64>;
+	#[method(name = "isBlockhashValid")]
+	async fn is_blockhash_valid(&self, blockhash: String, config: Option<RpcGenericConfigRequest>) -> RpcResult<RpcIsBlockhashValidResponse>;
+	
+	#[method(name = "getVersion")]
+	fn get_version(&self) -> RpcResult<RpcVersionResponse>;
+	/// Only ever lists the vote account synthesized for the identity. Nothing can be staked, so its stake is 0.
+	#[method(name = "getVoteAccounts")]
+	async fn get_vote_accounts(&self, config: Option<RpcGetVoteAccountsRequest>) -> RpcResult<RpcGetVoteAccountsResponse>;
+	#[method(name = "requestAirdrop")]
+	async fn request_airdrop(&self, pubkey: String, lamports: u64, _config: Option<RpcGenericConfigRequest>) -> RpcResult<String>;
+	#[method(name = "sendTransaction")]
+	async fn send_transaction(&self, tx_data: String, config: Option<RpcSendTransactionRequest>) -> RpcResult<String>;
+	#[method(name = "simulateTransaction")]
+	async fn simulate_transaction(&self, tx_data: String, config: Option<RpcSimulateTransactionRequest>) -> RpcResult<RpcSimulateTransactionResponse>;
+}
+
+pub struct SolanaDebuggerRpcImpl {
+	ledger: Arc<BokkenLedger>,
+	simulation_cache: Arc<std::sync::Mutex<SimulationCache>>,
+	fault_injector: Arc<BokkenFaultInjector>,
+	faucet: Arc<BokkenFaucet>,
+	airdrop_limiter: Arc<BokkenAirdropLimiter>
+}
+impl SolanaDebuggerRpcImpl {
+	fn new(
+		ledger: Arc<BokkenLedger>,
+		simulation_cache: Arc<std::sync::Mutex<SimulationCache>>,
+		fault_injector: Arc<BokkenFaultInjector>,
+		faucet: Arc<BokkenFaucet>,
+		airdrop_limiter: Arc<BokkenAirdropLimiter>
+	) -> Self {
+		Self {
+			ledger,
+			simulation_cache,
+			fault_injector,
+			faucet,
+			airdrop_limiter
+		}
+	}
+	async fn _request_airdrop(&self, pubkey: String, lamports: u64) -> Result<String, BokkenError> {
+		let pubkey = Pubkey::from_str(&pubkey)?;
+		self.airdrop_limiter.take_pubkey_quota(&pubkey, lamports)?;
+		let signature = self.faucet.request_airdrop(&self.ledger, &pubkey, lamports).await.map_err(|e| {
+			self.airdrop_limiter.refund_pubkey_quota(&pubkey, lamports);
+			e
+		})?;
+		Ok(signature.to_string())
+	}
+	async fn _get_signature_statuses(&self, sigs: Vec<String>, config: Option<RpcGetSignatureStatusesRequest>) -> Result<RpcGetSignatureStatusesResponse, BokkenError> {
+		let search_transaction_history = config.map_or(false, |config| config.search_transaction_history);
+		let snapshot = self.ledger.snapshot().await;
+		let mut result = Vec::new();
+		for sig in sigs {
+			let sig_bytes: [u8; 64] = bs58::decode(sig).into_vec()?.try_into().map_err(|_|{BokkenError::InvalidSignatureLength})?;
+			let entry = snapshot.get_bokken_entry_by_tx(sig_bytes).await?.filter(|data| {
+				search_transaction_history || snapshot.slot().saturating_sub(data.slot) <= SIGNATURE_STATUS_CACHE_SLOTS
+			});
+			if let Some(data) = entry {
+				result.push(Some(
+					RpcGetSignatureStatusesResponseValue {
+						slot: data.slot,
+						// Nothing can roll back a committed transaction, so it's rooted straight away. Real validators
+						// report no confirmation count for rooted transactions.
+						confirmations: None,
+						confirmation_status: RpcCommitment::Finalized,
+						err: data.tx_error.clone(),
+						status: data.tx_error.map_or(Ok(()), Err)
+					}
+				))
+			}else{
+				result.push(None)
+			}
+		}
+		Ok(
+			RpcGetSignatureStatusesResponse {
+				context: RpcResponseContext { slot: snapshot.slot() },
+				value: result
+			}
+		)
+	}
+	async fn _get_vote_accounts(&self, config: Option<RpcGetVoteAccountsRequest>) -> Result<RpcGetVoteAccountsResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let snapshot = self.ledger.snapshot().await;
+		let vote_pubkey = self.ledger.identity_vote_address();
+		let mut response = RpcGetVoteAccountsResponse {
+			current: Vec::new(),
+			delinquent: Vec::new()
+		};
+		if config.vote_pubkey.map_or(false, |requested| requested != vote_pubkey.to_string()) {
+			return Ok(response);
+		}
+		let vote_account = snapshot.read_account(&vote_pubkey).await?;
+		// Closed, or replaced with something which isn't a vote account
+		let Ok(vote_state) = VoteState::deserialize(&vote_account.data) else {
+			return Ok(response);
+		};
+		let last_vote = vote_state.last_voted_slot().unwrap_or_default();
+		let info = RpcVoteAccountInfo {
+			vote_pubkey: vote_pubkey.to_string(),
+			node_pubkey: vote_state.node_pubkey.to_string(),
+			activated_stake: 0,
+			commission: vote_state.commission,
+			epoch_vote_account: true,
+			epoch_credits: vote_state.epoch_credits.clone(),
+			last_vote,
+			root_slot: vote_state.root_slot.unwrap_or_default()
+		};
+		let delinquent_slot_distance = config.delinquent_slot_distance.unwrap_or(DELINQUENT_VALIDATOR_SLOT_DISTANCE);
+		if snapshot.slot().saturating_sub(last_vote) > delinquent_slot_distance {
+			if config.keep_unstaked_delinquents.unwrap_or(false) {
+				response.delinquent.push(info);
+			}
+		}else{
+			response.current.push(info);
+		}
+		Ok(response)
+	}
+	async fn _get_block_commitment(&self, slot: u64) -> Result<RpcGetBlockCommitmentResponse, BokkenError> {
+		let snapshot = self.ledger.snapshot().await;
+		let commitment = snapshot.get_block(slot).await?.map(|_| {
+			// All the stake has voted on the block at the deepest lockout
+			let mut commitment = vec![0; MAX_LOCKOUT_HISTORY + 1];
+			commitment[MAX_LOCKOUT_HISTORY] = BLOCK_COMMITMENT_TOTAL_STAKE;
+			commitment
+		});
+		Ok(
+			RpcGetBlockCommitmentResponse {
+				commitment,
+				total_stake: BLOCK_COMMITMENT_TOTAL_STAKE
+			}
+		)
+	}
+	async fn _get_block_production(&self, config: Option<RpcGetBlockProductionRequest>) -> Result<RpcGetBlockProductionResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let snapshot = self.ledger.snapshot().await;
+		let (first_slot, last_slot) = match config.range {
+			Some(range) => (range.first_slot, range.last_slot.unwrap_or(snapshot.slot())),
+			// Like a real validator, defaults to the current epoch so far
+			None => {
+				let epoch_schedule = self.ledger.epoch_schedule();
+				let epoch = epoch_schedule.get_epoch(snapshot.slot());
+				(epoch_schedule.get_first_slot_in_epoch(epoch), snapshot.slot())
+			}
+		};
+		if last_slot < first_slot {
+			return Err(BokkenError::InvalidBlockProductionRange(first_slot, last_slot));
+		}
+		let identity = self.ledger.identity().pubkey().to_string();
+		let mut by_identity = HashMap::new();
+		if config.identity.as_ref().map_or(true, |requested| *requested == identity) {
+			// Every slot after genesis has a block, blocks which haven't been produced yet don't count
+			let produced = (last_slot.min(snapshot.slot()) + 1).saturating_sub(first_slot.max(1));
+			by_identity.insert(identity, (produced, produced));
+		}
+		Ok(
+			RpcGetBlockProductionResponse {
+				context: RpcResponseContext { slot: snapshot.slot() },
+				value: RpcGetBlockProductionResponseValue {
+					by_identity,
+					range: RpcBlockProductionRange {
+						first_slot,
+						last_slot
+					}
+				}
+			}
+		)
+	}
+	async fn _get_block_time(&self, slot: u64) -> Result<i64, BokkenError> {
+		let snapshot = self.ledger.snapshot().await;
+		let (entry, _) = snapshot.get_block(slot).await?.ok_or(BokkenError::BlockNotAvailable(slot))?;
+		Ok(entry.timestamp)
+	}
+	async fn _get_block(&self, slot: u64, config: Option<RpcGetBlockRequest>) -> Result<RpcGetBlockResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let snapshot = self.ledger.snapshot().await;
+		let (entry, previous_blockhash) = snapshot.get_block(slot).await?.ok_or(BokkenError::BlockNotAvailable(slot))?;
+		let mut response = RpcGetBlockResponse {
+			blockhash: bs58::encode(entry.block_hash).into_string(),
+			previous_blockhash: bs58::encode(previous_blockhash).into_string(),
+			parent_slot: entry.slot.saturating_sub(1),
+			block_height: Some(entry.block_height),
+			block_time: Some(entry.timestamp),
+			transactions: None,
+			signatures: None,
+			rewards: config.rewards.unwrap_or(true).then(|| {
+				entry.rewards.iter().map(|reward| {
+					RpcReward {
+						pubkey: reward.pubkey.to_string(),
+						lamports: reward.lamports,
+						post_balance: reward.post_balance,
+						reward_type: Some(match reward.reward_type {
+							BokkenRewardType::Fee => RpcRewardType::Fee,
+							BokkenRewardType::Rent => RpcRewardType::Rent,
+							BokkenRewardType::Staking => RpcRewardType::Staking,
+							BokkenRewardType::Voting => RpcRewardType::Voting
+						}),
+						commission: None
+					}
+				}).collect()
+			})
+		};
+		match config.transaction_details {
+			RpcTransactionDetails::Full => {
+				let tx = &entry.tx_data;
+				let mut pre_balances = Vec::with_capacity(tx.message.account_keys.len());
+				let mut post_balances = Vec::with_capacity(tx.message.account_keys.len());
+				for pubkey in tx.message.account_keys.iter() {
+					pre_balances.push(self.ledger.read_account_at_slot(pubkey, entry.slot - 1, None).await?.lamports);
+					post_balances.push(self.ledger.read_account_at_slot(pubkey, entry.slot, None).await?.lamports);
+				}
+				response.transactions = Some(vec![
+					RpcBlockTransaction {
+						transaction: RPCBinaryEncodedString::from_bytes(&bincode::serialize(tx)?, config.encoding),
+						meta: RpcTransactionStatusMeta {
+							err: entry.tx_error.clone(),
+							status: entry.tx_error.clone().map_or(Ok(()), Err),
+							// Blocks saved before fees were recorded don't know theirs
+							fee: if entry.tx_fee > 0 {
+								entry.tx_fee
+							}else{
+								LAMPORTS_PER_SIGNATURE * tx.message.header.num_required_signatures as u64
+							},
+							pre_balances,
+							post_balances,
+							log_messages: entry.tx_logs.clone(),
+							return_data: entry.tx_return_data.as_ref().map(|(program_id, data)| {
+								RpcSimulateTransactionResponseReturnData {
+									program_id: program_id.to_string(),
+									data: RPCBinaryEncodedString::from_bytes(data, RpcBinaryEncoding::Base64)
+								}
+							}),
+							compute_units_consumed: 0,
+							inner_instructions: None,
+							pre_token_balances: None,
+							post_token_balances: None,
+							rewards: Vec::new(),
+							loaded_addresses: RpcLoadedAddresses::default()
+						},
+						version: config.max_supported_transaction_version.map(|_| "legacy".to_string())
+					}
+				]);
+			},
+			RpcTransactionDetails::Signatures => {
+				response.signatures = Some(vec![entry.tx_data.signatures[0].to_string()]);
+			},
+			RpcTransactionDetails::None => {}
+		}
+		Ok(response)
+	}
+	#[tracing::instrument(name = "rpc_request", skip_all, fields(method = "get_account_info"))]
+	async fn _get_account_info(&self, pubkey: String, config: Option<RpcGetAccountInfoRequest>) -> Result<RpcGetAccountInfoResponse, BokkenError> {
+		let pubkey = Pubkey::from_str(&pubkey)?;
+		let config = config.unwrap_or_default();
+		let snapshot = self.ledger.snapshot().await;
+		snapshot.check_min_context_slot(config.min_context_slot)?;
+		let data = snapshot.read_account(&pubkey).await?;
+		Ok(
+			RpcGetAccountInfoResponse {
+				context: RpcResponseContext { slot: snapshot.slot() },
+				value: if data.lamports == 0 {
+					// BokkenLedger returns fake data if the account doesn't exist, so we'll just return none here
+					None
+				}else{
+					Some(
+						RpcGetAccountInfoResponseValue {
+							lamports: data.lamports,
+							owner: data.owner.to_string(),
+							data: encode_account_data(&self.ledger, &data, config.encoding)?,
+							executable: data.executable,
+							rent_epoch: data.rent_epoch,
+						}
+					)
+				}
+			}
+		)
+	}
+	async fn _get_balance(&self, pubkey: String, config: Option<RpcGetBalanceRequest>) -> Result<RpcGetBalanceResponse, BokkenError> {
+		let pubkey = Pubkey::from_str(&pubkey)?;
+		let config = config.unwrap_or_default();
+		let snapshot = self.ledger.snapshot().await;
+		snapshot.check_min_context_slot(config.min_context_slot)?;
+		Ok(
+			RpcGetBalanceResponse {
+				context: RpcResponseContext { slot: snapshot.slot() },
+				value: snapshot.read_account(&pubkey).await?.lamports
+			}
+		)
+	}
+	#[tracing::instrument(name = "rpc_request", skip_all, fields(method = "send_transaction"))]
+	async fn _send_transaction(
+		&self,
+		tx_data: String,
+		config: Option<RpcSendTransactionRequest>
+	) -> Result<String, BokkenError> {
+		let config = config.unwrap_or_default();
+		// tx encoding has a default encoding type compared to everything else, woohoo!
+		let tx = decode_transaction(
+			&config.encoding.unwrap_or(RpcBinaryEncoding::Base58).decode_bytes(&tx_data)?
+		)?;
+
+		// Verify the message isn't garbage. Note how "skip preflight" is ignored. The transaction is executed on an
+		// overlay which is only committed if it succeeds, so failed transactions never touch the ledger either way.
+		let tx = sanitize_transaction(&self.ledger, tx, true).await?;
+		verify_signers(&tx)?;
+		if let Some(error) = self.fault_injector.transaction_failure() {
+			return Err(error);
+		}
+
+		let tx_sig = tx.signatures[0];
+		self.ledger.execute_transaction(tx, true).await?;
+		if config.wait_for_commitment.is_some() {
+			self.ledger.wait_for_transaction(tx_sig.into()).await?;
+		}
+		
+		// The documented response is to just reply with the tx signature, so we just do that
+		Ok(bs58::encode(tx_sig).into_string())
+	}
+	#[tracing::instrument(name = "rpc_request", skip_all, fields(method = "simulate_transaction"))]
+	async fn _simulate_transaction(
+		&self,
+		tx_data: String,
+		config: Option<RpcSimulateTransactionRequest>
+	) -> Result<RpcSimulateTransactionResponse, BokkenError> {
+		let config = config.unwrap_or_default();
+		let config_account_addresses = {
+			let mut config_account_addresses = Vec::new();
+			for pubkey_string in config.accounts.addresses.iter() {
+				config_account_addresses.push(Pubkey::from_str(pubkey_string)?);
+			}
+			config_account_addresses
+		};
+			
+		
+		// tx encoding has a default encoding type compared to everything else, woohoo!
+		let tx = decode_transaction(
+			&config.encoding.unwrap_or(RpcBinaryEncoding::Base58).decode_bytes(&tx_data)?
+		)?;
+
+		// Verify the message isn't garbage
+		let tx = sanitize_transaction(&self.ledger, tx, config.sig_verify).await?;
+		let signer_checks = if config.sig_verify {
+			Some(verify_signers(&tx)?)
+		}else{
+			None
+		};
+		if config.replace_recent_blockhash {
+			println!("Warning: simulate_transaction: config.replace_recent_blockhash not considered!");
+		}
+		
+		let ledger = &self.ledger;
+		let ixs = BokkenLedgerInstruction::from_transaction(&tx);
+
+		let message_hash = tx.message.hash();
+		let state_version = ledger.state_version();
+		// Everything is read as of this slot, so any transactions committed while we're simulating won't be seen
+		let snapshot_slot = ledger.slot().await;
+		let cached_result = self.simulation_cache.lock().unwrap().get(state_version, &message_hash);
+		let simulation_result = match cached_result {
+			Some(cached_result) => cached_result,
+			None => {
+				let simulation_result = simulate_instructions(ledger, &tx.message.account_keys[0], ixs, snapshot_slot).await?;
+				self.simulation_cache.lock().unwrap().insert(state_version, message_hash, simulation_result.clone());
+				simulation_result
+			}
+		};
+		let mut response = simulation_response(
+			simulation_result,
+			snapshot_slot,
+			&config_account_addresses,
+			config.accounts.encoding
+		)?;
+		response.value.bokken_diagnostics = signer_checks.map(|signer_checks| {
+			RpcSimulationDiagnostics {
+				signer_checks: signer_checks.into_iter().map(|check| {
+					RpcSignerCheck {
+						index: check.index,
+						pubkey: check.pubkey.to_string(),
+						status: check.status.to_string()
+					}
+				}).collect()
+			}
+		});
+		Ok(response)
+	}
+}
+
+/// Encodes the account's data as requested. With `jsonParsed`, the data is decoded using the schema registered for
+/// the account's owner, falling back to base64 if there is none or it doesn't fit.
+pub(crate) fn encode_account_data(
+	ledger: &BokkenLedger,
+	account: &BokkenAccountData,
+	encoding: RpcBinaryEncoding
+) -> Result<RpcAccountData, BokkenError> {
+	if matches!(encoding, RpcBinaryEncoding::JsonParsed) {
+		if let Some(schema) = ledger.account_schemas().get(&account.owner) {
+			if let Some((account_type, info)) = schema.decode(&account.data) {
+				return Ok(
+					RpcAccountData::Parsed(
+						RpcParsedAccountData {
+							program: schema.program_name().map_or_else(|| account.owner.to_string(), str::to_string),
+							parsed: RpcParsedAccountInfo {
+								account_type,
+								info
+							},
+							space: account.data.len() as u64
+						}
+					)
+				);
+			}
+		}
+	}
+	Ok(RpcAccountData::Binary(RPCBinaryEncodedString::from_account_data(&account.data, encoding)?))
+}
+
+/// Executes the instructions against the ledger as of `slot` without committing anything
+pub(crate) async fn simulate_instructions(
+	ledger: &BokkenLedger,
+	fee_payer: &Pubkey,
+	ixs: Vec<BokkenLedgerInstruction>,
+	slot: u64
+) -> Result<SimulationCacheEntry, BokkenError> {
+	// The overlay is simply dropped afterwards, so nothing gets committed
+	let mut overlay = AccountsOverlay::new(slot);
+	match ledger.execute_instructions(
+		fee_payer,
+		ixs,
+		BokkenLedgerAccountReturnChoice::All,
+		&mut overlay,
+		None
+	).await {
+		Ok((account_datas, logs, return_data)) => {
+			Ok(SimulationCacheEntry::Success { account_datas, logs, return_data })
+		},
+		Err(e) => {
+			match BokkenError::from(e) {
+				BokkenError::InstructionExecError(index, program_error, logs) => {
+					Ok(SimulationCacheEntry::InstructionError { index, program_error, logs })
+				},
+				e => Err(e)
+			}
+		}
+	}
+}
+
+/// Turns a simulation's outcome into a `simulateTransaction` response, including the states of `account_addresses`
+pub(crate) fn simulation_response(
+	simulation_result: SimulationCacheEntry,
+	snapshot_slot: u64,
+	account_addresses: &[Pubkey],
+	accounts_encoding: RpcBinaryEncoding
+) -> Result<RpcSimulateTransactionResponse, BokkenError> {
+	match simulation_result {
+		SimulationCacheEntry::Success { account_datas: states, logs, return_data } => {
+			Ok(
+				RpcSimulateTransactionResponse {
+					context: RpcResponseContext { slot: snapshot_slot },
+					value: RpcSimulateTransactionResponseValue {
+						err: None,
+						logs: Some(logs),
+						accounts: Some(account_addresses.iter().map(|pubkey| {
+							let state = states.get(pubkey).unwrap();
+							Ok(
+								RpcSimulateTransactionResponseAccounts{
+									lamports: state.lamports,
+									owner: state.owner.to_string(),
+									data: RPCBinaryEncodedString::from_account_data(&state.data, accounts_encoding)?,
+									executable: state.executable,
+									rent_epoch: state.rent_epoch,
+								}
+							)
+						}).collect::<Result<_, BokkenError>>()?),
+						units_consumed: Some(0),
+						return_data: return_data.map(|(program_id, data)| {
+							RpcSimulateTransactionResponseReturnData {
+								program_id: program_id.to_string(),
+								// Return data is always base64 encoded, regardless of the requested encoding
+								data: RPCBinaryEncodedString::from_bytes(&data, RpcBinaryEncoding::Base64)
+							}
+						}),
+						inner_instructions: None,
+						bokken_diagnostics: None
+					}
+				}
+			)
+		},
+		SimulationCacheEntry::InstructionError { index, program_error, logs } => {
+			Ok(
+				RpcSimulateTransactionResponse {
+					context: RpcResponseContext { slot: snapshot_slot },
+					value: RpcSimulateTransactionResponseValue {
+						err: Some(TransactionError::InstructionError(index as u8, match program_error {
+							// Why is there no "Into" definition for ProgramError -> InstructionError??
+							ProgramError::Custom(n) => InstructionError::Custom(n),
+							ProgramError::InvalidArgument => InstructionError::InvalidArgument,
+							ProgramError::InvalidInstructionData => InstructionError::InvalidInstructionData,
+							ProgramError::InvalidAccountData => InstructionError::InvalidAccountData,
+							ProgramError::AccountDataTooSmall => InstructionError::AccountDataTooSmall,
+							ProgramError::InsufficientFunds => InstructionError::InsufficientFunds,
+							ProgramError::IncorrectProgramId => InstructionError::IncorrectProgramId,
+							ProgramError::MissingRequiredSignature => InstructionError::MissingRequiredSignature,
+							ProgramError::AccountAlreadyInitialized => InstructionError::AccountAlreadyInitialized,
+							ProgramError::UninitializedAccount => InstructionError::UninitializedAccount,
+							ProgramError::NotEnoughAccountKeys => InstructionError::NotEnoughAccountKeys,
+							ProgramError::AccountBorrowFailed => InstructionError::AccountBorrowFailed,
+							ProgramError::MaxSeedLengthExceeded => InstructionError::MaxSeedLengthExceeded,
+							ProgramError::InvalidSeeds => InstructionError::InvalidSeeds,
+							ProgramError::BorshIoError(s) => InstructionError::BorshIoError(s),
+							ProgramError::AccountNotRentExempt => InstructionError::AccountNotRentExempt,
+							ProgramError::UnsupportedSysvar => InstructionError::UnsupportedSysvar,
+							ProgramError::IllegalOwner => InstructionError::IllegalOwner,
+							ProgramError::MaxAccountsDataSizeExceeded => InstructionError::MaxAccountsDataSizeExceeded,
+							ProgramError::InvalidRealloc => InstructionError::InvalidRealloc,
+						})),
+						logs: Some(logs),
+						accounts: None,
+						units_consumed: Some(0),
+						return_data: None, // todo
+						inner_instructions: None,
+						bokken_diagnostics: None
+					}
+				}
+			)
+		},
+	}
+}
+
+// Note that the trait name we use is `MyRpcServer`, not `MyRpc`!
+#[async_trait]
+impl SolanaDebuggerRpcServer for SolanaDebuggerRpcImpl {
+	async fn get_signature_statuses(&self, sigs: Vec<String>, config: Option<RpcGetSignatureStatusesRequest>) -> RpcResult<RpcGetSignatureStatusesResponse> {
+		Ok(self._get_signature_statuses(sigs, config).await?)
+	}
+	async fn get_account_info(&self, pubkey: String, config: Option<RpcGetAccountInfoRequest>) -> RpcResult<RpcGetAccountInfoResponse> {
+		Ok(self._get_account_info(pubkey, config).await?)
+	}
+	async fn get_balance(&self, pubkey: String, config: Option<RpcGetBalanceRequest>) -> RpcResult<RpcGetBalanceResponse> {
+		Ok(self._get_balance(pubkey, config).await?)
+	}
+	async fn get_block(&self, slot: u64, config: Option<RpcGetBlockRequest>) -> RpcResult<RpcGetBlockResponse> {
+		Ok(self._get_block(slot, config).await?)
+	}
+	async fn get_block_commitment(&self, slot: u64) -> RpcResult<RpcGetBlockCommitmentResponse> {
+		Ok(self._get_block_commitment(slot).await?)
+	}
+	async fn get_block_production(&self, config: Option<RpcGetBlockProductionRequest>) -> RpcResult<RpcGetBlockProductionResponse> {
+		Ok(self._get_block_production(config).await?)
+	}
+	async fn get_block_time(&self, slot: u64) -> RpcResult<i64> {
+		Ok(self._get_block_time(slot).await?)
+	}
+	async fn get_min_balance_for_rent_exemption(&self, size: u64, _config: Option<RpcGenericConfigRequest>) -> RpcResult<u64> {
+		Ok(self.ledger.calc_min_balance_for_rent_exemption(size))
+	}
+	async fn get_latest_blockhash(&self, _config: Option<RpcGetLatestBlockhashRequest>) -> RpcResult<RpcGetLatestBlockhashResponse> {
+		let snapshot = self.ledger.snapshot().await;
+		Ok(
+			RpcGetLatestBlockhashResponse {
+				context: RpcResponseContext {
+					slot: snapshot.slot()
+				},
+				value: RpcGetLatestBlockhashResponseValue {
+					blockhash: bs58::encode(snapshot.blockhash()).into_string(),
+					// Clients give up on confirming a transaction once the block height passes this
+					last_valid_block_height: self.ledger.last_valid_block_height(snapshot.slot())
+				}
+			}
+		)
+	}
+	async fn get_slot(&self, config: Option<RpcGenericConfigRequest>) -> RpcResult<u64> {
+		let snapshot = self.ledger.snapshot().await;
+		snapshot.check_min_context_slot(config.unwrap_or_default().min_context_slot)?;
+		Ok(snapshot.slot())
+	}
+	async fn is_blockhash_valid(&self, blockhash: String, config: Option<RpcGenericConfigRequest>) -> RpcResult<RpcIsBlockhashValidResponse> {
+		let snapshot = self.ledger.snapshot().await;
+		snapshot.check_min_context_slot(config.unwrap_or_default().min_context_slot)?;
+		let blockhash: [u8; 32] = bs58::decode(&blockhash).into_vec().ok()
+			.and_then(|blockhash| blockhash.try_into().ok())
+			.ok_or(BokkenError::InvalidBlockhash)?;
+		Ok(
+			RpcIsBlockhashValidResponse {
+				context: RpcResponseContext { slot: snapshot.slot() },
+				value: self.ledger.is_blockhash_valid(&blockhash, snapshot.slot())
+			}
+		)
+	}
+	async fn get_block_height(&self, _config: Option<RpcGetBalanceRequest>) -> RpcResult<u64> {
+		Ok(self.ledger.slot().await)
+	}
+	async fn get_epoch_info(&self, _config: Option<RpcGenericConfigRequest>) -> RpcResult<RpcGetEpochInfoResponse> {
+		let slot = self.ledger.slot().await;
+		let epoch_schedule = self.ledger.epoch_schedule();
+		let (epoch, slot_index) = epoch_schedule.get_epoch_and_slot_index(slot);
+		Ok(
+			RpcGetEpochInfoResponse {
+				absolute_slot: slot,
+				// Every slot has a block with exactly 1 transaction in it
+				block_height: slot,
+				epoch,
+				slot_index,
+				slots_in_epoch: epoch_schedule.get_slots_in_epoch(epoch),
+				transaction_count: Some(slot)
+			}
+		)
+	}
+	fn get_epoch_schedule(&self) -> RpcResult<RpcGetEpochScheduleResponse> {
+		let epoch_schedule = self.ledger.epoch_schedule();
+		Ok(
+			RpcGetEpochScheduleResponse {
+				slots_per_epoch: epoch_schedule.slots_per_epoch,
+				leader_schedule_slot_offset: epoch_schedule.leader_schedule_slot_offset,
+				warmup: epoch_schedule.warmup,
+				first_normal_epoch: epoch_schedule.first_normal_epoch,
+				first_normal_slot: epoch_schedule.first_normal_slot
+			}
+		)
+	}
+	fn get_genesis_hash(&self) -> RpcResult<String> {
+		Ok(bs58::encode(self.ledger.genesis_hash()).into_string())
+	}
+	fn get_identity(&self) -> RpcResult<RpcGetIdentityResponse> {
+		Ok(
+			RpcGetIdentityResponse {
+				identity: self.ledger.identity().pubkey().to_string()
+			}
+		)
+	}
+	async fn get_vote_accounts(&self, config: Option<RpcGetVoteAccountsRequest>) -> RpcResult<RpcGetVoteAccountsResponse> {
+		Ok(self._get_vote_accounts(config).await?)
+	}
+	fn get_version(&self) -> RpcResult<RpcVersionResponse> {
+		Ok(
+			RpcVersionResponse {
+				solana_core: "1.13.5+debug-validator-0.0.1".to_string(),
+				feature_set: self.ledger.feature_set().feature_set_id()
+			}
+		)
+	}
+	async fn request_airdrop(&self, pubkey: String, lamports: u64, _config: Option<RpcGenericConfigRequest>) -> RpcResult<String> {
+		Ok(self._request_airdrop(pubkey, lamports).await?)
+	}
+	async fn send_transaction(&self, tx_data: String, config: Option<RpcSendTransactionRequest>) -> RpcResult<String> {
+		Ok(self._send_transaction(tx_data, config).await?)
+	}
+	async fn simulate_transaction(
+		&self,
+		tx_data: String,
+		config: Option<RpcSimulateTransactionRequest>
+	) -> RpcResult<RpcSimulateTransactionResponse> {
+		Ok(self._simulate_transaction(tx_data, config).await?)
+	}
+}
+
+
+/// Builds the JSON-RPC methods for the ledger, including the `bokken_` admin methods.
+/// 
+/// Subscriptions are only registered if `with_subscriptions` is true.
+fn build_rpc_module(
+	ledger: Arc<BokkenLedger>,
+	simulation_cache: Arc<std::sync::Mutex<SimulationCache>>,
+	metrics: Arc<BokkenRpcMetrics>,
+	scheduler: Arc<BokkenScheduler>,
+	fault_injector: Arc<BokkenFaultInjector>,
+	faucet: Arc<BokkenFaucet>,
+	airdrop_limiter: Arc<BokkenAirdropLimiter>,
+	with_subscriptions: bool
+) -> eyre::Result<RpcModule<SolanaDebuggerRpcImpl>> {
+	let mut rpc_thing = SolanaDebuggerRpcImpl::new(
+		ledger.clone(),
+		simulation_cache,
+		fault_injector.clone(),
+		faucet,
+		airdrop_limiter
+	).into_rpc();
+	if with_subscriptions {
+		let subscriptions = Arc::new(BokkenSubscriptionManager::new(ledger.clone(), metrics.clone(), fault_injector.clone()));
+		let log_subscriptions = subscriptions.clone();
+		rpc_thing.register_subscription("signatureSubscribe", "signatureNotification", "signatureUnsubscribe", move |params, mut sink, _ctx| {
+			let sig = match params.parse::<(String, CommitmentConfig)>() {
+				Ok(x) => x,
+				Err(e) => {
+					eprint!("Couldn't parse subscription params: {}", e);
+					sink.reject(e)?;
+					return Ok(());
+				}
+			};
+			
+			let sig = match bs58::decode(sig.0).into_vec() {
+				Ok(x) => {
+					x
+				},
+				Err(e) => {
+					eprint!("Couldn't decode subscription sig: {}", e);
+					sink.reject(CallError::from_std_error(e))?;
+					return Ok(());
+				}
+			};
+			let sig: [u8; 64] = match sig.try_into() {
+				Ok(x) => {
+					x
+				},
+				Err(_) => {
+					eprint!("Couldn't try_into subscription sig");
+					sink.reject(CallError::from_std_error(BokkenError::InvalidSignatureLength))?;
+					return Ok(());
+				}
+			};
+			subscriptions.spawn_signature_subscription(sig, sink);
+			Ok(())
+		})?;
+		rpc_thing.register_subscription(
+			"bokken_getProgramLogsTail",
+			"bokken_programLogsTailNotification",
+			"bokken_programLogsTailUnsubscribe",
+			move |params, mut sink, _ctx| {
+				let config = match params.sequence().optional_next::<RpcProgramLogsTailRequest>() {
+					Ok(config) => config.unwrap_or_default(),
+					Err(e) => {
+						eprint!("Couldn't parse subscription params: {}", e);
+						sink.reject(e)?;
+						return Ok(());
+					}
+				};
+				let program_ids = match config.program_ids.map(|program_ids| {
+					program_ids.iter().map(|program_id| resolve_pubkey(program_id)).collect::<Result<HashSet<_>, _>>()
+				}).transpose() {
+					Ok(program_ids) => program_ids,
+					Err(e) => {
+						sink.reject(CallError::from_std_error(e))?;
+						return Ok(());
+					}
+				};
+				log_subscriptions.spawn_program_logs_subscription(
+					program_ids,
+					if config.include_debug { BokkenLogLevel::Debug } else { BokkenLogLevel::Info },
+					sink
+				);
+				Ok(())
+			}
+		)?;
+	}
+	rpc_thing.merge(BokkenAdminRpcImpl::new(metrics, ledger, scheduler, fault_injector).into_rpc())?;
+	Ok(rpc_thing)
+}
+
+/// Serves the ledger over JSON-RPC at `addr` and subscriptions at the port after it, until the servers stop.
+/// 
+/// If `instance_manager` is specified, requests to `/instances/<name>` are forwarded to the instance with that name,
+/// and the `bokken_*Instance` methods are available for managing them.
+/// 
+/// `fault_injector` is shared with the `bokken_setFaultInjection` method, so it can be pre-configured, e.g. with chaos.
+/// 
+/// `airdrop_limiter` decides how much `requestAirdrop` hands out to each recipient and client IP.
+/// 
+/// The subscription server uses `limits.max_ws_message_size` for both requests and responses, as everything it
+/// sends and receives are websocket messages.
+pub async fn start_endpoint(
+	addr: SocketAddr,
+	ledger: Arc<BokkenLedger>,
+	slow_call_threshold: Duration,
+	simulation_cache_size: usize,
+	limits: BokkenRpcServerLimits,
+	instance_manager: Option<Arc<BokkenInstanceManager>>,
+	fault_injector: Arc<BokkenFaultInjector>,
+	airdrop_limiter: Arc<BokkenAirdropLimiter>
+) -> eyre::Result<()> {
+	let simulation_cache = Arc::new(std::sync::Mutex::new(SimulationCache::new(simulation_cache_size)));
+	let faucet = Arc::new(BokkenFaucet::new());
+	let scheduler = BokkenScheduler::new(&ledger);
+	let rpc_logger = BokkenRpcLogger::new(Arc::new(BokkenRpcMetrics::new(slow_call_threshold)));
+	let router_layer = ServiceBuilder::new()
+		.layer(BokkenFaultInjectionLayer::new(fault_injector.clone(), limits.max_request_size))
+		.layer(BokkenInstanceRouterLayer::new(instance_manager.clone()))
+		// Instances have their own faucet, which isn't limited
+		.layer(BokkenAirdropLimitLayer::new(airdrop_limiter.clone()));
+	// No idea why these are handeled on seperate ports, but whatever.
+	let server2 = ServerBuilder::default()
+		.set_logger(rpc_logger.clone())
+		.max_request_body_size(limits.max_ws_message_size)
+		.max_response_body_size(limits.max_ws_message_size)
+		.max_connections(limits.max_connections)
+		.max_subscriptions_per_connection(limits.max_subscriptions_per_connection)
+		.build(
+		match &addr {
+			SocketAddr::V4(addr) => {
+				let mut new_addr = addr.clone();
+				new_addr.set_port(addr.port() + 1);
+				SocketAddr::V4(new_addr)
+			},
+			SocketAddr::V6(addr) => {
+				let mut new_addr = addr.clone();
+				new_addr.set_port(addr.port() + 1);
+				SocketAddr::V6(new_addr)
+			},
+		}
+	).await?;
+	let server_handle2 = server2.start(
+		build_rpc_module(
+			ledger.clone(),
+			simulation_cache.clone(),
+			rpc_logger.metrics(),
+			scheduler.clone(),
+			fault_injector.clone(),
+			faucet.clone(),
+			airdrop_limiter.clone(),
+			true
+		)?
+	)?;
+
+	let server = ServerBuilder::default()
+		.set_logger(rpc_logger.clone())
+		.max_request_body_size(limits.max_request_size)
+		.max_response_body_size(limits.max_response_size)
+		.max_connections(limits.max_connections)
+		.set_middleware(router_layer)
+		.build(addr)
+		.await?;
+	let server_handle = server.start(
+		{
+			let mut rpc_thing = build_rpc_module(
+				ledger.clone(),
+				simulation_cache.clone(),
+				rpc_logger.metrics(),
+				scheduler.clone(),
+				fault_injector.clone(),
+				faucet.clone(),
+				airdrop_limiter.clone(),
+				false
+			)?;
+			if let Some(instance_manager) = instance_manager {
+				rpc_thing.merge(BokkenInstanceRpcImpl::new(instance_manager).into_rpc())?;
+			}
+			rpc_thing
+		}
+	)?;
+	server_handle.stopped().await;
+	server_handle2.stopped().await;
+	println!("Server stopped");
+	Ok(())
+}
+
+/// Serves a ledger instance on a random local port, with both regular calls and subscriptions on the same port.
+/// 
+/// As websocket messages go through the same server, the larger of the request, response, and websocket message size
+/// limits apply.
+pub async fn start_instance_endpoint(
+	ledger: Arc<BokkenLedger>,
+	slow_call_threshold: Duration,
+	simulation_cache_size: usize,
+	limits: BokkenRpcServerLimits
+) -> eyre::Result<(SocketAddr, ServerHandle)> {
+	let simulation_cache = Arc::new(std::sync::Mutex::new(SimulationCache::new(simulation_cache_size)));
+	let scheduler = BokkenScheduler::new(&ledger);
+	let rpc_logger = BokkenRpcLogger::new(Arc::new(BokkenRpcMetrics::new(slow_call_threshold)));
+	let fault_injector = Arc::new(BokkenFaultInjector::new());
+	let max_request_size = limits.max_request_size.max(limits.max_ws_message_size);
+	let server = ServerBuilder::default()
+		.set_logger(rpc_logger.clone())
+		.max_request_body_size(max_request_size)
+		.max_response_body_size(limits.max_response_size.max(limits.max_ws_message_size))
+		.max_connections(limits.max_connections)
+		.max_subscriptions_per_connection(limits.max_subscriptions_per_connection)
+		.set_middleware(
+			ServiceBuilder::new().layer(BokkenFaultInjectionLayer::new(fault_injector.clone(), max_request_size))
+		)
+		.build(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+		.await?;
+	let addr = server.local_addr()?;
+	let server_handle = server.start(
+		build_rpc_module(
+			ledger,
+			simulation_cache,
+			rpc_logger.metrics(),
+			scheduler,
+			fault_injector,
+			Arc::new(BokkenFaucet::new()),
+			Arc::new(BokkenAirdropLimiter::default()),
+			true
+		)?
+	)?;
+	Ok((addr, server_handle))
+}
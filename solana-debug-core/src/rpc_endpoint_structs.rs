@@ -0,0 +1,1843 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::str::FromStr;
+
+use serde_with::{serde_as, DefaultOnNull};
+use solana_sdk::instruction::{Instruction, AccountMeta};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+
+use crate::debug_ledger::BokkenPreflightPreState;
+use crate::error::BokkenError;
+
+
+
+// start-common
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcBinaryEncoding {
+	Base64,
+	Base58,
+	#[serde(rename = "base64+zstd")]
+	Base64Compressed,
+	/// Only meaningful for account data, which is decoded if the account's owner has a schema registered, and
+	/// base64-encoded otherwise
+	#[serde(rename = "jsonParsed")]
+	JsonParsed
+}
+impl RpcBinaryEncoding {
+	pub fn decode_bytes(&self, data: &String) -> Result<Vec<u8>, BokkenError> {
+		match self {
+			RpcBinaryEncoding::Base58 => {
+				Ok(bs58::decode(data).into_vec()?)
+			}
+			RpcBinaryEncoding::Base64 => {
+				Ok(base64::decode(data)?)
+			}
+			RpcBinaryEncoding::Base64Compressed => {
+				Ok(
+					zstd::decode_all(
+						base64::decode(data)?.as_slice()
+					)?
+				)
+			},
+			RpcBinaryEncoding::JsonParsed => {
+				Err(BokkenError::Unimplemented)
+			}
+		}
+	}
+}
+impl Default for RpcBinaryEncoding {
+	fn default() -> Self {
+		Self::Base64
+	}
+}
+
+/// Real validators refuse to encode account data larger than this as base58, as it's slow and huge
+pub const MAX_BASE58_ACCOUNT_DATA_LEN: usize = 128;
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug, Clone)]
+pub struct RPCBinaryEncodedString (String, RpcBinaryEncoding);
+impl RPCBinaryEncodedString {
+	/// Like `from_bytes`, but errors like a real validator if base58 was requested for data which is too large
+	pub fn from_account_data(data: &[u8], encoding: RpcBinaryEncoding) -> Result<Self, BokkenError> {
+		if matches!(encoding, RpcBinaryEncoding::Base58) && data.len() > MAX_BASE58_ACCOUNT_DATA_LEN {
+			return Err(BokkenError::Base58DataTooLarge(MAX_BASE58_ACCOUNT_DATA_LEN));
+		}
+		Ok(Self::from_bytes(data, encoding))
+	}
+	pub fn from_bytes(data: &[u8], encoding: RpcBinaryEncoding) -> Self {
+		Self(
+			match &encoding {
+				RpcBinaryEncoding::Base64Compressed => {
+					// Compressed data is base64-encoded as it comes out, so it never exists as a separate buffer
+					let mut encoder = zstd::Encoder::new(
+						base64::write::EncoderStringWriter::new(base64::STANDARD),
+						0
+					).expect("zstd to not fail");
+					encoder.write_all(data).expect("zstd to not fail");
+					encoder.finish().expect("zstd to not fail").into_inner()
+				},
+				RpcBinaryEncoding::Base64 => {
+					base64::encode(data)
+				},
+				RpcBinaryEncoding::Base58 => {
+					bs58::encode(data).into_string()
+				},
+				// Same as what a real validator falls back to for data it can't parse
+				RpcBinaryEncoding::JsonParsed => {
+					return Self::from_bytes(data, RpcBinaryEncoding::Base64);
+				}
+			},
+			encoding
+		)
+	}
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcCommitment {
+	/// Older clients may still send the deprecated names
+	#[serde(alias = "max", alias = "root")]
+	Finalized,
+	#[serde(alias = "single", alias = "singleGossip")]
+	Confirmed,
+	#[serde(alias = "recent")]
+	Processed
+}
+impl Default for RpcCommitment {
+	fn default() -> Self {
+		Self::Finalized
+	}
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcResponseContext {
+	pub slot: u64
+}
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcDataSlice {
+	pub offset: usize,
+	pub length: usize
+}
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGenericConfigRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub commitment: RpcCommitment,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub min_context_slot: u64
+}
+// end-common
+
+// start-getAccountInfo
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetAccountInfoRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub commitment: RpcCommitment,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub encoding: RpcBinaryEncoding,
+	pub data_slice: Option<RpcDataSlice>,
+	#[serde(default)]
+	#[serde_as(deserialize_as  = "DefaultOnNull")]
+	pub min_context_slot: u64
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetAccountInfoResponse {
+	pub context: RpcResponseContext,
+	pub value: Option<RpcGetAccountInfoResponseValue>
+}
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetAccountInfoResponseValue {
+	pub lamports: u64,
+	pub owner: String,
+	pub data: RpcAccountData,
+	pub executable: bool,
+	pub rent_epoch: u64
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(untagged)]
+pub enum RpcAccountData {
+	Parsed(RpcParsedAccountData),
+	Binary(RPCBinaryEncodedString)
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcParsedAccountData {
+	/// The program's name according to its schema, or its ID if the schema doesn't name it
+	pub program: String,
+	pub parsed: RpcParsedAccountInfo,
+	pub space: u64
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcParsedAccountInfo {
+	/// Name of the account's type in the schema
+	#[serde(rename = "type")]
+	pub account_type: String,
+	pub info: serde_json::Value
+}
+
+// end-getAccountInfo
+
+// start-getBalance
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetBalanceRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub commitment: RpcCommitment,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub min_context_slot: u64
+}
+
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetBalanceResponse {
+	pub context: RpcResponseContext,
+	pub value: u64
+}
+// end-getBalance
+
+
+// start-isBlockhashValid
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcIsBlockhashValidResponse {
+	pub context: RpcResponseContext,
+	pub value: bool
+}
+// end-isBlockhashValid
+
+
+// start-getVersion
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct RpcVersionResponse {
+	pub solana_core: String,
+	pub feature_set: u32
+}
+// end-getVersion
+
+// start-getLatestBlockhash
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetLatestBlockhashRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub commitment: RpcCommitment,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub min_context_slot: u64
+}
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetLatestBlockhashResponse {
+	pub context: RpcResponseContext,
+	pub value: RpcGetLatestBlockhashResponseValue
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetLatestBlockhashResponseValue {
+	pub blockhash: String,
+	pub last_valid_block_height: u64
+}
+
+// end-getLatestBlockHash
+
+// start-sendTransaction
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSendTransactionRequest {
+	/// Accepted for compatibility, transactions are always executed before they're confirmed anyway
+	#[serde(default, alias = "skipVerify")]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub skip_preflight: bool,
+	#[serde(default, alias = "preFlightCommitment")]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub preflight_commitment: RpcCommitment,
+	pub encoding: Option<RpcBinaryEncoding>,
+	/// Accepted for compatibility, transactions are never retried as they're never dropped
+	pub max_retries: Option<usize>,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub min_context_slot: u64,
+	/// Bokken extension: only respond once the transaction has reached this commitment, so that reads sent after the
+	/// response see its changes. All commitments are reached at once, as committed transactions are final.
+	pub wait_for_commitment: Option<RpcCommitment>
+}
+/// Bokken extension: an account a failed transaction loaded, as it was before the transaction
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPreflightAccount {
+	pub pubkey: String,
+	pub lamports: u64,
+	pub owner: String,
+	pub data: RPCBinaryEncodedString,
+	pub executable: bool,
+	pub rent_epoch: u64
+}
+/// Bokken extension: attached to the error data of a failed transaction as `bokkenPreState` if
+/// `--preflight-pre-state-limit` is set
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPreflightPreState {
+	pub slot: u64,
+	pub accounts: Vec<RpcPreflightAccount>,
+	/// Accounts which were left out, as including them would've gone over the size limit
+	pub omitted_accounts: Vec<String>
+}
+impl From<&BokkenPreflightPreState> for RpcPreflightPreState {
+	fn from(pre_state: &BokkenPreflightPreState) -> Self {
+		Self {
+			slot: pre_state.slot,
+			accounts: pre_state.accounts.iter().map(|(pubkey, account)| {
+				RpcPreflightAccount {
+					pubkey: pubkey.to_string(),
+					lamports: account.lamports,
+					owner: account.owner.to_string(),
+					data: RPCBinaryEncodedString::from_bytes(&account.data, RpcBinaryEncoding::Base64),
+					executable: account.executable,
+					rent_epoch: account.rent_epoch
+				}
+			}).collect(),
+			omitted_accounts: pre_state.omitted.iter().map(|pubkey| pubkey.to_string()).collect()
+		}
+	}
+}
+//end-sendTransaction
+
+
+// start-simulateTransaction
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub sig_verify: bool,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub commitment: RpcCommitment,
+	pub encoding: Option<RpcBinaryEncoding>,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub replace_recent_blockhash: bool,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub accounts: RpcSimulateTransactionRequestAccounts,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub min_context_slot: u64,
+	/// Accepted for compatibility, inner instructions aren't recorded
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub inner_instructions: bool
+}
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionRequestAccounts {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub encoding: RpcBinaryEncoding,
+	#[serde(default)]
+	pub addresses: Vec<String>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionResponse {
+	pub context: RpcResponseContext,
+	pub value: RpcSimulateTransactionResponseValue
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionResponseValue {
+	pub err: Option<solana_sdk::transaction::TransactionError>,
+	pub logs: Option<Vec<String>>,
+	pub accounts: Option<Vec<RpcSimulateTransactionResponseAccounts>>,
+	pub units_consumed: Option<u64>,
+	pub return_data: Option<RpcSimulateTransactionResponseReturnData>,
+	/// Always null, inner instructions aren't recorded. Sent anyway, as stricter clients expect every field a real
+	/// validator sends.
+	pub inner_instructions: Option<Vec<serde_json::Value>>,
+	/// Not something a real validator returns, only present if `sigVerify` was set
+	#[serde(rename = "bokken_diagnostics", skip_serializing_if = "Option::is_none", default)]
+	pub bokken_diagnostics: Option<RpcSimulationDiagnostics>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulationDiagnostics {
+	pub signer_checks: Vec<RpcSignerCheck>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignerCheck {
+	pub index: usize,
+	pub pubkey: String,
+	/// "valid", "missing", or "invalid"
+	pub status: String
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionResponseAccounts {
+	pub lamports: u64,
+	pub owner: String,
+	pub data: RPCBinaryEncodedString,
+	pub executable: bool,
+	pub rent_epoch: u64
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionResponseReturnData {
+	pub program_id: String,
+	pub data: RPCBinaryEncodedString
+}
+// end-simulateTransaction
+
+
+// start-signatureSubscribe
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignatureSubscribeResponse {
+	pub context: RpcResponseContext,
+	pub value: RpcSignatureSubscribeResponseValue
+}
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignatureSubscribeResponseValue {
+	pub err: Option<TransactionError>
+}
+// start-signatureSubscribe
+
+
+
+// start-getSignatureStatusesRequest
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetSignatureStatusesRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub search_transaction_history: bool
+}
+
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetSignatureStatusesResponse {
+	pub context: RpcResponseContext,
+	pub value: Vec<Option<RpcGetSignatureStatusesResponseValue>>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetSignatureStatusesResponseValue {
+	pub slot: u64,
+	pub confirmations: Option<usize>,
+	pub confirmation_status: RpcCommitment,
+	pub err: Option<solana_sdk::transaction::TransactionError>,
+	/// Deprecated, but still sent by real validators as `{"Ok": null}` or `{"Err": ...}`
+	pub status: Result<(), solana_sdk::transaction::TransactionError>,
+}
+
+// end-getSignatureStatusesRequest
+
+
+// start-getBlock
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcTransactionDetails {
+	Full,
+	Signatures,
+	None
+}
+impl Default for RpcTransactionDetails {
+	fn default() -> Self {
+		Self::Full
+	}
+}
+
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetBlockRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub commitment: RpcCommitment,
+	/// Only binary encodings are supported, unlike a real validator which defaults to JSON
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub encoding: RpcBinaryEncoding,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub transaction_details: RpcTransactionDetails,
+	/// Whether to include `rewards` (Default: true)
+	pub rewards: Option<bool>,
+	pub max_supported_transaction_version: Option<u8>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcRewardType {
+	Fee,
+	Rent,
+	Staking,
+	Voting
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcReward {
+	pub pubkey: String,
+	pub lamports: i64,
+	pub post_balance: u64,
+	pub reward_type: Option<RpcRewardType>,
+	/// Only set for voting and staking rewards
+	pub commission: Option<u8>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetBlockResponse {
+	pub blockhash: String,
+	pub previous_blockhash: String,
+	pub parent_slot: u64,
+	pub block_height: Option<u64>,
+	pub block_time: Option<i64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub transactions: Option<Vec<RpcBlockTransaction>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub signatures: Option<Vec<String>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub rewards: Option<Vec<RpcReward>>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlockTransaction {
+	pub transaction: RPCBinaryEncodedString,
+	pub meta: RpcTransactionStatusMeta,
+	/// Always "legacy", only sent if `maxSupportedTransactionVersion` was set, like a real validator does
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub version: Option<String>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionStatusMeta {
+	pub err: Option<TransactionError>,
+	pub status: Result<(), TransactionError>,
+	pub fee: u64,
+	pub pre_balances: Vec<u64>,
+	pub post_balances: Vec<u64>,
+	pub log_messages: Vec<String>,
+	pub return_data: Option<RpcSimulateTransactionResponseReturnData>,
+	pub compute_units_consumed: u64,
+	/// Inner instructions and token balances aren't recorded, so these are always null
+	pub inner_instructions: Option<Vec<serde_json::Value>>,
+	pub pre_token_balances: Option<Vec<serde_json::Value>>,
+	pub post_token_balances: Option<Vec<serde_json::Value>>,
+	/// Rewards are only paid out per block
+	pub rewards: Vec<RpcReward>,
+	/// Always empty, as only legacy transactions can be executed
+	pub loaded_addresses: RpcLoadedAddresses
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLoadedAddresses {
+	pub writable: Vec<String>,
+	pub readonly: Vec<String>
+}
+// end-getBlock
+
+// start-getBlockCommitment
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetBlockCommitmentResponse {
+	/// Stake which has voted on the block at each lockout depth, null if the block is unknown
+	pub commitment: Option<Vec<u64>>,
+	pub total_stake: u64
+}
+// end-getBlockCommitment
+
+// start-getBlockProduction
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetBlockProductionRequestRange {
+	pub first_slot: u64,
+	pub last_slot: Option<u64>
+}
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetBlockProductionRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub commitment: RpcCommitment,
+	pub identity: Option<String>,
+	pub range: Option<RpcGetBlockProductionRequestRange>
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBlockProductionRange {
+	pub first_slot: u64,
+	pub last_slot: u64
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetBlockProductionResponseValue {
+	/// Identity -> (leader slots, blocks produced)
+	pub by_identity: HashMap<String, (u64, u64)>,
+	pub range: RpcBlockProductionRange
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetBlockProductionResponse {
+	pub context: RpcResponseContext,
+	pub value: RpcGetBlockProductionResponseValue
+}
+// end-getBlockProduction
+
+// start-getEpochInfo
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetEpochInfoResponse {
+	pub absolute_slot: u64,
+	pub block_height: u64,
+	pub epoch: u64,
+	pub slot_index: u64,
+	pub slots_in_epoch: u64,
+	pub transaction_count: Option<u64>
+}
+// end-getEpochInfo
+
+// start-getEpochSchedule
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetEpochScheduleResponse {
+	pub slots_per_epoch: u64,
+	pub leader_schedule_slot_offset: u64,
+	pub warmup: bool,
+	pub first_normal_epoch: u64,
+	pub first_normal_slot: u64
+}
+// end-getEpochSchedule
+
+// start-getVoteAccounts
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetVoteAccountsRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub commitment: RpcCommitment,
+	pub vote_pubkey: Option<String>,
+	pub keep_unstaked_delinquents: Option<bool>,
+	pub delinquent_slot_distance: Option<u64>
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteAccountInfo {
+	pub vote_pubkey: String,
+	pub node_pubkey: String,
+	pub activated_stake: u64,
+	pub commission: u8,
+	pub epoch_vote_account: bool,
+	/// Epoch, credits, previous credits
+	pub epoch_credits: Vec<(u64, u64, u64)>,
+	pub last_vote: u64,
+	pub root_slot: u64
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetVoteAccountsResponse {
+	pub current: Vec<RpcVoteAccountInfo>,
+	pub delinquent: Vec<RpcVoteAccountInfo>
+}
+// end-getVoteAccounts
+
+// start-getIdentity
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetIdentityResponse {
+	pub identity: String
+}
+// end-getIdentity
+
+
+// start-bokken_getMetrics
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMethodMetrics {
+	pub calls: u64,
+	pub errors: u64,
+	pub total_duration_us: u64,
+	pub max_duration_us: u64,
+	/// Call count for each entry in `latencyBucketsMs`, with an extra entry at the end for slower calls
+	pub latency_histogram: Vec<u64>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetMetricsResponse {
+	pub latency_buckets_ms: Vec<u64>,
+	pub rpc_methods: HashMap<String, RpcMethodMetrics>,
+	/// Amount of active websocket subscriptions for each subscription method
+	pub active_subscriptions: HashMap<String, u64>,
+	/// Every debuggable program launched along with Bokken
+	pub program_processes: Vec<RpcProgramProcess>
+}
+// end-bokken_getMetrics
+
+
+// start-bokken_forkSendTransaction
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcForkSendTransactionResponse {
+	pub signature: String,
+	pub logs: Vec<String>
+}
+// end-bokken_forkSendTransaction
+
+
+// start-bokken_createInstance
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCreateInstanceRequest {
+	/// Defaults to the main ledger's initial mint if not specified
+	pub initial_mint: Option<String>,
+	pub initial_mint_lamports: Option<u64>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcInstanceInfo {
+	pub name: String,
+	/// Path on the main JSON-RPC server which requests are forwarded to this instance from
+	pub path: String,
+	/// Address of the instance's own JSON-RPC server, which also accepts subscriptions
+	pub rpc_address: String,
+	pub socket_path: String
+}
+// end-bokken_createInstance
+
+// start-bokken_destroyInstance
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcDestroyInstanceRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub delete_data: bool
+}
+// end-bokken_destroyInstance
+
+
+// start-bokken_scheduleTransaction
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountMeta {
+	pub pubkey: String,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub is_signer: bool,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub is_writable: bool
+}
+
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcInstruction {
+	pub program_id: String,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub accounts: Vec<RpcAccountMeta>,
+	pub data: String,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub encoding: RpcBinaryEncoding
+}
+impl RpcInstruction {
+	pub fn to_instruction(&self) -> Result<Instruction, BokkenError> {
+		let mut accounts = Vec::with_capacity(self.accounts.len());
+		for meta in self.accounts.iter() {
+			accounts.push(
+				AccountMeta {
+					pubkey: Pubkey::from_str(&meta.pubkey)?,
+					is_signer: meta.is_signer,
+					is_writable: meta.is_writable
+				}
+			);
+		}
+		Ok(
+			Instruction {
+				program_id: Pubkey::from_str(&self.program_id)?,
+				accounts,
+				data: self.encoding.decode_bytes(&self.data)?
+			}
+		)
+	}
+}
+
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcScheduleTransactionRequest {
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub name: String,
+	pub every_slots: u64,
+	pub instructions: Vec<RpcInstruction>,
+	/// 64-byte keypair in the same format as keypair files created by `solana-keygen`
+	pub signer: Vec<u8>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcScheduledTransactionInfo {
+	pub id: u64,
+	pub name: String,
+	pub every_slots: u64,
+	pub signer: String,
+	pub next_slot: u64,
+	pub successes: u64,
+	pub failures: u64
+}
+// end-bokken_scheduleTransaction
+
+
+// start-bokken_createFundedKeypair
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCreateFundedKeypairResponse {
+	pub pubkey: String,
+	/// 64-byte keypair in the same format as keypair files created by `solana-keygen`
+	pub secret_key: Vec<u8>
+}
+// end-bokken_createFundedKeypair
+
+
+// start-bokken_buildAndSendTransaction
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBuildAndSendTransactionRequest {
+	pub instructions: Vec<RpcInstruction>,
+	/// Pays the fee. Its keypair, along with the keypair of every other signer the instructions need, must be in the
+	/// ledger's keystore, e.g. because it was created with `bokken_createFundedKeypair`.
+	pub payer: String
+}
+// end-bokken_buildAndSendTransaction
+
+
+// start-bokken_signTransaction
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignTransactionRequest {
+	/// Used for both the provided and the returned transaction
+	pub encoding: Option<RpcBinaryEncoding>,
+	/// Signers to sign for, each of which must have its keypair in the ledger's keystore. If not set, every signer
+	/// which hasn't signed yet is signed for if its keypair is in the keystore, and left unsigned otherwise.
+	pub signers: Option<Vec<String>>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignTransactionResponse {
+	pub transaction: RPCBinaryEncodedString,
+	/// Signers which were signed for
+	pub signed: Vec<String>,
+	/// Signers which still haven't signed the transaction
+	pub missing_signers: Vec<String>
+}
+// end-bokken_signTransaction
+
+
+// start-bokken_lintTransaction
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLintTransactionRequest {
+	pub encoding: Option<RpcBinaryEncoding>,
+	/// Only do checks which don't require executing the transaction
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub skip_simulation: bool
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum RpcLintIssue {
+	#[serde(rename_all = "camelCase")]
+	MissingSignature {
+		account: String
+	},
+	#[serde(rename_all = "camelCase")]
+	InvalidSignature {
+		account: String
+	},
+	/// The account ends up with less lamports than needed to be rent exempt
+	#[serde(rename_all = "camelCase")]
+	NotRentExempt {
+		instruction_index: Option<usize>,
+		account: String,
+		lamports: u64,
+		minimum_lamports: u64
+	},
+	/// The account was changed even though the transaction doesn't mark it as writable
+	#[serde(rename_all = "camelCase")]
+	UnmarkedWrite {
+		account: String
+	},
+	#[serde(rename_all = "camelCase")]
+	TransactionTooLarge {
+		size: u64,
+		limit: u64
+	},
+	#[serde(rename_all = "camelCase")]
+	TooManyAccounts {
+		count: usize,
+		limit: usize
+	},
+	#[serde(rename_all = "camelCase")]
+	SimulationFailed {
+		message: String
+	}
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLintTransactionResponse {
+	pub context: RpcResponseContext,
+	pub issues: Vec<RpcLintIssue>
+}
+// end-bokken_lintTransaction
+
+
+// start-bokken_getTransactionAccountDiff
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetTransactionAccountDiffRequest {
+	pub encoding: Option<RpcBinaryEncoding>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountDataChange {
+	pub offset: usize,
+	/// Empty if the data was shorter than `offset` before the transaction
+	pub pre: RPCBinaryEncodedString,
+	/// Empty if the data is shorter than `offset` after the transaction
+	pub post: RPCBinaryEncodedString
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountDiff {
+	pub pubkey: String,
+	pub pre_lamports: u64,
+	pub post_lamports: u64,
+	pub pre_owner: String,
+	pub post_owner: String,
+	pub pre_data_len: usize,
+	pub post_data_len: usize,
+	pub data_changes: Vec<RpcAccountDataChange>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetTransactionAccountDiffResponse {
+	pub context: RpcResponseContext,
+	/// None if the transaction wasn't found
+	pub value: Option<Vec<RpcAccountDiff>>
+}
+// end-bokken_getTransactionAccountDiff
+
+// start-bokken_getTransactionCrashReport
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramPanic {
+	pub program_id: String,
+	pub call_depth: u8,
+	pub message: String,
+	pub backtrace: String
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionCrashReport {
+	/// The slot the transaction was executed against
+	pub slot: u64,
+	pub error: String,
+	/// In the order the programs panicked, so a CPI's panic comes before the one of its caller
+	pub panics: Vec<RpcProgramPanic>,
+	pub logs: Vec<String>
+}
+// end-bokken_getTransactionCrashReport
+
+// start-bokken_searchLogs
+fn default_search_logs_limit() -> usize {
+	100
+}
+
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSearchLogsRequest {
+	/// Only search transactions which invoked this program
+	pub program_id: Option<String>,
+	/// Only return log lines containing this text
+	pub contains: Option<String>,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub min_slot: u64,
+	pub max_slot: Option<u64>,
+	/// Capped at 1000
+	#[serde(default = "default_search_logs_limit")]
+	pub limit: usize
+}
+impl Default for RpcSearchLogsRequest {
+	fn default() -> Self {
+		Self {
+			program_id: None,
+			contains: None,
+			min_slot: 0,
+			max_slot: None,
+			limit: default_search_logs_limit()
+		}
+	}
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLogSearchResult {
+	pub slot: u64,
+	pub signature: String,
+	pub err: Option<TransactionError>,
+	pub logs: Vec<String>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSearchLogsResponse {
+	pub context: RpcResponseContext,
+	pub value: Vec<RpcLogSearchResult>,
+	/// Pass this as `minSlot` to get the next page. None if there are no more results.
+	pub next_min_slot: Option<u64>
+}
+// end-bokken_searchLogs
+
+// start-bokken_getProgramStats
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramStats {
+	pub program_id: String,
+	pub invocations: u64,
+	pub failures: u64,
+	pub failure_rate: f64,
+	/// Top-level instructions have a depth of 0
+	pub average_cpi_depth: f64,
+	pub account_data_bytes: u64
+}
+// end-bokken_getProgramStats
+
+// start-bokken_listPrograms
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcDebuggableProgram {
+	pub program_id: String,
+	pub name: Option<String>,
+	pub transport: String,
+	pub uptime_ms: u64,
+	pub pending_invokes: u64,
+	/// Time since the program last sent anything, including heartbeats
+	pub last_seen_ms: u64,
+	pub healthy: bool,
+	pub account_layout: RpcProgramAccountLayout,
+	/// Only set for programs launched along with Bokken. Launched programs which aren't connected are listed too, with
+	/// `healthy` being false and every connection stat being 0.
+	pub process: Option<RpcProgramProcess>
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcProgramProcessState {
+	/// Running, but not connected yet
+	Starting,
+	Ready,
+	/// Exited unsuccessfully or was killed, and may be waiting to be restarted
+	Crashed,
+	/// Exited successfully
+	Exited
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramProcess {
+	pub program_id: String,
+	pub pid: Option<u32>,
+	pub state: RpcProgramProcessState,
+	pub restart_count: u64,
+	/// None if it hasn't exited yet, or was killed by a signal
+	pub last_exit_code: Option<i32>,
+	/// How long the current process has been running, None if it isn't
+	pub uptime_ms: Option<u64>
+}
+// end-bokken_listPrograms
+
+// start-bokken_setProgramAccountLayout
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcProgramAccountLayout {
+	/// Owned by Bokken's own loader, with placeholder data
+	Debuggable,
+	/// Program and program data accounts owned by the upgradeable BPF loader
+	UpgradeableLoader,
+	/// A single program account owned by loader-v4
+	LoaderV4
+}
+// end-bokken_setProgramAccountLayout
+
+// start-bokken_setFaultInjection
+/// Probabilities are between 0 and 1
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcFaultInjectionConfig {
+	/// Added to the response time of every HTTP request
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub latency_ms: u64,
+	/// Up to this much extra latency is randomly added
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub latency_jitter_ms: u64,
+	/// Chance of an HTTP request being answered with 429 Too Many Requests
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub rate_limit_probability: f64,
+	/// Chance of a websocket notification never being sent
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub dropped_notification_probability: f64,
+	/// Chance of `sendTransaction` failing with `BlockhashNotFound` without executing the transaction
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub blockhash_expired_probability: f64
+}
+// end-bokken_setFaultInjection
+
+// start-bokken_setRent
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcRentConfig {
+	/// 0 makes every account rent-exempt
+	pub lamports_per_byte_year: u64,
+	pub exemption_threshold: f64
+}
+// end-bokken_setRent
+
+// start-bokken_getFeatureActivation
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcFeatureActivation {
+	pub id: String,
+	/// Only known for features which solana-sdk knows about
+	pub description: Option<String>,
+	/// None if the feature is inactive
+	pub activated_at: Option<u64>
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetFeatureActivationResponse {
+	/// What getVersion reports as the `feature-set`
+	pub feature_set: u32,
+	pub features: Vec<RpcFeatureActivation>
+}
+// end-bokken_getFeatureActivation
+
+// start-bokken_setTimeModel
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcTimeModelType {
+	RealTime,
+	SlotDerived,
+	Manual
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTimeModelConfig {
+	#[serde(rename = "type")]
+	pub model_type: RpcTimeModelType,
+	/// Only used by `slotDerived` (Default: 400)
+	pub slot_duration_ms: Option<u64>,
+	/// Timestamp of the current slot, only used by `slotDerived` and `manual` (Default: the current timestamp)
+	pub unix_timestamp: Option<i64>
+}
+// end-bokken_setTimeModel
+
+// start-bokken_getLedgerInfo
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcGetLedgerInfoResponse {
+	pub context: RpcResponseContext,
+	/// Accounts which currently hold any lamports
+	pub account_count: u64,
+	/// Accounts which no longer hold any lamports, but still have their history on disk
+	pub zero_lamport_account_count: u64,
+	/// Saved account states across all slots
+	pub account_version_count: u64,
+	pub account_data_bytes: u64,
+	pub block_count: u64,
+	pub accounts_disk_bytes: u64,
+	pub blocks_disk_bytes: u64,
+	pub transaction_index_disk_bytes: u64,
+	pub log_index_disk_bytes: u64,
+	pub total_disk_bytes: u64
+}
+// end-bokken_getLedgerInfo
+
+// start-bokken_compactAccounts
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCompactAccountsResponse {
+	pub removed_accounts: u64,
+	pub freed_bytes: u64
+}
+// end-bokken_compactAccounts
+
+// start-bokken_getCompactionStatus
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCompactionStatus {
+	pub running: bool,
+	pub percent_done: u64,
+	pub total_accounts: u64,
+	pub processed_accounts: u64,
+	pub removed_accounts: u64,
+	pub freed_bytes: u64,
+	pub started_at: Option<i64>,
+	pub finished_at: Option<i64>,
+	pub error: Option<String>
+}
+// end-bokken_getCompactionStatus
+
+// start-bokken_getSlotDependencyGraph
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcDependencyGraphNode {
+	pub signature: String,
+	pub slot: u64,
+	pub writable_accounts: Vec<String>
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcDependencyGraphEdge {
+	/// Index of the earlier transaction in `nodes`
+	pub from: usize,
+	pub to: usize,
+	/// The writable accounts both transactions use
+	pub accounts: Vec<String>
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSlotDependencyGraph {
+	pub context: RpcResponseContext,
+	pub nodes: Vec<RpcDependencyGraphNode>,
+	pub edges: Vec<RpcDependencyGraphEdge>,
+	/// The same graph in graphviz's DOT language
+	pub dot: String
+}
+// end-bokken_getSlotDependencyGraph
+
+// start-bokken_simulateTransactionUnchecked
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionUncheckedRequest {
+	pub encoding: Option<RpcBinaryEncoding>,
+	/// Accounts which are treated as having signed the transaction, whether or not the message says they're signers
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub signers: Vec<String>,
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub accounts: RpcSimulateTransactionRequestAccounts
+}
+// end-bokken_simulateTransactionUnchecked
+
+// start-bokken_patchAccountData
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPatchAccountDataRequest {
+	/// Encoding of the bytes, base64 by default
+	pub encoding: Option<RpcBinaryEncoding>
+}
+// end-bokken_patchAccountData
+
+// start-bokken_createProgramOwnedAccount
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCreateProgramOwnedAccountRequest {
+	/// Lamports to fund the account with, anything below the rent-exempt minimum is raised to it
+	pub lamports: Option<u64>,
+	/// Create the account at the program address derived from these seeds rather than a random address
+	pub seeds: Option<Vec<String>>,
+	/// Encoding of the seeds, base64 by default
+	pub seed_encoding: Option<RpcBinaryEncoding>
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCreateProgramOwnedAccountResponse {
+	pub pubkey: String,
+	/// Only set if the account is a program address
+	pub bump: Option<u8>,
+	pub lamports: u64
+}
+// end-bokken_createProgramOwnedAccount
+
+// start-bokken_findProgramAddress
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramAddress {
+	pub pubkey: String,
+	pub bump: u8
+}
+// end-bokken_findProgramAddress
+
+// start-bokken_registerAccountSchema
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcAccountSchemaFormat {
+	/// Accounts start with Anchor's 8-byte discriminator
+	Anchor,
+	/// Accounts are plain Borsh without a discriminator
+	Borsh
+}
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcRegisterAccountSchemaRequest {
+	/// (Default: anchor)
+	pub format: Option<RpcAccountSchemaFormat>
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountSchemaInfo {
+	pub program_id: String,
+	pub program_name: Option<String>,
+	pub format: RpcAccountSchemaFormat,
+	pub account_types: Vec<String>
+}
+// end-bokken_registerAccountSchema
+
+// start-bokken_getProgramLogsTail
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramLogsTailRequest {
+	/// Only stream logs from these programs, every debuggable program if not specified
+	pub program_ids: Option<Vec<String>>,
+	/// Also stream the Bokken runtime's own messages, such as unsupported syscall warnings
+	#[serde(default)]
+	#[serde_as(deserialize_as = "DefaultOnNull")]
+	pub include_debug: bool
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramLogsTailNotification {
+	/// `None` for messages from Bokken itself, e.g. when logs had to be skipped
+	pub program_id: Option<String>,
+	/// "debug", "info", or "warning"
+	pub level: String,
+	pub message: String
+}
+// end-bokken_getProgramLogsTail
+
+// start-bokken_getAccountAliases
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountAlias {
+	pub name: String,
+	pub pubkey: String
+}
+// end-bokken_getAccountAliases
+
+#[cfg(test)]
+mod tests {
+	// Responses are compared against the example responses in Solana's JSON-RPC docs, so a change to what Bokken sends
+	// shows up here. Where Bokken knowingly differs from the docs, the difference is noted next to the field.
+	use serde_json::json;
+	use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+	use super::*;
+
+	fn assert_serializes_to<T: serde::Serialize>(value: &T, expected: serde_json::Value) {
+		assert_eq!(serde_json::to_value(value).unwrap(), expected);
+	}
+
+	#[test]
+	fn get_account_info_response() {
+		let data = "11116bv5nS2h3y12kD1yUKeMZvGcKLSjQgX6BeV7u1FrjeJcKfsHRTPuR3oZ1EioKtYGiYxpxMG5vpbZLsbcBYBEmZZcMKaSoGx9JZeAuWf";
+		assert_serializes_to(
+			&RpcGetAccountInfoResponse {
+				context: RpcResponseContext { slot: 1 },
+				value: Some(
+					RpcGetAccountInfoResponseValue {
+						lamports: 1000000000,
+						owner: "11111111111111111111111111111111".to_string(),
+						data: RpcAccountData::Binary(RPCBinaryEncodedString(data.to_string(), RpcBinaryEncoding::Base58)),
+						executable: false,
+						rent_epoch: 2
+					}
+				)
+			},
+			json!({
+				"context": {"slot": 1},
+				"value": {
+					"data": [data, "base58"],
+					"executable": false,
+					"lamports": 1000000000,
+					"owner": "11111111111111111111111111111111",
+					"rentEpoch": 2
+				}
+			})
+		);
+		assert_serializes_to(
+			&RpcGetAccountInfoResponse { context: RpcResponseContext { slot: 1 }, value: None },
+			json!({"context": {"slot": 1}, "value": null})
+		);
+	}
+
+	#[test]
+	fn account_data_encodings() {
+		assert_serializes_to(
+			&RPCBinaryEncodedString::from_bytes(&[42, 0, 0, 0, 0, 0, 0, 0], RpcBinaryEncoding::Base64),
+			json!(["KgAAAAAAAAA=", "base64"])
+		);
+		assert_serializes_to(
+			&RPCBinaryEncodedString::from_bytes(&[42], RpcBinaryEncoding::Base58),
+			json!(["j", "base58"])
+		);
+		// Nothing has a schema here, so this falls back to base64 like a real validator does
+		assert_serializes_to(
+			&RPCBinaryEncodedString::from_bytes(&[42], RpcBinaryEncoding::JsonParsed),
+			json!(["Kg==", "base64"])
+		);
+		assert!(RPCBinaryEncodedString::from_account_data(&[0; MAX_BASE58_ACCOUNT_DATA_LEN + 1], RpcBinaryEncoding::Base58).is_err());
+	}
+
+	#[test]
+	fn get_balance_response() {
+		assert_serializes_to(
+			&RpcGetBalanceResponse { context: RpcResponseContext { slot: 1 }, value: 0 },
+			json!({"context": {"slot": 1}, "value": 0})
+		);
+	}
+
+	#[test]
+	fn get_latest_blockhash_response() {
+		assert_serializes_to(
+			&RpcGetLatestBlockhashResponse {
+				context: RpcResponseContext { slot: 2792 },
+				value: RpcGetLatestBlockhashResponseValue {
+					blockhash: "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N".to_string(),
+					last_valid_block_height: 3090
+				}
+			},
+			json!({
+				"context": {"slot": 2792},
+				"value": {
+					"blockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+					"lastValidBlockHeight": 3090
+				}
+			})
+		);
+	}
+
+	#[test]
+	fn get_version_response() {
+		assert_serializes_to(
+			&RpcVersionResponse { solana_core: "1.14.17".to_string(), feature_set: 2891131721 },
+			json!({"solana-core": "1.14.17", "feature-set": 2891131721u32})
+		);
+	}
+
+	#[test]
+	fn get_epoch_info_response() {
+		assert_serializes_to(
+			&RpcGetEpochInfoResponse {
+				absolute_slot: 166598,
+				block_height: 166500,
+				epoch: 27,
+				slot_index: 2790,
+				slots_in_epoch: 8192,
+				transaction_count: Some(22661093)
+			},
+			json!({
+				"absoluteSlot": 166598,
+				"blockHeight": 166500,
+				"epoch": 27,
+				"slotIndex": 2790,
+				"slotsInEpoch": 8192,
+				"transactionCount": 22661093
+			})
+		);
+	}
+
+	#[test]
+	fn get_signature_statuses_response() {
+		assert_serializes_to(
+			&RpcGetSignatureStatusesResponse {
+				context: RpcResponseContext { slot: 82 },
+				value: vec![
+					Some(
+						RpcGetSignatureStatusesResponseValue {
+							slot: 48,
+							confirmations: None,
+							confirmation_status: RpcCommitment::Finalized,
+							err: None,
+							status: Ok(())
+						}
+					),
+					Some(
+						RpcGetSignatureStatusesResponseValue {
+							slot: 49,
+							confirmations: None,
+							confirmation_status: RpcCommitment::Finalized,
+							err: Some(TransactionError::InstructionError(0, InstructionError::Custom(1))),
+							status: Err(TransactionError::InstructionError(0, InstructionError::Custom(1)))
+						}
+					),
+					None
+				]
+			},
+			json!({
+				"context": {"slot": 82},
+				"value": [
+					{
+						"slot": 48,
+						"confirmations": null,
+						"err": null,
+						"status": {"Ok": null},
+						"confirmationStatus": "finalized"
+					},
+					{
+						"slot": 49,
+						"confirmations": null,
+						"err": {"InstructionError": [0, {"Custom": 1}]},
+						"status": {"Err": {"InstructionError": [0, {"Custom": 1}]}},
+						"confirmationStatus": "finalized"
+					},
+					null
+				]
+			})
+		);
+	}
+
+	#[test]
+	fn simulate_transaction_response() {
+		let program_id = "83astBRguLMdt2h5U1Tpdq5tjFoJ6noeGwaY3mDLVcri";
+		let logs = vec![
+			format!("Program {} invoke [1]", program_id),
+			format!("Program {} consumed 2366 of 1400000 compute units", program_id),
+			format!("Program return: {} KgAAAAAAAAA=", program_id),
+			format!("Program {} success", program_id)
+		];
+		assert_serializes_to(
+			&RpcSimulateTransactionResponse {
+				context: RpcResponseContext { slot: 218 },
+				value: RpcSimulateTransactionResponseValue {
+					err: None,
+					logs: Some(logs.clone()),
+					accounts: None,
+					units_consumed: Some(2366),
+					return_data: Some(
+						RpcSimulateTransactionResponseReturnData {
+							program_id: program_id.to_string(),
+							data: RPCBinaryEncodedString::from_bytes(&[42], RpcBinaryEncoding::Base64)
+						}
+					),
+					inner_instructions: None,
+					bokken_diagnostics: None
+				}
+			},
+			json!({
+				"context": {"slot": 218},
+				"value": {
+					"err": null,
+					"accounts": null,
+					"logs": logs,
+					"returnData": {"data": ["Kg==", "base64"], "programId": program_id},
+					"unitsConsumed": 2366,
+					// Not in the example, but sent by newer validators, and expected by stricter clients
+					"innerInstructions": null
+				}
+			})
+		);
+	}
+
+	#[test]
+	fn get_block_response() {
+		let transaction = "AVj7dxHlQ9IrvdYVIjuiRFs1jLaDMHixgrv+qtHBwz51L4/ImLZhszwiyEJDIp7xeBSpm/TX5B7mYzxa+fPOMw0BAAMFJMJVqLw+hJYheizSoYlLm53KzgT82cDVmazarqQKG2GQsLgiqktA+a+FDR4/7xnDX7rsusMwryYVUdixfz1B1Qan1RcZLwqvxvJl4/t3zHragsUp0L47E24tAFUgAAAABqfVFxjHdMkoVmOYaR1etoteuKObS21cc1VbIQAAAAAHYUgdNXR0u3xNdiTr072z2DVec9EQQ/wNo1OAAAAAAAtxOUhPBp2WSjUNJEgfvy70BbxI00fZyEPvFHNfxrtEAQQEAQIDADUCAAAAAQAAAAAAAACtAQAAAAAAAAdUE18R96XTJCe0YLRfKs2h0QZ8ogGnufBhoqOD9LDbAA==";
+		assert_serializes_to(
+			&RpcGetBlockResponse {
+				blockhash: "3Eq21vXNB5s86c62bVuUfTeaMif1N2kUqRPBmGRJhyTA".to_string(),
+				previous_blockhash: "mDCpbbAhn5P3N66W7iEMJWDi8cDBvPjGXzZxnZhSU7r".to_string(),
+				parent_slot: 429,
+				block_height: Some(428),
+				block_time: None,
+				transactions: Some(vec![
+					RpcBlockTransaction {
+						transaction: RPCBinaryEncodedString(transaction.to_string(), RpcBinaryEncoding::Base64),
+						meta: RpcTransactionStatusMeta {
+							err: None,
+							status: Ok(()),
+							fee: 5000,
+							pre_balances: vec![499998937500, 26858640, 1, 1, 1],
+							post_balances: vec![499998932500, 26858640, 1, 1, 1],
+							log_messages: Vec::new(),
+							return_data: None,
+							compute_units_consumed: 0,
+							inner_instructions: None,
+							pre_token_balances: None,
+							post_token_balances: None,
+							rewards: Vec::new(),
+							loaded_addresses: RpcLoadedAddresses::default()
+						},
+						version: None
+					}
+				]),
+				signatures: None,
+				rewards: Some(Vec::new())
+			},
+			json!({
+				"blockHeight": 428,
+				"blockTime": null,
+				"blockhash": "3Eq21vXNB5s86c62bVuUfTeaMif1N2kUqRPBmGRJhyTA",
+				"parentSlot": 429,
+				"previousBlockhash": "mDCpbbAhn5P3N66W7iEMJWDi8cDBvPjGXzZxnZhSU7r",
+				"rewards": [],
+				"transactions": [
+					{
+						"meta": {
+							"err": null,
+							"fee": 5000,
+							// Empty lists in the example, Bokken doesn't record these
+							"innerInstructions": null,
+							"logMessages": [],
+							"postBalances": [499998932500u64, 26858640, 1, 1, 1],
+							"postTokenBalances": null,
+							"preBalances": [499998937500u64, 26858640, 1, 1, 1],
+							"preTokenBalances": null,
+							// null in the example, rewards are only ever paid out per block here
+							"rewards": [],
+							"status": {"Ok": null},
+							// Not in the example, but sent by newer validators
+							"returnData": null,
+							"computeUnitsConsumed": 0,
+							"loadedAddresses": {"writable": [], "readonly": []}
+						},
+						"transaction": [transaction, "base64"]
+					}
+				]
+			})
+		);
+	}
+
+	// Requests are checked against what @solana/web3.js and solana-client send, which aren't always the same: web3.js
+	// leaves out unset fields, while solana-client sends them as null. Both include fields Bokken doesn't know about.
+
+	fn deserialize<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+		serde_json::from_value(value).unwrap()
+	}
+
+	#[test]
+	fn send_transaction_request() {
+		let (tx, config): (String, Option<RpcSendTransactionRequest>) = deserialize(json!([
+			"AQID",
+			{
+				"encoding": "base64",
+				"skipPreflight": true,
+				"preflightCommitment": "confirmed",
+				"maxRetries": 5,
+				"minContextSlot": 10,
+				"someFutureOption": {"a": 1}
+			}
+		]));
+		let config = config.unwrap();
+		assert_eq!(tx, "AQID");
+		assert!(config.skip_preflight);
+		assert!(matches!(config.preflight_commitment, RpcCommitment::Confirmed));
+		assert!(matches!(config.encoding, Some(RpcBinaryEncoding::Base64)));
+		assert_eq!(config.max_retries, Some(5));
+		assert_eq!(config.min_context_slot, 10);
+		assert!(config.wait_for_commitment.is_none());
+
+		// solana-client
+		let config: RpcSendTransactionRequest = deserialize(json!({
+			"skipPreflight": false,
+			"preflightCommitment": null,
+			"encoding": null,
+			"maxRetries": null,
+			"minContextSlot": null
+		}));
+		assert!(!config.skip_preflight);
+		assert!(matches!(config.preflight_commitment, RpcCommitment::Finalized));
+		assert!(config.encoding.is_none());
+		assert_eq!(config.max_retries, None);
+		assert_eq!(config.min_context_slot, 0);
+
+		// Misspellings older clients used
+		let config: RpcSendTransactionRequest = deserialize(json!({"skipVerify": true, "preFlightCommitment": "recent"}));
+		assert!(config.skip_preflight);
+		assert!(matches!(config.preflight_commitment, RpcCommitment::Processed));
+
+		let (_, config): (String, Option<RpcSendTransactionRequest>) = deserialize(json!(["AQID", null]));
+		assert!(config.is_none());
+	}
+
+	#[test]
+	fn simulate_transaction_request() {
+		let address = "83astBRguLMdt2h5U1Tpdq5tjFoJ6noeGwaY3mDLVcri";
+		let (_, config): (String, Option<RpcSimulateTransactionRequest>) = deserialize(json!([
+			"AQID",
+			{
+				"encoding": "base64",
+				"commitment": "processed",
+				"sigVerify": true,
+				"replaceRecentBlockhash": false,
+				"accounts": {"encoding": "base64", "addresses": [address]},
+				"minContextSlot": 3,
+				"innerInstructions": true,
+				"someFutureOption": null
+			}
+		]));
+		let config = config.unwrap();
+		assert!(config.sig_verify);
+		assert!(matches!(config.commitment, RpcCommitment::Processed));
+		assert!(matches!(config.encoding, Some(RpcBinaryEncoding::Base64)));
+		assert!(!config.replace_recent_blockhash);
+		assert!(matches!(config.accounts.encoding, RpcBinaryEncoding::Base64));
+		assert_eq!(config.accounts.addresses, vec![address.to_string()]);
+		assert_eq!(config.min_context_slot, 3);
+		assert!(config.inner_instructions);
+
+		// solana-client flattens its commitment config into the request, and sends unset options as null
+		let config: RpcSimulateTransactionRequest = deserialize(json!({
+			"sigVerify": false,
+			"replaceRecentBlockhash": true,
+			"commitment": "singleGossip",
+			"encoding": "base64",
+			"accounts": null,
+			"minContextSlot": null
+		}));
+		assert!(!config.sig_verify);
+		assert!(config.replace_recent_blockhash);
+		assert!(matches!(config.commitment, RpcCommitment::Confirmed));
+		assert!(config.accounts.addresses.is_empty());
+		assert_eq!(config.min_context_slot, 0);
+
+		let config: RpcSimulateTransactionRequest = deserialize(json!({
+			"sigVerify": null,
+			"commitment": null,
+			"encoding": null,
+			"replaceRecentBlockhash": null,
+			"accounts": {"encoding": null, "addresses": []},
+			"innerInstructions": null
+		}));
+		assert!(!config.sig_verify);
+		assert!(matches!(config.commitment, RpcCommitment::Finalized));
+		assert!(config.encoding.is_none());
+		assert!(matches!(config.accounts.encoding, RpcBinaryEncoding::Base64));
+	}
+
+	#[test]
+	fn get_account_info_request() {
+		let (pubkey, config): (String, Option<RpcGetAccountInfoRequest>) = deserialize(json!([
+			"vines1vzrYbzLMRdu58ou5XTby4qAqVRLmqo36NKPTg",
+			{
+				"encoding": "base58",
+				"commitment": "confirmed",
+				"dataSlice": {"offset": 4, "length": 8},
+				"minContextSlot": 7,
+				"someFutureOption": true
+			}
+		]));
+		let config = config.unwrap();
+		assert_eq!(pubkey, "vines1vzrYbzLMRdu58ou5XTby4qAqVRLmqo36NKPTg");
+		assert!(matches!(config.encoding, RpcBinaryEncoding::Base58));
+		assert!(matches!(config.commitment, RpcCommitment::Confirmed));
+		let data_slice = config.data_slice.unwrap();
+		assert_eq!((data_slice.offset, data_slice.length), (4, 8));
+		assert_eq!(config.min_context_slot, 7);
+
+		// solana-client
+		let config: RpcGetAccountInfoRequest = deserialize(json!({
+			"encoding": "base64+zstd",
+			"dataSlice": null,
+			"minContextSlot": null
+		}));
+		assert!(matches!(config.encoding, RpcBinaryEncoding::Base64Compressed));
+		assert!(matches!(config.commitment, RpcCommitment::Finalized));
+		assert!(config.data_slice.is_none());
+		assert_eq!(config.min_context_slot, 0);
+
+		let config: RpcGetAccountInfoRequest = deserialize(json!({"encoding": null, "commitment": null}));
+		assert!(matches!(config.encoding, RpcBinaryEncoding::Base64));
+		assert!(matches!(config.commitment, RpcCommitment::Finalized));
+		let config: RpcGetAccountInfoRequest = deserialize(json!({"encoding": "jsonParsed"}));
+		assert!(matches!(config.encoding, RpcBinaryEncoding::JsonParsed));
+	}
+
+	#[test]
+	fn deprecated_commitment_names() {
+		for (name, expected) in [
+			("finalized", RpcCommitment::Finalized),
+			("max", RpcCommitment::Finalized),
+			("root", RpcCommitment::Finalized),
+			("confirmed", RpcCommitment::Confirmed),
+			("single", RpcCommitment::Confirmed),
+			("singleGossip", RpcCommitment::Confirmed),
+			("processed", RpcCommitment::Processed),
+			("recent", RpcCommitment::Processed)
+		] {
+			let config: RpcGetAccountInfoRequest = deserialize(json!({"commitment": name}));
+			assert_eq!(
+				std::mem::discriminant(&config.commitment),
+				std::mem::discriminant(&expected),
+				"{} should mean {:?}",
+				name,
+				expected
+			);
+		}
+		assert!(serde_json::from_value::<RpcGetAccountInfoRequest>(json!({"commitment": "final"})).is_err());
+	}
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use jsonrpsee::{proc_macros::rpc, core::async_trait, core::RpcResult};
+
+use crate::debug_ledger::{BokkenLedgerInitConfig, DEFAULT_INITIAL_MINT_LAMPORTS};
+use crate::account_aliases::resolve_pubkey;
+use crate::instance_manager::{BokkenInstanceManager, BokkenInstance};
+use crate::rpc_endpoint_structs::{RpcCreateInstanceRequest, RpcInstanceInfo, RpcDestroyInstanceRequest};
+
+/// Methods for managing ledger instances, only available on the main JSON-RPC server
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "bokken"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "bokken"))]
+pub trait BokkenInstanceRpc {
+	#[method(name = "createInstance")]
+	async fn create_instance(&self, name: String, config: Option<RpcCreateInstanceRequest>) -> RpcResult<RpcInstanceInfo>;
+	/// Returns false if the instance didn't exist
+	#[method(name = "destroyInstance")]
+	async fn destroy_instance(&self, name: String, config: Option<RpcDestroyInstanceRequest>) -> RpcResult<bool>;
+	#[method(name = "listInstances")]
+	async fn list_instances(&self) -> RpcResult<Vec<RpcInstanceInfo>>;
+}
+
+fn instance_info(instance: &BokkenInstance) -> RpcInstanceInfo {
+	RpcInstanceInfo {
+		name: instance.name().to_string(),
+		path: instance.url_path(),
+		rpc_address: instance.rpc_addr().to_string(),
+		socket_path: instance.socket_path().to_string_lossy().to_string()
+	}
+}
+
+pub struct BokkenInstanceRpcImpl {
+	manager: Arc<BokkenInstanceManager>
+}
+impl BokkenInstanceRpcImpl {
+	pub fn new(manager: Arc<BokkenInstanceManager>) -> Self {
+		Self {
+			manager
+		}
+	}
+	async fn _create_instance(
+		&self,
+		name: String,
+		config: Option<RpcCreateInstanceRequest>
+	) -> Result<RpcInstanceInfo, jsonrpsee::core::Error> {
+		let config = config.unwrap_or_default();
+		let init_config = match config.initial_mint {
+			Some(initial_mint) => {
+				Some(
+					BokkenLedgerInitConfig {
+						initial_mint: resolve_pubkey(&initial_mint)?,
+						initial_mint_lamports: config.initial_mint_lamports.unwrap_or(DEFAULT_INITIAL_MINT_LAMPORTS),
+						identity: None
+					}
+				)
+			},
+			None => None
+		};
+		let instance = self.manager.create_instance(name, init_config).await
+			.map_err(|e| jsonrpsee::core::Error::Custom(e.to_string()))?;
+		Ok(instance_info(&instance))
+	}
+}
+
+#[async_trait]
+impl BokkenInstanceRpcServer for BokkenInstanceRpcImpl {
+	async fn create_instance(&self, name: String, config: Option<RpcCreateInstanceRequest>) -> RpcResult<RpcInstanceInfo> {
+		self._create_instance(name, config).await
+	}
+	async fn destroy_instance(&self, name: String, config: Option<RpcDestroyInstanceRequest>) -> RpcResult<bool> {
+		let config = config.unwrap_or_default();
+		self.manager.destroy_instance(&name, config.delete_data).await
+			.map_err(|e| jsonrpsee::core::Error::Custom(e.to_string()))
+	}
+	async fn list_instances(&self) -> RpcResult<Vec<RpcInstanceInfo>> {
+		Ok(
+			self.manager.list_instances().await.iter().map(|instance| instance_info(instance)).collect()
+		)
+	}
+}
@@ -0,0 +1,119 @@
+use std::{collections::HashMap, net::SocketAddr, sync::{Arc, Mutex}, time::{Duration, Instant}};
+
+use jsonrpsee::server::logger::{HttpRequest, MethodKind, TransportProtocol, Logger};
+use jsonrpsee::types::Params;
+
+use crate::{faucet::ConnectionPeerAddr, rpc_endpoint_structs::{RpcMethodMetrics, RpcGetMetricsResponse}};
+
+/// Upper bounds (inclusive) of each latency histogram bucket. Calls slower than the last one go into an extra bucket.
+pub const RPC_LATENCY_BUCKETS_MS: [u64; 11] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// Collects call counts and timings for every JSON-RPC method
+#[derive(Debug)]
+pub struct BokkenRpcMetrics {
+	slow_call_threshold: Duration,
+	methods: Mutex<HashMap<String, RpcMethodMetrics>>,
+	active_subscriptions: Mutex<HashMap<String, u64>>
+}
+impl BokkenRpcMetrics {
+	pub fn new(slow_call_threshold: Duration) -> Self {
+		Self {
+			slow_call_threshold,
+			methods: Mutex::new(HashMap::new()),
+			active_subscriptions: Mutex::new(HashMap::new())
+		}
+	}
+	/// Slow calls are logged without their parameters, as `Logger` has no way of telling which call has finished
+	/// when the same method is being called concurrently. They can be found in the `[JSON RPC Call]` log instead.
+	fn record_call_end(&self, method_name: &str, success: bool, duration: Duration) {
+		if duration >= self.slow_call_threshold {
+			println!(
+				"[JSON RPC Slow Call]: method: {:?}, took: {}ms, success: {}",
+				method_name,
+				duration.as_millis(),
+				success
+			);
+		}
+		let duration_us = duration.as_micros() as u64;
+		let duration_ms = duration.as_millis() as u64;
+		let mut methods = self.methods.lock().unwrap();
+		let method_metrics = methods.entry(method_name.to_string()).or_insert_with(|| {
+			RpcMethodMetrics {
+				latency_histogram: vec![0; RPC_LATENCY_BUCKETS_MS.len() + 1],
+				..Default::default()
+			}
+		});
+		method_metrics.calls += 1;
+		if !success {
+			method_metrics.errors += 1;
+		}
+		method_metrics.total_duration_us += duration_us;
+		if duration_us > method_metrics.max_duration_us {
+			method_metrics.max_duration_us = duration_us;
+		}
+		let bucket = RPC_LATENCY_BUCKETS_MS.iter()
+			.position(|bucket_ms| duration_ms <= *bucket_ms)
+			.unwrap_or(RPC_LATENCY_BUCKETS_MS.len());
+		method_metrics.latency_histogram[bucket] += 1;
+	}
+	pub(crate) fn record_subscription_start(&self, method_name: &str) {
+		*self.active_subscriptions.lock().unwrap().entry(method_name.to_string()).or_default() += 1;
+	}
+	pub(crate) fn record_subscription_end(&self, method_name: &str) {
+		let mut active_subscriptions = self.active_subscriptions.lock().unwrap();
+		if let Some(count) = active_subscriptions.get_mut(method_name) {
+			*count = count.saturating_sub(1);
+		}
+	}
+	/// Returns a copy of the metrics collected so far. Program processes are left empty, as they're tracked by the
+	/// ledger.
+	pub fn snapshot(&self) -> RpcGetMetricsResponse {
+		RpcGetMetricsResponse {
+			latency_buckets_ms: RPC_LATENCY_BUCKETS_MS.to_vec(),
+			rpc_methods: self.methods.lock().unwrap().clone(),
+			active_subscriptions: self.active_subscriptions.lock().unwrap().clone(),
+			program_processes: Vec::new()
+		}
+	}
+}
+
+/// Feeds every JSON-RPC call into `BokkenRpcMetrics`
+#[derive(Clone, Debug)]
+pub struct BokkenRpcLogger {
+	metrics: Arc<BokkenRpcMetrics>
+}
+impl BokkenRpcLogger {
+	pub fn new(metrics: Arc<BokkenRpcMetrics>) -> Self {
+		Self {
+			metrics
+		}
+	}
+	pub fn metrics(&self) -> Arc<BokkenRpcMetrics> {
+		self.metrics.clone()
+	}
+}
+impl Logger for BokkenRpcLogger {
+	type Instant = Instant;
+
+	fn on_connect(&self, remote_addr: SocketAddr, request: &HttpRequest, _t: TransportProtocol) {
+		if let Some(peer_addr) = request.extensions().get::<ConnectionPeerAddr>() {
+			peer_addr.set(remote_addr);
+		}
+	}
+
+	fn on_call(&self, method: &str, params: Params, kind: MethodKind, _t: TransportProtocol) {
+		println!("[JSON RPC Call]: method: {:?}, params: {:?}, kind: {:?}", method, params, kind);
+	}
+	fn on_request(&self, _t: TransportProtocol) -> Self::Instant {
+		Self::Instant::now()
+	}
+	fn on_result(&self, name: &str, success: bool, started_at: Self::Instant, _t: TransportProtocol) {
+		self.metrics.record_call_end(name, success, started_at.elapsed());
+	}
+	fn on_response(&self, _result: &str, _started_at: Self::Instant, _t: TransportProtocol) {
+
+	}
+	fn on_disconnect(&self, _remote_addr: SocketAddr, _t: TransportProtocol) {
+
+	}
+}
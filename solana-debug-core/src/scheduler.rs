@@ -0,0 +1,136 @@
+use std::{collections::{HashMap, HashSet}, sync::{Arc, Weak, atomic::{AtomicU64, Ordering}}};
+
+use solana_sdk::{hash::Hash, instruction::Instruction, message::Message, signature::{Keypair, Signature}, signer::Signer, transaction::Transaction};
+use tokio::sync::{broadcast, Mutex};
+
+use crate::debug_ledger::{BokkenLedger, BokkenLedgerChange};
+
+/// Instructions which get submitted as a transaction every `every_slots` slots
+#[derive(Debug)]
+pub struct BokkenScheduledJob {
+	pub name: String,
+	pub every_slots: u64,
+	pub instructions: Vec<Instruction>,
+	/// Pays for and signs the transaction. Any instructions requiring other signers will fail.
+	pub signer: Keypair,
+	/// The job runs after the first transaction at or after this slot
+	next_slot: u64,
+	/// Amount of times the transaction failed
+	failures: u64,
+	/// Amount of times the transaction was committed
+	successes: u64
+}
+impl BokkenScheduledJob {
+	pub fn new(name: String, every_slots: u64, instructions: Vec<Instruction>, signer: Keypair) -> Self {
+		Self {
+			name,
+			every_slots: every_slots.max(1),
+			instructions,
+			signer,
+			next_slot: 0,
+			failures: 0,
+			successes: 0
+		}
+	}
+	pub fn next_slot(&self) -> u64 {
+		self.next_slot
+	}
+	pub fn failures(&self) -> u64 {
+		self.failures
+	}
+	pub fn successes(&self) -> u64 {
+		self.successes
+	}
+}
+
+/// Submits scheduled transactions to the ledger, like a keeper bot cranking a protocol would.
+///
+/// Slots only advance when a transaction is committed, so jobs are checked after every committed transaction which
+/// wasn't submitted by the scheduler itself.
+#[derive(Debug)]
+pub struct BokkenScheduler {
+	ledger: Weak<BokkenLedger>,
+	jobs: Mutex<HashMap<u64, BokkenScheduledJob>>,
+	next_job_id: AtomicU64,
+	/// Signatures of transactions we submitted, so they don't trigger jobs themselves
+	own_signatures: Mutex<HashSet<Signature>>
+}
+impl BokkenScheduler {
+	/// Starts watching the ledger for new slots. The scheduler stops once the ledger has been dropped.
+	pub fn new(ledger: &Arc<BokkenLedger>) -> Arc<Self> {
+		let new_self = Arc::new(Self {
+			ledger: Arc::downgrade(ledger),
+			jobs: Mutex::new(HashMap::new()),
+			next_job_id: AtomicU64::new(0),
+			own_signatures: Mutex::new(HashSet::new())
+		});
+		let mut changes = ledger.subscribe_changes();
+		let scheduler = Arc::downgrade(&new_self);
+		tokio::task::spawn(async move {
+			loop {
+				let (slot, signature) = match changes.recv().await {
+					Ok(BokkenLedgerChange::Transaction { slot, signature, .. }) => (slot, signature),
+					Ok(_) => continue,
+					// Missing a few slots doesn't matter, the next one will still trigger any jobs which are due
+					Err(broadcast::error::RecvError::Lagged(_)) => continue,
+					Err(broadcast::error::RecvError::Closed) => break
+				};
+				let Some(scheduler) = scheduler.upgrade() else {
+					break;
+				};
+				if scheduler.own_signatures.lock().await.remove(&signature) {
+					continue;
+				}
+				scheduler.run_due_jobs(slot).await;
+			}
+		});
+		new_self
+	}
+	/// Returns the job's ID. The job first runs after the next transaction.
+	pub async fn add_job(&self, job: BokkenScheduledJob) -> u64 {
+		let job_id = self.next_job_id.fetch_add(1, Ordering::Relaxed);
+		self.jobs.lock().await.insert(job_id, job);
+		job_id
+	}
+	/// Returns false if the job didn't exist
+	pub async fn remove_job(&self, job_id: u64) -> bool {
+		self.jobs.lock().await.remove(&job_id).is_some()
+	}
+	/// Calls `f` with every job and its ID
+	pub async fn for_each_job(&self, mut f: impl FnMut(u64, &BokkenScheduledJob)) {
+		for (job_id, job) in self.jobs.lock().await.iter() {
+			f(*job_id, job);
+		}
+	}
+	async fn run_due_jobs(&self, slot: u64) {
+		let Some(ledger) = self.ledger.upgrade() else {
+			return;
+		};
+		let mut jobs = self.jobs.lock().await;
+		for job in jobs.values_mut() {
+			if slot < job.next_slot {
+				continue;
+			}
+			job.next_slot = slot + job.every_slots;
+			let mut tx = Transaction::new_unsigned(Message::new(&job.instructions, Some(&job.signer.pubkey())));
+			if let Err(e) = tx.try_sign(&[&job.signer], Hash::new_from_array(ledger.blockhash().await)) {
+				job.failures += 1;
+				eprintln!("Scheduled job {:?} couldn't be signed: {}", job.name, e);
+				continue;
+			}
+			let signature = tx.signatures[0];
+			self.own_signatures.lock().await.insert(signature);
+			match ledger.execute_transaction(tx, true).await {
+				Ok(_) => {
+					job.successes += 1;
+				},
+				Err(e) => {
+					// Nothing was committed, so there's no change to wait for
+					self.own_signatures.lock().await.remove(&signature);
+					job.failures += 1;
+					eprintln!("Scheduled job {:?} failed: {}", job.name, e);
+				}
+			}
+		}
+	}
+}
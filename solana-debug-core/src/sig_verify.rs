@@ -0,0 +1,75 @@
+//! Signature verification which reports which signer failed, rather than just that one did.
+
+use std::fmt;
+
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::{Transaction, TransactionError}};
+
+use crate::error::BokkenError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BokkenSignerCheckStatus {
+	Valid,
+	/// The signature is absent or all zeroes, i.e. the signer hasn't signed yet
+	Missing,
+	/// The signature doesn't match the message and signer
+	Invalid
+}
+impl fmt::Display for BokkenSignerCheckStatus {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Valid => write!(f, "valid"),
+			Self::Missing => write!(f, "missing"),
+			Self::Invalid => write!(f, "invalid")
+		}
+	}
+}
+
+/// Result of checking the signature of a required signer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BokkenSignerCheck {
+	/// Index of the signer in the message's account keys, which is also the index of its signature
+	pub index: usize,
+	pub pubkey: Pubkey,
+	pub status: BokkenSignerCheckStatus
+}
+
+/// Checks the signature of every signer the message requires, in order. The message must already be sanitized.
+pub fn check_signers(tx: &Transaction) -> Vec<BokkenSignerCheck> {
+	let message_data = tx.message.serialize();
+	tx.message.account_keys.iter()
+		.take(tx.message.header.num_required_signatures as usize)
+		.enumerate()
+		.map(|(index, pubkey)| {
+			let status = match tx.signatures.get(index) {
+				None => BokkenSignerCheckStatus::Missing,
+				Some(signature) if *signature == Signature::default() => BokkenSignerCheckStatus::Missing,
+				Some(signature) if !signature.verify(pubkey.as_ref(), &message_data) => BokkenSignerCheckStatus::Invalid,
+				Some(_) => BokkenSignerCheckStatus::Valid
+			};
+			BokkenSignerCheck {
+				index,
+				pubkey: *pubkey,
+				status
+			}
+		})
+		.collect()
+}
+
+/// Same as `check_signers`, except the first signer which isn't valid is returned as an error naming it
+pub fn verify_signers(tx: &Transaction) -> Result<Vec<BokkenSignerCheck>, BokkenError> {
+	let checks = check_signers(tx);
+	if let Some(failed_check) = checks.iter().find(|check| check.status != BokkenSignerCheckStatus::Valid) {
+		return Err(
+			BokkenError::SignerVerificationFailed {
+				index: failed_check.index,
+				pubkey: failed_check.pubkey,
+				status: failed_check.status
+			}
+		);
+	}
+	if tx.signatures.len() > checks.len() {
+		// Signatures without a signer would be rejected by sanitization on a real validator
+		return Err(TransactionError::SanitizeFailure.into());
+	}
+	Ok(checks)
+}
@@ -0,0 +1,67 @@
+use std::collections::{HashMap, VecDeque};
+
+use bokken_runtime::debug_env::BokkenAccountData;
+use solana_sdk::{hash::Hash, program_error::ProgramError, pubkey::Pubkey};
+
+/// The outcome of a simulated transaction
+#[derive(Debug, Clone)]
+pub enum SimulationCacheEntry {
+	Success {
+		account_datas: HashMap<Pubkey, BokkenAccountData>,
+		logs: Vec<String>,
+		return_data: Option<(Pubkey, Vec<u8>)>
+	},
+	InstructionError {
+		index: usize,
+		program_error: ProgramError,
+		logs: Vec<String>
+	}
+}
+
+/// Remembers simulation results by message hash so that preflight checks, explicit simulations, and client retries
+/// don't invoke the same programs over and over again.
+///
+/// All entries are forgotten as soon as the ledger's state version changes.
+#[derive(Debug)]
+pub struct SimulationCache {
+	capacity: usize,
+	state_version: u64,
+	entries: HashMap<Hash, SimulationCacheEntry>,
+	insertion_order: VecDeque<Hash>
+}
+impl SimulationCache {
+	/// A capacity of 0 disables the cache
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			capacity,
+			state_version: 0,
+			entries: HashMap::new(),
+			insertion_order: VecDeque::new()
+		}
+	}
+	fn reset_if_stale(&mut self, state_version: u64) {
+		if self.state_version != state_version {
+			self.state_version = state_version;
+			self.entries.clear();
+			self.insertion_order.clear();
+		}
+	}
+	pub fn get(&mut self, state_version: u64, message_hash: &Hash) -> Option<SimulationCacheEntry> {
+		self.reset_if_stale(state_version);
+		self.entries.get(message_hash).cloned()
+	}
+	pub fn insert(&mut self, state_version: u64, message_hash: Hash, entry: SimulationCacheEntry) {
+		if self.capacity == 0 {
+			return;
+		}
+		self.reset_if_stale(state_version);
+		if self.entries.insert(message_hash, entry).is_none() {
+			self.insertion_order.push_back(message_hash);
+			if self.insertion_order.len() > self.capacity {
+				if let Some(oldest) = self.insertion_order.pop_front() {
+					self.entries.remove(&oldest);
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,271 @@
+use std::{collections::{HashMap, HashSet, VecDeque}, sync::{Arc, Mutex, Weak}, time::Duration};
+
+use jsonrpsee::{SubscriptionSink, types::SubscriptionId};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::TransactionError};
+use tokio::sync::broadcast;
+
+use crate::{
+	account_aliases::annotate_pubkeys,
+	debug_ledger::{BokkenLedger, BokkenLedgerChange},
+	fault_injection::BokkenFaultInjector,
+	program_caller::BokkenLogLevel,
+	rpc_endpoint_structs::{RpcResponseContext, RpcSignatureSubscribeResponse, RpcSignatureSubscribeResponseValue, RpcProgramLogsTailNotification},
+	rpc_metrics::BokkenRpcMetrics
+};
+
+/// How often subscriptions check whether their client has gone away
+const CLIENT_DISCONNECT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// How many of the most recent transaction outcomes are kept for signature subscriptions made after the fact
+const SIGNATURE_REPLAY_BUFFER_LEN: usize = 4096;
+
+/// Outcomes of the most recently processed or dropped transactions, so clients which subscribe to a signature just
+/// after its transaction went through are still notified, like with a real validator's status cache. Dropped
+/// transactions aren't saved in the ledger at all, so this is the only place they can be found.
+#[derive(Debug, Default)]
+struct SignatureReplayBuffer {
+	statuses: HashMap<Signature, (u64, Option<TransactionError>)>,
+	/// Oldest first
+	order: VecDeque<Signature>
+}
+impl SignatureReplayBuffer {
+	fn record(&mut self, signature: Signature, slot: u64, tx_error: Option<TransactionError>) {
+		if self.statuses.insert(signature, (slot, tx_error)).is_some() {
+			return;
+		}
+		self.order.push_back(signature);
+		while self.order.len() > SIGNATURE_REPLAY_BUFFER_LEN {
+			if let Some(oldest) = self.order.pop_front() {
+				self.statuses.remove(&oldest);
+			}
+		}
+	}
+	fn get(&self, signature: &Signature) -> Option<(u64, Option<TransactionError>)> {
+		self.statuses.get(signature).cloned()
+	}
+}
+
+/// Keeps track of active websocket subscriptions and delivers their notifications.
+///
+/// Every subscription is served by its own task, which ends as soon as the subscription is done with or its client
+/// has gone away, so nothing is left behind watching the ledger.
+#[derive(Debug)]
+pub struct BokkenSubscriptionManager {
+	ledger: Arc<BokkenLedger>,
+	metrics: Arc<BokkenRpcMetrics>,
+	fault_injector: Arc<BokkenFaultInjector>,
+	/// Subscription method of each active subscription
+	active: Mutex<HashMap<SubscriptionId<'static>, &'static str>>,
+	recent_signatures: Arc<Mutex<SignatureReplayBuffer>>
+}
+impl BokkenSubscriptionManager {
+	/// Must be called from within a tokio runtime, as recent transaction outcomes are recorded by a background task
+	/// which lives as long as the ledger does
+	pub fn new(
+		ledger: Arc<BokkenLedger>,
+		metrics: Arc<BokkenRpcMetrics>,
+		fault_injector: Arc<BokkenFaultInjector>
+	) -> Self {
+		let recent_signatures = Arc::new(Mutex::new(SignatureReplayBuffer::default()));
+		tokio::task::spawn(
+			record_recent_signatures(Arc::downgrade(&ledger), ledger.subscribe_changes(), recent_signatures.clone())
+		);
+		Self {
+			ledger,
+			metrics,
+			fault_injector,
+			active: Mutex::new(HashMap::new()),
+			recent_signatures
+		}
+	}
+	/// Amount of active subscriptions
+	pub fn active_count(&self) -> usize {
+		self.active.lock().unwrap().len()
+	}
+	/// Accepts the subscription and returns its ID, or None if the client has already gone away
+	fn start(&self, method: &'static str, sink: &mut SubscriptionSink) -> Option<SubscriptionId<'static>> {
+		sink.accept().ok()?;
+		let subscription_id = sink.subscription_id()?;
+		self.active.lock().unwrap().insert(subscription_id.clone(), method);
+		self.metrics.record_subscription_start(method);
+		Some(subscription_id)
+	}
+	fn end(&self, subscription_id: &SubscriptionId<'static>) {
+		if let Some(method) = self.active.lock().unwrap().remove(subscription_id) {
+			self.metrics.record_subscription_end(method);
+		}
+	}
+	/// Notifies the subscriber once, when the transaction with the specified signature has been committed or dropped.
+	/// If that already happened, the notification is sent right away.
+	pub fn spawn_signature_subscription(self: &Arc<Self>, signature: [u8; 64], mut sink: SubscriptionSink) {
+		let Some(subscription_id) = self.start("signatureSubscribe", &mut sink) else {
+			return;
+		};
+		let manager = self.clone();
+		tokio::task::spawn(async move {
+			// Listen for changes before looking at the ledger, so that a commit in-between can't be missed
+			let mut changes = manager.ledger.subscribe_changes();
+			let mut disconnect_check = tokio::time::interval(CLIENT_DISCONNECT_CHECK_INTERVAL);
+			let mut check_ledger = true;
+			loop {
+				if check_ledger {
+					check_ledger = false;
+					let recent_status = manager.recent_signatures.lock().unwrap().get(&Signature::new(&signature));
+					if let Some((slot, tx_error)) = recent_status {
+						manager.notify_signature(&mut sink, slot, tx_error);
+						break;
+					}
+					match manager.ledger.get_bokken_entry_by_tx(signature).await {
+						Ok(Some(entry)) => {
+							manager.notify_signature(&mut sink, entry.slot, entry.tx_error);
+							break;
+						},
+						Ok(None) => {},
+						Err(e) => {
+							eprintln!("signatureSubscribe couldn't look up transaction: {}", e);
+						}
+					}
+				}
+				tokio::select! {
+					change = changes.recv() => {
+						match change {
+							Ok(BokkenLedgerChange::Transaction { slot, signature: tx_signature, tx_error, .. }) => {
+								if tx_signature.as_ref() == signature.as_slice() {
+									manager.notify_signature(&mut sink, slot, tx_error);
+									break;
+								}
+							},
+							Ok(BokkenLedgerChange::TransactionDropped { signature: tx_signature, tx_error }) => {
+								if tx_signature.as_ref() == signature.as_slice() {
+									let slot = manager.ledger.slot().await;
+									manager.notify_signature(&mut sink, slot, Some(tx_error));
+									break;
+								}
+							},
+							Ok(_) => {},
+							// The transaction might have been among the changes we missed
+							Err(broadcast::error::RecvError::Lagged(_)) => {
+								check_ledger = true;
+							},
+							Err(broadcast::error::RecvError::Closed) => break
+						}
+					},
+					_ = disconnect_check.tick() => {
+						if sink.is_closed() {
+							break;
+						}
+					}
+				}
+			}
+			manager.end(&subscription_id);
+			// Dropping the sink unsubscribes the client, as signature subscriptions only ever notify once
+		});
+	}
+	/// Streams the messages logged by debuggable programs as they arrive, until the client unsubscribes. If
+	/// `program_ids` is specified, only messages from those programs are sent.
+	pub fn spawn_program_logs_subscription(
+		self: &Arc<Self>,
+		program_ids: Option<HashSet<Pubkey>>,
+		min_level: BokkenLogLevel,
+		mut sink: SubscriptionSink
+	) {
+		let Some(subscription_id) = self.start("bokken_getProgramLogsTail", &mut sink) else {
+			return;
+		};
+		let manager = self.clone();
+		tokio::task::spawn(async move {
+			let mut logs = manager.ledger.subscribe_program_logs();
+			let mut disconnect_check = tokio::time::interval(CLIENT_DISCONNECT_CHECK_INTERVAL);
+			loop {
+				let notification = tokio::select! {
+					log = logs.recv() => {
+						match log {
+							Ok(log) => {
+								if
+									log.level < min_level ||
+									program_ids.as_ref().map_or(false, |program_ids| !program_ids.contains(&log.program_id))
+								{
+									continue;
+								}
+								RpcProgramLogsTailNotification {
+									program_id: Some(log.program_id.to_string()),
+									level: match log.level {
+										BokkenLogLevel::Debug => "debug",
+										BokkenLogLevel::Info => "info"
+									}.to_string(),
+									message: annotate_pubkeys(&log.message)
+								}
+							},
+							// Better to let the client know than to leave a silent gap in the logs
+							Err(broadcast::error::RecvError::Lagged(skipped)) => {
+								RpcProgramLogsTailNotification {
+									program_id: None,
+									level: "warning".to_string(),
+									message: format!("{} log messages were skipped as this subscription fell behind", skipped)
+								}
+							},
+							Err(broadcast::error::RecvError::Closed) => break
+						}
+					},
+					_ = disconnect_check.tick() => {
+						if sink.is_closed() {
+							break;
+						}
+						continue;
+					}
+				};
+				if let Err(e) = sink.send(&notification) {
+					eprintln!("Couldn't send program log notification: {}", e);
+					break;
+				}
+			}
+			manager.end(&subscription_id);
+		});
+	}
+	fn notify_signature(
+		&self,
+		sink: &mut SubscriptionSink,
+		slot: u64,
+		tx_error: Option<solana_sdk::transaction::TransactionError>
+	) {
+		if self.fault_injector.should_drop_notification() {
+			return;
+		}
+		let result = sink.send(
+			&RpcSignatureSubscribeResponse {
+				context: RpcResponseContext {
+					slot
+				},
+				value: RpcSignatureSubscribeResponseValue { err: tx_error },
+			}
+		);
+		if let Err(e) = result {
+			eprintln!("Couldn't send signature notification: {}", e);
+		}
+	}
+}
+
+/// Records the outcome of every transaction in `recent_signatures` until the ledger is dropped
+async fn record_recent_signatures(
+	ledger: Weak<BokkenLedger>,
+	mut changes: broadcast::Receiver<BokkenLedgerChange>,
+	recent_signatures: Arc<Mutex<SignatureReplayBuffer>>
+) {
+	loop {
+		match changes.recv().await {
+			Ok(BokkenLedgerChange::Transaction { slot, signature, tx_error, .. }) => {
+				recent_signatures.lock().unwrap().record(signature, slot, tx_error);
+			},
+			Ok(BokkenLedgerChange::TransactionDropped { signature, tx_error }) => {
+				let Some(ledger) = ledger.upgrade() else {
+					break;
+				};
+				let slot = ledger.slot().await;
+				recent_signatures.lock().unwrap().record(signature, slot, Some(tx_error));
+			},
+			Ok(_) => {},
+			// Committed transactions can still be found in the ledger, only dropped ones are lost for good
+			Err(broadcast::error::RecvError::Lagged(_)) => {},
+			Err(broadcast::error::RecvError::Closed) => break
+		}
+	}
+}
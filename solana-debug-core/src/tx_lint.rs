@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use solana_sdk::{packet::PACKET_DATA_SIZE, sanitize::Sanitize, system_instruction::SystemInstruction, system_program, transaction::Transaction};
+
+use crate::{debug_ledger::{AccountsOverlay, BokkenLedger, BokkenLedgerAccountReturnChoice, BokkenLedgerInstruction}, error::BokkenError, rpc_endpoint_structs::RpcLintIssue, sig_verify::{check_signers, BokkenSignerCheckStatus}};
+
+/// Maximum amount of accounts a transaction may lock
+const MAX_TX_ACCOUNT_LOCKS: usize = 64;
+
+/// Looks for common transaction construction mistakes.
+///
+/// If `simulate` is true, the transaction is also executed against the current ledger state (without committing
+/// anything) to find problems which can only be seen from its effects.
+pub async fn lint_transaction(
+	ledger: &BokkenLedger,
+	tx: &Transaction,
+	simulate: bool
+) -> Result<Vec<RpcLintIssue>, BokkenError> {
+	let mut issues = Vec::new();
+	let message = &tx.message;
+	// Everything below indexes into the message, so it has to be sane
+	message.sanitize()?;
+
+	for check in check_signers(tx) {
+		match check.status {
+			BokkenSignerCheckStatus::Missing => {
+				issues.push(RpcLintIssue::MissingSignature { account: check.pubkey.to_string() });
+			},
+			BokkenSignerCheckStatus::Invalid => {
+				issues.push(RpcLintIssue::InvalidSignature { account: check.pubkey.to_string() });
+			},
+			BokkenSignerCheckStatus::Valid => {}
+		}
+	}
+
+	let size = bincode::serialized_size(tx)?;
+	if size > PACKET_DATA_SIZE as u64 {
+		issues.push(RpcLintIssue::TransactionTooLarge { size, limit: PACKET_DATA_SIZE as u64 });
+	}
+	if message.account_keys.len() > MAX_TX_ACCOUNT_LOCKS {
+		issues.push(RpcLintIssue::TooManyAccounts { count: message.account_keys.len(), limit: MAX_TX_ACCOUNT_LOCKS });
+	}
+
+	// Account creations can be checked without executing anything
+	let mut rent_checked_accounts = HashSet::new();
+	for (i, ix) in message.instructions.iter().enumerate() {
+		if message.account_keys[ix.program_id_index as usize] != system_program::id() {
+			continue;
+		}
+		let (lamports, space) = match bincode::deserialize::<SystemInstruction>(&ix.data) {
+			Ok(SystemInstruction::CreateAccount { lamports, space, .. }) |
+			Ok(SystemInstruction::CreateAccountWithSeed { lamports, space, .. }) => (lamports, space),
+			_ => continue
+		};
+		let Some(new_account_index) = ix.accounts.get(1) else {
+			continue;
+		};
+		let account = message.account_keys[*new_account_index as usize];
+		rent_checked_accounts.insert(account);
+		let minimum_lamports = ledger.calc_min_balance_for_rent_exemption(space);
+		if lamports < minimum_lamports {
+			issues.push(RpcLintIssue::NotRentExempt {
+				instruction_index: Some(i),
+				account: account.to_string(),
+				lamports,
+				minimum_lamports
+			});
+		}
+	}
+
+	if simulate {
+		// The overlay is dropped afterwards, so nothing gets committed
+		let mut overlay = AccountsOverlay::new(ledger.slot().await);
+		match ledger.execute_instructions(
+			&message.account_keys[0],
+			BokkenLedgerInstruction::from_transaction(tx),
+			BokkenLedgerAccountReturnChoice::Edited,
+			&mut overlay,
+			None
+		).await {
+			Ok((edited_accounts, _, _)) => {
+				for (pubkey, data) in edited_accounts.iter() {
+					let is_writable = message.account_keys.iter()
+						.position(|key| key == pubkey)
+						.map_or(false, |index| message.is_writable(index));
+					if !is_writable {
+						issues.push(RpcLintIssue::UnmarkedWrite { account: pubkey.to_string() });
+					}
+					if data.lamports == 0 || rent_checked_accounts.contains(pubkey) {
+						continue;
+					}
+					let minimum_lamports = ledger.calc_min_balance_for_rent_exemption(data.data.len() as u64);
+					if data.lamports < minimum_lamports {
+						issues.push(RpcLintIssue::NotRentExempt {
+							instruction_index: None,
+							account: pubkey.to_string(),
+							lamports: data.lamports,
+							minimum_lamports
+						});
+					}
+				}
+			},
+			Err(e) => {
+				issues.push(RpcLintIssue::SimulationFailed { message: e.to_string() });
+			}
+		}
+	}
+	Ok(issues)
+}
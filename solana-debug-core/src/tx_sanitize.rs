@@ -0,0 +1,222 @@
+//! Every check a transaction goes through before it's executed, rejecting malformed ones with the same errors a real
+//! validator does, so client error handling can be exercised against Bokken.
+//!
+//! Versioned transactions are sanitized and have their address table lookups resolved against the emulated address
+//! lookup table program, but as only legacy transactions can be executed, they're rejected with `UnsupportedVersion`
+//! once they've passed every other check.
+
+use std::collections::HashSet;
+
+use solana_sdk::{message::{VersionedMessage, v0::MessageAddressTableLookup}, pubkey::Pubkey, transaction::{Transaction, TransactionError, VersionedTransaction}};
+
+use crate::{debug_ledger::{BokkenLedger, is_closed_account}, error::BokkenError, native_program_stubs::address_lookup_table::{PUBKEY_ADDRESS_LOOKUP_TABLE_PROGRAM, deserialize_lookup_table}};
+
+/// Decodes a transaction in either the legacy or versioned format
+pub fn decode_transaction(data: &[u8]) -> Result<VersionedTransaction, BokkenError> {
+	Ok(bincode::deserialize(data)?)
+}
+
+/// The writable and read-only addresses the lookups resolve to, as of the slot currently being executed. Fails like
+/// a real validator does if a table doesn't exist (or is fully deactivated), isn't a table, or doesn't have an address
+/// at one of the indexes. Addresses added to a table in the current slot can't be used yet.
+pub async fn resolve_address_table_lookups(
+	ledger: &BokkenLedger,
+	address_table_lookups: &[MessageAddressTableLookup]
+) -> Result<(Vec<Pubkey>, Vec<Pubkey>), BokkenError> {
+	let current_slot = ledger.slot().await + 1;
+	let mut writable = Vec::new();
+	let mut readonly = Vec::new();
+	for lookup in address_table_lookups {
+		let table_account = ledger.read_account(&lookup.account_key, None).await?;
+		if is_closed_account(&table_account) {
+			return Err(TransactionError::AddressLookupTableNotFound.into());
+		}
+		if table_account.owner != PUBKEY_ADDRESS_LOOKUP_TABLE_PROGRAM {
+			return Err(TransactionError::InvalidAddressLookupTableOwner.into());
+		}
+		let (meta, addresses) = deserialize_lookup_table(&table_account.data)
+			.ok_or(TransactionError::InvalidAddressLookupTableData)?;
+		if meta.closable_at_slot().map_or(false, |closable_at_slot| current_slot >= closable_at_slot) {
+			return Err(TransactionError::AddressLookupTableNotFound.into());
+		}
+		let active_address_count = if current_slot > meta.last_extended_slot {
+			addresses.len()
+		}else{
+			meta.last_extended_slot_start_index as usize
+		};
+		for (indexes, resolved) in [(&lookup.writable_indexes, &mut writable), (&lookup.readonly_indexes, &mut readonly)] {
+			for index in indexes.iter() {
+				if *index as usize >= active_address_count {
+					return Err(TransactionError::InvalidAddressLookupTableIndex.into());
+				}
+				resolved.push(addresses[*index as usize]);
+			}
+		}
+	}
+	Ok((writable, readonly))
+}
+
+/// Sanitizes the transaction like a real validator does before executing it, returning it as a legacy transaction.
+///
+/// If `check_signatures` is false, the amount of signatures isn't checked, for simulating transactions which haven't
+/// been signed. Whether the signatures are valid is never checked here, see `sig_verify`.
+pub async fn sanitize_transaction(
+	ledger: &BokkenLedger,
+	tx: VersionedTransaction,
+	check_signatures: bool
+) -> Result<Transaction, BokkenError> {
+	// Like a real validator, programs can't be looked up, so that what a transaction invokes can be known without
+	// loading any accounts
+	let sanitized = if check_signatures {
+		tx.sanitize(true)
+	}else{
+		tx.message.sanitize(true)
+	};
+	sanitized.map_err(|_| BokkenError::InvalidTransaction(TransactionError::SanitizeFailure))?;
+	let mut account_keys: Vec<Pubkey> = tx.message.static_account_keys().to_vec();
+	if let VersionedMessage::V0(message) = &tx.message {
+		let (writable, readonly) = resolve_address_table_lookups(ledger, &message.address_table_lookups).await
+			.map_err(|error| match error {
+				BokkenError::TransactionError(error) => BokkenError::InvalidTransaction(error),
+				error => error
+			})?;
+		account_keys.extend(writable);
+		account_keys.extend(readonly);
+	}
+	let mut unique_account_keys = HashSet::with_capacity(account_keys.len());
+	if !account_keys.iter().all(|account_key| unique_account_keys.insert(account_key)) {
+		return Err(BokkenError::InvalidTransaction(TransactionError::AccountLoadedTwice));
+	}
+	match tx.message {
+		VersionedMessage::Legacy(message) => Ok(
+			Transaction {
+				signatures: tx.signatures,
+				message
+			}
+		),
+		VersionedMessage::V0(_) => Err(BokkenError::InvalidTransaction(TransactionError::UnsupportedVersion))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use solana_sdk::{hash::Hash, message::{Message, MessageHeader, compiled_instruction::CompiledInstruction, v0}, signature::Signature};
+
+	use crate::debug_ledger::tests::TestLedger;
+	use super::*;
+
+	fn header(num_required_signatures: u8, num_readonly_signed_accounts: u8, num_readonly_unsigned_accounts: u8) -> MessageHeader {
+		MessageHeader { num_required_signatures, num_readonly_signed_accounts, num_readonly_unsigned_accounts }
+	}
+	/// A transfer-like instruction from the fee payer to the second account, by the program at `program_id_index`
+	fn instruction(program_id_index: u8) -> CompiledInstruction {
+		CompiledInstruction { program_id_index, accounts: vec![0, 1], data: Vec::new() }
+	}
+	/// A fee payer, another writable account, and a program
+	fn account_keys() -> Vec<Pubkey> {
+		vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()]
+	}
+	fn legacy_transaction(header: MessageHeader, account_keys: Vec<Pubkey>, instruction: CompiledInstruction) -> VersionedTransaction {
+		VersionedTransaction {
+			signatures: vec![Signature::default(); header.num_required_signatures as usize],
+			message: VersionedMessage::Legacy(
+				Message { header, account_keys, recent_blockhash: Hash::default(), instructions: vec![instruction] }
+			)
+		}
+	}
+	fn v0_transaction(
+		account_keys: Vec<Pubkey>,
+		instruction: CompiledInstruction,
+		address_table_lookups: Vec<MessageAddressTableLookup>
+	) -> VersionedTransaction {
+		VersionedTransaction {
+			signatures: vec![Signature::default()],
+			message: VersionedMessage::V0(
+				v0::Message {
+					header: header(1, 0, 1),
+					account_keys,
+					recent_blockhash: Hash::default(),
+					instructions: vec![instruction],
+					address_table_lookups
+				}
+			)
+		}
+	}
+	fn lookup(writable_indexes: Vec<u8>) -> MessageAddressTableLookup {
+		MessageAddressTableLookup { account_key: Pubkey::new_unique(), writable_indexes, readonly_indexes: Vec::new() }
+	}
+	async fn rejection(test: &TestLedger, tx: VersionedTransaction) -> TransactionError {
+		match sanitize_transaction(&test.ledger, tx, true).await {
+			Err(BokkenError::InvalidTransaction(error)) => error,
+			result => panic!("Expected the transaction to be rejected, got {:?}", result)
+		}
+	}
+
+	#[tokio::test]
+	async fn well_formed_legacy_transaction_passes() {
+		let test = TestLedger::new().await;
+		let tx = legacy_transaction(header(1, 0, 1), account_keys(), instruction(2));
+		assert!(sanitize_transaction(&test.ledger, tx, true).await.is_ok());
+	}
+
+	#[tokio::test]
+	async fn malformed_messages_are_rejected() {
+		let test = TestLedger::new().await;
+		for (case, tx) in [
+			("header overflow", legacy_transaction(header(2, 0, 2), account_keys(), instruction(2))),
+			("read-only fee payer", legacy_transaction(header(1, 1, 1), account_keys(), instruction(2))),
+			("fee payer as program", legacy_transaction(header(1, 0, 1), account_keys(), instruction(0))),
+			("program out of range", legacy_transaction(header(1, 0, 1), account_keys(), instruction(3))),
+			(
+				"account out of range",
+				legacy_transaction(
+					header(1, 0, 1),
+					account_keys(),
+					CompiledInstruction { program_id_index: 2, accounts: vec![0, 3], data: Vec::new() }
+				)
+			),
+			("v0 read-only fee payer", {
+				let mut tx = v0_transaction(account_keys(), instruction(2), Vec::new());
+				let VersionedMessage::V0(message) = &mut tx.message else { unreachable!() };
+				message.header = header(1, 1, 1);
+				tx
+			}),
+			("empty lookup", v0_transaction(account_keys(), instruction(2), vec![lookup(Vec::new())])),
+			// 3 static keys and 254 looked up ones
+			("more than 256 keys", v0_transaction(account_keys(), instruction(2), vec![lookup((0..254).collect())])),
+			("looked up program", v0_transaction(account_keys(), instruction(3), vec![lookup(vec![0])]))
+		] {
+			assert_eq!(rejection(&test, tx).await, TransactionError::SanitizeFailure, "{}", case);
+		}
+	}
+
+	#[tokio::test]
+	async fn signature_count_must_match_the_header() {
+		let test = TestLedger::new().await;
+		let mut too_few = legacy_transaction(header(1, 0, 1), account_keys(), instruction(2));
+		too_few.signatures.clear();
+		assert_eq!(rejection(&test, too_few.clone()).await, TransactionError::SanitizeFailure);
+		// Simulations don't need to be signed
+		assert!(sanitize_transaction(&test.ledger, too_few, false).await.is_ok());
+
+		let mut too_many = legacy_transaction(header(1, 0, 1), account_keys(), instruction(2));
+		too_many.signatures.push(Signature::default());
+		assert_eq!(rejection(&test, too_many).await, TransactionError::SanitizeFailure);
+	}
+
+	#[tokio::test]
+	async fn duplicate_account_keys_are_rejected() {
+		let test = TestLedger::new().await;
+		let mut account_keys = account_keys();
+		account_keys[1] = account_keys[0];
+		let tx = legacy_transaction(header(1, 0, 1), account_keys, instruction(2));
+		assert_eq!(rejection(&test, tx).await, TransactionError::AccountLoadedTwice);
+	}
+
+	#[tokio::test]
+	async fn well_formed_v0_transaction_is_unsupported() {
+		let test = TestLedger::new().await;
+		let tx = v0_transaction(account_keys(), instruction(2), Vec::new());
+		assert_eq!(rejection(&test, tx).await, TransactionError::UnsupportedVersion);
+	}
+}
@@ -217,6 +217,26 @@ impl<
 			},
 		}
 	}
+	/// Returns up to `limit` entries with identifiers between `start` and `end` (inclusive), in order
+	pub async fn range(&self, start: &I, end: &I, limit: usize) -> Result<Vec<(I, T)>, BokkenDetailedError> {
+		let file_ref = &mut self.file_ref.lock().await;
+		let mut index = match self._binary_search(start, file_ref).await? {
+			IndexableFileSearchResult::Found(index) => index,
+			IndexableFileSearchResult::NotFound(index) => index
+		};
+		let len = self.len();
+		let mut result = Vec::new();
+		while index < len && result.len() < limit {
+			let identifier = self._read_identifier_at_index(index, file_ref).await?;
+			if identifier > *end {
+				break;
+			}
+			let entry = self._read_entry_at_index(index, file_ref).await?;
+			result.push((identifier, entry));
+			index += 1;
+		}
+		Ok(result)
+	}
 	pub async fn insert(&mut self, key: &I, value: T) -> Result<Option<T>, BokkenDetailedError> {
 		let file_ref = &mut self.file_ref.lock().await;
 		let (index, old_value) = match self._binary_search(key, file_ref).await? {
@@ -280,6 +300,13 @@ impl<
 		file_ref.write(&entry_bytes).await?;
 		Ok(())
 	}
+	/// Makes sure everything written so far is on disk, not only in the OS's page cache
+	pub async fn sync(&self) -> Result<(), BokkenDetailedError> {
+		let file_ref = &mut self.file_ref.lock().await;
+		file_ref.flush().await?;
+		file_ref.sync_data().await?;
+		Ok(())
+	}
 }
 
 #[derive(Debug)]
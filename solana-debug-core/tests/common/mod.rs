@@ -0,0 +1,28 @@
+//! Helpers shared by the integration tests which drive a running Bokken over RPC
+#![allow(dead_code)]
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, native_token::LAMPORTS_PER_SOL, signature::{Keypair, Signature}, signer::Signer};
+
+pub fn client() -> RpcClient {
+	RpcClient::new_with_commitment(
+		std::env::var("BOKKEN_RPC_URL").unwrap_or_else(|_| "http://127.0.0.1:8899".to_string()),
+		CommitmentConfig::confirmed()
+	)
+}
+
+/// A new keypair with 1 SOL, airdropped and confirmed
+pub fn funded_keypair(client: &RpcClient) -> Keypair {
+	let keypair = Keypair::new();
+	let signature = client.request_airdrop(&keypair.pubkey(), LAMPORTS_PER_SOL).unwrap();
+	confirm(client, &signature);
+	keypair
+}
+
+pub fn confirm(client: &RpcClient, signature: &Signature) {
+	client.poll_for_signature(signature).unwrap();
+	let statuses = client.get_signature_statuses(&[*signature]).unwrap();
+	let status = statuses.value[0].as_ref().expect("the confirmed transaction to have a status");
+	assert_eq!(status.status, Ok(()));
+	assert!(status.satisfies_commitment(CommitmentConfig::finalized()));
+}
@@ -0,0 +1,97 @@
+//! Smoke tests driving Bokken with `solana-client`, to catch responses which a real client can't deserialize.
+//!
+//! They need a running Bokken, so they're ignored by default. Start one, then run them with
+//! `cargo test -p bokken-core --test solana_client -- --ignored`. `BOKKEN_RPC_URL` overrides where Bokken is expected
+//! to be listening (Default: http://127.0.0.1:8899).
+
+mod common;
+
+use common::{client, confirm, funded_keypair};
+use solana_client::rpc_config::RpcBlockConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signer::Signer, system_instruction, transaction::Transaction};
+use solana_transaction_status::{TransactionDetails, UiTransactionEncoding};
+
+#[test]
+#[ignore = "needs a running Bokken"]
+fn cluster_info() {
+	let client = client();
+	let version = client.get_version().unwrap();
+	assert!(version.feature_set.is_some());
+	client.get_genesis_hash().unwrap();
+	let slot = client.get_slot().unwrap();
+	assert!(client.get_block_height().unwrap() >= slot);
+	let epoch_info = client.get_epoch_info().unwrap();
+	assert!(epoch_info.absolute_slot >= slot);
+	let (blockhash, last_valid_block_height) = client.get_latest_blockhash_with_commitment(
+		CommitmentConfig::confirmed()
+	).unwrap();
+	assert!(last_valid_block_height > client.get_block_height().unwrap());
+	assert!(client.is_blockhash_valid(&blockhash, CommitmentConfig::processed()).unwrap());
+	assert!(client.get_minimum_balance_for_rent_exemption(0).unwrap() > 0);
+}
+
+#[test]
+#[ignore = "needs a running Bokken"]
+fn airdrop_and_read_accounts() {
+	let client = client();
+	let keypair = funded_keypair(&client);
+	assert_eq!(client.get_balance(&keypair.pubkey()).unwrap(), LAMPORTS_PER_SOL);
+	let account = client.get_account(&keypair.pubkey()).unwrap();
+	assert_eq!(account.lamports, LAMPORTS_PER_SOL);
+	assert_eq!(account.owner, solana_sdk::system_program::id());
+	assert!(account.data.is_empty());
+	let missing = client.get_account_with_commitment(&Pubkey::new_unique(), CommitmentConfig::confirmed()).unwrap();
+	assert!(missing.value.is_none());
+}
+
+#[test]
+#[ignore = "needs a running Bokken"]
+fn send_and_confirm_transfer() {
+	let client = client();
+	let payer = funded_keypair(&client);
+	let recipient = Pubkey::new_unique();
+	let tx = Transaction::new_signed_with_payer(
+		&[system_instruction::transfer(&payer.pubkey(), &recipient, LAMPORTS_PER_SOL / 10)],
+		Some(&payer.pubkey()),
+		&[&payer],
+		client.get_latest_blockhash().unwrap()
+	);
+	let signature = client.send_and_confirm_transaction(&tx).unwrap();
+	confirm(&client, &signature);
+	assert_eq!(client.get_balance(&recipient).unwrap(), LAMPORTS_PER_SOL / 10);
+
+	let slot = client.get_signature_statuses(&[signature]).unwrap().value[0].as_ref().unwrap().slot;
+	let block = client.get_block_with_config(
+		slot,
+		RpcBlockConfig {
+			// Bokken only supports binary transaction encodings
+			encoding: Some(UiTransactionEncoding::Base64),
+			transaction_details: Some(TransactionDetails::Full),
+			rewards: Some(false),
+			commitment: None,
+			max_supported_transaction_version: Some(0)
+		}
+	).unwrap();
+	let transactions = block.transactions.expect("full transaction details");
+	assert_eq!(transactions.len(), 1);
+	assert_eq!(transactions[0].transaction.decode().expect("a decodable transaction").signatures[0], signature);
+	let meta = transactions[0].meta.as_ref().expect("the transaction to have a meta");
+	assert_eq!(meta.status, Ok(()));
+	assert_eq!(meta.pre_balances.len(), tx.message.account_keys.len());
+}
+
+#[test]
+#[ignore = "needs a running Bokken"]
+fn failed_transaction_is_rejected() {
+	let client = client();
+	let payer = funded_keypair(&client);
+	let tx = Transaction::new_signed_with_payer(
+		&[system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), LAMPORTS_PER_SOL * 2)],
+		Some(&payer.pubkey()),
+		&[&payer],
+		client.get_latest_blockhash().unwrap()
+	);
+	assert!(client.send_and_confirm_transaction(&tx).is_err());
+	// Failed transactions are rolled back entirely, including their fees
+	assert_eq!(client.get_balance(&payer.pubkey()).unwrap(), LAMPORTS_PER_SOL);
+}
@@ -0,0 +1,105 @@
+//! End to end tests of the bundled test program's instructions, sent over RPC with `solana-client`.
+//!
+//! They need a running Bokken with `test-program-debug` connected as `TheDebugab1eProgram111111111111111111111111`, so
+//! they're ignored by default. Start both, then run them with
+//! `cargo test -p bokken-core --test test_program -- --ignored`. `BOKKEN_RPC_URL` overrides where Bokken is expected
+//! to be listening (Default: http://127.0.0.1:8899).
+
+mod common;
+
+use common::{client, funded_keypair};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{instruction::{AccountMeta, Instruction}, pubkey::Pubkey, signature::Keypair, signer::Signer, system_program, transaction::Transaction};
+use test_program::instruction::TestProgramInstruction;
+
+const PROGRAM_ID: Pubkey = solana_sdk::pubkey!("TheDebugab1eProgram111111111111111111111111");
+
+fn transaction(client: &RpcClient, payer: &Keypair, instruction: Instruction) -> Transaction {
+	Transaction::new_signed_with_payer(&[instruction], Some(&payer.pubkey()), &[payer], client.get_latest_blockhash().unwrap())
+}
+
+/// Creates the PDA of `payer` with `CreatePda`, returning its address
+fn create_pda(client: &RpcClient, payer: &Keypair, space: u64) -> Pubkey {
+	let (pda, _) = Pubkey::find_program_address(&[b"pda", payer.pubkey().as_ref()], &PROGRAM_ID);
+	client.send_and_confirm_transaction(&transaction(
+		client,
+		payer,
+		Instruction::new_with_borsh(
+			PROGRAM_ID,
+			&TestProgramInstruction::CreatePda { space },
+			vec![
+				AccountMeta::new(payer.pubkey(), true),
+				AccountMeta::new(pda, false),
+				AccountMeta::new_readonly(system_program::id(), false)
+			]
+		)
+	)).unwrap();
+	pda
+}
+
+#[test]
+#[ignore = "needs a running Bokken with test-program-debug connected"]
+fn create_pda_with_invoke_signed() {
+	let client = client();
+	let payer = funded_keypair(&client);
+	let pda = create_pda(&client, &payer, 16);
+	let account = client.get_account(&pda).unwrap();
+	assert_eq!(account.owner, PROGRAM_ID);
+	assert_eq!(account.data, vec![0; 16]);
+	assert_eq!(account.lamports, client.get_minimum_balance_for_rent_exemption(16).unwrap());
+}
+
+#[test]
+#[ignore = "needs a running Bokken with test-program-debug connected"]
+fn realloc_grows_and_shrinks() {
+	let client = client();
+	let payer = funded_keypair(&client);
+	let pda = create_pda(&client, &payer, 16);
+	for new_size in [1024, 8] {
+		client.send_and_confirm_transaction(&transaction(
+			&client,
+			&payer,
+			Instruction::new_with_borsh(
+				PROGRAM_ID,
+				&TestProgramInstruction::Realloc { new_size },
+				vec![AccountMeta::new(pda, false)]
+			)
+		)).unwrap();
+		assert_eq!(client.get_account(&pda).unwrap().data, vec![0; new_size as usize]);
+	}
+}
+
+#[test]
+#[ignore = "needs a running Bokken with test-program-debug connected"]
+fn return_data_is_reported() {
+	let client = client();
+	let payer = funded_keypair(&client);
+	let data = b"Bokken today!".to_vec();
+	for ix in [
+		TestProgramInstruction::SetReturnData { data: data.clone() },
+		// The program checks that it can see the return data the CPI left behind
+		TestProgramInstruction::InvokeSetReturnData { data: data.clone() }
+	] {
+		let simulation = client.simulate_transaction(
+			&transaction(&client, &payer, Instruction::new_with_borsh(PROGRAM_ID, &ix, Vec::new()))
+		).unwrap().value;
+		assert_eq!(simulation.err, None, "{:?}", simulation.logs);
+		let return_data = simulation.return_data.expect("the simulation to have return data");
+		assert_eq!(return_data.program_id, PROGRAM_ID.to_string());
+		assert_eq!(base64::decode(&return_data.data.0).unwrap(), data);
+	}
+}
+
+#[test]
+#[ignore = "needs a running Bokken with test-program-debug connected"]
+fn sysvars_are_readable() {
+	let client = client();
+	let payer = funded_keypair(&client);
+	let simulation = client.simulate_transaction(
+		&transaction(&client, &payer, Instruction::new_with_borsh(PROGRAM_ID, &TestProgramInstruction::ReadSysvars, Vec::new()))
+	).unwrap().value;
+	assert_eq!(simulation.err, None, "{:?}", simulation.logs);
+	let logs = simulation.logs.unwrap_or_default();
+	assert!(logs.iter().any(|log| log.contains("Clock: slot")), "{:?}", logs);
+	assert!(logs.iter().any(|log| log.contains("Rent: lamports_per_byte_year")), "{:?}", logs);
+}
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use bokken_runtime::{debug_env::{BokkenAccountData, BokkenRuntimeMessage, BokkenValidatorMessage}, ipc_comm::IPCComm};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use solana_program::pubkey::Pubkey;
+use tokio::{net::UnixStream, runtime::Runtime};
+
+/// Time for an invocation carrying `data_size` bytes of account data to reach a program, and for its answer to arrive
+fn ipc_round_trip(c: &mut Criterion) {
+	let rt = Runtime::new().unwrap();
+	let mut group = c.benchmark_group("ipc_round_trip");
+	for data_size in [0usize, 1024, 10 * 1024, 1024 * 1024] {
+		let (mut validator, mut program) = rt.block_on(async {
+			let (validator_stream, program_stream) = UnixStream::pair().unwrap();
+			(IPCComm::new(validator_stream), IPCComm::new(program_stream))
+		});
+		let program_id = Pubkey::new_unique();
+		let account = Pubkey::new_unique();
+		group.throughput(Throughput::Bytes(data_size as u64));
+		group.bench_with_input(BenchmarkId::from_parameter(data_size), &data_size, |b, &data_size| {
+			b.iter(|| rt.block_on(async {
+				validator.send_msg(
+					BokkenValidatorMessage::Invoke {
+						nonce: 0,
+						program_id,
+						instruction: Vec::new(),
+						account_metas: Vec::new(),
+						account_datas: HashMap::from([
+							(account, BokkenAccountData { data: vec![0; data_size], ..Default::default() })
+						]),
+						call_depth: 0,
+						return_data: None
+					}
+				).await.unwrap();
+				let _: BokkenValidatorMessage = program.until_recv_msg().await.unwrap().unwrap();
+				program.send_msg(BokkenRuntimeMessage::Pong).await.unwrap();
+				let _: BokkenRuntimeMessage = validator.until_recv_msg().await.unwrap().unwrap();
+			}));
+		});
+		validator.stop();
+		program.stop();
+	}
+	group.finish();
+}
+
+criterion_group!(benches, ipc_round_trip);
+criterion_main!(benches);
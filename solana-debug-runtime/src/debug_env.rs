@@ -1,7 +1,7 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, io, str::FromStr};
 
 use borsh::{BorshSerialize, BorshDeserialize};
-use solana_program::{pubkey::Pubkey, instruction::AccountMeta, program_error::ProgramError};
+use solana_program::{pubkey::Pubkey, instruction::AccountMeta, program_error::ProgramError, entrypoint::MAX_PERMITTED_DATA_INCREASE, system_instruction::MAX_PERMITTED_DATA_LENGTH};
 
 /// The structure used to store a Solana account's information
 #[derive(PartialEq, Eq, Debug, Clone, BorshSerialize, BorshDeserialize, Default)]
@@ -24,6 +24,24 @@ impl BokkenAccountData {
 	}
 }
 
+/// Limits on account sizes which programs are held to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BokkenAccountSizeLimits {
+	/// Maximum data size of any account
+	pub max_account_size: u64,
+	/// How much an account's data may grow within a single instruction. Anything above `MAX_PERMITTED_DATA_INCREASE`
+	/// has no effect on debuggable programs, as that's all the room the entrypoint gives them.
+	pub max_data_increase: u64
+}
+impl Default for BokkenAccountSizeLimits {
+	fn default() -> Self {
+		Self {
+			max_account_size: MAX_PERMITTED_DATA_LENGTH,
+			max_data_increase: MAX_PERMITTED_DATA_INCREASE as u64
+		}
+	}
+}
+
 /// Same as Solana's own `AccountMeta`, except this implements `BorshSerialize` and `BorshDeserialize`
 #[derive(Debug, Default, PartialEq, Clone, BorshSerialize, BorshDeserialize)]
 pub struct BorshAccountMeta {
@@ -66,14 +84,17 @@ impl From<BorshAccountMeta> for AccountMeta {
 /// IPC message sent from a debuggable program to the main Bokken process.
 #[derive(Debug, BorshSerialize, BorshDeserialize)]
 pub enum BokkenRuntimeMessage {
+	/// Log messages are batched, so this may contain any amount of them, in the order they were logged
 	Log {
 		nonce: u64,
-		message: String
+		messages: Vec<BokkenLogMessage>
 	},
 	Executed {
 		nonce: u64,
 		return_code: u64,
-		account_datas: HashMap<Pubkey, BokkenAccountData>
+		account_datas: HashMap<Pubkey, BokkenAccountData>,
+		/// The program which last set return data, and the data itself
+		return_data: Option<(Pubkey, Vec<u8>)>
 	},
 	CrossProgramInvoke {
 		nonce: u64,
@@ -81,7 +102,71 @@ pub enum BokkenRuntimeMessage {
 		instruction: Vec<u8>,
 		account_metas: Vec<BorshAccountMeta>,
 		account_datas: HashMap<Pubkey, BokkenAccountData>,
-		call_depth: u8
+		call_depth: u8,
+		/// Return data as the calling program left it, which the invoked program starts with
+		return_data: Option<(Pubkey, Vec<u8>)>
+	},
+	/// Answer to `BokkenValidatorMessage::Ping`
+	Pong,
+	/// Sent right before the `Executed` message of an invocation whose program panicked
+	Panicked {
+		nonce: u64,
+		message: String,
+		/// Where the panic happened, as captured by the runtime's panic hook
+		backtrace: String
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, BorshSerialize, BorshDeserialize)]
+pub enum BokkenLogLevel {
+	/// Messages from the Bokken runtime itself, such as unsupported syscall warnings
+	Debug,
+	/// Messages logged by the Solana program
+	#[default]
+	Info
+}
+impl FromStr for BokkenLogLevel {
+	type Err = String;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_ascii_lowercase().as_str() {
+			"debug" => Ok(Self::Debug),
+			"info" => Ok(Self::Info),
+			_ => Err(format!("Unknown log level \"{}\", expected \"debug\" or \"info\"", s))
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BokkenLogMessage {
+	pub level: BokkenLogLevel,
+	pub message: String
+}
+
+/// The first message a debuggable program sends after connecting to the main Bokken process
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize)]
+pub struct BokkenProgramIdentity {
+	pub program_id: Pubkey,
+	/// Human-readable name, such as the crate name and version
+	pub name: Option<String>
+}
+impl BorshDeserialize for BokkenProgramIdentity {
+	fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+		let program_id = Pubkey::deserialize(buf)?;
+		// Older runtimes only send the program ID
+		let name = if buf.is_empty() {
+			None
+		}else{
+			Option::<String>::deserialize(buf)?
+		};
+		Ok(Self { program_id, name })
+	}
+}
+impl fmt::Display for BokkenProgramIdentity {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match &self.name {
+			Some(name) => write!(f, "{} ({})", name, self.program_id),
+			None => write!(f, "{}", self.program_id)
+		}
 	}
 }
 
@@ -94,11 +179,25 @@ pub enum BokkenValidatorMessage {
 		instruction: Vec<u8>,
 		account_metas: Vec<BorshAccountMeta>,
 		account_datas: HashMap<Pubkey, BokkenAccountData>,
-		call_depth: u8
+		call_depth: u8,
+		/// The transaction's return data as of the start of this invocation
+		return_data: Option<(Pubkey, Vec<u8>)>
 	},
 	CrossProgramInvokeResult {
 		nonce: u64,
 		return_code: u64,
-		account_datas: HashMap<Pubkey, BokkenAccountData>
+		account_datas: HashMap<Pubkey, BokkenAccountData>,
+		/// Return data as left by the invoked program, which becomes what `sol_get_return_data` gives the caller
+		return_data: Option<(Pubkey, Vec<u8>)>
+	},
+	/// Log messages below `min_level` won't be sent to the main Bokken process at all
+	SetLogLevel {
+		min_level: BokkenLogLevel
+	},
+	/// Heartbeat, must be answered with `BokkenRuntimeMessage::Pong`
+	Ping,
+	/// Account size limits which invocations from now on are held to
+	SetAccountSizeLimits {
+		limits: BokkenAccountSizeLimits
 	}
 }
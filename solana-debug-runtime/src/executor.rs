@@ -1,15 +1,40 @@
-use std::{mem::size_of, collections::HashMap, sync::Arc, thread};
+use std::{mem::size_of, collections::HashMap, sync::{Arc, Mutex, RwLock, mpsc}, thread, backtrace::Backtrace};
 
 
+use lazy_static::lazy_static;
 use bytemuck::{Zeroable, Pod};
 use solana_program::{
-	entrypoint::MAX_PERMITTED_DATA_INCREASE,
+	entrypoint::{MAX_PERMITTED_DATA_INCREASE, BPF_ALIGN_OF_U128, NON_DUP_MARKER},
 	pubkey::Pubkey,
 	program_error::ProgramError, instruction::AccountMeta
 };
-use tokio::{sync::{Mutex, RwLock, mpsc}};
 
-use crate::{debug_env::{BokkenAccountData, BokkenRuntimeMessage}, ipc_comm::IPCComm, sol_syscalls::BokkenSyscallMsg};
+use crate::{debug_env::{BokkenAccountData, BokkenAccountSizeLimits, BokkenRuntimeMessage, BokkenLogLevel, BokkenLogMessage}, ipc_comm::IPCCommSender, log_batcher::BokkenLogBatcher, sol_syscalls::BokkenCPIResult};
+
+lazy_static! {
+	/// Backtraces of panics in program threads, by the thread which panicked, until the invocation picks them up
+	static ref PANIC_BACKTRACES: Mutex<HashMap<thread::ThreadId, String>> = Mutex::new(HashMap::new());
+}
+/// Threads running program code are named with this prefix, see `BokkenExecutor::execute`
+const PROGRAM_THREAD_NAME_PREFIX: &str = "bokken-program-";
+
+/// Makes panics within program code record their backtrace, so that it can be sent to the main process along with
+/// the panic message. The previously installed panic hook still runs afterwards.
+/// 
+/// Backtraces are captured regardless of `RUST_BACKTRACE`, and point at source lines as long as the program was built
+/// with debug info.
+pub(crate) fn install_panic_hook() {
+	let previous_hook = std::panic::take_hook();
+	std::panic::set_hook(Box::new(move |info| {
+		let current_thread = thread::current();
+		if current_thread.name().map_or(false, |name| name.starts_with(PROGRAM_THREAD_NAME_PREFIX)) {
+			if let Ok(mut backtraces) = PANIC_BACKTRACES.lock() {
+				backtraces.insert(current_thread.id(), Backtrace::force_capture().to_string());
+			}
+		}
+		previous_hook(info);
+	}));
+}
 
 /// Raw header data for the `SolanaAccountsBlob`
 #[derive(PartialEq, Eq, Debug, Clone, Copy, Zeroable, Pod)]
@@ -37,13 +62,29 @@ impl AccountInfoHeader {
 	}
 }
 
+/// Offset of the next position at or after `offset` which is aligned the way `solana_program`'s entrypoint parser
+/// expects, assuming the blob itself starts at an aligned address.
+fn align_offset(offset: usize) -> usize {
+	(offset + BPF_ALIGN_OF_U128 - 1) / BPF_ALIGN_OF_U128 * BPF_ALIGN_OF_U128
+}
+
 /// An instance of multiple Solana `AccountInfo`s, structured in a manner which the `solana_program`'s entrypoint
 /// parser expects.
+///
+/// The layout matches `solana_program::entrypoint::deserialize`: Each account appears in the same order as its
+/// account meta, accounts which appear more than once are referenced by the index of their first appearance (with the
+/// signer and writable flags of all appearances combined), and every rent epoch is aligned to `BPF_ALIGN_OF_U128`.
 #[derive(Debug)]
 pub(crate) struct SolanaAccountsBlob {
 	pub account_offsets: HashMap<Pubkey, usize>,
-	pub bytes: Vec<u8>,
-	pub non_entrypointed_account_infos: HashMap<Pubkey, BokkenAccountData>
+	/// Stored as u128s so that the blob starts at an address as aligned as the entrypoint parser requires
+	aligned_bytes: Vec<u128>,
+	len: usize,
+	pub non_entrypointed_account_infos: HashMap<Pubkey, BokkenAccountData>,
+	program_id: Pubkey,
+	/// Account states the program's changes are checked against by `invariant_violations`
+	pre_states: HashMap<Pubkey, BokkenAccountData>,
+	size_limits: BokkenAccountSizeLimits
 }
 impl SolanaAccountsBlob {
 	/// Creates a new instance of solana account data with the information provided
@@ -51,7 +92,8 @@ impl SolanaAccountsBlob {
 		program_id: Pubkey,
 		instruction: Vec<u8>,
 		account_metas: Vec<AccountMeta>,
-		mut account_datas: HashMap<Pubkey, BokkenAccountData>
+		mut account_datas: HashMap<Pubkey, BokkenAccountData>,
+		size_limits: BokkenAccountSizeLimits
 	) -> Self {
 		let mut blob: Vec<u8> = Vec::with_capacity(
 			account_metas.len() * 20480 + // this value is arbitrary
@@ -60,65 +102,100 @@ impl SolanaAccountsBlob {
 			size_of::<Pubkey>()
 		);
 		blob.extend((account_metas.len() as u64).to_le_bytes());
-		// println!("SolanaAccountsBlob: account_datas: {:#?}", account_datas.keys().map(|k|{*k}).collect::<Vec<Pubkey>>());
-		// println!("SolanaAccountsBlob: account_metas: {:#?}", account_metas);
+		// Duplicate metas all refer to the same account info, so it gets the privileges of all of them
+		let mut account_privileges: HashMap<Pubkey, (bool, bool)> = HashMap::new();
+		for account_meta in account_metas.iter() {
+			let (is_signer, is_writable) = account_privileges.entry(account_meta.pubkey).or_default();
+			*is_signer |= account_meta.is_signer;
+			*is_writable |= account_meta.is_writable;
+		}
 		let mut account_indices: HashMap<Pubkey, usize> = HashMap::new();
 		let mut account_offsets: HashMap<Pubkey, usize> = HashMap::new();
+		let mut pre_states: HashMap<Pubkey, BokkenAccountData> = HashMap::new();
 		for (index, account_meta) in account_metas.iter().enumerate() {
 			if let Some(entry_index) = account_indices.get(&account_meta.pubkey) {
-				blob.extend((*entry_index as u64).to_le_bytes());
+				blob.push(*entry_index as u8);
+				blob.extend([0; 7]);
 			}else{
 				let account_data = account_datas.remove(&account_meta.pubkey)
 					.expect("The account metas should reference accounts in the account datas");
+				let (is_signer, is_writable) = account_privileges[&account_meta.pubkey];
 				account_indices.insert(account_meta.pubkey, index);
 				account_offsets.insert(account_meta.pubkey, blob.len());
+				pre_states.insert(account_meta.pubkey, account_data.clone());
 
-				blob.push(u8::MAX);
-				blob.push(account_meta.is_signer as u8);
-				blob.push(account_meta.is_writable as u8);
+				blob.push(NON_DUP_MARKER);
+				blob.push(is_signer as u8);
+				blob.push(is_writable as u8);
 				blob.push(account_data.executable as u8);
-				blob.extend((account_data.data.len() as u32).to_le_bytes()); // "Original data length" (immediatly overwritten?)
+				// The entrypoint parser treats this as padding, we use it to remember how much the data can grow
+				blob.extend((account_data.data.len() as u32).to_le_bytes());
 				blob.extend(account_meta.pubkey.as_ref());
 				blob.extend(account_data.owner.as_ref());
 				blob.extend((account_data.lamports).to_le_bytes());
 				blob.extend((account_data.data.len() as u64).to_le_bytes());
 				blob.extend(account_data.data);
-				blob.extend(vec![0; MAX_PERMITTED_DATA_INCREASE]);
-				blob.extend(vec![0; blob.len() % 8]);
+				blob.resize(align_offset(blob.len() + MAX_PERMITTED_DATA_INCREASE), 0);
 				blob.extend(account_data.rent_epoch.to_le_bytes());		
 			}
 		}
 		blob.extend((instruction.len() as u64).to_le_bytes());
 		blob.extend(instruction);
 		blob.extend(program_id.as_ref());
+
+		let len = blob.len();
+		let mut aligned_bytes = vec![0u128; (len + size_of::<u128>() - 1) / size_of::<u128>()];
+		bytemuck::cast_slice_mut::<u128, u8>(&mut aligned_bytes)[..len].copy_from_slice(&blob);
 		Self {
-			bytes: blob,
+			aligned_bytes,
+			len,
 			account_offsets,
-			non_entrypointed_account_infos: account_datas
+			non_entrypointed_account_infos: account_datas,
+			program_id,
+			pre_states,
+			size_limits
 		}
 	}
 
+	/// The serialized accounts, instruction, and program ID
+	pub fn bytes(&self) -> &[u8] {
+		&bytemuck::cast_slice::<u128, u8>(&self.aligned_bytes)[..self.len]
+	}
+	fn bytes_mut(&mut self) -> &mut [u8] {
+		&mut bytemuck::cast_slice_mut::<u128, u8>(&mut self.aligned_bytes)[..self.len]
+	}
+	/// Pointer to the start of the blob, for the program's entrypoint
+	pub fn as_mut_ptr(&mut self) -> *mut u8 {
+		self.bytes_mut().as_mut_ptr()
+	}
+
 	/// Returns a copy of the account info associated with the specified pubkey
 	/// 
 	/// Returns None if the account doesn't exist in this context.
 	pub fn get_account_data(&self, pubkey: &Pubkey) -> Option<BokkenAccountData> {
 		if let Some(account_offset) = self.account_offsets.get(pubkey) {
+			let bytes = self.bytes();
 			let account_data_offset = *account_offset + std::mem::size_of::<AccountInfoHeader>();
 			let account_header = bytemuck::from_bytes::<AccountInfoHeader>(
-				&self.bytes[*account_offset..account_data_offset]
+				&bytes[*account_offset..account_data_offset]
 			);
-			let rent_epoch_offset =
+			let rent_epoch_offset = align_offset(
 				account_data_offset +
 				account_header.original_data_len as usize +
-				MAX_PERMITTED_DATA_INCREASE +
-				(account_header.original_data_len as usize % 8);
+				MAX_PERMITTED_DATA_INCREASE
+			);
+			
+			// Programs growing their data past the limit is reported by `invariant_violations`
+			let data_len = (account_header.data_len as usize).min(
+				account_header.original_data_len as usize + MAX_PERMITTED_DATA_INCREASE
+			);
 			
 			Some( BokkenAccountData {
 				lamports: account_header.lamports,
-				data: self.bytes[account_data_offset..{account_data_offset + account_header.data_len as usize}].to_vec(),
+				data: bytes[account_data_offset..{account_data_offset + data_len}].to_vec(),
 				owner: account_header.owner,
 				executable: account_header.executable > 0,
-				rent_epoch: u64::from_le_bytes(self.bytes[rent_epoch_offset..{rent_epoch_offset + 8}].try_into().unwrap())
+				rent_epoch: u64::from_le_bytes(bytes[rent_epoch_offset..{rent_epoch_offset + 8}].try_into().unwrap())
 			})
 		}else{
 			None
@@ -130,21 +207,31 @@ impl SolanaAccountsBlob {
 		)
 	}
 
-	/// Edits the account data accessible by the solana program with the data provided
+	/// Edits the account data accessible by the solana program with the data provided.
+	/// 
+	/// This is used to apply the results of a CPI, which were already checked in the callee's context, so the new state
+	/// is what `invariant_violations` checks the program's changes against from now on.
 	pub fn set_account_data(&mut self, pubkey: &Pubkey, account_data: BokkenAccountData) -> Result<(), ProgramError> {
-		if let Some(account_offset) = self.account_offsets.get(pubkey) {
-			let account_data_offset = *account_offset + std::mem::size_of::<AccountInfoHeader>();
+		if let Some(&account_offset) = self.account_offsets.get(pubkey) {
+			let max_data_increase = self.max_data_increase();
+			let max_account_size = self.size_limits.max_account_size;
+			let account_data_offset = account_offset + std::mem::size_of::<AccountInfoHeader>();
 			let account_header = bytemuck::from_bytes_mut::<AccountInfoHeader>(
-				&mut self.bytes[*account_offset..account_data_offset]
+				&mut self.bytes_mut()[account_offset..account_data_offset]
 			);
-			if account_data.data.len() > account_header.original_data_len as usize + MAX_PERMITTED_DATA_INCREASE {
+			if account_data.data.len() > account_header.original_data_len as usize + max_data_increase {
 				println!("Debug runtime: set_account_data: {} was grown too much", pubkey);
 				return Err(ProgramError::InvalidRealloc);
 			}
+			if account_data.data.len() as u64 > max_account_size {
+				println!("Debug runtime: set_account_data: {} was grown past the max account size", pubkey);
+				return Err(ProgramError::InvalidRealloc);
+			}
 			account_header.data_len = account_data.data.len() as u64;
 			account_header.lamports = account_data.lamports;
 			account_header.owner = account_data.owner;
-			self.bytes[account_data_offset..{account_data_offset + account_data.data.len()}].copy_from_slice(&account_data.data);
+			self.bytes_mut()[account_data_offset..{account_data_offset + account_data.data.len()}].copy_from_slice(&account_data.data);
+			self.pre_states.insert(*pubkey, account_data);
 			Ok(())
 		}else{
 			println!(
@@ -160,7 +247,7 @@ impl SolanaAccountsBlob {
 		if let Some(account_offset) = self.account_offsets.get(pubkey) {
 			let account_data_offset = *account_offset + std::mem::size_of::<AccountInfoHeader>();
 			let account_header = bytemuck::from_bytes::<AccountInfoHeader>(
-				&self.bytes[*account_offset..account_data_offset]
+				&self.bytes()[*account_offset..account_data_offset]
 			);
 			Some(account_header)
 		}else{
@@ -186,6 +273,115 @@ impl SolanaAccountsBlob {
 		}
 	}
 
+	/// How much an account's data may grow, as configured but never more than there's room for
+	fn max_data_increase(&self) -> usize {
+		(self.size_limits.max_data_increase as usize).min(MAX_PERMITTED_DATA_INCREASE)
+	}
+
+	/// Checks the changes the program made against the rules the real runtime enforces, returning a description of
+	/// every broken rule which points at the offending account.
+	pub fn invariant_violations(&self) -> Vec<String> {
+		let mut violations = Vec::new();
+		let mut pre_lamports: u128 = 0;
+		let mut post_lamports: u128 = 0;
+		let mut lamports_knowable = true;
+		for (pubkey, pre) in self.pre_states.iter() {
+			let header = self.get_account_data_header(pubkey).expect("pre_states to only have accounts in the blob");
+			if header.data_len as usize > header.original_data_len as usize + MAX_PERMITTED_DATA_INCREASE {
+				violations.push(format!(
+					"Account {} was grown to {} bytes, more than MAX_PERMITTED_DATA_INCREASE ({}) past its original {} bytes",
+					pubkey,
+					header.data_len,
+					MAX_PERMITTED_DATA_INCREASE,
+					header.original_data_len
+				));
+				// Its data was cut short, so the rest of the account can't be compared
+				lamports_knowable = false;
+				continue;
+			}
+			if header.data_len as usize > header.original_data_len as usize + self.max_data_increase() {
+				violations.push(format!(
+					"Account {} was grown to {} bytes, more than the configured max data increase ({}) past its original {} bytes",
+					pubkey,
+					header.data_len,
+					self.max_data_increase(),
+					header.original_data_len
+				));
+			}
+			if header.data_len > self.size_limits.max_account_size {
+				violations.push(format!(
+					"Account {} was grown to {} bytes, more than the max account size ({})",
+					pubkey,
+					header.data_len,
+					self.size_limits.max_account_size
+				));
+			}
+			let post = self.get_account_data(pubkey).expect("pre_states to only have accounts in the blob");
+			let is_writable = header.is_writable() && !header.executable();
+			let is_owned = pre.owner == self.program_id;
+			if post.owner != pre.owner {
+				if !is_owned {
+					violations.push(format!(
+						"Owner of account {} was changed from {} to {}, but it isn't owned by the program",
+						pubkey,
+						pre.owner,
+						post.owner
+					));
+				}else if !is_writable {
+					violations.push(format!(
+						"Owner of account {} was changed to {}, but it isn't writable",
+						pubkey,
+						post.owner
+					));
+				}else if post.data.iter().any(|byte| *byte != 0) {
+					violations.push(format!(
+						"Owner of account {} was changed to {}, but its data isn't zeroed",
+						pubkey,
+						post.owner
+					));
+				}
+			}
+			if post.data != pre.data {
+				if !is_owned {
+					violations.push(format!(
+						"Data of account {} was modified, but it's owned by {} rather than the program",
+						pubkey,
+						pre.owner
+					));
+				}else if !is_writable {
+					violations.push(format!("Data of account {} was modified, but it isn't writable", pubkey));
+				}
+			}
+			if post.lamports != pre.lamports {
+				if !is_writable {
+					violations.push(format!(
+						"Lamports of account {} were changed from {} to {}, but it isn't writable",
+						pubkey,
+						pre.lamports,
+						post.lamports
+					));
+				}else if post.lamports < pre.lamports && !is_owned {
+					violations.push(format!(
+						"Account {} was debited {} lamports, but it's owned by {} rather than the program",
+						pubkey,
+						pre.lamports - post.lamports,
+						pre.owner
+					));
+				}
+			}
+			pre_lamports += pre.lamports as u128;
+			post_lamports += post.lamports as u128;
+		}
+		if lamports_knowable && pre_lamports != post_lamports {
+			violations.push(format!(
+				"The instruction's accounts held {} lamports in total before it was executed, but {} after",
+				pre_lamports,
+				post_lamports
+			));
+		}
+		violations
+	}
+
 	/// Gets all the account information stored in this context
 	pub fn get_account_datas(&self) -> HashMap<Pubkey, BokkenAccountData> {
 		let mut result = HashMap::new();
@@ -215,6 +411,7 @@ impl BokkenSolanaContext {
 		account_datas: HashMap<Pubkey, BokkenAccountData>,
 		nonce: u64,
 		cpi_height: u8,
+		size_limits: BokkenAccountSizeLimits
 	) -> Self {
 		
 		Self {
@@ -224,7 +421,8 @@ impl BokkenSolanaContext {
 					program_id,
 					instruction,
 					account_metas,
-					account_datas
+					account_datas,
+					size_limits
 				)
 			)),
 			nonce,
@@ -232,17 +430,17 @@ impl BokkenSolanaContext {
 		}
 	}
 	pub fn get_account_data(&self, pubkey: &Pubkey) -> Option<BokkenAccountData> {
-		self.blob.blocking_read().get_account_data(pubkey)
+		self.blob.read().unwrap().get_account_data(pubkey)
 	}
 	pub fn is_writable(&self, pubkey: &Pubkey) -> bool {
-		self.blob.blocking_read().is_writable(pubkey)
+		self.blob.read().unwrap().is_writable(pubkey)
 	}
 	pub fn is_signer(&self, pubkey: &Pubkey) -> bool {
-		self.blob.blocking_read().is_signer(pubkey)
+		self.blob.read().unwrap().is_signer(pubkey)
 	}
 
 	pub fn get_account_datas(&self) -> HashMap<Pubkey, BokkenAccountData> {
-		self.blob.blocking_read().get_account_datas()
+		self.blob.read().unwrap().get_account_datas()
 	}
 	pub fn cpi_height(&self) -> u8 {
 		self.cpi_height
@@ -252,81 +450,278 @@ impl BokkenSolanaContext {
 	}
 }
 
-/// Spawns a new thread to execute the Solana program in.
-/// 
-/// Does not await until the new thread is finished, await is only used to properly use the RwLock
-/// After the program execution has finished, `comm` is used to notify the main process of the results, and
-/// `context_drop_notifier` is used to notify `BokkenSyscalls` to pop the context.
-pub(crate) async fn execute_sol_program_thread(
-	nonce: u64,
-	blob: Arc<RwLock<SolanaAccountsBlob>>,
-	comm: Arc<Mutex<IPCComm>>,
-	context_drop_notifier: mpsc::Sender<BokkenSyscallMsg>
-) {
-		// This is "unsafe", but we cannot write-lock the blob during the entire SOL program's execution.
-		// This is because we need to update the account data as a result of a CPI. If we locked it here, then we'd
-		// deadlock ourselves as we'd never be able to update the account data.
-		let blob_ptr = {
-			// And so, we're bypassing the RwLock to make that happen.
-			blob.read().await.bytes.as_ptr() as usize
-		};
-		
-		// All Solana syscalls methods, including invoke, log, are all blocking. So we spawn another thread in order
-		// to avoid deadlocking ourselves.
-		thread::spawn(move || {
+/// Pointer to the bytes of a `SolanaAccountsBlob`, which the program's entrypoint parses its accounts from.
+///
+/// The program writes to the blob through this for its entire execution, so the blob can't stay locked in the
+/// meantime, as CPI results have to be written to it too (see `BokkenSyscalls::sol_invoke_signed`). This is sound as
+/// the bytes are never reallocated once the blob is created, the executing thread keeps the blob alive, and the blob is
+/// only ever written to by the executing thread while the program is running.
+struct BokkenBlobPtr(*mut u8);
+// SAFETY: The pointer is only dereferenced by the thread executing the program, see above
+unsafe impl Send for BokkenBlobPtr {}
+impl BokkenBlobPtr {
+	/// Taking `self` makes closures capture the whole `BokkenBlobPtr` rather than the pointer in it, which isn't `Send`
+	fn into_inner(self) -> *mut u8 {
+		self.0
+	}
+}
+
+/// Runs invocations of the program and keeps track of the ones in progress. Shared between the IPC read loop, which
+/// starts invocations and passes on CPI results, and `BokkenSyscalls`.
+///
+/// Program code is synchronous and blocks within syscalls, e.g. while waiting on a CPI's result, so each invocation
+/// runs on a dedicated OS thread rather than within the tokio runtime. Everything in here is guarded by `std::sync`
+/// primitives which are never held across an `.await` or while waiting on another thread, so both sides can use them
+/// freely. Tokio's `blocking_*` methods aren't used at all, as they panic when called from within the runtime.
+#[derive(Debug)]
+pub(crate) struct BokkenExecutor {
+	ipc: IPCCommSender,
+	log_batcher: BokkenLogBatcher,
+	/// Invocations in progress. Only the last one is executing, the others are waiting on the result of a CPI.
+	contexts: Mutex<Vec<BokkenSolanaContext>>,
+	/// The transaction's return data, as of the executing invocation
+	return_data: Mutex<Option<(Pubkey, Vec<u8>)>>,
+	/// Where the IPC read loop sends the results of CPIs to, by the nonce of the invocation waiting on them
+	invoke_result_senders: Mutex<HashMap<u64, mpsc::Sender<BokkenCPIResult>>>
+}
+impl BokkenExecutor {
+	pub fn new(ipc: IPCCommSender, log_batcher: BokkenLogBatcher) -> Self {
+		Self {
+			ipc,
+			log_batcher,
+			contexts: Mutex::new(Vec::new()),
+			return_data: Mutex::new(None),
+			invoke_result_senders: Mutex::new(HashMap::new())
+		}
+	}
+	pub fn ipc(&self) -> &IPCCommSender {
+		&self.ipc
+	}
+	pub fn log_batcher(&self) -> &BokkenLogBatcher {
+		&self.log_batcher
+	}
+	/// Calls `f` with the context of the executing invocation.
+	/// 
+	/// Panics if nothing is executing, which can only happen if a syscall is made outside of the program's entrypoint.
+	pub fn with_current_context<R>(&self, f: impl FnOnce(&BokkenSolanaContext) -> R) -> R {
+		f(self.contexts.lock().unwrap().last().expect("not be empty during program execution"))
+	}
+	pub fn return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+		self.return_data.lock().unwrap().clone()
+	}
+	pub fn set_return_data(&self, return_data: Option<(Pubkey, Vec<u8>)>) {
+		*self.return_data.lock().unwrap() = return_data;
+	}
+	/// Returns where the result of the CPI the invocation with the specified nonce is about to make will arrive. This
+	/// must be called before the CPI is sent, so that the result can't arrive before anything is waiting for it.
+	pub fn expect_invoke_result(&self, nonce: u64) -> mpsc::Receiver<BokkenCPIResult> {
+		let (sender, receiver) = mpsc::channel();
+		self.invoke_result_senders.lock().unwrap().insert(nonce, sender);
+		receiver
+	}
+	/// Passes the result of a CPI on to the invocation waiting for it. Results nothing is waiting for are dropped.
+	pub fn send_invoke_result(&self, nonce: u64, result: BokkenCPIResult) {
+		let sender = self.invoke_result_senders.lock().unwrap().remove(&nonce);
+		if let Some(sender) = sender {
+			// The receiver is only dropped if the invocation's thread is gone, in which case there's nobody to tell
+			let _ = sender.send(result);
+		}
+	}
+
+	/// Starts executing the invocation on a new thread, without waiting for it to finish.
+	/// 
+	/// After the program execution has finished, the main process is notified of the results, along with any logs
+	/// still pending and the return data left by the program.
+	pub fn execute(self: &Arc<Self>, ctx: BokkenSolanaContext, return_data: Option<(Pubkey, Vec<u8>)>) {
+		let nonce = ctx.nonce();
+		let blob = ctx.blob.clone();
+		let blob_ptr = BokkenBlobPtr(blob.write().unwrap().as_mut_ptr());
+		self.contexts.lock().unwrap().push(ctx);
+		self.set_return_data(return_data);
+		println!("Program execution start");
+		let executor = self.clone();
+		thread::Builder::new().name(format!("bokken-invoke-{}", nonce)).spawn(move || {
 			// Solana programs might panic for any reason. So we spawn yet another thread in order to catch any
 			// potential panics.
-			let result = thread::spawn(move || {
+			let program_thread = thread::Builder::new().name(format!("{}{}", PROGRAM_THREAD_NAME_PREFIX, nonce)).spawn(move || {
 				extern "C" {
 					// The entrypoint macro provided by `solana_program` simply exports a C function called
 					// `entrypoint`. This is how we call upon the provided solana program.
 					fn entrypoint(input: *mut u8) -> u64;
 				}
 				let result = unsafe {
-					entrypoint(blob_ptr as *mut u8)
+					entrypoint(blob_ptr.into_inner())
 				};
 				result
-			}).join();
-			let mut comm = comm.blocking_lock();
-			context_drop_notifier.blocking_send(
-				BokkenSyscallMsg::PopContext
-			).expect("mpsc::Sender to not fail");
-			let account_datas = blob.blocking_read().get_account_datas();
-			match result {
-				Ok(return_code) => {
-					comm.blocking_send_msg(
-						BokkenRuntimeMessage::Executed{
-							nonce,
-							return_code,
-							account_datas
+			}).expect("the OS to spawn a thread");
+			let program_thread_id = program_thread.thread().id();
+			let result = program_thread.join();
+			let backtrace = PANIC_BACKTRACES.lock().unwrap().remove(&program_thread_id);
+			executor.finish_execution(nonce, &blob, result, backtrace);
+		}).expect("the OS to spawn a thread");
+	}
+
+	/// Pops the finished invocation's context, and sends its results to the main process. `backtrace` is where the
+	/// program panicked, if it did.
+	fn finish_execution(
+		&self,
+		nonce: u64,
+		blob: &RwLock<SolanaAccountsBlob>,
+		result: thread::Result<u64>,
+		backtrace: Option<String>
+	) {
+		// Popped before the results are sent, as the main process may start another invocation as soon as they arrive
+		self.contexts.lock().unwrap().pop();
+		let blob = blob.read().unwrap();
+		// Changes are thrown away if the program fails, so they only need checking if it succeeded
+		let invariant_violations = if matches!(result, Ok(0)) {
+			blob.invariant_violations()
+		}else{
+			Vec::new()
+		};
+		for violation in invariant_violations.into_iter() {
+			if let Some(batch) = self.log_batcher.push(nonce, BokkenLogLevel::Info, format!("Bokken: {}", violation)) {
+				self.ipc.send_msg(batch).expect("encoding to not fail");
+			}
+		}
+		let account_datas = blob.get_account_datas();
+		let return_data = self.return_data();
+		if let Some(batch) = self.log_batcher.take_batch() {
+			self.ipc.send_msg(batch).expect("encoding to not fail");
+		}
+		match result {
+			Ok(return_code) => {
+				self.ipc.send_msg(
+					BokkenRuntimeMessage::Executed{
+						nonce,
+						return_code,
+						account_datas,
+						return_data
+					}
+				).expect("encoding to not fail");
+			},
+			Err(err) => {
+				let panic_msg = match err.downcast_ref::<&str>() {
+					Some(str) => str.to_string(),
+					None => {
+						match err.downcast_ref::<String>() {
+							Some(str) => str.clone(),
+							None => String::from("<Unknown panic message>")
 						}
-					).expect("encoding to not fail");
-				},
-				Err(err) => {
-					let panic_msg = match err.downcast_ref::<&str>() {
-						Some(str) => str.to_string(),
-						None => {
-							match err.downcast_ref::<String>() {
-								Some(str) => str.clone(),
-								None => String::from("<Unknown panic message>")
+					},
+				};
+				self.ipc.send_msg(
+					BokkenRuntimeMessage::Log{
+						nonce,
+						messages: vec![
+							BokkenLogMessage {
+								level: BokkenLogLevel::Info,
+								message: format!("Program panicked: {}", panic_msg)
 							}
-						},
-					};
-					comm.blocking_send_msg(
-						BokkenRuntimeMessage::Log{
-							nonce,
-							message: format!("Program panicked: {}", panic_msg)
-						}
-					).expect("encoding to not fail");
-					comm.blocking_send_msg(
-						// TODO: Treat panics differently
-						BokkenRuntimeMessage::Executed{
-							nonce,
-							return_code: ProgramError::Custom(0).into(),
-							account_datas
-						}
-					).expect("encoding to not fail");
-				},
-			}
-		});
+						]
+					}
+				).expect("encoding to not fail");
+				self.ipc.send_msg(
+					BokkenRuntimeMessage::Panicked {
+						nonce,
+						message: panic_msg,
+						backtrace: backtrace.unwrap_or_else(|| String::from("<No backtrace captured>"))
+					}
+				).expect("encoding to not fail");
+				self.ipc.send_msg(
+					BokkenRuntimeMessage::Executed{
+						nonce,
+						return_code: ProgramError::Custom(0).into(),
+						account_datas,
+						return_data
+					}
+				).expect("encoding to not fail");
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::rc::Rc;
+
+	use solana_program::entrypoint::deserialize;
+
+	use super::*;
+
+	fn account(lamports: u64, data: Vec<u8>, executable: bool, rent_epoch: u64) -> BokkenAccountData {
+		BokkenAccountData { lamports, data, owner: Pubkey::new_unique(), executable, rent_epoch }
+	}
+
+	#[test]
+	fn align_offset_rounds_up_to_the_next_boundary() {
+		for offset in 0..(BPF_ALIGN_OF_U128 * 4) {
+			let aligned = align_offset(offset);
+			assert_eq!(aligned % BPF_ALIGN_OF_U128, 0);
+			assert!(aligned >= offset && aligned - offset < BPF_ALIGN_OF_U128, "{} aligned to {}", offset, aligned);
+		}
+	}
+
+	#[test]
+	fn blob_round_trips_through_the_entrypoint_parser() {
+		let program_id = Pubkey::new_unique();
+		let instruction = vec![1, 2, 3, 4, 5];
+		let (empty, odd, large, unused) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+		let account_datas = HashMap::from([
+			(empty, account(1, Vec::new(), false, 10)),
+			(odd, account(2, vec![7; 3], false, 20)),
+			(large, account(3, (0..10 * 1024).map(|i| i as u8).collect(), true, u64::MAX)),
+			(unused, account(4, vec![1], false, 40))
+		]);
+		let account_metas = vec![
+			AccountMeta::new_readonly(empty, true),
+			AccountMeta::new(odd, false),
+			// Duplicates, which should get the privileges of every meta for the same account
+			AccountMeta::new(empty, false),
+			AccountMeta::new_readonly(large, false),
+			AccountMeta::new_readonly(odd, false)
+		];
+		let mut blob = SolanaAccountsBlob::new(
+			program_id,
+			instruction.clone(),
+			account_metas.clone(),
+			account_datas.clone(),
+			BokkenAccountSizeLimits::default()
+		);
+		assert_eq!(blob.as_mut_ptr() as usize % BPF_ALIGN_OF_U128, 0);
+		assert_eq!(blob.non_entrypointed_account_infos, HashMap::from([(unused, account_datas[&unused].clone())]));
+
+		let (parsed_program_id, account_infos, parsed_instruction) = unsafe { deserialize(blob.as_mut_ptr()) };
+		assert_eq!(*parsed_program_id, program_id);
+		assert_eq!(parsed_instruction, instruction.as_slice());
+		assert_eq!(account_infos.len(), account_metas.len());
+		for (account_info, account_meta) in account_infos.iter().zip(account_metas.iter()) {
+			let account_data = &account_datas[&account_meta.pubkey];
+			assert_eq!(*account_info.key, account_meta.pubkey);
+			assert_eq!(*account_info.owner, account_data.owner);
+			assert_eq!(account_info.lamports(), account_data.lamports);
+			assert_eq!(*account_info.data.borrow(), account_data.data.as_slice());
+			assert_eq!(account_info.executable, account_data.executable);
+			assert_eq!(account_info.rent_epoch, account_data.rent_epoch);
+		}
+		assert_eq!(
+			account_infos.iter().map(|account_info| (account_info.is_signer, account_info.is_writable)).collect::<Vec<_>>(),
+			vec![(true, true), (false, true), (true, true), (false, false), (false, true)]
+		);
+		// Duplicates refer to the account info of the account's first appearance
+		for (first, dup) in [(0, 2), (1, 4)] {
+			assert!(Rc::ptr_eq(&account_infos[first].lamports, &account_infos[dup].lamports));
+			assert!(Rc::ptr_eq(&account_infos[first].data, &account_infos[dup].data));
+		}
+		assert!(!Rc::ptr_eq(&account_infos[0].data, &account_infos[1].data));
+
+		// Writes through a duplicate land in the blob
+		**account_infos[2].lamports.borrow_mut() = 100;
+		account_infos[4].data.borrow_mut()[0] = 0xaa;
+		drop(account_infos);
+		assert_eq!(blob.get_account_data(&empty).unwrap().lamports, 100);
+		assert_eq!(blob.get_account_data(&odd).unwrap().data, vec![0xaa, 7, 7]);
+		assert_eq!(blob.get_account_data(&large).unwrap(), account_datas[&large]);
+		assert_eq!(blob.get_account_data(&unused), None);
+	}
 }
@@ -1,10 +1,14 @@
-use std::{collections::{VecDeque}, io, sync::{Arc, atomic::{AtomicBool, Ordering}}};
+use std::{collections::{VecDeque}, io, path::Path, sync::{Arc, Mutex as StdMutex, atomic::{AtomicBool, Ordering}}};
 
 use borsh::{BorshSerialize, BorshDeserialize};
 // use borsh::{BorshSerialize, BorshDeserialize};
-use tokio::{task, net::{UnixStream, unix}, sync::{Mutex, watch}};
+use tokio::{task, net::{UnixListener, UnixStream, unix}, sync::{Mutex, watch}};
 
 
+/// Messages declaring a larger size than this are rejected and the connection is closed, instead of trying to allocate
+/// however much the other side asked for.
+pub const MAX_IPC_MESSAGE_SIZE: u64 = 256 * 1024 * 1024;
+
 enum IPCCommReadState {
 	MsgLength,
 	MsgBody
@@ -38,9 +42,6 @@ impl IPCCommReadHandler {
 
 		
 		let buf_slice = &mut self.buffer.as_mut_slice()[self.buffer_index..];
-		if buf_slice.len() == 0 {
-			panic!("Zero-length message, this shouldn't happen");
-		}
 		let read_result = match self.stream.try_read(buf_slice) {
 			Ok(0) => {
 				IPCCommReadResult::Shutdown
@@ -58,10 +59,22 @@ impl IPCCommReadHandler {
 									.try_into()
 									.expect("vector for msg len should have been 8 bytes long")
 							);
-							self.buffer = vec![0; size as usize];
+							if size > MAX_IPC_MESSAGE_SIZE {
+								return Err(io::Error::new(
+									io::ErrorKind::InvalidData,
+									format!("Message size {} exceeds the maximum of {}", size, MAX_IPC_MESSAGE_SIZE)
+								));
+							}
 							self.buffer_index = 0;
-							self.state = IPCCommReadState::MsgBody;
-							IPCCommReadResult::Waiting
+							if size == 0 {
+								// There's no body to wait for, reading into an empty buffer would look like a shutdown
+								self.buffer = vec![0; 8];
+								IPCCommReadResult::Message(Vec::new())
+							}else{
+								self.buffer = vec![0; size as usize];
+								self.state = IPCCommReadState::MsgBody;
+								IPCCommReadResult::Waiting
+							}
 						},
 						IPCCommReadState::MsgBody => {
 							let final_msg = self.buffer.clone();
@@ -85,13 +98,13 @@ impl IPCCommReadHandler {
 
 
 struct IPCCommWriteHandler {
-	queue: Arc<Mutex<VecDeque<Vec<u8>>>>,
+	queue: Arc<StdMutex<VecDeque<Vec<u8>>>>,
 	stream: unix::OwnedWriteHalf
 }
 impl IPCCommWriteHandler {
 	pub fn new(
 		stream: unix::OwnedWriteHalf,
-		bytes_queue: Arc<Mutex<VecDeque<Vec<u8>>>>,
+		bytes_queue: Arc<StdMutex<VecDeque<Vec<u8>>>>,
 	) -> Self {
 		Self {
 			queue: bytes_queue,
@@ -100,7 +113,8 @@ impl IPCCommWriteHandler {
 	}
 	async fn write_tick(&mut self) -> Result<(), io::Error> {
 		self.stream.writable().await?;
-		let mut send_queue = self.queue.lock().await;
+		// Never held across an await, see `IPCCommSender`
+		let mut send_queue = self.queue.lock().unwrap();
 		if let Some(send_data) = send_queue.pop_front() {
 			match self.stream.try_write(send_data.as_slice()) {
 				Ok(n) => {
@@ -122,13 +136,101 @@ impl IPCCommWriteHandler {
 	}
 }
 
+/// Socket paths starting with `@` are Linux abstract-namespace sockets. These don't exist on the filesystem, so they
+/// can't be left behind by a crashed process, and are cleaned up automatically.
+pub fn is_abstract_socket_path(path: &Path) -> bool {
+	path.to_string_lossy().starts_with('@')
+}
+
+#[cfg(target_os = "linux")]
+fn abstract_socket_addr(path: &Path) -> io::Result<std::os::unix::net::SocketAddr> {
+	use std::os::linux::net::SocketAddrExt;
+	std::os::unix::net::SocketAddr::from_abstract_name(&path.to_string_lossy().as_bytes()[1..])
+}
+#[cfg(not(target_os = "linux"))]
+fn abstract_socket_addr(_path: &Path) -> io::Result<std::os::unix::net::SocketAddr> {
+	Err(io::Error::new(io::ErrorKind::Unsupported, "Abstract-namespace sockets are only supported on Linux"))
+}
+
+/// Listens on the socket at `path` (see `is_abstract_socket_path`).
+///
+/// If a socket file already exists at the path, but nothing is listening on it, it's assumed to have been left behind
+/// by a process which didn't exit cleanly, and it's replaced.
+pub fn bind_ipc_socket(path: &Path) -> io::Result<UnixListener> {
+	let listener = if is_abstract_socket_path(path) {
+		std::os::unix::net::UnixListener::bind_addr(&abstract_socket_addr(path)?)?
+	}else{
+		if let Err(e) = std::os::unix::net::UnixStream::connect(path) {
+			if e.kind() == io::ErrorKind::ConnectionRefused {
+				eprintln!("Removing stale socket {:?}", path);
+				std::fs::remove_file(path)?;
+			}
+		}
+		std::os::unix::net::UnixListener::bind(path)?
+	};
+	listener.set_nonblocking(true)?;
+	UnixListener::from_std(listener)
+}
+
+/// Connects to the socket at `path` (see `is_abstract_socket_path`)
+pub async fn connect_ipc_socket(path: &Path) -> io::Result<UnixStream> {
+	if is_abstract_socket_path(path) {
+		let stream = std::os::unix::net::UnixStream::connect_addr(&abstract_socket_addr(path)?)?;
+		stream.set_nonblocking(true)?;
+		UnixStream::from_std(stream)
+	}else{
+		UnixStream::connect(path).await
+	}
+}
+
+/// Removes the socket file at `path`, abstract sockets are left alone as they don't have one
+pub async fn remove_ipc_socket(path: &Path) -> io::Result<()> {
+	if is_abstract_socket_path(path) {
+		return Ok(());
+	}
+	tokio::fs::remove_file(path).await
+}
+
+/// The other side would reject the message anyway
+fn check_message_size(msg_bytes: &[u8]) -> Result<(), io::Error> {
+	if msg_bytes.len() as u64 > MAX_IPC_MESSAGE_SIZE {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!("Message size {} exceeds the maximum of {}", msg_bytes.len(), MAX_IPC_MESSAGE_SIZE)
+		));
+	}
+	Ok(())
+}
+
+/// Queues messages for sending over an `IPCComm`'s connection.
+///
+/// Unlike `IPCComm` itself, this can be cloned and used from plain threads as well as async tasks. The queue is
+/// guarded by a `std` mutex which is only ever held for as long as it takes to push or pop a message, so sending can't
+/// panic like tokio's `blocking_*` methods do within a runtime, and can't deadlock waiting on the connection.
+#[derive(Debug, Clone)]
+pub struct IPCCommSender {
+	send_queue_bytes: Arc<StdMutex<VecDeque<Vec<u8>>>>
+}
+impl IPCCommSender {
+	/// Adds the provided message to a queue for sending over the underlying connection, but does not wait until
+	/// the message is actually sent
+	pub fn send_msg<S: BorshSerialize>(&self, msg: S) -> Result<(), io::Error> {
+		let msg_bytes = msg.try_to_vec()?;
+		check_message_size(&msg_bytes)?;
+		let mut send_queue_bytes = self.send_queue_bytes.lock().unwrap();
+		send_queue_bytes.push_back((msg_bytes.len() as u64).to_le_bytes().to_vec());
+		send_queue_bytes.push_back(msg_bytes);
+		Ok(())
+	}
+}
+
 // #[derive(Debug, Clone)]
 #[derive(Debug)]
 pub struct IPCComm {
 	write_handle: task::JoinHandle<()>,
 	read_handle: task::JoinHandle<()>,
 	should_stop: Arc<AtomicBool>,
-	send_queue_bytes: Arc<Mutex<VecDeque<Vec<u8>>>>,
+	sender: IPCCommSender,
 	recv_queue_bytes: Arc<Mutex<VecDeque<Vec<u8>>>>,
 	recv_notif: watch::Receiver<usize>
 }
@@ -140,7 +242,7 @@ impl IPCComm {
 		stream: UnixStream,
 	) -> Self {
 		let recv_queue_bytes_mutex = Arc::new(Mutex::new(VecDeque::new()));
-		let send_queue_bytes_mutex = Arc::new(Mutex::new(VecDeque::new()));
+		let send_queue_bytes_mutex = Arc::new(StdMutex::new(VecDeque::new()));
 		let should_stop = Arc::new(AtomicBool::new(false));
 		let (recv_notif_sender, recv_notif) = watch::channel(0usize);
 
@@ -152,7 +254,15 @@ impl IPCComm {
 		let recv_queue_bytes_mutex_clone = recv_queue_bytes_mutex.clone();
 		let read_handle = task::spawn(async move {
 			while !should_stop_clone.load(Ordering::Relaxed) {
-				match read_handler.read_tick().await.unwrap() {
+				let read_result = match read_handler.read_tick().await {
+					Ok(read_result) => read_result,
+					Err(e) => {
+						// The stream can't be trusted to be in sync anymore, so treat it as closed
+						eprintln!("IPC connection closed due to read error: {}", e);
+						IPCCommReadResult::Shutdown
+					}
+				};
+				match read_result {
 					IPCCommReadResult::Shutdown => {
 						should_stop_clone.store(true, Ordering::Relaxed);
 						recv_notif_sender.send_modify(|val| {
@@ -177,7 +287,10 @@ impl IPCComm {
 		let should_stop_clone = should_stop.clone();
 		let write_handle = task::spawn(async move {
 			while !should_stop_clone.load(Ordering::Relaxed) {
-				write_handler.write_tick().await.unwrap();
+				if let Err(e) = write_handler.write_tick().await {
+					eprintln!("IPC connection closed due to write error: {}", e);
+					should_stop_clone.store(true, Ordering::Relaxed);
+				}
 			}
 		});
 		
@@ -185,7 +298,9 @@ impl IPCComm {
 			write_handle,
 			read_handle,
 			should_stop,
-			send_queue_bytes: send_queue_bytes_mutex,
+			sender: IPCCommSender {
+				send_queue_bytes: send_queue_bytes_mutex
+			},
 			recv_queue_bytes: recv_queue_bytes_mutex,
 			recv_notif
 		}
@@ -203,21 +318,18 @@ impl IPCComm {
 	/// Adds the provided message to a queue for sending over the underlying connection, but does not wait until
 	/// the message is actually sent
 	pub async fn send_msg<S: BorshSerialize>(&mut self, msg: S) -> Result<(), io::Error> {
-		let msg_bytes = msg.try_to_vec()?;
-		let mut send_queue_bytes = self.send_queue_bytes.lock().await;
-		send_queue_bytes.push_back((msg_bytes.len() as u64).to_le_bytes().to_vec());
-		send_queue_bytes.push_back(msg_bytes);
-		Ok(())
+		self.sender.send_msg(msg)
 	}
 
 	/// Adds the provided message to a queue for sending over the underlying connection, but does not block until
-	/// the message is actually sent
+	/// the message is actually sent. Safe to call from within an async context.
 	pub fn blocking_send_msg<S: BorshSerialize>(&mut self, msg: S) -> Result<(), io::Error> {
-		let msg_bytes = msg.try_to_vec()?;
-		let mut send_queue_bytes = self.send_queue_bytes.blocking_lock();
-		send_queue_bytes.push_back((msg_bytes.len() as u64).to_le_bytes().to_vec());
-		send_queue_bytes.push_back(msg_bytes);
-		Ok(())
+		self.sender.send_msg(msg)
+	}
+
+	/// A handle for sending messages without needing access to this `IPCComm`
+	pub fn sender(&self) -> IPCCommSender {
+		self.sender.clone()
 	}
 
 	/// Removes and parses a message received messages queue.
@@ -264,3 +376,127 @@ impl IPCComm {
 	}
 
 }
+
+#[cfg(test)]
+mod tests {
+	use proptest::prelude::*;
+	use tokio::{io::AsyncWriteExt, net::UnixStream};
+
+	use super::*;
+
+	/// A read handler for one end of a new connection, along with the other end for feeding it bytes
+	fn read_handler() -> (IPCCommReadHandler, unix::OwnedWriteHalf, UnixStream) {
+		let (stream, peer) = UnixStream::pair().unwrap();
+		let (read_stream, write_stream) = stream.into_split();
+		(IPCCommReadHandler::new(read_stream), write_stream, peer)
+	}
+	fn frame(body: &[u8]) -> Vec<u8> {
+		let mut frame = (body.len() as u64).to_le_bytes().to_vec();
+		frame.extend_from_slice(body);
+		frame
+	}
+	/// Reads until something other than `Waiting` comes out
+	async fn read_next(handler: &mut IPCCommReadHandler) -> Result<IPCCommReadResult, io::Error> {
+		loop {
+			match handler.read_tick().await? {
+				IPCCommReadResult::Waiting => {},
+				result => return Ok(result)
+			}
+		}
+	}
+	/// Reads every message until the connection is closed or rejected
+	async fn read_all(handler: &mut IPCCommReadHandler) -> (Vec<Vec<u8>>, Option<io::Error>) {
+		let mut messages = Vec::new();
+		loop {
+			match read_next(handler).await {
+				Ok(IPCCommReadResult::Message(msg_bytes)) => messages.push(msg_bytes),
+				Ok(IPCCommReadResult::Shutdown) => return (messages, None),
+				Ok(IPCCommReadResult::Waiting) => unreachable!(),
+				Err(e) => return (messages, Some(e))
+			}
+		}
+	}
+	fn runtime() -> tokio::runtime::Runtime {
+		tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap()
+	}
+
+	#[tokio::test]
+	async fn zero_length_frame_is_an_empty_message() {
+		let (mut handler, _write_stream, mut peer) = read_handler();
+		peer.write_all(&frame(&[])).await.unwrap();
+		peer.write_all(&frame(&[1, 2, 3])).await.unwrap();
+		drop(peer);
+		let (messages, error) = read_all(&mut handler).await;
+		assert!(error.is_none());
+		assert_eq!(messages, vec![Vec::new(), vec![1, 2, 3]]);
+	}
+
+	#[tokio::test]
+	async fn oversized_frame_is_rejected() {
+		let (mut handler, _write_stream, mut peer) = read_handler();
+		peer.write_all(&(MAX_IPC_MESSAGE_SIZE + 1).to_le_bytes()).await.unwrap();
+		let error = read_next(&mut handler).await.err().expect("the frame to be rejected");
+		assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+	}
+
+	#[tokio::test]
+	async fn truncated_body_is_never_delivered() {
+		let (mut handler, _write_stream, mut peer) = read_handler();
+		peer.write_all(&frame(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10])[..12]).await.unwrap();
+		drop(peer);
+		let (messages, error) = read_all(&mut handler).await;
+		assert!(error.is_none());
+		assert!(messages.is_empty());
+	}
+
+	#[tokio::test]
+	async fn undecodable_message_is_an_error_and_later_messages_still_arrive() {
+		let (stream, mut peer) = UnixStream::pair().unwrap();
+		let mut comm = IPCComm::new(stream);
+		// Too short to be a u64
+		peer.write_all(&frame(&[1, 2, 3])).await.unwrap();
+		peer.write_all(&frame(&42u64.to_le_bytes())).await.unwrap();
+		assert!(comm.until_recv_msg::<u64>().await.is_err());
+		assert!(!comm.stopped());
+		assert_eq!(comm.until_recv_msg::<u64>().await.unwrap(), Some(42));
+		comm.stop();
+	}
+
+	proptest! {
+		#[test]
+		fn arbitrary_bytes_never_panic(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+			let (messages, error) = runtime().block_on(async {
+				let (mut handler, _write_stream, mut peer) = read_handler();
+				peer.write_all(&bytes).await.unwrap();
+				drop(peer);
+				read_all(&mut handler).await
+			});
+			if let Some(error) = error {
+				prop_assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+			}
+			prop_assert!(messages.iter().map(|msg_bytes| msg_bytes.len() + 8).sum::<usize>() <= bytes.len());
+		}
+
+		#[test]
+		fn framed_messages_arrive_intact_however_they_are_split(
+			messages in prop::collection::vec(prop::collection::vec(any::<u8>(), 0..64), 0..8),
+			chunk_size in 1..16usize
+		) {
+			let bytes: Vec<u8> = messages.iter().flat_map(|msg_bytes| frame(msg_bytes)).collect();
+			let (received, error) = runtime().block_on(async {
+				let (mut handler, _write_stream, mut peer) = read_handler();
+				let writer = tokio::spawn(async move {
+					for chunk in bytes.chunks(chunk_size) {
+						peer.write_all(chunk).await.unwrap();
+						tokio::task::yield_now().await;
+					}
+				});
+				let received = read_all(&mut handler).await;
+				writer.await.unwrap();
+				received
+			});
+			prop_assert!(error.is_none());
+			prop_assert_eq!(received, messages);
+		}
+	}
+}
@@ -1,26 +1,27 @@
-use std::{path::PathBuf, sync::{Arc}, collections::{HashMap}, time::Duration};
+use std::{path::PathBuf, sync::{Arc}};
 
 use color_eyre::eyre;
-use debug_env::{BokkenValidatorMessage, BokkenAccountData};
-use executor::BokkenSolanaContext;
-use ipc_comm::IPCComm;
-use sol_syscalls::{BokkenSyscalls, BokkenSyscallMsg};
+use debug_env::{BokkenValidatorMessage, BokkenRuntimeMessage, BokkenProgramIdentity, BokkenAccountSizeLimits};
+use executor::{BokkenSolanaContext, BokkenExecutor, install_panic_hook};
+use ipc_comm::{IPCComm, connect_ipc_socket};
+use log_batcher::BokkenLogBatcher;
+use sol_syscalls::BokkenSyscalls;
 use solana_program::{pubkey::Pubkey, program_stubs::set_syscall_stubs};
 use bpaf::Bpaf;
-use tokio::{net::UnixStream, sync::{Mutex, mpsc}, time::sleep};
 
 
 pub mod sol_syscalls;
 pub mod executor;
 pub mod debug_env;
 pub mod ipc_comm;
+mod log_batcher;
 
 
 #[derive(Clone, Debug, Bpaf)]
 #[bpaf(options, version)]
 /// A native-compiled Solana program to be used with Bokken
 struct CommandOptions {
-	/// The unix socket of the Bokken instance to link to
+	/// The unix socket of the Bokken instance to link to, start with @ for an abstract-namespace socket
 	#[bpaf(short, long, argument::<PathBuf>("PATH"))]
 	socket_path: PathBuf,
 
@@ -30,24 +31,12 @@ struct CommandOptions {
 }
 
 async fn ipc_read_loop(
-	comm: Arc<Mutex<IPCComm>>,
-	syscall_sender: mpsc::Sender<BokkenSyscallMsg>,
-	invoke_result_senders: Arc<Mutex<HashMap<u64, mpsc::Sender<(u64, HashMap<Pubkey, BokkenAccountData>)>>>>
+	mut comm: IPCComm,
+	executor: Arc<BokkenExecutor>
 ) -> eyre::Result<()> {
-	loop {
-		// Solana program executions 
-		let msg = {
-			let mut comm = comm.lock().await;
-			if comm.stopped() {
-				break;
-			}
-			if let Some(msg) = comm.recv_msg().await? {
-				msg
-			}else{
-				sleep(Duration::from_millis(1)).await;
-				continue;
-			}
-		};
+	let mut account_size_limits = BokkenAccountSizeLimits::default();
+	// Nothing else needs `comm`, as the executor sends its messages through its own `IPCCommSender`
+	while let Some(msg) = comm.until_recv_msg().await? {
 		match msg {
 			BokkenValidatorMessage::Invoke {
 				nonce,
@@ -55,7 +44,8 @@ async fn ipc_read_loop(
 				instruction,
 				account_metas,
 				account_datas,
-				call_depth
+				call_depth,
+				return_data
 			} => {
 				println!("Invoke: call_depth: {}", call_depth);
 				let context = BokkenSolanaContext::new(
@@ -64,23 +54,27 @@ async fn ipc_read_loop(
 					account_metas.into_iter().map(|v|{v.into()}).collect(),
 					account_datas,
 					nonce,
-					call_depth
+					call_depth,
+					account_size_limits
 				);
-				syscall_sender.send(
-					BokkenSyscallMsg::PushContext{
-						ctx: context,
-						msg_sender_clone: syscall_sender.clone()
-					}
-				).await?;
+				executor.execute(context, return_data);
 			},
    			BokkenValidatorMessage::CrossProgramInvokeResult {
 				nonce,
 				return_code,
-				account_datas
+				account_datas,
+				return_data
 			} => {
-				if let Some(sender) = invoke_result_senders.lock().await.remove(&nonce) {
-					sender.send((return_code, account_datas)).await?;
-				}
+				executor.send_invoke_result(nonce, (return_code, account_datas, return_data));
+			},
+			BokkenValidatorMessage::SetLogLevel { min_level } => {
+				executor.log_batcher().set_min_level(min_level);
+			},
+			BokkenValidatorMessage::Ping => {
+				comm.send_msg(BokkenRuntimeMessage::Pong).await?;
+			},
+			BokkenValidatorMessage::SetAccountSizeLimits { limits } => {
+				account_size_limits = limits;
 			},
 		}
 	}
@@ -88,27 +82,31 @@ async fn ipc_read_loop(
 }
 
 pub async fn bokken_runtime_main() -> eyre::Result<()> {
+	bokken_runtime_main_with_name(None).await
+}
+
+/// Same as `bokken_runtime_main`, except the main Bokken process will also refer to this program by `program_name`
+pub async fn bokken_runtime_main_with_name(program_name: Option<&str>) -> eyre::Result<()> {
 	let opts = command_options().run();
-	// The actual solana program execution happens in a different thread as all the syscall methods are blocking.
-	// Therefore, IPCComm is in a mutex so it can be shared with BokkenSyscalls for when a log or CPI happens.
-	let comm = Arc::new(Mutex::new(IPCComm::new(UnixStream::connect(opts.socket_path).await?)));
-	{
-		// Send our configured program ID to the main process in order to register it
-		comm.lock().await.send_msg(opts.program_id).await?;
-	}
-	let (syscall_sender, syscall_receiver) = mpsc::channel::<BokkenSyscallMsg>(1);
-	let invoke_result_senders = Arc::new(Mutex::new(HashMap::new()));
-	let syscall_mgr = Box::new(BokkenSyscalls::new(
-		comm.clone(),
-		opts.program_id,
-		invoke_result_senders.clone(),
-		syscall_receiver
-	));
+	let mut comm = IPCComm::new(connect_ipc_socket(&opts.socket_path).await?);
+	// Send our configured program ID to the main process in order to register it
+	comm.send_msg(
+		BokkenProgramIdentity {
+			program_id: opts.program_id,
+			name: program_name.map(String::from)
+		}
+	).await?;
+	// The actual solana program execution happens in dedicated threads as all the syscall methods are blocking, see
+	// `BokkenExecutor`.
+	let executor = Arc::new(BokkenExecutor::new(comm.sender(), BokkenLogBatcher::new()));
+	let syscall_mgr = Box::new(BokkenSyscalls::new(executor.clone(), opts.program_id));
 	// Override default `solana_program` syscalls with our `BokkenSyscalls`
 	set_syscall_stubs(syscall_mgr);
+	// Installed last so that it wraps any hook installed beforehand, such as color_eyre's
+	install_panic_hook();
 	println!("bokken_runtime_main: Sent program ID, set syscalls, awaiting execution requests...");
 	// TODO: Listen for signals and exit gracefully
-	ipc_read_loop(comm, syscall_sender, invoke_result_senders).await?;
+	ipc_read_loop(comm, executor).await?;
 	Ok(())
 }
 
@@ -120,7 +118,10 @@ macro_rules! bokken_program {
 		#[tokio::main]
 		async fn main() -> color_eyre::eyre::Result<()> {
 			color_eyre::install()?;
-			bokken_runtime::bokken_runtime_main().await
+			// The version is the one of the crate using this macro
+			bokken_runtime::bokken_runtime_main_with_name(
+				Some(concat!(stringify!($program_crate_name), " v", env!("CARGO_PKG_VERSION")))
+			).await
 		}
     };
 }
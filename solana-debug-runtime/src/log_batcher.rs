@@ -0,0 +1,84 @@
+use std::{sync::{Mutex as StdMutex, atomic::{AtomicU8, Ordering}}, time::{Duration, Instant}};
+
+use crate::debug_env::{BokkenLogLevel, BokkenLogMessage, BokkenRuntimeMessage};
+
+/// Pending logs are sent once they add up to this many bytes
+const LOG_BATCH_MAX_BYTES: usize = 16 * 1024;
+/// Pending logs are sent when something is logged this long after the oldest pending log
+const LOG_BATCH_MAX_AGE: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
+struct PendingLogs {
+	nonce: u64,
+	messages: Vec<BokkenLogMessage>,
+	bytes: usize,
+	oldest: Instant
+}
+
+/// Collects log messages so that they can be sent to the main Bokken process in batches rather than one IPC message
+/// per `msg!` call.
+///
+/// Whatever is pending must be sent with `take_batch` before anything else is sent for the same invocation, otherwise
+/// the logs will end up out of order.
+#[derive(Debug)]
+pub(crate) struct BokkenLogBatcher {
+	min_level: AtomicU8,
+	pending: StdMutex<Option<PendingLogs>>
+}
+impl BokkenLogBatcher {
+	pub fn new() -> Self {
+		Self {
+			min_level: AtomicU8::new(BokkenLogLevel::default() as u8),
+			pending: StdMutex::new(None)
+		}
+	}
+	pub fn set_min_level(&self, level: BokkenLogLevel) {
+		self.min_level.store(level as u8, Ordering::Relaxed);
+	}
+	fn is_enabled(&self, level: BokkenLogLevel) -> bool {
+		level as u8 >= self.min_level.load(Ordering::Relaxed)
+	}
+	/// Queues the message for sending, returns a batch which should be sent right away if the pending logs have grown
+	/// too large or too old, or if they belong to a different invocation.
+	#[must_use]
+	pub fn push(&self, nonce: u64, level: BokkenLogLevel, message: String) -> Option<BokkenRuntimeMessage> {
+		if !self.is_enabled(level) {
+			return None;
+		}
+		let mut pending = self.pending.lock().unwrap();
+		let mut batch = None;
+		if pending.as_ref().map_or(false, |pending| pending.nonce != nonce) {
+			batch = Self::into_batch(pending.take());
+		}
+		let pending_logs = pending.get_or_insert_with(|| {
+			PendingLogs {
+				nonce,
+				messages: Vec::new(),
+				bytes: 0,
+				oldest: Instant::now()
+			}
+		});
+		pending_logs.bytes += message.len();
+		pending_logs.messages.push(BokkenLogMessage { level, message });
+		if batch.is_none() && (
+			pending_logs.bytes >= LOG_BATCH_MAX_BYTES ||
+			pending_logs.oldest.elapsed() >= LOG_BATCH_MAX_AGE
+		) {
+			batch = Self::into_batch(pending.take());
+		}
+		batch
+	}
+	/// Removes all pending logs, this should be called before the end of an invocation or a CPI is sent
+	#[must_use]
+	pub fn take_batch(&self) -> Option<BokkenRuntimeMessage> {
+		Self::into_batch(self.pending.lock().unwrap().take())
+	}
+	fn into_batch(pending: Option<PendingLogs>) -> Option<BokkenRuntimeMessage> {
+		pending.map(|pending| {
+			BokkenRuntimeMessage::Log {
+				nonce: pending.nonce,
+				messages: pending.messages
+			}
+		})
+	}
+}
@@ -1,117 +1,62 @@
-use std::{sync::{Arc}, collections::{HashSet, HashMap}};
+use std::{sync::{Arc, RwLock}, collections::{HashSet, HashMap}};
 
-use solana_program::{program_stubs::SyscallStubs, program_error::{UNSUPPORTED_SYSVAR, ProgramError}, entrypoint::ProgramResult, pubkey::Pubkey, instruction::Instruction, account_info::AccountInfo, sysvar};
-use tokio::{sync::{Mutex, mpsc, RwLock}, task};
+use solana_program::{program_stubs::SyscallStubs, program_error::{UNSUPPORTED_SYSVAR, ProgramError}, entrypoint::ProgramResult, pubkey::Pubkey, instruction::Instruction, account_info::AccountInfo, sysvar, epoch_schedule::EpochSchedule};
 use itertools::Itertools;
 
-use crate::{ipc_comm::IPCComm, debug_env::{BokkenRuntimeMessage, BokkenAccountData}, executor::{BokkenSolanaContext, execute_sol_program_thread, SolanaAccountsBlob}};
+use crate::{debug_env::{BokkenRuntimeMessage, BokkenAccountData, BokkenLogLevel}, executor::{BokkenExecutor, SolanaAccountsBlob}};
 
-#[derive(Debug)]
-pub(crate) enum BokkenSyscallMsg {
-	PushContext {
-		ctx: BokkenSolanaContext,
-		msg_sender_clone: mpsc::Sender<BokkenSyscallMsg>,
-	},
-	PopContext
-}
+/// Return code, resulting account states and return data of a CPI
+pub(crate) type BokkenCPIResult = (u64, HashMap<Pubkey, BokkenAccountData>, Option<(Pubkey, Vec<u8>)>);
 
 /// Syscall replacements for the `solana_program` crate, with support for recursive invocations.
 /// 
 /// This does not support multiple instances of the same solana program executing in parallel. There should be only
 /// once instance undergoing active execution at a time.
+///
+/// Syscalls are only ever made from the threads `BokkenExecutor` executes the program on, so they're free to block.
 #[derive(Debug)]
 pub(crate) struct BokkenSyscalls {
-	ipc: Arc<Mutex<IPCComm>>,
-	program_id: Pubkey,
-	invoke_result_senders: Arc<Mutex<HashMap<u64, mpsc::Sender<(u64, HashMap<Pubkey, BokkenAccountData>)>>>>,
-	// Using a mutex is just the easiest way to make the property mutable while being Send + Sync that I know of
-	return_data: Arc<Mutex<Option<(Pubkey, Vec<u8>)>>>,
-	contexts: Arc<Mutex<Vec<BokkenSolanaContext>>>,
+	executor: Arc<BokkenExecutor>,
+	program_id: Pubkey
 }
 impl BokkenSyscalls {
 
 	/// Creates an instance of `BokkenSyscalls`
 	/// 
-	/// * `ipc` Used for sending log messages and CPI requests
+	/// * `executor` Keeps track of the invocations in progress, and is used for sending log messages and CPI requests
 	/// * `program_id` Our program ID
-	/// * `invoke_result_senders` Where the main IPC Read loop can put its CPI results while we wait for them
-	/// * `msg_receiver` For receiving new execution contexts
 	pub fn new(
-		ipc: Arc<Mutex<IPCComm>>,
-		program_id: Pubkey,
-		invoke_result_senders: Arc<Mutex<HashMap<u64, mpsc::Sender<(u64, HashMap<Pubkey, BokkenAccountData>)>>>>,
-		mut msg_receiver: mpsc::Receiver<BokkenSyscallMsg>
+		executor: Arc<BokkenExecutor>,
+		program_id: Pubkey
 	) -> Self {
-		let contexts= Arc::new(Mutex::new(Vec::new()));
-		let contexts_clone = contexts.clone();
-		let ipc_clone = ipc.clone();
-		task::spawn(async move {
-			while let Some(msg) = msg_receiver.recv().await {
-				match msg {
-					BokkenSyscallMsg::PushContext { ctx, msg_sender_clone } => {
-						let blob = ctx.blob.clone();
-						let nonce = ctx.nonce();
-						contexts_clone.lock().await.push(ctx);
-						println!("Program execution start");
-						execute_sol_program_thread(nonce, blob, ipc_clone.clone(), msg_sender_clone).await;
-					},
-					BokkenSyscallMsg::PopContext => {
-						contexts_clone.lock().await.pop();
-					},
-				}
-			}
-		});
 		Self {
-			ipc,
-			program_id,
-			invoke_result_senders,
-			return_data: Arc::new(Mutex::new(None)),
-			contexts
+			executor,
+			program_id
 		}
 	}
 	fn stack_height(&self) -> u8 {
-		self.contexts.blocking_lock().last().expect("not be empty during program execution").cpi_height()
+		self.executor.with_current_context(|ctx| ctx.cpi_height())
 	}
 	fn nonce(&self) -> u64 {
-		self.contexts.blocking_lock().last().expect("not be empty during program execution").nonce()
+		self.executor.with_current_context(|ctx| ctx.nonce())
 	}
 	fn account_data_lock(&self) -> Arc<RwLock<SolanaAccountsBlob>> {
-		self.contexts.blocking_lock()
-			.last()
-			.expect("not be empty during program execution")
-			.blob
-			.clone()
-	}
-	fn is_valid_signer(&self, pubkey: &Pubkey) -> bool {
-		self.contexts
-			.blocking_lock()
-			.last()
-			.expect("not be empty during program execution")
-			.is_signer(pubkey)
+		self.executor.with_current_context(|ctx| ctx.blob.clone())
 	}
-	fn is_valid_writable(&self, pubkey: &Pubkey) -> bool {
-		self.contexts
-			.blocking_lock()
-			.last()
-			.expect("not be empty during program execution")
-			.is_writable(pubkey)
+	fn log(&self, level: BokkenLogLevel, message: String) {
+		println!("{}", message);
+		if let Some(batch) = self.executor.log_batcher().push(self.nonce(), level, message) {
+			self.executor.ipc().send_msg(batch).expect("Message encoding not to fail");
+		}
 	}
 }
 
 impl SyscallStubs for BokkenSyscalls {
 	fn sol_log(&self, message: &str) {
-		let msg = format!("Program logged: {}", message);
-		println!("{}", msg);
-		let mut ipc = self.ipc.blocking_lock();
-		ipc.blocking_send_msg(
-			BokkenRuntimeMessage::Log {
-				nonce: self.nonce(),
-				message: msg
-			}
-		).expect("Message encoding not to fail");
+		self.log(BokkenLogLevel::Info, format!("Program logged: {}", message));
 	}
 	fn sol_log_compute_units(&self) {
-		self.sol_log("WARNING: sol_log_compute_units() not available");
+		self.log(BokkenLogLevel::Debug, String::from("WARNING: sol_log_compute_units() not available"));
 	}
 	fn sol_invoke_signed(
 		&self,
@@ -128,18 +73,19 @@ impl SyscallStubs for BokkenSyscalls {
 		let mut outgoing_account_datas = HashMap::new();
 		let ctx_account_data_lock = self.account_data_lock();
 		{
-			let ctx_acocunt_datas = ctx_account_data_lock.blocking_read();
+			let ctx_acocunt_datas = ctx_account_data_lock.read().unwrap();
 			for (i, meta) in instruction.accounts.iter().enumerate() {
 				if *account_infos[i].key != meta.pubkey {
 					self.sol_log("Invoke: Accoune meta doesn't match account info");
 					return Err(ProgramError::InvalidAccountData);
 				}
-				if meta.is_writable && !self.is_valid_writable(&meta.pubkey) {
+				// Checked against the blob we already have locked, locking it again from the same thread could deadlock
+				if meta.is_writable && !ctx_acocunt_datas.is_writable(&meta.pubkey) {
 					// TODO: Find out what error should be returned, or if this is even needed
 					self.sol_log("Invoke: Cannot instruction requres an non-writable account to be writable");
 					return Err(ProgramError::Custom(0));
 				}
-				if meta.is_signer && !self.is_valid_signer(&meta.pubkey) && !just_signed.contains(&meta.pubkey) {
+				if meta.is_signer && !ctx_acocunt_datas.is_signer(&meta.pubkey) && !just_signed.contains(&meta.pubkey) {
 					self.sol_log(format!(
 						"Invoke: Account {} needs to be signed, but it isn't and doesn't match any given PDA seeds",
 						meta.pubkey
@@ -154,16 +100,11 @@ impl SyscallStubs for BokkenSyscalls {
 			// ctx_acocunt_datas drops unlocks
 		}
 		
-		let mut receiver = {
-			let (sender, receiver) = mpsc::channel(1);
-			self.invoke_result_senders.blocking_lock().insert(self.nonce(), sender);
-			receiver
-			// self.invoke_result_senders unlocks
-		};
+		let receiver = self.executor.expect_invoke_result(self.nonce());
 		{
 			let mut account_datas_for_ipc = HashMap::new();
 			{
-				let ctx_acocunt_datas = ctx_account_data_lock.blocking_write();
+				let ctx_acocunt_datas = ctx_account_data_lock.read().unwrap();
 				for acc_meta in instruction.accounts.iter() {
 					account_datas_for_ipc.insert(acc_meta.pubkey.clone(), ctx_acocunt_datas.get_account_data(&acc_meta.pubkey).ok_or(ProgramError::NotEnoughAccountKeys)?);
 				}
@@ -177,21 +118,28 @@ impl SyscallStubs for BokkenSyscalls {
 				);
 				// ctx_acocunt_datas drops and unlocks
 			}
-			self.ipc.blocking_lock().blocking_send_msg(
+			let ipc = self.executor.ipc();
+			// The logs leading up to the CPI have to arrive before it
+			if let Some(batch) = self.executor.log_batcher().take_batch() {
+				ipc.send_msg(batch).expect("encoding to not fail");
+			}
+			ipc.send_msg(
 				BokkenRuntimeMessage::CrossProgramInvoke {
 					nonce: self.nonce(),
 					program_id: self.program_id,
 					instruction: instruction.data.clone(),
 					account_metas: instruction.accounts.iter().map(|v|{v.into()}).collect(),
 					account_datas: account_datas_for_ipc,
-					call_depth: self.stack_height()
+					call_depth: self.stack_height(),
+					return_data: self.executor.return_data()
 				}
 			).expect("encoding to not fail");
-			// self.ipc unlocks
 		}
-		let (return_code, account_datas) = receiver.blocking_recv().expect("get a response from CPI");
+		let (return_code, account_datas, return_data) = receiver.recv().expect("get a response from CPI");
+		// Like on-chain, the caller sees whatever return data the CPI left behind
+		self.executor.set_return_data(return_data);
 		{
-			let mut ctx_acocunt_datas = ctx_account_data_lock.blocking_write();
+			let mut ctx_acocunt_datas = ctx_account_data_lock.write().unwrap();
 			// We update these before potentially panicking for extra debugging flexibility
 			for (pubkey, account_data) in account_datas.into_iter() {
 				ctx_acocunt_datas.set_account_data(&pubkey, account_data)?;
@@ -206,7 +154,7 @@ impl SyscallStubs for BokkenSyscalls {
 	}
 	fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
 		let ctx_account_data_lock = self.account_data_lock();
-		let ctx_acocunt_datas = ctx_account_data_lock.blocking_read();
+		let ctx_acocunt_datas = ctx_account_data_lock.read().unwrap();
 		let account_data = ctx_acocunt_datas.get_sysvar_data(&sysvar::clock::id());
 		if let Some(account_data) = account_data {
 			// We are assuming that the data given by the parent process is valid.
@@ -220,13 +168,25 @@ impl SyscallStubs for BokkenSyscalls {
 	}
 	fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
 		let ctx_account_data_lock = self.account_data_lock();
-		let ctx_acocunt_datas = ctx_account_data_lock.blocking_read();
+		let ctx_acocunt_datas = ctx_account_data_lock.read().unwrap();
 		let account_data = ctx_acocunt_datas.get_sysvar_data(&sysvar::epoch_schedule::id());
 		if let Some(account_data) = account_data {
 			// We are assuming that the data given by the parent process is valid.
-			// The parent process actually uses bincode, but it should be 1:1 with casting as it's all 64-bit values
+			// Unlike the other sysvars, the bincode encoding can't be casted, as `warmup` is a 1 byte bool followed by
+			// 64-bit values which are aligned in memory.
+			let data = &account_data.data;
+			let read_u64 = |offset: usize| {
+				u64::from_le_bytes(data[offset..offset + 8].try_into().expect("slice to be 8 bytes"))
+			};
+			let epoch_schedule = EpochSchedule {
+				slots_per_epoch: read_u64(0),
+				leader_schedule_slot_offset: read_u64(8),
+				warmup: data[16] != 0,
+				first_normal_epoch: read_u64(17),
+				first_normal_slot: read_u64(25)
+			};
 			unsafe {
-				var_addr.copy_from(account_data.data.as_ptr(), account_data.data.len());
+				(var_addr as *mut EpochSchedule).write_unaligned(epoch_schedule);
 			}
 			return 0;
 		}
@@ -237,7 +197,7 @@ impl SyscallStubs for BokkenSyscalls {
 	}
 	fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
 		let ctx_account_data_lock = self.account_data_lock();
-		let ctx_acocunt_datas = ctx_account_data_lock.blocking_read();
+		let ctx_acocunt_datas = ctx_account_data_lock.read().unwrap();
 		let account_data = ctx_acocunt_datas.get_sysvar_data(&sysvar::rent::id());
 		if let Some(account_data) = account_data {
 			// We are assuming that the data given by the parent process is valid.
@@ -250,17 +210,17 @@ impl SyscallStubs for BokkenSyscalls {
 		UNSUPPORTED_SYSVAR
 	}
 	fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
-		self.return_data.blocking_lock().clone()
+		// On-chain, empty return data can't be told apart from none at all
+		self.executor.return_data().filter(|(_, data)| !data.is_empty())
 	}
 	fn sol_set_return_data(&self, data: &[u8]) {
-		let mut return_data = self.return_data.blocking_lock();
-		*return_data = Some((self.program_id, data.to_vec()));
+		self.executor.set_return_data(Some((self.program_id, data.to_vec())));
 	}
 	fn sol_log_data(&self, fields: &[&[u8]]) {
 		self.sol_log(format!("data: {}", fields.iter().map(base64::encode).join(" ")).as_str());
 	}
 	fn sol_get_processed_sibling_instruction(&self, _index: usize) -> Option<Instruction> {
-		self.sol_log("WARNING: sol_get_processed_sibling_instruction() not available");
+		self.log(BokkenLogLevel::Debug, String::from("WARNING: sol_get_processed_sibling_instruction() not available"));
 		None
 	}
 	fn sol_get_stack_height(&self) -> u64 {
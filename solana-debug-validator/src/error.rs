@@ -1,126 +1,118 @@
-use solana_sdk::{transaction::TransactionError, sanitize::SanitizeError, program_error::ProgramError, pubkey::ParsePubkeyError};
-use thiserror::Error;
-use std::{io, backtrace::Backtrace, fmt::Display};
+//! Exit codes of the `bokken` binary, so scripts can tell why it failed to start without parsing its output.
+//!
+//! The codes are stable, new ones are only ever added. Besides the exit code, the last line printed to stderr on
+//! failure is `bokken-error: {"code": <CODE>, "kind": "<KIND>", "message": "<MESSAGE>"}`.
 
-#[derive(Error, Debug)]
-pub enum BokkenError {
-	// Original errors
-	#[error("This shouldn't happen!")]
-	ShouldNotHappen,
-	#[error("IO Error: {0}")]
-	IO(#[from] io::Error),
-	#[error("All initialization options must be specified if a pre-existing state doesn't exist at the path provided")]
-	InitConfigIsNone,
-	#[error("this feature is unimplemented")]
-	Unimplemented,
-	#[error("Base64 Error: {0}")]
-	Base64DecodeError(#[from] base64::DecodeError),
-	#[error("Base58 Error: {0}")]
-	Base58DecodeError(#[from] bs58::decode::Error),
-	#[error("Bincode Error: {0}")]
-	BincodeDecodeError(#[from] bincode::Error),
-	#[error("Solana tx error: {0}")]
-	TransactionError(#[from] TransactionError),
-	#[error("Sanitize error: {0}")]
-	SanitizeError(#[from] SanitizeError),
-	#[error("Pubkey parse error: {0}")]
-	PubkeyParseError(#[from] ParsePubkeyError),
-	#[error("Connection to program dropped while waiting for execution result")]
-	ProgramClosedConnection,
-	#[error("The program is stopping")]
-	Stopping,
-	#[error("Instruction #{0}: Program returned: {1}")]
-	InstructionExecError(usize, ProgramError, Vec<String>),
+use std::{fmt::Display, io, process::ExitCode};
 
-	// Errors during ledger lookup
-	#[error("Couldn't serialize {0}; encoded size was {2} > {1}")]
-	MaximumSerializationLengthExceeded(String, usize, usize),
-	#[error("Cannot append data to the end of the index as the identifiers would be unsorted")]
-	CannotAppendToIndex,
-	#[error("Unexpected end of file")]
-	UnexpectedEOF,
-	#[error("Indexed file out of bounds index={0}, length={1}")]
-	IndexFileOutOfBounds(usize, usize),
-	#[error("Invalid signature length")]
-	InvalidSignatureLength
+use color_eyre::eyre;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BokkenExitCode {
+	/// Anything not covered by a more specific code
+	Other = 1,
+	/// Invalid command-line arguments, or a file they point to (keypair, account schema, programs config) couldn't be
+	/// used
+	InvalidArguments = 2,
+	/// The JSON-RPC or websocket port is already in use
+	AddressInUse = 3,
+	/// The RPC servers couldn't be started, or stopped with an error
+	RpcServerFailed = 4,
+	/// The debuggable program socket couldn't be bound, usually because another Bokken instance is using it
+	SocketUnavailable = 5,
+	/// The ledger at the save path couldn't be accessed
+	LedgerUnreadable = 6,
+	/// The ledger at the save path couldn't be loaded, as its contents are corrupt or from an incompatible version
+	LedgerCorrupt = 7,
+	/// A debuggable program from `--programs-config` couldn't be launched
+	ProgramLaunchFailed = 8
 }
-impl From<BokkenError> for jsonrpsee::core::Error {
-	fn from(err: BokkenError) -> Self {
-		Self::Custom(err.to_string())
+impl BokkenExitCode {
+	/// Used as the `kind` of the final error line
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Self::Other => "other",
+			Self::InvalidArguments => "invalid_arguments",
+			Self::AddressInUse => "address_in_use",
+			Self::RpcServerFailed => "rpc_server_failed",
+			Self::SocketUnavailable => "socket_unavailable",
+			Self::LedgerUnreadable => "ledger_unreadable",
+			Self::LedgerCorrupt => "ledger_corrupt",
+			Self::ProgramLaunchFailed => "program_launch_failed"
+		}
+	}
+}
+impl From<BokkenExitCode> for ExitCode {
+	fn from(value: BokkenExitCode) -> Self {
+		ExitCode::from(value as u8)
 	}
 }
 
-
+/// An error which makes Bokken exit with a specific code, see `ExitCodeContext`
 #[derive(Debug)]
-pub struct BokkenDetailedError {
-	// TODO:Maybe switch this thing back to using the Error derive macro when this is stable, as apparently the
-	//		Error derive macro for errors which contain backtraces depend on this:
-	//		https://github.com/rust-lang/rust/issues/99301
-	source: Box<BokkenError>,
-	backtrace: Backtrace,
+pub struct BokkenStartupError {
+	pub code: BokkenExitCode,
+	report: eyre::Report
 }
-impl Display for BokkenDetailedError {
+impl Display for BokkenStartupError {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		return Display::fmt(&self.source, f);
+		write!(f, "{} (exit code {})", self.code.kind(), self.code as u8)
 	}
 }
-impl From<BokkenError> for BokkenDetailedError {
-	fn from(source_error: BokkenError) -> Self {
-		Self {
-			source: Box::new(source_error),
-			backtrace: Backtrace::force_capture(),
-		}
-	}
-}
-impl From<BokkenDetailedError> for BokkenError {
-	fn from(value: BokkenDetailedError) -> Self {
-		eprintln!("Warning! Collapsing a BokkenDetailedError back into a BokkenError!");
-		eprintln!("BokkenError: {}", value.source);
-		eprintln!("backtrace: {}", value.backtrace);
-		*value.source // Looks like if we consume stuff, we can do anything
-	}
-}
-
-impl std::error::Error for BokkenDetailedError {
+impl std::error::Error for BokkenStartupError {
 	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-		// TODO: have this reference the source property somehow
-		Some(&self.source)
+		Some(&**self.report)
 	}
 }
 
-// I should put a derive macro for this
-impl From<io::Error> for BokkenDetailedError {
-	fn from(value: io::Error) -> Self {
-		Self::from(BokkenError::from(value))
-	}
-}
-impl From<base64::DecodeError> for BokkenDetailedError {
-	fn from(value: base64::DecodeError) -> Self {
-		Self::from(BokkenError::from(value))
-	}
+/// The kind of the first IO error which caused `report`, if any
+fn io_error_kind(report: &eyre::Report) -> Option<io::ErrorKind> {
+	report.chain().find_map(|error| error.downcast_ref::<io::Error>()).map(|error| error.kind())
 }
-impl From<bs58::decode::Error> for BokkenDetailedError {
-	fn from(value: bs58::decode::Error) -> Self {
-		Self::from(BokkenError::from(value))
-	}
+
+pub trait ExitCodeContext<T> {
+	/// Makes Bokken exit with `code` if this is an error. A few codes are refined by looking at what caused the error,
+	/// e.g. an RPC server failure caused by the port being taken is reported as `AddressInUse`.
+	fn exit_code(self, code: BokkenExitCode) -> eyre::Result<T>;
 }
-impl From<bincode::Error> for BokkenDetailedError {
-	fn from(value: bincode::Error) -> Self {
-		Self::from(BokkenError::from(value))
+impl<T, E: Into<eyre::Report>> ExitCodeContext<T> for Result<T, E> {
+	fn exit_code(self, code: BokkenExitCode) -> eyre::Result<T> {
+		self.map_err(|error| startup_error(code, error.into()))
 	}
 }
-impl From<TransactionError> for BokkenDetailedError {
-	fn from(value: TransactionError) -> Self {
-		Self::from(BokkenError::from(value))
-	}
+
+/// Makes `report` exit Bokken with `code`, refined as described in `ExitCodeContext::exit_code`
+pub fn startup_error(code: BokkenExitCode, report: eyre::Report) -> eyre::Report {
+	let code = match (code, io_error_kind(&report)) {
+		(BokkenExitCode::RpcServerFailed, Some(io::ErrorKind::AddrInUse)) => BokkenExitCode::AddressInUse,
+		(
+			BokkenExitCode::LedgerCorrupt,
+			Some(io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied)
+		) => BokkenExitCode::LedgerUnreadable,
+		(code, _) => code
+	};
+	eyre::Report::new(BokkenStartupError { code, report })
 }
-impl From<SanitizeError> for BokkenDetailedError {
-	fn from(value: SanitizeError) -> Self {
-		Self::from(BokkenError::from(value))
-	}
+
+/// Prints the error along with the final structured error line, and returns the code to exit with
+pub fn report_error(report: eyre::Report) -> ExitCode {
+	let (code, message) = match report.downcast_ref::<BokkenStartupError>() {
+		Some(startup_error) => (startup_error.code, error_chain_message(&startup_error.report)),
+		None => (BokkenExitCode::Other, error_chain_message(&report))
+	};
+	eprintln!("Error: {:?}", report);
+	eprintln!(
+		"bokken-error: {}",
+		serde_json::json!({
+			"code": code as u8,
+			"kind": code.kind(),
+			"message": message
+		})
+	);
+	code.into()
 }
-impl From<ParsePubkeyError> for BokkenDetailedError {
-	fn from(value: ParsePubkeyError) -> Self {
-		Self::from(BokkenError::from(value))
-	}
+
+/// Every error in the chain on a single line, outermost first
+fn error_chain_message(report: &eyre::Report) -> String {
+	report.chain().map(|error| error.to_string()).collect::<Vec<_>>().join(": ")
 }
@@ -1,39 +1,60 @@
 
 use std::net::{SocketAddr, IpAddr, Ipv4Addr, SocketAddrV4, SocketAddrV6};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::process::ExitCode;
 
 
-use debug_ledger::BokkenLedgerInitConfig;
-use program_caller::ProgramCaller;
+use bokken_core::{BokkenLedger, BokkenLedgerInitConfig, ProgramCaller, rpc_endpoint};
+use bokken_core::account_schema::{BokkenAccountSchema, BokkenAccountSchemaFormat};
+use bokken_core::debug_ledger::{BokkenRentConfig, BokkenTimeModel, BokkenProgramAccountLayout, BokkenDurability, DEFAULT_INITIAL_MINT_LAMPORTS, DEFAULT_MAX_TRANSACTION_AGE, read_ledger_info};
+use bokken_core::rpc_endpoint::{BokkenRpcServerLimits, DEFAULT_MAX_REQUEST_SIZE, DEFAULT_MAX_RESPONSE_SIZE, DEFAULT_MAX_WS_MESSAGE_SIZE, DEFAULT_MAX_CONNECTIONS, DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION};
+use bokken_core::instance_manager::BokkenInstanceManager;
+use bokken_core::keystore::{BokkenKeystore, keypair_to_json};
+use bokken_core::fault_injection::{BokkenChaos, BokkenFaultInjector};
+use bokken_core::faucet::{BokkenAirdropLimiter, BokkenAirdropQuota};
+use bokken_core::program_launcher::{BokkenProgramsConfig, BokkenProgramSupervisor};
+use bokken_core::account_watch::BokkenAccountWatcher;
+use bokken_core::event_export::BokkenLedgerEventReader;
+use bokken_core::ledger_diff::{read_ledger_accounts, diff_ledger_accounts, BokkenLedgerAccountChangeKind};
+use bokken_core::bench::run_transfer_bench;
+use bokken_core::account_aliases::{set_account_alias, display_pubkey};
+use bokken_core::program_caller::{BokkenLogLevel, BokkenAccountSizeLimits, ProgramCallerConfig, bind_ipc_socket, remove_ipc_socket};
+#[cfg(feature = "grpc")]
+use bokken_core::grpc_endpoint;
 
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::clock::DEFAULT_SLOTS_PER_EPOCH;
+use solana_sdk::epoch_schedule::{EpochSchedule, MINIMUM_SLOTS_PER_EPOCH};
+use solana_sdk::signer::Signer;
+use solana_sdk::signature::{Keypair, read_keypair_file};
 use solana_sdk::{pubkey};
-use color_eyre::eyre::Result;
-use tokio::net::UnixListener;
-
-use bpaf::Bpaf;
+use color_eyre::eyre::{Result, eyre};
 
+use bpaf::{Args, Bpaf, ParseFailure};
 
 mod error;
-mod utils;
-mod debug_ledger;
-mod rpc_endpoint_structs;
-mod rpc_endpoint;
-mod native_program_stubs;
-mod program_caller;
+use error::{BokkenExitCode, ExitCodeContext, report_error, startup_error};
 
-use crate::debug_ledger::BokkenLedger;
 
 
 #[derive(Clone, Debug, Bpaf)]
 #[bpaf(options, version)]
 /// A barebones emulated solana enviroment for quick e2e testing
 struct CommandOptions {
-	/// Where the unix socket will be. Used to connect to debuggable programs.
-	/// (Default: solana-debug-validator.sock)
+	/// Where the unix socket will be. Used to connect to debuggable programs. Start with @ to use an abstract-namespace
+	/// socket instead of a file (Linux only). A stale socket file left behind by a previous run is replaced.
+	/// (Default: bokken.sock)
 	#[bpaf(short, long, argument::<PathBuf>("PATH"), fallback(PathBuf::from("bokken.sock")))]
 	socket_path: PathBuf,
 
+	/// Directory to keep all unix sockets in, including those of named instances, in which case the main socket will
+	/// be `<DIR>/bokken.sock`. Useful for keeping multiple Bokken processes on the same machine apart.
+	#[bpaf(long, argument::<PathBuf>("DIR"))]
+	socket_dir: Option<PathBuf>,
+
    	/// Where to save the state of the Bokken ledger
 	/// (Default: not-ledger)
 	#[bpaf(short('S'), long, argument::<PathBuf>("PATH"), fallback(PathBuf::from("not-ledger")))]
@@ -55,27 +76,850 @@ struct CommandOptions {
 
 	/// Amount to initialize `initial-mint-pubkey` with if save-path doesn't already exist
 	/// (Default: 500000000000000000)
-	#[bpaf(short('M'), long, argument::<u64>("LAMPORTS"), fallback(500000000000000000))]
-	initial_mint_lamports: u64
+	#[bpaf(short('M'), long, argument::<u64>("LAMPORTS"), fallback(DEFAULT_INITIAL_MINT_LAMPORTS))]
+	initial_mint_lamports: u64,
+
+	/// If save-path doesn't already exist, use the keypair in this file as the validator's identity, as reported by
+	/// getIdentity
+	/// (Default: a new keypair)
+	#[bpaf(long, argument::<PathBuf>("KEYPAIR_FILE"))]
+	identity: Option<PathBuf>,
+
+	/// Rent charged per byte per year. Only changes the ledger's rent if specified.
+	#[bpaf(long, argument::<u64>("LAMPORTS"))]
+	rent_lamports_per_byte_year: Option<u64>,
+
+	/// How many years of rent accounts need to hold to be rent-exempt. Only changes the ledger's rent if specified.
+	#[bpaf(long, argument::<f64>("YEARS"))]
+	rent_exemption_threshold: Option<f64>,
+
+	/// Make every account rent-exempt, same as `--rent-lamports-per-byte-year 0`
+	#[bpaf(long, switch)]
+	rent_free: bool,
+
+	/// Where the clock sysvar's and blocks' timestamps come from: "real-time" (the system clock), "slot-derived"
+	/// (a fixed duration per slot, continuing from the current timestamp), or "manual" (frozen until changed with
+	/// `bokken_setTimeModel`). Saved in the ledger.
+	#[bpaf(long, argument::<TimeModelOption>("MODEL"))]
+	time_model: Option<TimeModelOption>,
+
+	/// How long each slot takes with the slot-derived time model
+	/// (Default: 400)
+	#[bpaf(long, argument::<u64>("MILLISECONDS"))]
+	slot_duration: Option<u64>,
+
+	/// Decode the accounts of a program with an Anchor IDL (or a schema written like one), specified as
+	/// PUBKEY=FILE. Accounts are returned decoded when `getAccountInfo` is called with `jsonParsed` encoding, and the
+	/// IDL is served from the program's IDL account for `anchor.Program.fetchIdl`. Can be specified multiple times,
+	/// and is saved in the ledger.
+	#[bpaf(long, argument::<AccountSchemaPath>("PUBKEY=FILE"), many)]
+	account_schema: Vec<AccountSchemaPath>,
+
+	/// Same as `--account-schema`, for programs whose accounts are plain Borsh without Anchor's discriminator
+	#[bpaf(long, argument::<AccountSchemaPath>("PUBKEY=FILE"), many)]
+	borsh_account_schema: Vec<AccountSchemaPath>,
+
+	/// `feature-set` reported by getVersion, saved in the ledger
+	#[bpaf(long, argument::<u32>("ID"))]
+	feature_set: Option<u32>,
+
+	/// Report this feature as active from the current slot onwards, can be specified multiple times. Saved in the
+	/// ledger.
+	#[bpaf(long, argument::<Pubkey>("PUBKEY"), many)]
+	activate_feature: Vec<Pubkey>,
+
+	/// Report this feature as inactive, can be specified multiple times. Saved in the ledger.
+	#[bpaf(long, argument::<Pubkey>("PUBKEY"), many)]
+	deactivate_feature: Vec<Pubkey>,
+
+	/// Fail transactions with AccountInUse when they conflict with a transaction which is still pending, instead of
+	/// waiting for it to finish. For exercising client retry logic.
+	#[bpaf(long, switch)]
+	account_in_use_errors: bool,
+
+	/// Fail transactions which read an account that was never created, instead of treating it as an empty account.
+	/// For hermetic tests, where a missing account means the test's setup is wrong.
+	#[bpaf(long, switch)]
+	strict_accounts: bool,
+
+	/// Drop transactions whose blockhash is older than this many slots by the time they're processed, with
+	/// BlockhashNotFound. 0 accepts any blockhash.
+	/// (Default: 150, same as a real validator)
+	#[bpaf(long, argument::<u64>("SLOTS"), fallback(DEFAULT_MAX_TRANSACTION_AGE))]
+	max_transaction_age: u64,
+
+	/// Transaction logs are truncated in their block like on a real validator, in which case the full logs are archived
+	/// next to the ledger (see bokken_getTransactionLogs). Archives of blocks more than this many slots old are removed.
+	/// 0 keeps them forever.
+	#[bpaf(long, argument::<u64>("SLOTS"), fallback(0))]
+	log_archive_retention: u64,
+
+	/// When account states, blocks, and index entries are synced to disk: "always" as soon as they're written,
+	/// "per-slot" once each slot is committed, or "never", leaving it to the OS. "never" is the fastest, but the latest
+	/// slots may be lost if the machine crashes.
+	/// (Default: per-slot)
+	#[bpaf(long, argument::<BokkenDurability>("POLICY"), fallback(BokkenDurability::PerSlot))]
+	durability: BokkenDurability,
+
+	/// Attach the accounts a transaction loaded, as they were before it ran, to the error returned when it fails, so the
+	/// failure can be reproduced elsewhere. Accounts are left out once their data adds up to more than this many bytes.
+	/// (Default: 0, nothing is attached)
+	#[bpaf(long, argument::<u64>("BYTES"), fallback(0))]
+	preflight_pre_state_limit: u64,
+
+	/// How many slots each epoch lasts, as seen by the clock and epoch schedule sysvars and getEpochInfo. At least 32.
+	/// (Default: 432000, same as a real cluster)
+	#[bpaf(long, argument::<u64>("SLOTS"), fallback(DEFAULT_SLOTS_PER_EPOCH))]
+	slots_per_epoch: u64,
+
+	/// Start with short epochs which double in length until they reach `slots-per-epoch`, like a real cluster does
+	#[bpaf(long, switch)]
+	epoch_warmup: bool,
+
+	/// Whenever a transaction fails, save a copy of the ledger along with the transaction, its logs, and how it changed
+	/// accounts into a new timestamped directory in here. Useful for investigating failures in CI.
+	#[bpaf(long, argument::<PathBuf>("PATH"))]
+	failure_artifacts_dir: Option<PathBuf>,
+
+	/// Don't start the JSON-RPC server until at least this many debuggable programs have connected
+	/// (Default: 0)
+	#[bpaf(long, argument::<usize>("COUNT"), fallback(0))]
+	wait_for_programs: usize,
+
+	/// Don't start the JSON-RPC server until the debuggable program with this ID has connected, can be specified
+	/// multiple times
+	#[bpaf(long, argument::<Pubkey>("PUBKEY"), many)]
+	wait_for_program: Vec<Pubkey>,
+
+	/// TOML file listing debuggable programs to launch along with Bokken, each with its own command, working
+	/// directory, environment variables, RUST_BACKTRACE and RUST_LOG. They're stopped when Bokken exits. See
+	/// `bokken_core::program_launcher` for the format.
+	#[bpaf(long, argument::<PathBuf>("FILE"))]
+	programs_config: Option<PathBuf>,
+
+	/// JSON-RPC calls taking longer than this are logged along with their parameters
+	/// (Default: 1000)
+	#[bpaf(long, argument::<u64>("MILLISECONDS"), fallback(1000))]
+	slow_rpc_threshold: u64,
+
+	/// Remember the results of up to this many simulated transactions until the ledger state changes
+	/// (Default: 0, disabled)
+	#[bpaf(long, argument::<usize>("ENTRIES"), fallback(0))]
+	simulation_cache_size: usize,
+
+	/// Maximum size of a JSON-RPC request body in bytes
+	/// (Default: 10485760)
+	#[bpaf(long, argument::<u32>("BYTES"), fallback(DEFAULT_MAX_REQUEST_SIZE))]
+	max_request_size: u32,
+
+	/// Maximum size of a JSON-RPC response in bytes
+	/// (Default: 33554432)
+	#[bpaf(long, argument::<u32>("BYTES"), fallback(DEFAULT_MAX_RESPONSE_SIZE))]
+	max_response_size: u32,
+
+	/// Maximum size of a message sent or received over the subscription websocket in bytes
+	/// (Default: 33554432)
+	#[bpaf(long, argument::<u32>("BYTES"), fallback(DEFAULT_MAX_WS_MESSAGE_SIZE))]
+	max_ws_message_size: u32,
+
+	/// Maximum number of simultaneous connections to each JSON-RPC server
+	/// (Default: 100)
+	#[bpaf(long, argument::<u32>("COUNT"), fallback(DEFAULT_MAX_CONNECTIONS))]
+	max_connections: u32,
+
+	/// Maximum number of subscriptions a single websocket connection may have open
+	/// (Default: 1024)
+	#[bpaf(long, argument::<u32>("COUNT"), fallback(DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION))]
+	max_subscriptions_per_connection: u32,
+
+	/// Lowest level of debuggable program logs to record, either "info" (logged by the program) or "debug" (also
+	/// includes messages from the Bokken runtime)
+	/// (Default: info)
+	#[bpaf(long, argument::<BokkenLogLevel>("LEVEL"), fallback(BokkenLogLevel::Info))]
+	program_log_level: BokkenLogLevel,
+
+	/// Only allow the debuggable program with this ID to connect, can be specified multiple times. Any program ID
+	/// other than a builtin one is allowed if this isn't specified.
+	#[bpaf(long, argument::<Pubkey>("PUBKEY"), many)]
+	allow_program: Vec<Pubkey>,
+
+	/// Let a debuggable program take the place of the builtin program with this ID, e.g. to debug a fork of it, can be
+	/// specified multiple times. Once it connects, it's invoked instead of Bokken's native stub for the program.
+	#[bpaf(long, argument::<Pubkey>("PUBKEY"), many)]
+	override_program: Vec<Pubkey>,
+
+	/// How long to wait for a disconnected debuggable program to reconnect when it's invoked, e.g. while it's being
+	/// rebuilt
+	/// (Default: 0)
+	#[bpaf(long, argument::<u64>("MILLISECONDS"), fallback(0))]
+	reconnect_wait: u64,
+
+	/// Same as `reconnect-wait`, but only for the specified program, can be specified multiple times
+	#[bpaf(long, argument::<ProgramReconnectWait>("PUBKEY=MILLISECONDS"), many)]
+	program_reconnect_wait: Vec<ProgramReconnectWait>,
+
+	/// Makes the debuggable program's accounts look like they belong to the specified loader, one of debuggable,
+	/// upgradeable-loader, or loader-v4, can be specified multiple times. Overrides the programs config.
+	#[bpaf(long, argument::<ProgramAccountLayout>("PUBKEY=LAYOUT"), many)]
+	program_account_layout: Vec<ProgramAccountLayout>,
+
+	/// Gives the account a human-readable alias, which output and error messages show it as, and which admin RPC
+	/// methods accept in place of its pubkey, can be specified multiple times
+	#[bpaf(long, argument::<AccountAlias>("NAME=PUBKEY"), many)]
+	alias: Vec<AccountAlias>,
+
+	/// Largest data size any account may have
+	/// (Default: 10485760, same as a real validator)
+	#[bpaf(long, argument::<u64>("BYTES"), fallback(BokkenAccountSizeLimits::default().max_account_size))]
+	max_account_size: u64,
+
+	/// How much an account's data may grow within a single instruction. Debuggable programs can never grow an account
+	/// by more than the default.
+	/// (Default: 10240, same as a real validator)
+	#[bpaf(long, argument::<u64>("BYTES"), fallback(BokkenAccountSizeLimits::default().max_data_increase))]
+	max_data_increase: u64,
+
+	/// Fail this percentage of sent transactions with BlockhashNotFound or "Node is unhealthy" before executing them,
+	/// for testing client retry logic
+	/// (Default: 0, disabled)
+	#[bpaf(long, argument::<f64>("PERCENT"), fallback(0.0))]
+	chaos_failure_percent: f64,
+
+	/// Seed deciding which transactions fail when `chaos-failure-percent` is set
+	/// (Default: random, printed on startup)
+	#[bpaf(long, argument::<u64>("SEED"))]
+	chaos_seed: Option<u64>,
+
+	/// Maximum amount of lamports requestAirdrop sends to the same account within `airdrop-limit-window`
+	/// (Default: unlimited)
+	#[bpaf(long, argument::<u64>("LAMPORTS"))]
+	airdrop_limit: Option<u64>,
+
+	/// Maximum amount of lamports requestAirdrop sends to the same client IP within `airdrop-limit-window`. Clients
+	/// are identified by the IP they connect from, see `trusted-proxy` for running Bokken behind a reverse proxy.
+	/// (Default: unlimited)
+	#[bpaf(long, argument::<u64>("LAMPORTS"))]
+	airdrop_ip_limit: Option<u64>,
+
+	/// Reverse proxy whose X-Forwarded-For and X-Real-IP headers identify clients for `airdrop-ip-limit`. The client
+	/// is the right-most forwarded address which isn't a trusted proxy. Can be specified multiple times.
+	#[bpaf(long, argument::<IpAddr>("IP ADDRESS"), many)]
+	trusted_proxy: Vec<IpAddr>,
+
+	/// Time window the airdrop limits apply to
+	/// (Default: 86400, one day)
+	#[bpaf(long, argument::<u64>("SECONDS"), fallback(86400))]
+	airdrop_limit_window: u64,
+
+	/// gRPC port to listen to on `listen-addr`. The gRPC endpoint is disabled if this isn't specified.
+	#[cfg(feature = "grpc")]
+	#[bpaf(long, argument::<u16>("PORT"))]
+	grpc_port: Option<u16>,
+
+	/// OTLP (gRPC) collector to export tracing spans to, e.g. http://localhost:4317. Nothing is exported if this isn't
+	/// specified.
+	#[cfg(feature = "otlp")]
+	#[bpaf(long, argument::<String>("URL"))]
+	otlp_endpoint: Option<String>,
+
+	#[bpaf(external(bokken_command), optional)]
+	command: Option<BokkenCommand>
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-	println!("Is your program Bokken today?");
-	color_eyre::install()?;
+#[derive(Clone, Debug, Bpaf)]
+enum BokkenCommand {
+	/// Creates a new keypair in the ledger's keystore funded with the specified amount of lamports, then exits.
+	/// Bokken must not be running with the same save path while doing this.
+	#[bpaf(command)]
+	Keygen {
+		/// Amount of lamports to fund the new keypair with
+		/// (Default: 1000000000)
+		#[bpaf(short('l'), long, argument::<u64>("LAMPORTS"), fallback(1000000000))]
+		lamports: u64,
+
+		/// Also save the keypair to this path
+		#[bpaf(short('o'), long, argument::<PathBuf>("PATH"))]
+		outfile: Option<PathBuf>
+	},
+	/// Tools for inspecting saved ledgers
+	#[bpaf(command)]
+	Ledger(#[bpaf(external(ledger_command))] LedgerCommand),
+	/// Tools for inspecting accounts of a saved ledger, which may be in use by a running Bokken instance
+	#[bpaf(command)]
+	Accounts(#[bpaf(external(accounts_command))] AccountsCommand),
+	/// Sends transfer transactions through a throwaway ledger one after another, then reports throughput, latency, and
+	/// how long each stage took
+	#[bpaf(command)]
+	Bench {
+		/// Amount of transactions to send
+		/// (Default: 1000)
+		#[bpaf(short('n'), long, argument::<usize>("COUNT"), fallback(1000))]
+		transactions: usize
+	}
+}
+
+#[derive(Clone, Debug, Bpaf)]
+enum LedgerCommand {
+	/// Lists the accounts which were created, modified, or deleted going from SNAPSHOT_A to SNAPSHOT_B, where both are
+	/// ledger save paths (or copies of them)
+	#[bpaf(command)]
+	Diff {
+		#[bpaf(positional::<PathBuf>("SNAPSHOT_A"))]
+		snapshot_a: PathBuf,
+		#[bpaf(positional::<PathBuf>("SNAPSHOT_B"))]
+		snapshot_b: PathBuf
+	},
+	/// Shows account counts, block count, and disk usage of the ledger at LEDGER, or the save path if not specified
+	#[bpaf(command)]
+	Info {
+		#[bpaf(positional::<PathBuf>("LEDGER"), optional)]
+		ledger: Option<PathBuf>
+	},
+	/// Prints every committed transaction of the ledger at LEDGER, or the save path if not specified, as one line of
+	/// JSON each: signature, slot, invoked programs, logs, and how the writable accounts changed
+	#[bpaf(command("export-events"))]
+	ExportEvents {
+		/// Start from this slot instead of the first one
+		/// (Default: 1)
+		#[bpaf(long, argument::<u64>("SLOT"), fallback(1))]
+		from_slot: u64,
+		/// Keep printing transactions as they're committed instead of exiting once caught up
+		#[bpaf(short('f'), long, switch)]
+		follow: bool,
+		/// How often to check for new transactions when following
+		/// (Default: 250)
+		#[bpaf(short('i'), long, argument::<u64>("MILLISECONDS"), fallback(250))]
+		interval: u64,
+		#[bpaf(positional::<PathBuf>("LEDGER"), optional)]
+		ledger: Option<PathBuf>
+	}
+}
+
+#[derive(Clone, Debug, Bpaf)]
+enum AccountsCommand {
+	/// Prints what changed every time the account changes: lamports, changed byte ranges, and decoded fields if its
+	/// owner has an account schema registered. Runs until interrupted.
+	#[bpaf(command)]
+	Watch {
+		/// Ledger to watch, the save path if not specified
+		#[bpaf(short('l'), long, argument::<PathBuf>("LEDGER"))]
+		ledger: Option<PathBuf>,
+		/// How often to check for new blocks
+		/// (Default: 250)
+		#[bpaf(short('i'), long, argument::<u64>("MILLISECONDS"), fallback(250))]
+		interval: u64,
+		#[bpaf(positional::<Pubkey>("PUBKEY"))]
+		pubkey: Pubkey
+	}
+}
+
+fn init_mint_config(opts: &CommandOptions) -> Result<Option<BokkenLedgerInitConfig>> {
+	let Some(initial_mint) = opts.initial_mint_pubkey else {
+		return Ok(None);
+	};
+	let identity = match &opts.identity {
+		Some(identity_path) => Some(Arc::new(
+			read_keypair_file(identity_path).map_err(|e| eyre!("Couldn't read identity {:?}: {}", identity_path, e))?
+		)),
+		None => None
+	};
+	Ok(
+		Some(
+			BokkenLedgerInitConfig {
+				initial_mint,
+				initial_mint_lamports: opts.initial_mint_lamports,
+				identity
+			}
+		)
+	)
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TimeModelOption {
+	RealTime,
+	SlotDerived,
+	Manual
+}
+impl FromStr for TimeModelOption {
+	type Err = String;
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		match s {
+			"real-time" => Ok(Self::RealTime),
+			"slot-derived" => Ok(Self::SlotDerived),
+			"manual" => Ok(Self::Manual),
+			_ => Err(format!("Expected real-time, slot-derived, or manual, got {}", s))
+		}
+	}
+}
+
+/// Returns None if no time model was specified. Timestamps continue from the current one.
+async fn time_model(opts: &CommandOptions, ledger: &BokkenLedger) -> Option<BokkenTimeModel> {
+	let slot = ledger.slot().await;
+	let unix_timestamp = ledger.unix_timestamp_at_slot(slot);
+	match opts.time_model? {
+		TimeModelOption::RealTime => Some(BokkenTimeModel::RealTime),
+		TimeModelOption::SlotDerived => Some(BokkenTimeModel::slot_derived_from(slot, unix_timestamp, opts.slot_duration)),
+		TimeModelOption::Manual => Some(BokkenTimeModel::Manual { unix_timestamp })
+	}
+}
+
+#[derive(Clone, Debug)]
+struct AccountSchemaPath {
+	program_id: Pubkey,
+	path: PathBuf
+}
+impl FromStr for AccountSchemaPath {
+	type Err = String;
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		let (program_id, path) = s.split_once('=').ok_or_else(|| format!("Expected PUBKEY=FILE, got {}", s))?;
+		Ok(
+			Self {
+				program_id: Pubkey::from_str(program_id).map_err(|e| e.to_string())?,
+				path: PathBuf::from(path)
+			}
+		)
+	}
+}
+
+async fn register_account_schemas(opts: &CommandOptions, ledger: &BokkenLedger) -> Result<()> {
+	let schema_paths = opts.account_schema.iter().map(|schema_path| (schema_path, BokkenAccountSchemaFormat::Anchor))
+		.chain(opts.borsh_account_schema.iter().map(|schema_path| (schema_path, BokkenAccountSchemaFormat::Borsh)));
+	for (schema_path, format) in schema_paths {
+		let schema = BokkenAccountSchema::from_json(format, &tokio::fs::read_to_string(&schema_path.path).await?)
+			.map_err(|e| eyre!("Couldn't read account schema {:?}: {}", schema_path.path, e))?;
+		println!(
+			"Registered account schema for {} ({})",
+			schema_path.program_id,
+			schema.account_type_names().join(", ")
+		);
+		ledger.account_schemas().register(schema_path.program_id, schema).await?;
+	}
+	Ok(())
+}
+
+#[derive(Clone, Debug)]
+struct ProgramReconnectWait {
+	program_id: Pubkey,
+	wait: Duration
+}
+impl FromStr for ProgramReconnectWait {
+	type Err = String;
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		let (program_id, wait_ms) = s.split_once('=').ok_or_else(|| format!("Expected PUBKEY=MILLISECONDS, got {}", s))?;
+		Ok(
+			Self {
+				program_id: Pubkey::from_str(program_id).map_err(|e| e.to_string())?,
+				wait: Duration::from_millis(wait_ms.parse::<u64>().map_err(|e| e.to_string())?)
+			}
+		)
+	}
+}
+
+#[derive(Clone, Debug)]
+struct ProgramAccountLayout {
+	program_id: Pubkey,
+	layout: BokkenProgramAccountLayout
+}
+impl FromStr for ProgramAccountLayout {
+	type Err = String;
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		let (program_id, layout) = s.split_once('=').ok_or_else(|| format!("Expected PUBKEY=LAYOUT, got {}", s))?;
+		Ok(
+			Self {
+				program_id: Pubkey::from_str(program_id).map_err(|e| e.to_string())?,
+				layout: BokkenProgramAccountLayout::from_str(layout)?
+			}
+		)
+	}
+}
+
+#[derive(Clone, Debug)]
+struct AccountAlias {
+	name: String,
+	pubkey: Pubkey
+}
+impl FromStr for AccountAlias {
+	type Err = String;
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		let (name, pubkey) = s.split_once('=').ok_or_else(|| format!("Expected NAME=PUBKEY, got {}", s))?;
+		Ok(
+			Self {
+				name: name.to_string(),
+				pubkey: Pubkey::from_str(pubkey).map_err(|e| e.to_string())?
+			}
+		)
+	}
+}
 
-	let opts = command_options().run();
-	let ipc_listener = UnixListener::bind(opts.socket_path)?;
+fn program_caller_config(opts: &CommandOptions) -> ProgramCallerConfig {
+	ProgramCallerConfig {
+		min_log_level: opts.program_log_level,
+		allowed_programs: if opts.allow_program.is_empty() {
+			None
+		}else{
+			Some(opts.allow_program.iter().cloned().collect())
+		},
+		reconnect_wait: Duration::from_millis(opts.reconnect_wait),
+		program_reconnect_wait: opts.program_reconnect_wait.iter().map(|wait| (wait.program_id, wait.wait)).collect(),
+		account_size_limits: BokkenAccountSizeLimits {
+			max_account_size: opts.max_account_size,
+			max_data_increase: opts.max_data_increase
+		},
+		program_overrides: opts.override_program.iter().cloned().collect()
+	}
+}
+
+fn rpc_server_limits(opts: &CommandOptions) -> BokkenRpcServerLimits {
+	BokkenRpcServerLimits {
+		max_request_size: opts.max_request_size,
+		max_response_size: opts.max_response_size,
+		max_ws_message_size: opts.max_ws_message_size,
+		max_connections: opts.max_connections,
+		max_subscriptions_per_connection: opts.max_subscriptions_per_connection
+	}
+}
+
+/// Returns None if no rent options were specified
+fn rent_config(opts: &CommandOptions, current: BokkenRentConfig) -> Option<BokkenRentConfig> {
+	if opts.rent_free {
+		return Some(BokkenRentConfig::rent_free());
+	}
+	if opts.rent_lamports_per_byte_year.is_none() && opts.rent_exemption_threshold.is_none() {
+		return None;
+	}
+	Some(
+		BokkenRentConfig {
+			lamports_per_byte_year: opts.rent_lamports_per_byte_year.unwrap_or(current.lamports_per_byte_year),
+			exemption_threshold: opts.rent_exemption_threshold.unwrap_or(current.exemption_threshold)
+		}
+	)
+}
+
+async fn keygen(opts: &CommandOptions, lamports: u64, outfile: Option<PathBuf>) -> Result<()> {
+	// Use a throwaway socket so this doesn't clash with the one a running Bokken instance would use
+	let socket_path = std::env::temp_dir().join(format!("bokken-keygen-{}.sock", std::process::id()));
 	let ledger = BokkenLedger::new(
-		opts.save_path,
-		ProgramCaller::new(ipc_listener),
-		opts.initial_mint_pubkey.map(|pubkey| {
+		opts.save_path.clone(),
+		ProgramCaller::new(bind_ipc_socket(&socket_path)?, program_caller_config(opts)),
+		init_mint_config(opts)?
+	).await?;
+	let keypair = BokkenKeystore::for_ledger(&ledger).create_funded_keypair(&ledger, lamports).await?;
+	ledger.stop();
+	remove_ipc_socket(&socket_path).await?;
+	if let Some(outfile) = outfile {
+		tokio::fs::write(&outfile, keypair_to_json(&keypair)).await?;
+		println!("Saved keypair to {:?}", outfile);
+	}
+	println!("Created {} with {} lamports", keypair.pubkey(), lamports);
+	Ok(())
+}
+
+async fn bench(opts: &CommandOptions, transactions: usize) -> Result<()> {
+	// Throwaway everything, so that this can run alongside a Bokken instance without touching its ledger
+	let bench_path = std::env::temp_dir().join(format!("bokken-bench-{}", std::process::id()));
+	let socket_path = std::env::temp_dir().join(format!("bokken-bench-{}.sock", std::process::id()));
+	let payer = Keypair::new();
+	let ledger = BokkenLedger::new(
+		bench_path.clone(),
+		ProgramCaller::new(bind_ipc_socket(&socket_path)?, program_caller_config(opts)),
+		Some(
 			BokkenLedgerInitConfig {
-				initial_mint: pubkey,
-				initial_mint_lamports: opts.initial_mint_lamports
+				initial_mint: payer.pubkey(),
+				initial_mint_lamports: DEFAULT_INITIAL_MINT_LAMPORTS,
+				identity: None
 			}
-		})
+		)
 	).await?;
+	println!("Sending {} transfers...", transactions);
+	let report = run_transfer_bench(&ledger, &payer, transactions).await;
+	ledger.stop();
+	remove_ipc_socket(&socket_path).await?;
+	tokio::fs::remove_dir_all(&bench_path).await?;
+
+	let average = report.average_timings();
+	println!("Transactions: {} ({} failed) in {:?}", report.transactions, report.failed, report.elapsed);
+	println!("Throughput:   {:.1} TPS", report.transactions_per_second());
+	println!("Latency:      p50 {:?}, p99 {:?}", report.latency_percentile(50.0), report.latency_percentile(99.0));
+	println!("Average time per stage:");
+	println!("  Sign:       {:?}", average.sign);
+	println!("  Execute:    {:?}", average.execute);
+	println!("  Confirm:    {:?}", average.confirm);
+	Ok(())
+}
+
+#[cfg(feature = "otlp")]
+fn init_otlp_tracing(endpoint: &str) -> Result<()> {
+	use opentelemetry::{KeyValue, sdk::{trace, Resource}};
+	use opentelemetry_otlp::WithExportConfig;
+	use tracing_subscriber::layer::SubscriberExt;
+
+	let tracer = opentelemetry_otlp::new_pipeline()
+		.tracing()
+		.with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+		.with_trace_config(trace::config().with_resource(Resource::new(vec![KeyValue::new("service.name", "bokken")])))
+		.install_batch(opentelemetry::runtime::Tokio)?;
+	tracing::subscriber::set_global_default(
+		tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer))
+	)?;
+	println!("Exporting traces to {}", endpoint);
+	Ok(())
+}
+
+async fn ledger_diff(snapshot_a: &Path, snapshot_b: &Path) -> Result<()> {
+	let changes = diff_ledger_accounts(
+		&read_ledger_accounts(snapshot_a).await?,
+		&read_ledger_accounts(snapshot_b).await?
+	);
+	for change in changes.iter() {
+		println!(
+			"{:<8} {:<44} lamports: {:+} data size: {:+}{}",
+			match change.kind {
+				BokkenLedgerAccountChangeKind::Created => "created",
+				BokkenLedgerAccountChangeKind::Modified => "modified",
+				BokkenLedgerAccountChangeKind::Deleted => "deleted"
+			},
+			display_pubkey(&change.pubkey),
+			change.lamports_delta,
+			change.data_size_delta,
+			if change.owner_changed { " (owner changed)" } else { "" }
+		);
+	}
+	println!("{} account(s) changed", changes.len());
+	Ok(())
+}
+
+async fn ledger_info(ledger_path: &Path) -> Result<()> {
+	let info = read_ledger_info(ledger_path).await?;
+	println!("Accounts:               {}", info.account_count);
+	println!("Zero-lamport accounts:  {}", info.zero_lamport_account_count);
+	println!("Account versions:       {}", info.account_version_count);
+	println!("Account data:           {} bytes", info.account_data_bytes);
+	println!("Blocks:                 {}", info.block_count);
+	println!("Disk usage:");
+	println!("  Accounts:             {} bytes", info.accounts_disk_bytes);
+	println!("  Blocks:               {} bytes", info.blocks_disk_bytes);
+	println!("  Transaction index:    {} bytes", info.transaction_index_disk_bytes);
+	println!("  Log index:            {} bytes", info.log_index_disk_bytes);
+	println!("  Total:                {} bytes", info.total_disk_bytes);
+	Ok(())
+}
+
+async fn ledger_export_events(ledger_path: PathBuf, from_slot: u64, follow: bool, interval: Duration) -> Result<()> {
+	let mut reader = BokkenLedgerEventReader::new(ledger_path, from_slot);
+	loop {
+		let events = reader.read_events().await?;
+		if events.is_empty() {
+			if !follow {
+				return Ok(());
+			}
+			tokio::time::sleep(interval).await;
+			continue;
+		}
+		for event in events.iter() {
+			println!("{}", event.to_json_line());
+		}
+	}
+}
+
+fn format_byte_ranges(ranges: &[std::ops::Range<usize>]) -> String {
+	ranges.iter().map(|range| format!("{}..{}", range.start, range.end)).collect::<Vec<_>>().join(", ")
+}
+
+async fn accounts_watch(ledger_path: PathBuf, pubkey: Pubkey, interval: Duration) -> Result<()> {
+	let mut watcher = BokkenAccountWatcher::new(ledger_path, pubkey).await?;
+	let account = watcher.account();
+	println!(
+		"Watching {} as of slot {}: {} lamports, {} bytes, owned by {}",
+		display_pubkey(&pubkey),
+		watcher.slot(),
+		account.lamports,
+		account.data.len(),
+		display_pubkey(&account.owner)
+	);
+	loop {
+		let change = watcher.next_change(interval).await?;
+		println!("Slot {}:", change.slot);
+		println!("  lamports: {} -> {} ({:+})", change.pre.lamports, change.post.lamports, change.lamports_delta());
+		if change.pre.owner != change.post.owner {
+			println!("  owner:    {} -> {}", display_pubkey(&change.pre.owner), display_pubkey(&change.post.owner));
+		}
+		if change.pre.data.len() != change.post.data.len() {
+			println!("  size:     {} -> {} bytes", change.pre.data.len(), change.post.data.len());
+		}
+		if !change.changed_ranges.is_empty() {
+			println!("  changed:  {}", format_byte_ranges(&change.changed_ranges));
+		}
+		if let Some((account_type, changed_fields)) = &change.decoded {
+			println!("  {}:", account_type);
+			for field in changed_fields.iter() {
+				println!(
+					"    {}: {} -> {}",
+					field.path,
+					field.pre.as_ref().map_or("-".to_string(), |value| value.to_string()),
+					field.post.as_ref().map_or("-".to_string(), |value| value.to_string())
+				);
+			}
+		}
+	}
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+	if let Err(e) = color_eyre::install() {
+		eprintln!("Couldn't install the error report handler: {}", e);
+	}
+	let opts = match command_options().run_inner(Args::current_args()) {
+		Ok(opts) => opts,
+		// --help and --version
+		Err(ParseFailure::Stdout(message)) => {
+			print!("{}", message);
+			return ExitCode::SUCCESS;
+		},
+		Err(ParseFailure::Stderr(message)) => {
+			return report_error(startup_error(BokkenExitCode::InvalidArguments, eyre!(message.trim_end().to_string())));
+		}
+	};
+	match run(opts).await {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(report) => report_error(report)
+	}
+}
+
+async fn run(mut opts: CommandOptions) -> Result<()> {
+	// Exported events are meant to be piped into other tools, so nothing else may be printed alongside them
+	if !matches!(opts.command, Some(BokkenCommand::Ledger(LedgerCommand::ExportEvents { .. }))) {
+		println!("Is your program Bokken today?");
+	}
+	if let Some(socket_dir) = &opts.socket_dir {
+		tokio::fs::create_dir_all(socket_dir).await?;
+		opts.socket_path = socket_dir.join("bokken.sock");
+	}
+	for alias in opts.alias.iter() {
+		set_account_alias(alias.name.clone(), alias.pubkey).exit_code(BokkenExitCode::InvalidArguments)?;
+	}
+	match opts.command.clone() {
+		Some(BokkenCommand::Keygen { lamports, outfile }) => {
+			return keygen(&opts, lamports, outfile).await;
+		},
+		Some(BokkenCommand::Ledger(LedgerCommand::Diff { snapshot_a, snapshot_b })) => {
+			return ledger_diff(&snapshot_a, &snapshot_b).await;
+		},
+		Some(BokkenCommand::Ledger(LedgerCommand::Info { ledger })) => {
+			return ledger_info(ledger.as_ref().unwrap_or(&opts.save_path)).await;
+		},
+		Some(BokkenCommand::Ledger(LedgerCommand::ExportEvents { from_slot, follow, interval, ledger })) => {
+			let ledger_path = ledger.unwrap_or_else(|| opts.save_path.clone());
+			return ledger_export_events(ledger_path, from_slot, follow, Duration::from_millis(interval)).await;
+		},
+		Some(BokkenCommand::Accounts(AccountsCommand::Watch { ledger, interval, pubkey })) => {
+			return accounts_watch(ledger.unwrap_or_else(|| opts.save_path.clone()), pubkey, Duration::from_millis(interval)).await;
+		},
+		Some(BokkenCommand::Bench { transactions }) => {
+			return bench(&opts, transactions).await;
+		},
+		None => {}
+	}
+	#[cfg(feature = "otlp")]
+	if let Some(otlp_endpoint) = &opts.otlp_endpoint {
+		init_otlp_tracing(otlp_endpoint).exit_code(BokkenExitCode::InvalidArguments)?;
+	}
+	let ipc_listener = bind_ipc_socket(&opts.socket_path).exit_code(BokkenExitCode::SocketUnavailable)?;
+	let init_mint_config = init_mint_config(&opts).exit_code(BokkenExitCode::InvalidArguments)?;
+	let ledger = BokkenLedger::new(
+		opts.save_path.clone(),
+		ProgramCaller::new(ipc_listener, program_caller_config(&opts)),
+		init_mint_config.clone()
+	).await.exit_code(BokkenExitCode::LedgerCorrupt)?;
+	if let Some(rent) = rent_config(&opts, ledger.rent()) {
+		ledger.set_rent(rent).await?;
+		println!("Rent: {} lamports per byte-year, exempt after {} years", rent.lamports_per_byte_year, rent.exemption_threshold);
+	}
+	if let Some(time_model) = time_model(&opts, &ledger).await {
+		ledger.set_time_model(time_model).await?;
+		println!("Time model: {:?}", time_model);
+	}
+	register_account_schemas(&opts, &ledger).await.exit_code(BokkenExitCode::InvalidArguments)?;
+	if let Some(feature_set) = opts.feature_set {
+		ledger.set_feature_set_id(Some(feature_set)).await?;
+	}
+	for feature_id in opts.activate_feature.iter() {
+		ledger.activate_feature(*feature_id, None).await?;
+	}
+	for feature_id in opts.deactivate_feature.iter() {
+		ledger.deactivate_feature(*feature_id).await?;
+	}
+	ledger.set_account_in_use_errors(opts.account_in_use_errors);
+	ledger.set_strict_accounts(opts.strict_accounts);
+	ledger.set_max_transaction_age(opts.max_transaction_age);
+	ledger.set_log_archive_retention(opts.log_archive_retention);
+	ledger.set_durability(opts.durability);
+	ledger.set_preflight_pre_state_limit(opts.preflight_pre_state_limit);
+	let slots_per_epoch = opts.slots_per_epoch.max(MINIMUM_SLOTS_PER_EPOCH);
+	ledger.set_epoch_schedule(EpochSchedule::custom(slots_per_epoch, slots_per_epoch, opts.epoch_warmup));
+	ledger.set_failure_artifacts_path(opts.failure_artifacts_dir.clone());
+	// The ledger keeps the supervisor around until Bokken exits, dropping it kills the programs
+	if let Some(programs_config) = &opts.programs_config {
+		let programs_config = BokkenProgramsConfig::read(programs_config).await
+			.exit_code(BokkenExitCode::InvalidArguments)?;
+		for program in programs_config.programs.iter() {
+			ledger.set_program_account_layout(program.program_id, program.account_layout);
+		}
+		ledger.set_program_supervisor(Arc::new(
+			BokkenProgramSupervisor::launch(&programs_config, &opts.socket_path)
+				.exit_code(BokkenExitCode::ProgramLaunchFailed)?
+		));
+	}
+	for program_account_layout in opts.program_account_layout.iter() {
+		ledger.set_program_account_layout(program_account_layout.program_id, program_account_layout.layout);
+	}
+	if opts.wait_for_programs > 0 || !opts.wait_for_program.is_empty() {
+		println!(
+			"Waiting for {} debuggable program(s) to connect to {:?}...",
+			opts.wait_for_programs.max(opts.wait_for_program.len()),
+			opts.socket_path
+		);
+		ledger.wait_for_debuggable_programs(opts.wait_for_programs, &opts.wait_for_program).await?;
+		println!("All expected debuggable programs have connected");
+	}
+	let ledger = Arc::new(ledger);
+	let instance_manager = Arc::new(BokkenInstanceManager::new(
+		opts.save_path.join("instances"),
+		opts.socket_path.clone(),
+		// Instances are separate validators as far as clients are concerned, so they get their own identity
+		init_mint_config.map(|config| BokkenLedgerInitConfig { identity: None, ..config }),
+		Duration::from_millis(opts.slow_rpc_threshold),
+		opts.simulation_cache_size,
+		rpc_server_limits(&opts),
+		program_caller_config(&opts)
+	));
+
+	let fault_injector = if opts.chaos_failure_percent > 0.0 {
+		let chaos_seed = opts.chaos_seed.unwrap_or_else(|| {
+			SystemTime::now().duration_since(UNIX_EPOCH).expect("We're in 1970").as_nanos() as u64
+		});
+		println!("Chaos mode: failing {}% of transactions with seed {}", opts.chaos_failure_percent, chaos_seed);
+		BokkenFaultInjector::with_chaos(BokkenChaos::new(opts.chaos_failure_percent, chaos_seed))
+	}else{
+		BokkenFaultInjector::new()
+	};
+
+	let airdrop_window = Duration::from_secs(opts.airdrop_limit_window);
+	let airdrop_limiter = BokkenAirdropLimiter::new(
+		opts.airdrop_limit.map(|lamports| BokkenAirdropQuota { lamports, window: airdrop_window }),
+		opts.airdrop_ip_limit.map(|lamports| BokkenAirdropQuota { lamports, window: airdrop_window }),
+		opts.trusted_proxy.clone()
+	);
+
+	#[cfg(feature = "grpc")]
+	if let Some(grpc_port) = opts.grpc_port {
+		let grpc_addr = SocketAddr::new(opts.listen_addr, grpc_port);
+		let ledger = ledger.clone();
+		tokio::task::spawn(async move {
+			if let Err(e) = grpc_endpoint::start_grpc_endpoint(grpc_addr, ledger).await {
+				eprintln!("gRPC endpoint stopped: {}", e);
+			}
+		});
+	}
 	
 	rpc_endpoint::start_endpoint(
 		match opts.listen_addr {
@@ -87,6 +931,14 @@ async fn main() -> Result<()> {
 			},
 		},
 		ledger,
-	).await?;
+		Duration::from_millis(opts.slow_rpc_threshold),
+		opts.simulation_cache_size,
+		rpc_server_limits(&opts),
+		Some(instance_manager),
+		Arc::new(fault_injector),
+		Arc::new(airdrop_limiter)
+	).await.exit_code(BokkenExitCode::RpcServerFailed)?;
+	#[cfg(feature = "otlp")]
+	opentelemetry::global::shutdown_tracer_provider();
 	Ok(())
 }
@@ -4,7 +4,18 @@ use solana_program::{
 	msg, program_error::ProgramError, entrypoint,
 };
 
-use crate::{instruction::TestProgramInstruction, processor::{process_increment_number, process_recurse_then_increment_number}};
+use crate::{
+	instruction::TestProgramInstruction,
+	processor::{
+		process_increment_number,
+		process_recurse_then_increment_number,
+		process_create_pda,
+		process_realloc,
+		process_set_return_data,
+		process_invoke_set_return_data,
+		process_read_sysvars
+	}
+};
 
 entrypoint!(process_instruction);
 fn process_instruction<'a>(
@@ -31,6 +42,26 @@ fn process_instruction<'a>(
 				call_depth,
 				amount
 			)?;
+		},
+		TestProgramInstruction::CreatePda { space } => {
+			msg!("ix: CreatePda");
+			process_create_pda(program_id, &mut account_info_iter, space)?;
+		},
+		TestProgramInstruction::Realloc { new_size } => {
+			msg!("ix: Realloc");
+			process_realloc(program_id, &mut account_info_iter, new_size)?;
+		},
+		TestProgramInstruction::SetReturnData { data } => {
+			msg!("ix: SetReturnData");
+			process_set_return_data(program_id, &data)?;
+		},
+		TestProgramInstruction::InvokeSetReturnData { data } => {
+			msg!("ix: InvokeSetReturnData");
+			process_invoke_set_return_data(program_id, data)?;
+		},
+		TestProgramInstruction::ReadSysvars => {
+			msg!("ix: ReadSysvars");
+			process_read_sysvars()?;
 		}
 	}
 	Ok(())
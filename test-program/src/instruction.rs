@@ -18,6 +18,34 @@ pub enum TestProgramInstruction {
 		call_depth: u8,
 		amount: u64
 	},
+	/// Creates the program-owned account at the PDA with the seeds `["pda", payer]`, using invoke_signed
+	/// 
+	/// Accounts expected:
+	///
+	/// 0. `[writable, signer]` payer: Pays for the new account,
+	/// 1. `[writable]` pda_account: The account to create,
+	/// 2. `[]` system_program: The system program,
+	CreatePda {
+		space: u64
+	},
+	/// Grows or shrinks the account's data to `new_size` bytes, new bytes are zeroed
+	/// 
+	/// Accounts expected:
+	///
+	/// 0. `[writable]` test_account: The program-owned account to resize,
+	Realloc {
+		new_size: u64
+	},
+	/// Sets the return data to `data`, then reads it back
+	SetReturnData {
+		data: Vec<u8>
+	},
+	/// Invokes SetReturnData on this program, then reads the return data it left behind
+	InvokeSetReturnData {
+		data: Vec<u8>
+	},
+	/// Logs the clock and rent sysvars
+	ReadSysvars,
 }
 
 impl TestProgramInstruction {
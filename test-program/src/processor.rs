@@ -1,6 +1,6 @@
 use std::{slice::Iter, cell::RefMut};
 
-use solana_program::{account_info::{AccountInfo, next_account_info}, pubkey::Pubkey, program_error::ProgramError, msg, program::invoke, instruction::{Instruction, AccountMeta}, clock::Clock, sysvar::Sysvar};
+use solana_program::{account_info::{AccountInfo, next_account_info}, pubkey::Pubkey, program_error::ProgramError, msg, program::{invoke, invoke_signed, set_return_data, get_return_data}, instruction::{Instruction, AccountMeta}, clock::Clock, rent::Rent, sysvar::Sysvar, system_instruction};
 use std::backtrace::Backtrace;
 
 use crate::{state::TestProgramState, instruction::TestProgramInstruction};
@@ -68,3 +68,99 @@ pub fn process_recurse_then_increment_number (
 	)?;
 	Ok(())
 }
+
+pub fn process_create_pda(
+	program_id: &Pubkey,
+	account_iter: &mut Iter<AccountInfo>,
+	space: u64
+) -> Result<(), ProgramError> {
+	let payer = next_account_info(account_iter)?;
+	let pda_account = next_account_info(account_iter)?;
+	let system_program = next_account_info(account_iter)?;
+	let (pda, bump) = Pubkey::find_program_address(&[b"pda", payer.key.as_ref()], program_id);
+	if pda != *pda_account.key {
+		msg!("Expected the PDA to be {}, got {}", pda, pda_account.key);
+		return Err(ProgramError::InvalidSeeds);
+	}
+	invoke_signed(
+		&system_instruction::create_account(
+			payer.key,
+			pda_account.key,
+			Rent::get()?.minimum_balance(space as usize),
+			space,
+			program_id
+		),
+		&[payer.clone(), pda_account.clone(), system_program.clone()],
+		&[&[b"pda", payer.key.as_ref(), &[bump]]]
+	)?;
+	msg!("Created {} with bump {}", pda, bump);
+	Ok(())
+}
+
+pub fn process_realloc(
+	program_id: &Pubkey,
+	account_iter: &mut Iter<AccountInfo>,
+	new_size: u64
+) -> Result<(), ProgramError> {
+	let test_account = next_account_info(account_iter)?;
+	if test_account.owner != program_id {
+		return Err(ProgramError::IncorrectProgramId);
+	}
+	msg!("Resizing {} from {} to {} bytes", test_account.key, test_account.data_len(), new_size);
+	test_account.realloc(new_size as usize, true)?;
+	Ok(())
+}
+
+pub fn process_set_return_data(
+	program_id: &Pubkey,
+	data: &[u8]
+) -> Result<(), ProgramError> {
+	set_return_data(data);
+	match get_return_data() {
+		Some((return_program_id, return_data)) if return_program_id == *program_id && return_data == data => {
+			msg!("Return data: {:?}", return_data);
+			Ok(())
+		},
+		other => {
+			msg!("Return data didn't match what was set: {:?}", other);
+			Err(ProgramError::InvalidAccountData)
+		}
+	}
+}
+
+pub fn process_invoke_set_return_data(
+	program_id: &Pubkey,
+	data: Vec<u8>
+) -> Result<(), ProgramError> {
+	invoke(
+		&Instruction::new_with_borsh(
+			*program_id,
+			&TestProgramInstruction::SetReturnData { data: data.clone() },
+			vec![]
+		),
+		&[]
+	)?;
+	match get_return_data() {
+		Some((return_program_id, return_data)) if return_program_id == *program_id && return_data == data => {
+			msg!("Return data left by the CPI: {:?}", return_data);
+			Ok(())
+		},
+		other => {
+			msg!("Return data left by the CPI didn't match: {:?}", other);
+			Err(ProgramError::InvalidAccountData)
+		}
+	}
+}
+
+pub fn process_read_sysvars() -> Result<(), ProgramError> {
+	let clock = Clock::get()?;
+	msg!("Clock: slot {}, unix_timestamp {}, epoch {}", clock.slot, clock.unix_timestamp, clock.epoch);
+	let rent = Rent::get()?;
+	msg!(
+		"Rent: lamports_per_byte_year {}, exemption_threshold {}, 100 bytes need {} lamports",
+		rent.lamports_per_byte_year,
+		rent.exemption_threshold,
+		rent.minimum_balance(100)
+	);
+	Ok(())
+}
@@ -66,4 +66,94 @@ export class TestProgramInstructionBuilder {
 			data: programIxData
 		});
 	};
+	static buildCreatePdaIx(
+		programId: PublicKey,
+		payer: PublicKey,
+		pdaAccount: PublicKey,
+		space: bigint,
+	): TransactionInstruction {
+		const [programIxData] = encode.TestProgramInstruction({
+			_enum: "CreatePda", space
+		});
+		return new TransactionInstruction({
+			programId,
+			keys: ([
+				{
+					pubkey: payer,
+					isSigner: true,
+					isWritable: true
+				},
+				{
+					pubkey: pdaAccount,
+					isSigner: false,
+					isWritable: true
+				},
+				{
+					pubkey: SystemProgram.programId,
+					isSigner: false,
+					isWritable: false
+				},
+			]),
+			data: programIxData
+		});
+	};
+	static buildReallocIx(
+		programId: PublicKey,
+		testAccount: PublicKey,
+		newSize: bigint,
+	): TransactionInstruction {
+		const [programIxData] = encode.TestProgramInstruction({
+			_enum: "Realloc", newSize
+		});
+		return new TransactionInstruction({
+			programId,
+			keys: ([
+				{
+					pubkey: testAccount,
+					isSigner: false,
+					isWritable: true
+				},
+			]),
+			data: programIxData
+		});
+	};
+	static buildSetReturnDataIx(
+		programId: PublicKey,
+		data: Buffer,
+	): TransactionInstruction {
+		const [programIxData] = encode.TestProgramInstruction({
+			_enum: "SetReturnData", data
+		});
+		return new TransactionInstruction({
+			programId,
+			keys: ([
+			]),
+			data: programIxData
+		});
+	};
+	static buildInvokeSetReturnDataIx(
+		programId: PublicKey,
+		data: Buffer,
+	): TransactionInstruction {
+		const [programIxData] = encode.TestProgramInstruction({
+			_enum: "InvokeSetReturnData", data
+		});
+		return new TransactionInstruction({
+			programId,
+			keys: ([
+			]),
+			data: programIxData
+		});
+	};
+	static buildReadSysvarsIx(
+		programId: PublicKey,
+	): TransactionInstruction {
+		const [programIxData] = encode.TestProgramInstruction("ReadSysvars");
+		return new TransactionInstruction({
+			programId,
+			keys: ([
+			]),
+			data: programIxData
+		});
+	};
 };
@@ -92,7 +92,24 @@ type TestProgramInstruction_RecurseThenIncrementNumber = {
 	callDepth: number;
 	amount: bigint;
 };
-export type TestProgramInstruction = TestProgramInstruction_HelloWorld | TestProgramInstruction_IncrementNumber | TestProgramInstruction_RecurseThenIncrementNumber;
+type TestProgramInstruction_CreatePda = {
+	_enum: "CreatePda"
+	space: bigint;
+};
+type TestProgramInstruction_Realloc = {
+	_enum: "Realloc"
+	newSize: bigint;
+};
+type TestProgramInstruction_SetReturnData = {
+	_enum: "SetReturnData"
+	data: Buffer;
+};
+type TestProgramInstruction_InvokeSetReturnData = {
+	_enum: "InvokeSetReturnData"
+	data: Buffer;
+};
+type TestProgramInstruction_ReadSysvars = "ReadSysvars";
+export type TestProgramInstruction = TestProgramInstruction_HelloWorld | TestProgramInstruction_IncrementNumber | TestProgramInstruction_RecurseThenIncrementNumber | TestProgramInstruction_CreatePda | TestProgramInstruction_Realloc | TestProgramInstruction_SetReturnData | TestProgramInstruction_InvokeSetReturnData | TestProgramInstruction_ReadSysvars;
 
 export type TestProgramState = {
 	property1: bigint;
@@ -123,6 +140,35 @@ export namespace encode {
 				curBuf.writeBigUInt64LE((obj as any).amount, i);
 				i += 8;
 				break;
+			case "CreatePda":
+				curBuf[i++] = 3;
+				bufs.push(curBuf);
+				totalLen += curBuf.length;
+				curBuf = Buffer.allocUnsafe(8); i = 0;
+				curBuf.writeBigUInt64LE((obj as any).space, i);
+				i += 8;
+				break;
+			case "Realloc":
+				curBuf[i++] = 4;
+				bufs.push(curBuf);
+				totalLen += curBuf.length;
+				curBuf = Buffer.allocUnsafe(8); i = 0;
+				curBuf.writeBigUInt64LE((obj as any).newSize, i);
+				i += 8;
+				break;
+			case "SetReturnData":
+			case "InvokeSetReturnData":
+				curBuf[i++] = (obj as any)._enum == "SetReturnData" ? 5 : 6;
+				bufs.push(curBuf);
+				totalLen += curBuf.length;
+				curBuf = Buffer.allocUnsafe(4 + (obj as any).data.length); i = 0;
+				curBuf.writeUInt32LE((obj as any).data.length, i);
+				i += 4;
+				i += (obj as any).data.copy(curBuf, i);
+				break;
+			case "ReadSysvars":
+				curBuf[i++] = 7;
+				break;
 			default:
 				throw new Error("Unknown enum type");
 		}
@@ -181,6 +227,39 @@ export namespace decode {
 					return subResult;
 				})();
 				break;
+			case 3:
+				result = {};
+				result._enum = "CreatePda";
+				result.space = (() => {
+					const subResult = buf.readBigUInt64LE(i);
+					i += 8;
+					return subResult;
+				})();
+				break;
+			case 4:
+				result = {};
+				result._enum = "Realloc";
+				result.newSize = (() => {
+					const subResult = buf.readBigUInt64LE(i);
+					i += 8;
+					return subResult;
+				})();
+				break;
+			case 5:
+			case 6:
+				result = {};
+				result._enum = buf[i - 1] == 5 ? "SetReturnData" : "InvokeSetReturnData";
+				result.data = (() => {
+					const len = buf.readUInt32LE(i);
+					i += 4;
+					const subResult = Buffer.from(buf.subarray(i, i + len));
+					i += len;
+					return subResult;
+				})();
+				break;
+			case 7:
+				result = "ReadSysvars";
+				break;
 			default:
 				throw new Error("Unknown enum type");
 		}
@@ -208,6 +287,9 @@ export namespace sizeOf {
 		export const HelloWorld = 1;
 		export const IncrementNumber = 9;
 		export const RecurseThenIncrementNumber = 10;
+		export const CreatePda = 9;
+		export const Realloc = 9;
+		export const ReadSysvars = 1;
 	};
 
 	export const TestProgramState = 16;
@@ -1,4 +1,5 @@
 import {inspect} from "util";
+import {strict as assert} from "assert";
 import {Connection, PublicKey, Keypair, Transaction, SystemProgram, TransactionInstruction, sendAndConfirmTransaction} from "@solana/web3.js";
 import { sizeOf, decode } from "./autogen/serialization";
 import {TestProgramInstructionBuilder} from "./autogen/instructions";
@@ -123,6 +124,58 @@ async function printStateAccountInfo(connection: Connection, programState: Publi
 		);
 		await printAccountInfo(connection, testKeypair.publicKey);
 		await printStateAccountInfo(connection, programState);
+
+		console.log("-- create PDA --");
+		const [pdaAccount] = await PublicKey.findProgramAddress(
+			[Buffer.from("pda"), testKeypair.publicKey.toBuffer()],
+			programId
+		);
+		if (await connection.getAccountInfo(pdaAccount) == null) {
+			await sendAndConfirmTransaction(
+				connection,
+				new Transaction().add(TestProgramInstructionBuilder.buildCreatePdaIx(
+					programId,
+					testKeypair.publicKey,
+					pdaAccount,
+					16n
+				)),
+				[testKeypair]
+			);
+		}
+		let pdaAccountInfo = await connection.getAccountInfo(pdaAccount);
+		assert(pdaAccountInfo != null, "PDA wasn't created");
+		assert(pdaAccountInfo.owner.equals(programId), "PDA isn't owned by the program");
+
+		console.log("-- realloc --");
+		for (const newSize of [1024, 8]) {
+			await sendAndConfirmTransaction(
+				connection,
+				new Transaction().add(TestProgramInstructionBuilder.buildReallocIx(programId, pdaAccount, BigInt(newSize))),
+				[testKeypair]
+			);
+			pdaAccountInfo = await connection.getAccountInfo(pdaAccount);
+			assert.equal(pdaAccountInfo?.data.length, newSize, "PDA wasn't resized");
+		}
+
+		console.log("-- return data --");
+		const returnData = Buffer.from("Bokken today!");
+		for (const ix of [
+			TestProgramInstructionBuilder.buildSetReturnDataIx(programId, returnData),
+			TestProgramInstructionBuilder.buildInvokeSetReturnDataIx(programId, returnData)
+		]) {
+			const simulation = await connection.simulateTransaction(new Transaction().add(ix), [testKeypair]);
+			assert.equal(simulation.value.err, null, simulation.value.logs?.join("\n"));
+			assert.equal(simulation.value.returnData?.programId, programId.toBase58());
+			assert.deepEqual(Buffer.from(simulation.value.returnData?.data[0] ?? "", "base64"), returnData);
+		}
+
+		console.log("-- read sysvars --");
+		const sysvarSimulation = await connection.simulateTransaction(
+			new Transaction().add(TestProgramInstructionBuilder.buildReadSysvarsIx(programId)),
+			[testKeypair]
+		);
+		assert.equal(sysvarSimulation.value.err, null, sysvarSimulation.value.logs?.join("\n"));
+		console.log(sysvarSimulation.value.logs?.join("\n"));
 		/*
 		console.log("-- inc number again, in a loop --");
 		
@@ -0,0 +1,125 @@
+// Drives @solana/web3.js v2 against Bokken, checking that responses have the shapes its stricter types expect.
+// Unlike v1, v2 doesn't paper over missing fields, and parses every integer as a bigint.
+import {strict as assert} from "assert";
+import {
+	appendTransactionMessageInstruction,
+	createSolanaRpc,
+	createTransactionMessage,
+	generateKeyPairSigner,
+	getBase64EncodedWireTransaction,
+	getSignatureFromTransaction,
+	lamports,
+	pipe,
+	setTransactionMessageFeePayerSigner,
+	setTransactionMessageLifetimeUsingBlockhash,
+	signTransactionMessageWithSigners,
+	Signature
+} from "@solana/web3.js-v2";
+import {getTransferSolInstruction} from "@solana-program/system";
+
+const rpc = createSolanaRpc(process.env.BOKKEN_RPC_URL ?? "http://127.0.0.1:8899");
+
+function assertContext(response: {context: {slot: bigint}}, method: string) {
+	assert.equal(typeof response.context.slot, "bigint", `${method} context.slot isn't an integer`);
+}
+
+async function waitForSignature(signature: Signature) {
+	for (let attempt = 0; attempt < 50; attempt += 1) {
+		const statuses = await rpc.getSignatureStatuses([signature]).send();
+		assertContext(statuses, "getSignatureStatuses");
+		const status = statuses.value[0];
+		if (status != null) {
+			assert.equal(status.err, null, `${signature} failed`);
+			assert.deepEqual(status.status, {Ok: null}, "status isn't a result object");
+			assert.equal(status.confirmationStatus, "finalized");
+			assert.equal(typeof status.slot, "bigint");
+			return status.slot;
+		}
+		await new Promise((resolve) => setTimeout(resolve, 100));
+	}
+	throw new Error(`${signature} wasn't confirmed`);
+}
+
+(async () => {
+	try {
+		console.log("-- cluster info --");
+		const version = await rpc.getVersion().send();
+		assert.equal(typeof version["solana-core"], "string");
+		assert.equal(typeof version["feature-set"], "bigint");
+		const epochInfo = await rpc.getEpochInfo().send();
+		for (const field of ["absoluteSlot", "blockHeight", "epoch", "slotIndex", "slotsInEpoch"] as const) {
+			assert.equal(typeof epochInfo[field], "bigint", `getEpochInfo ${field} isn't an integer`);
+		}
+		assert.equal(typeof (await rpc.getBlockHeight().send()), "bigint");
+		assert.equal(typeof (await rpc.getGenesisHash().send()), "string");
+		assert.equal(typeof (await rpc.getMinimumBalanceForRentExemption(0n).send()), "bigint");
+
+		console.log("-- airdrop --");
+		const payer = await generateKeyPairSigner();
+		const recipient = (await generateKeyPairSigner()).address;
+		await waitForSignature(await rpc.requestAirdrop(payer.address, lamports(1_000_000_000n)).send());
+		const balance = await rpc.getBalance(payer.address).send();
+		assertContext(balance, "getBalance");
+		assert.equal(balance.value, 1_000_000_000n);
+
+		const payerInfo = await rpc.getAccountInfo(payer.address, {encoding: "base64"}).send();
+		assertContext(payerInfo, "getAccountInfo");
+		assert(payerInfo.value != null, "The payer doesn't exist");
+		assert.equal(payerInfo.value.lamports, 1_000_000_000n);
+		assert.equal(typeof payerInfo.value.rentEpoch, "bigint");
+		assert.deepEqual(payerInfo.value.data, ["", "base64"]);
+		const missingInfo = await rpc.getAccountInfo(recipient, {encoding: "base64"}).send();
+		assert.equal(missingInfo.value, null, "Accounts which don't exist should be null");
+
+		console.log("-- transfer --");
+		const latestBlockhash = await rpc.getLatestBlockhash().send();
+		assertContext(latestBlockhash, "getLatestBlockhash");
+		assert.equal(typeof latestBlockhash.value.lastValidBlockHeight, "bigint");
+		// Bokken only executes legacy transactions
+		const transaction = await signTransactionMessageWithSigners(pipe(
+			createTransactionMessage({version: "legacy"}),
+			(message) => setTransactionMessageFeePayerSigner(payer, message),
+			(message) => setTransactionMessageLifetimeUsingBlockhash(latestBlockhash.value, message),
+			(message) => appendTransactionMessageInstruction(
+				getTransferSolInstruction({source: payer, destination: recipient, amount: lamports(1_000_000n)}),
+				message
+			)
+		));
+		const wireTransaction = getBase64EncodedWireTransaction(transaction);
+
+		const simulation = await rpc.simulateTransaction(wireTransaction, {encoding: "base64"}).send();
+		assertContext(simulation, "simulateTransaction");
+		assert.equal(simulation.value.err, null, simulation.value.logs?.join("\n"));
+		assert("innerInstructions" in simulation.value, "simulateTransaction is missing innerInstructions");
+		assert.equal(typeof simulation.value.unitsConsumed, "bigint");
+
+		const signature = await rpc.sendTransaction(wireTransaction, {encoding: "base64"}).send();
+		assert.equal(signature, getSignatureFromTransaction(transaction));
+		const slot = await waitForSignature(signature);
+		assert.equal((await rpc.getBalance(recipient).send()).value, 1_000_000n);
+
+		console.log("-- block --");
+		const block = await rpc.getBlock(slot, {
+			encoding: "base64",
+			transactionDetails: "full",
+			maxSupportedTransactionVersion: 0,
+			rewards: false
+		}).send();
+		assert(block != null, `Block ${slot} doesn't exist`);
+		assert.equal(typeof block.parentSlot, "bigint");
+		assert.equal(block.transactions.length, 1);
+		const meta = block.transactions[0].meta;
+		assert(meta != null, "The transaction has no meta");
+		assert.deepEqual(meta.status, {Ok: null});
+		assert.equal(typeof meta.fee, "bigint");
+		assert.deepEqual(meta.loadedAddresses, {writable: [], readonly: []});
+		for (const field of ["innerInstructions", "preTokenBalances", "postTokenBalances", "rewards"]) {
+			assert(field in meta, `getBlock transaction meta is missing ${field}`);
+		}
+
+		console.log("All web3.js v2 checks passed");
+	}catch(ex: any) {
+		console.error(ex);
+		process.exitCode = 1;
+	}
+})();